@@ -1,33 +1,83 @@
+use crate::accuracy::average_precision::AveragePrecision;
+use crate::accuracy::binary_accuracy::BinaryAccuracy;
+use crate::accuracy::categorical_accuracy::CategoricalAccuracy;
+use crate::accuracy::confusion_matrix::{Average, ConfusionMatrix};
+use crate::accuracy::f1_score::F1Score;
+use crate::accuracy::log_loss::LogLoss;
+use crate::accuracy::precision::Precision;
+use crate::accuracy::recall::Recall;
+use crate::accuracy::regression::RegressionMetric;
+use crate::accuracy::regression_score::RegressionScore;
+use crate::accuracy::roc_auc::RocAuc;
+use crate::accuracy::sparse_categorical_accuracy::SparseCategoricalAccuracy;
+use crate::accuracy::top_k_accuracy::TopKAccuracy;
+use crate::utils::error;
+use crate::utils::error::MlError;
 use ndarray::{Array1, Array2, Axis};
 use ndarray_stats::QuantileExt;
 
-pub fn accuracy(function: &str, y_pred: &Array2<f64>, y_true: &Array2<f64>) -> f64 {
-    if y_pred.shape() != y_true.shape() {
-        panic!(
-            "shapes are not equals {:?} != {:?}",
-            y_pred.shape(),
-            y_true.shape()
-        );
-    }
-    match function {
-        "categorical_accuracy" => categorical_accuracy(y_pred, y_true),
-        _ => panic!("unknown accuracy function '{}'", function),
-    }
+pub trait Metric {
+    fn update(&mut self, y_pred: &Array2<f64>, y_true: &Array2<f64>) -> Result<(), MlError>;
+
+    fn result(&self) -> f64;
+
+    fn reset(&mut self);
+
+    fn get_name(&self) -> String;
 }
 
-fn categorical_accuracy(y_pred: &Array2<f64>, y_true: &Array2<f64>) -> f64 {
-    if y_pred.shape()[1] < 2 || y_true.shape()[1] < 2 {
-        panic!("array must be one hot encoding");
+/// Top-k accuracies are named after their `k`, like `top_5_accuracy`.
+pub fn from_string(name: String) -> Result<Box<dyn Metric>, MlError> {
+    let upper_name: String = name.to_uppercase();
+    let k: Option<usize> = upper_name
+        .strip_prefix("TOP_")
+        .and_then(|suffix| suffix.strip_suffix("_ACCURACY"))
+        .and_then(|k| k.parse().ok())
+        .filter(|&k| k > 0);
+    if let Some(k) = k {
+        return Ok(Box::new(TopKAccuracy::build(k)));
     }
-    let pred_argmax: Array1<usize> = y_pred.map_axis(Axis(1), |row| row.argmax().unwrap());
-    let true_argmax: Array1<usize> = y_true.map_axis(Axis(1), |row| row.argmax().unwrap());
-    let mut correct: i32 = 0;
-    for i in 0..pred_argmax.len() {
-        if pred_argmax[i] == true_argmax[i] {
-            correct += 1;
-        }
+    match upper_name.as_str() {
+        "BINARY_ACCURACY" => Ok(Box::new(BinaryAccuracy::build(0.5))),
+        "CATEGORICAL_ACCURACY" => Ok(Box::new(CategoricalAccuracy::new())),
+        "SPARSE_CATEGORICAL_ACCURACY" => Ok(Box::new(SparseCategoricalAccuracy::new())),
+        "CONFUSION_MATRIX" => Ok(Box::new(ConfusionMatrix::new())),
+        "PRECISION_MICRO" => Ok(Box::new(Precision::build(Average::Micro))),
+        "PRECISION" | "PRECISION_MACRO" => Ok(Box::new(Precision::build(Average::Macro))),
+        "PRECISION_WEIGHTED" => Ok(Box::new(Precision::build(Average::Weighted))),
+        "RECALL_MICRO" => Ok(Box::new(Recall::build(Average::Micro))),
+        "RECALL" | "RECALL_MACRO" => Ok(Box::new(Recall::build(Average::Macro))),
+        "RECALL_WEIGHTED" => Ok(Box::new(Recall::build(Average::Weighted))),
+        "F1_MICRO" => Ok(Box::new(F1Score::build(Average::Micro))),
+        "F1" | "F1_MACRO" => Ok(Box::new(F1Score::build(Average::Macro))),
+        "F1_WEIGHTED" => Ok(Box::new(F1Score::build(Average::Weighted))),
+        "ROC_AUC" => Ok(Box::new(RocAuc::new())),
+        "AVERAGE_PRECISION" | "PR_AUC" => Ok(Box::new(AveragePrecision::new())),
+        "LOG_LOSS" => Ok(Box::new(LogLoss::new())),
+        "R2" => Ok(Box::new(RegressionScore::build(RegressionMetric::R2))),
+        "MAE" => Ok(Box::new(RegressionScore::build(RegressionMetric::Mae))),
+        "RMSE" => Ok(Box::new(RegressionScore::build(RegressionMetric::Rmse))),
+        "MAPE" => Ok(Box::new(RegressionScore::build(RegressionMetric::Mape))),
+        "EXPLAINED_VARIANCE" => Ok(Box::new(RegressionScore::build(
+            RegressionMetric::ExplainedVariance,
+        ))),
+        _ => Err(MlError::unknown_component("metric", &name)),
     }
-    correct as f64 / pred_argmax.len() as f64
+}
+
+pub fn check_shapes(y_pred: &Array2<f64>, y_true: &Array2<f64>) -> Result<(), MlError> {
+    error::check_shapes(y_pred.shape(), y_true.shape())
+}
+
+/// Index of the greatest value of each row, fails on NaN values.
+pub fn argmax_rows(x: &Array2<f64>) -> Result<Array1<usize>, MlError> {
+    x.axis_iter(Axis(0))
+        .map(|row| {
+            row.argmax().map_err(|_| {
+                MlError::Numeric("can not take the argmax of an empty or NaN row".to_string())
+            })
+        })
+        .collect()
 }
 
 #[cfg(test)]
@@ -36,39 +86,89 @@ mod tests {
     use ndarray::arr2;
 
     #[test]
-    fn categorical_accuracy_should_compute_accuracy() -> () {
-        let y_pred: Array2<f64> = arr2(&[
-            [0.0, 0.0, 1.0],
-            [0.0, 1.0, 0.0],
-            [1.0, 0.0, 0.0],
-            [1.0, 0.0, 0.0],
-        ]);
-        let y_true: Array2<f64> = arr2(&[
-            [0.0, 1.0, 0.0],
-            [0.0, 1.0, 0.0],
-            [1.0, 0.0, 0.0],
-            [1.0, 0.0, 0.0],
-        ]);
-        assert_eq!(accuracy("categorical_accuracy", &y_pred, &y_true), 0.75)
+    fn from_string_should_return_metric() {
+        assert_eq!(
+            from_string("binary_accuracy".to_string())
+                .unwrap()
+                .get_name(),
+            "binary_accuracy".to_string()
+        );
+        assert_eq!(
+            from_string("categorical_accuracy".to_string())
+                .unwrap()
+                .get_name(),
+            "categorical_accuracy".to_string()
+        );
+        assert_eq!(
+            from_string("sparse_categorical_accuracy".to_string())
+                .unwrap()
+                .get_name(),
+            "sparse_categorical_accuracy".to_string()
+        );
+        assert_eq!(
+            from_string("top_3_accuracy".to_string())
+                .unwrap()
+                .get_name(),
+            "top_3_accuracy".to_string()
+        );
+        assert_eq!(
+            from_string("confusion_matrix".to_string())
+                .unwrap()
+                .get_name(),
+            "confusion_matrix".to_string()
+        );
+        assert_eq!(
+            from_string("precision".to_string()).unwrap().get_name(),
+            "precision_macro".to_string()
+        );
+        assert_eq!(
+            from_string("recall_weighted".to_string())
+                .unwrap()
+                .get_name(),
+            "recall_weighted".to_string()
+        );
+        assert_eq!(
+            from_string("f1_micro".to_string()).unwrap().get_name(),
+            "f1_micro".to_string()
+        );
+        assert_eq!(
+            from_string("roc_auc".to_string()).unwrap().get_name(),
+            "roc_auc".to_string()
+        );
+        assert_eq!(
+            from_string("pr_auc".to_string()).unwrap().get_name(),
+            "average_precision".to_string()
+        );
+        assert_eq!(
+            from_string("log_loss".to_string()).unwrap().get_name(),
+            "log_loss".to_string()
+        );
+        for name in ["r2", "mae", "rmse", "mape", "explained_variance"] {
+            assert_eq!(
+                from_string(name.to_string()).unwrap().get_name(),
+                name.to_string()
+            );
+        }
     }
 
     #[test]
-    #[should_panic(expected = "array must be one hot encoding")]
-    fn categorical_accuracy_should_panic_when_arrays_are_not_one_hot() -> () {
-        let y_pred: Array2<f64> = arr2(&[[0.0], [0.0], [1.0], [0.0], [1.0], [0.0], [1.0], [0.0]]);
-        let y_true: Array2<f64> = arr2(&[[0.0], [1.0], [0.0], [0.0], [1.0], [0.0], [1.0], [0.0]]);
-        accuracy("categorical_accuracy", &y_pred, &y_true);
+    fn from_string_should_raise_error_when_name_is_unknown() {
+        let error: MlError = from_string("Unknown".to_string()).err().unwrap();
+        assert_eq!(error.to_string(), "unknown metric 'Unknown'");
     }
 
     #[test]
-    #[should_panic(expected = "unknown accuracy function 'Unknown'")]
-    fn accuracy_should_panic_when_function_is_unknown() -> () {
-        accuracy("Unknown", &Array2::zeros((0, 0)), &Array2::zeros((0, 0)));
+    fn from_string_should_reject_top_k_accuracy_without_k() {
+        for name in ["top_k_accuracy", "top_0_accuracy", "top__accuracy"] {
+            assert!(matches!(
+                from_string(name.to_string()),
+                Err(MlError::UnknownComponent { .. })
+            ));
+        }
     }
 
     #[test]
-    #[should_panic(expected = "shapes are not equals [2, 3] != [4, 3]")]
-    fn accuracy_should_panic_when_array_shapes_are_not_equal() -> () {
+    fn check_shapes_should_fail_when_array_shapes_are_not_equal() {
         let y_pred: Array2<f64> = arr2(&[[0.0, 0.0, 1.0], [0.0, 1.0, 0.0]]);
         let y_true: Array2<f64> = arr2(&[
             [0.0, 1.0, 0.0],
@@ -76,6 +176,21 @@ mod tests {
             [1.0, 0.0, 0.0],
             [1.0, 0.0, 0.0],
         ]);
-        accuracy("categorical_accuracy", &y_pred, &y_true);
+        assert_eq!(
+            check_shapes(&y_pred, &y_true).unwrap_err().to_string(),
+            "shape mismatch: shapes are not equals [2, 3] != [4, 3]"
+        );
+    }
+
+    #[test]
+    fn argmax_rows_should_fail_on_nan() {
+        assert_eq!(
+            argmax_rows(&arr2(&[[0.2, 0.8], [0.6, 0.4]])).unwrap(),
+            Array1::from_vec(vec![1, 0])
+        );
+        assert!(matches!(
+            argmax_rows(&arr2(&[[f64::NAN, 0.8]])),
+            Err(MlError::Numeric(_))
+        ));
     }
 }
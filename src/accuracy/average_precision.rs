@@ -0,0 +1,89 @@
+use crate::accuracy::accuracy::Metric;
+use crate::accuracy::ranking::{
+    average_precision, one_vs_rest_curves, precision_recall_curve, Curve, PredictionBuffer,
+};
+use crate::utils::error::MlError;
+use ndarray::Array2;
+
+/// Area under the precision recall curve, averaged over classes (one vs rest) when arrays are
+/// one hot encoded.
+#[derive(Debug, Default)]
+pub struct AveragePrecision {
+    buffer: PredictionBuffer,
+}
+
+impl AveragePrecision {
+    pub fn new() -> AveragePrecision {
+        AveragePrecision {
+            buffer: PredictionBuffer::new(),
+        }
+    }
+
+    pub fn curves(&self) -> Vec<Curve> {
+        one_vs_rest_curves(
+            self.buffer.get_y_pred(),
+            self.buffer.get_y_true(),
+            precision_recall_curve,
+        )
+    }
+}
+
+impl Metric for AveragePrecision {
+    fn update(&mut self, y_pred: &Array2<f64>, y_true: &Array2<f64>) -> Result<(), MlError> {
+        self.buffer.push(y_pred, y_true)
+    }
+
+    fn result(&self) -> f64 {
+        let curves: Vec<Curve> = self.curves();
+        if curves.is_empty() {
+            return 0.0;
+        }
+        curves
+            .iter()
+            .map(|(_, recalls, precisions)| average_precision(recalls, precisions))
+            .sum::<f64>()
+            / curves.len() as f64
+    }
+
+    fn reset(&mut self) {
+        self.buffer.clear();
+    }
+
+    fn get_name(&self) -> String {
+        "average_precision".to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::arr2;
+
+    #[test]
+    fn average_precision_should_compute_binary_average_precision() {
+        let mut metric: AveragePrecision = AveragePrecision::new();
+        metric
+            .update(
+                &arr2(&[[0.1], [0.4], [0.35], [0.8]]),
+                &arr2(&[[0.0], [0.0], [1.0], [1.0]]),
+            )
+            .unwrap();
+        assert!((metric.result() - 0.8333333333).powf(2.0) < 0.00001);
+        metric.reset();
+        assert_eq!(metric.result(), 0.0);
+    }
+
+    #[test]
+    fn average_precision_should_be_one_for_perfect_ranking() {
+        let y_pred: Array2<f64> = arr2(&[[0.9, 0.1], [0.2, 0.8], [0.7, 0.3]]);
+        let y_true: Array2<f64> = arr2(&[[1.0, 0.0], [0.0, 1.0], [1.0, 0.0]]);
+        let mut metric: AveragePrecision = AveragePrecision::new();
+        metric.update(&y_pred, &y_true).unwrap();
+        assert_eq!(metric.result(), 1.0);
+    }
+
+    #[test]
+    fn get_name_should_return_metric_name() {
+        assert_eq!(AveragePrecision::new().get_name(), "average_precision");
+    }
+}
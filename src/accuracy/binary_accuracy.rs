@@ -0,0 +1,82 @@
+use crate::accuracy::accuracy::{check_shapes, Metric};
+use crate::utils::error::MlError;
+use ndarray::Array2;
+
+#[derive(Debug)]
+pub struct BinaryAccuracy {
+    threshold: f64,
+    correct: usize,
+    total: usize,
+}
+
+impl BinaryAccuracy {
+    pub fn build(threshold: f64) -> BinaryAccuracy {
+        BinaryAccuracy {
+            threshold,
+            correct: 0,
+            total: 0,
+        }
+    }
+}
+
+impl Metric for BinaryAccuracy {
+    fn update(&mut self, y_pred: &Array2<f64>, y_true: &Array2<f64>) -> Result<(), MlError> {
+        check_shapes(y_pred, y_true)?;
+        y_pred.iter().zip(y_true.iter()).for_each(|(&pred, &real)| {
+            let pred_class: bool = pred >= self.threshold;
+            let true_class: bool = real >= 0.5;
+            if pred_class == true_class {
+                self.correct += 1;
+            }
+        });
+        self.total += y_pred.len();
+        Ok(())
+    }
+
+    fn result(&self) -> f64 {
+        if self.total == 0 {
+            return 0.0;
+        }
+        self.correct as f64 / self.total as f64
+    }
+
+    fn reset(&mut self) {
+        self.correct = 0;
+        self.total = 0;
+    }
+
+    fn get_name(&self) -> String {
+        "binary_accuracy".to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::arr2;
+
+    #[test]
+    fn binary_accuracy_should_compute_accuracy() {
+        let y_pred: Array2<f64> = arr2(&[[0.9], [0.2], [0.6], [0.4]]);
+        let y_true: Array2<f64> = arr2(&[[1.0], [0.0], [0.0], [0.0]]);
+        let mut metric: BinaryAccuracy = BinaryAccuracy::build(0.5);
+        metric.update(&y_pred, &y_true).unwrap();
+        assert_eq!(metric.result(), 0.75);
+    }
+
+    #[test]
+    fn binary_accuracy_should_apply_threshold() {
+        let y_pred: Array2<f64> = arr2(&[[0.9], [0.2], [0.6], [0.4]]);
+        let y_true: Array2<f64> = arr2(&[[1.0], [0.0], [0.0], [0.0]]);
+        let mut metric: BinaryAccuracy = BinaryAccuracy::build(0.7);
+        metric.update(&y_pred, &y_true).unwrap();
+        assert_eq!(metric.result(), 1.0);
+        metric.reset();
+        assert_eq!(metric.result(), 0.0);
+    }
+
+    #[test]
+    fn get_name_should_return_metric_name() {
+        assert_eq!(BinaryAccuracy::build(0.5).get_name(), "binary_accuracy");
+    }
+}
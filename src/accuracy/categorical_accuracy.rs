@@ -0,0 +1,117 @@
+use crate::accuracy::accuracy::{argmax_rows, check_shapes, Metric};
+use crate::utils::error::MlError;
+use ndarray::{Array1, Array2};
+
+#[derive(Debug, Default)]
+pub struct CategoricalAccuracy {
+    correct: usize,
+    total: usize,
+}
+
+impl CategoricalAccuracy {
+    pub fn new() -> CategoricalAccuracy {
+        CategoricalAccuracy {
+            correct: 0,
+            total: 0,
+        }
+    }
+}
+
+impl Metric for CategoricalAccuracy {
+    fn update(&mut self, y_pred: &Array2<f64>, y_true: &Array2<f64>) -> Result<(), MlError> {
+        check_shapes(y_pred, y_true)?;
+        if y_pred.shape()[1] < 2 || y_true.shape()[1] < 2 {
+            return Err(MlError::InvalidInput(
+                "array must be one hot encoding".to_string(),
+            ));
+        }
+        let pred_argmax: Array1<usize> = argmax_rows(y_pred)?;
+        let true_argmax: Array1<usize> = argmax_rows(y_true)?;
+        for i in 0..pred_argmax.len() {
+            if pred_argmax[i] == true_argmax[i] {
+                self.correct += 1;
+            }
+        }
+        self.total += pred_argmax.len();
+        Ok(())
+    }
+
+    fn result(&self) -> f64 {
+        if self.total == 0 {
+            return 0.0;
+        }
+        self.correct as f64 / self.total as f64
+    }
+
+    fn reset(&mut self) {
+        self.correct = 0;
+        self.total = 0;
+    }
+
+    fn get_name(&self) -> String {
+        "categorical_accuracy".to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::arr2;
+
+    #[test]
+    fn categorical_accuracy_should_compute_accuracy() {
+        let y_pred: Array2<f64> = arr2(&[
+            [0.0, 0.0, 1.0],
+            [0.0, 1.0, 0.0],
+            [1.0, 0.0, 0.0],
+            [1.0, 0.0, 0.0],
+        ]);
+        let y_true: Array2<f64> = arr2(&[
+            [0.0, 1.0, 0.0],
+            [0.0, 1.0, 0.0],
+            [1.0, 0.0, 0.0],
+            [1.0, 0.0, 0.0],
+        ]);
+        let mut metric: CategoricalAccuracy = CategoricalAccuracy::new();
+        metric.update(&y_pred, &y_true).unwrap();
+        assert_eq!(metric.result(), 0.75)
+    }
+
+    #[test]
+    fn categorical_accuracy_should_accumulate_batches() {
+        let mut metric: CategoricalAccuracy = CategoricalAccuracy::new();
+        metric
+            .update(&arr2(&[[0.2, 0.8]]), &arr2(&[[0.0, 1.0]]))
+            .unwrap();
+        metric
+            .update(&arr2(&[[0.9, 0.1]]), &arr2(&[[0.0, 1.0]]))
+            .unwrap();
+        metric
+            .update(&arr2(&[[0.6, 0.4]]), &arr2(&[[1.0, 0.0]]))
+            .unwrap();
+        assert!((metric.result() - 2.0 / 3.0).powf(2.0) < 0.00001);
+        metric.reset();
+        assert_eq!(metric.result(), 0.0);
+    }
+
+    #[test]
+    fn categorical_accuracy_should_fail_when_arrays_are_not_one_hot() {
+        let y_pred: Array2<f64> = arr2(&[[0.0], [0.0], [1.0], [0.0], [1.0], [0.0], [1.0], [0.0]]);
+        let y_true: Array2<f64> = arr2(&[[0.0], [1.0], [0.0], [0.0], [1.0], [0.0], [1.0], [0.0]]);
+        let error: MlError = CategoricalAccuracy::new()
+            .update(&y_pred, &y_true)
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "invalid input: array must be one hot encoding"
+        );
+    }
+
+    #[test]
+    fn get_name_should_return_metric_name() {
+        assert_eq!(
+            CategoricalAccuracy::new().get_name(),
+            "categorical_accuracy"
+        );
+    }
+}
@@ -0,0 +1,239 @@
+use crate::accuracy::accuracy::{argmax_rows, check_shapes, Metric};
+use crate::utils::error::MlError;
+use ndarray::{Array1, Array2, Axis};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Average {
+    Micro,
+    Macro,
+    Weighted,
+}
+
+impl Average {
+    pub fn get_name(&self) -> String {
+        match self {
+            Average::Micro => "micro".to_string(),
+            Average::Macro => "macro".to_string(),
+            Average::Weighted => "weighted".to_string(),
+        }
+    }
+}
+
+/// Rows are true classes, columns are predicted classes.
+#[derive(Debug, Default)]
+pub struct ConfusionMatrix {
+    matrix: Array2<f64>,
+}
+
+impl ConfusionMatrix {
+    pub fn new() -> ConfusionMatrix {
+        ConfusionMatrix {
+            matrix: Array2::zeros((0, 0)),
+        }
+    }
+
+    pub fn get_matrix(&self) -> &Array2<f64> {
+        &self.matrix
+    }
+
+    fn true_positives(&self) -> Array1<f64> {
+        self.matrix.diag().to_owned()
+    }
+
+    fn supports(&self) -> Array1<f64> {
+        self.matrix.sum_axis(Axis(1))
+    }
+
+    fn safe_divide(numerator: f64, denominator: f64) -> f64 {
+        if denominator == 0.0 {
+            0.0
+        } else {
+            numerator / denominator
+        }
+    }
+
+    /// Averages the per class ratios `numerators / denominators`, the micro average divides
+    /// the sums of the global counts instead.
+    fn average(
+        &self,
+        numerators: &Array1<f64>,
+        denominators: &Array1<f64>,
+        average: Average,
+    ) -> f64 {
+        if numerators.is_empty() {
+            return 0.0;
+        }
+        let scores: Array1<f64> = numerators
+            .iter()
+            .zip(denominators.iter())
+            .map(|(&n, &d)| ConfusionMatrix::safe_divide(n, d))
+            .collect();
+        match average {
+            Average::Micro => ConfusionMatrix::safe_divide(numerators.sum(), denominators.sum()),
+            Average::Macro => scores.mean().unwrap(),
+            Average::Weighted => {
+                let supports: Array1<f64> = self.supports();
+                ConfusionMatrix::safe_divide((scores * &supports).sum(), supports.sum())
+            }
+        }
+    }
+
+    pub fn precision(&self, average: Average) -> f64 {
+        let predicted: Array1<f64> = self.matrix.sum_axis(Axis(0));
+        self.average(&self.true_positives(), &predicted, average)
+    }
+
+    pub fn recall(&self, average: Average) -> f64 {
+        self.average(&self.true_positives(), &self.supports(), average)
+    }
+
+    pub fn f1_score(&self, average: Average) -> f64 {
+        let predicted: Array1<f64> = self.matrix.sum_axis(Axis(0));
+        self.average(
+            &(self.true_positives() * 2.0),
+            &(predicted + self.supports()),
+            average,
+        )
+    }
+}
+
+impl Metric for ConfusionMatrix {
+    fn update(&mut self, y_pred: &Array2<f64>, y_true: &Array2<f64>) -> Result<(), MlError> {
+        check_shapes(y_pred, y_true)?;
+        if y_pred.shape()[1] < 2 {
+            return Err(MlError::InvalidInput(
+                "array must be one hot encoding".to_string(),
+            ));
+        }
+        let class_number: usize = y_pred.shape()[1];
+        if self.matrix.shape() != [class_number, class_number] {
+            self.matrix = Array2::zeros((class_number, class_number));
+        }
+        let pred_argmax: Array1<usize> = argmax_rows(y_pred)?;
+        let true_argmax: Array1<usize> = argmax_rows(y_true)?;
+        for i in 0..pred_argmax.len() {
+            self.matrix[[true_argmax[i], pred_argmax[i]]] += 1.0;
+        }
+        Ok(())
+    }
+
+    /// Overall accuracy, the full matrix is available through `get_matrix`.
+    fn result(&self) -> f64 {
+        ConfusionMatrix::safe_divide(self.matrix.diag().sum(), self.matrix.sum())
+    }
+
+    fn reset(&mut self) {
+        self.matrix.fill(0.0);
+    }
+
+    fn get_name(&self) -> String {
+        "confusion_matrix".to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::arr2;
+
+    fn generate_test_confusion_matrix() -> ConfusionMatrix {
+        let y_pred: Array2<f64> = arr2(&[
+            [1.0, 0.0, 0.0],
+            [1.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0],
+            [0.0, 1.0, 0.0],
+            [0.0, 1.0, 0.0],
+            [1.0, 0.0, 0.0],
+            [0.0, 0.0, 1.0],
+        ]);
+        let y_true: Array2<f64> = arr2(&[
+            [1.0, 0.0, 0.0],
+            [1.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0],
+            [0.0, 1.0, 0.0],
+            [0.0, 0.0, 1.0],
+            [0.0, 0.0, 1.0],
+            [0.0, 0.0, 1.0],
+        ]);
+        let mut confusion_matrix: ConfusionMatrix = ConfusionMatrix::new();
+        confusion_matrix.update(&y_pred, &y_true).unwrap();
+        confusion_matrix
+    }
+
+    #[test]
+    fn update_should_count_predictions() {
+        let confusion_matrix: ConfusionMatrix = generate_test_confusion_matrix();
+        assert_eq!(
+            confusion_matrix.get_matrix(),
+            arr2(&[[2.0, 0.0, 0.0], [0.0, 2.0, 0.0], [1.0, 1.0, 1.0]])
+        );
+        assert!((confusion_matrix.result() - 5.0 / 7.0).powf(2.0) < 0.00001);
+    }
+
+    #[test]
+    fn reset_should_clear_counts() {
+        let mut confusion_matrix: ConfusionMatrix = generate_test_confusion_matrix();
+        confusion_matrix.reset();
+        assert_eq!(confusion_matrix.get_matrix(), Array2::<f64>::zeros((3, 3)));
+        assert_eq!(confusion_matrix.result(), 0.0);
+        assert_eq!(confusion_matrix.f1_score(Average::Micro), 0.0);
+    }
+
+    #[test]
+    fn precision_should_support_averages() {
+        let confusion_matrix: ConfusionMatrix = generate_test_confusion_matrix();
+        assert!((confusion_matrix.precision(Average::Micro) - 5.0 / 7.0).powf(2.0) < 0.00001);
+        assert!(
+            (confusion_matrix.precision(Average::Macro) - (2.0 / 3.0 + 2.0 / 3.0 + 1.0) / 3.0)
+                .powf(2.0)
+                < 0.00001
+        );
+        assert!(
+            (confusion_matrix.precision(Average::Weighted)
+                - (2.0 * 2.0 / 3.0 + 2.0 * 2.0 / 3.0 + 3.0) / 7.0)
+                .powf(2.0)
+                < 0.00001
+        );
+    }
+
+    #[test]
+    fn recall_should_support_averages() {
+        let confusion_matrix: ConfusionMatrix = generate_test_confusion_matrix();
+        assert!((confusion_matrix.recall(Average::Micro) - 5.0 / 7.0).powf(2.0) < 0.00001);
+        assert!(
+            (confusion_matrix.recall(Average::Macro) - (1.0 + 1.0 + 1.0 / 3.0) / 3.0).powf(2.0)
+                < 0.00001
+        );
+        assert!((confusion_matrix.recall(Average::Weighted) - 5.0 / 7.0).powf(2.0) < 0.00001);
+    }
+
+    #[test]
+    fn f1_score_should_support_averages() {
+        let confusion_matrix: ConfusionMatrix = generate_test_confusion_matrix();
+        assert!((confusion_matrix.f1_score(Average::Micro) - 5.0 / 7.0).powf(2.0) < 0.00001);
+        assert!(
+            (confusion_matrix.f1_score(Average::Macro) - (0.8 + 0.8 + 0.5) / 3.0).powf(2.0)
+                < 0.00001
+        );
+        assert!(
+            (confusion_matrix.f1_score(Average::Weighted)
+                - (0.8 * 2.0 + 0.8 * 2.0 + 0.5 * 3.0) / 7.0)
+                .powf(2.0)
+                < 0.00001
+        );
+    }
+
+    #[test]
+    fn update_should_fail_when_arrays_are_not_one_hot() {
+        let y: Array2<f64> = arr2(&[[0.0], [1.0]]);
+        assert!(matches!(
+            ConfusionMatrix::new().update(&y, &y),
+            Err(MlError::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn get_name_should_return_metric_name() {
+        assert_eq!(ConfusionMatrix::new().get_name(), "confusion_matrix");
+    }
+}
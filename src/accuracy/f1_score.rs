@@ -0,0 +1,60 @@
+use crate::accuracy::accuracy::Metric;
+use crate::accuracy::confusion_matrix::{Average, ConfusionMatrix};
+use crate::utils::error::MlError;
+use ndarray::Array2;
+
+#[derive(Debug)]
+pub struct F1Score {
+    average: Average,
+    confusion_matrix: ConfusionMatrix,
+}
+
+impl F1Score {
+    pub fn build(average: Average) -> F1Score {
+        F1Score {
+            average,
+            confusion_matrix: ConfusionMatrix::new(),
+        }
+    }
+}
+
+impl Metric for F1Score {
+    fn update(&mut self, y_pred: &Array2<f64>, y_true: &Array2<f64>) -> Result<(), MlError> {
+        self.confusion_matrix.update(y_pred, y_true)
+    }
+
+    fn result(&self) -> f64 {
+        self.confusion_matrix.f1_score(self.average)
+    }
+
+    fn reset(&mut self) {
+        self.confusion_matrix.reset();
+    }
+
+    fn get_name(&self) -> String {
+        format!("f1_{}", self.average.get_name())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::arr2;
+
+    #[test]
+    fn f1_score_should_compute_macro_f1_score() {
+        let y_pred: Array2<f64> = arr2(&[[1.0, 0.0], [1.0, 0.0], [0.0, 1.0], [1.0, 0.0]]);
+        let y_true: Array2<f64> = arr2(&[[1.0, 0.0], [0.0, 1.0], [0.0, 1.0], [1.0, 0.0]]);
+        let mut metric: F1Score = F1Score::build(Average::Macro);
+        metric.update(&y_pred, &y_true).unwrap();
+        assert!((metric.result() - (0.8 + 2.0 / 3.0) / 2.0).powf(2.0) < 0.00001);
+        metric.reset();
+        assert_eq!(metric.result(), 0.0);
+    }
+
+    #[test]
+    fn get_name_should_return_metric_name() {
+        assert_eq!(F1Score::build(Average::Micro).get_name(), "f1_micro");
+        assert_eq!(F1Score::build(Average::Weighted).get_name(), "f1_weighted");
+    }
+}
@@ -0,0 +1,102 @@
+use crate::accuracy::accuracy::{check_shapes, Metric};
+use crate::utils::error::MlError;
+use ndarray::{Array2, Axis};
+
+const EPSILON: f64 = 1e-15;
+
+/// Binary cross entropy for a single column, categorical cross entropy for one hot arrays.
+#[derive(Debug, Default)]
+pub struct LogLoss {
+    sum: f64,
+    total: usize,
+}
+
+impl LogLoss {
+    pub fn new() -> LogLoss {
+        LogLoss { sum: 0.0, total: 0 }
+    }
+}
+
+impl Metric for LogLoss {
+    fn update(&mut self, y_pred: &Array2<f64>, y_true: &Array2<f64>) -> Result<(), MlError> {
+        check_shapes(y_pred, y_true)?;
+        let y_pred: Array2<f64> = y_pred.mapv(|p| p.clamp(EPSILON, 1.0 - EPSILON));
+        if y_pred.shape()[1] == 1 {
+            self.sum += y_pred
+                .iter()
+                .zip(y_true.iter())
+                .map(|(&p, &t)| -(t * p.ln() + (1.0 - t) * (1.0 - p).ln()))
+                .sum::<f64>();
+        } else {
+            for (pred_row, true_row) in y_pred.axis_iter(Axis(0)).zip(y_true.axis_iter(Axis(0))) {
+                let pred_sum: f64 = pred_row.sum();
+                self.sum -= pred_row
+                    .iter()
+                    .zip(true_row.iter())
+                    .map(|(&p, &t)| t * (p / pred_sum).ln())
+                    .sum::<f64>();
+            }
+        }
+        self.total += y_pred.shape()[0];
+        Ok(())
+    }
+
+    fn result(&self) -> f64 {
+        if self.total == 0 {
+            return 0.0;
+        }
+        self.sum / self.total as f64
+    }
+
+    fn reset(&mut self) {
+        self.sum = 0.0;
+        self.total = 0;
+    }
+
+    fn get_name(&self) -> String {
+        "log_loss".to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::arr2;
+
+    #[test]
+    fn log_loss_should_compute_binary_cross_entropy() {
+        let mut metric: LogLoss = LogLoss::new();
+        metric
+            .update(&arr2(&[[0.9], [0.2]]), &arr2(&[[1.0], [0.0]]))
+            .unwrap();
+        let target: f64 = -(0.9_f64.ln() + 0.8_f64.ln()) / 2.0;
+        assert!((metric.result() - target).powf(2.0) < 0.00001);
+    }
+
+    #[test]
+    fn log_loss_should_compute_categorical_cross_entropy() {
+        let mut metric: LogLoss = LogLoss::new();
+        metric
+            .update(
+                &arr2(&[[0.7, 0.2, 0.1], [0.1, 0.1, 0.8]]),
+                &arr2(&[[1.0, 0.0, 0.0], [0.0, 1.0, 0.0]]),
+            )
+            .unwrap();
+        let target: f64 = -(0.7_f64.ln() + 0.1_f64.ln()) / 2.0;
+        assert!((metric.result() - target).powf(2.0) < 0.00001);
+        metric.reset();
+        assert_eq!(metric.result(), 0.0);
+    }
+
+    #[test]
+    fn log_loss_should_clip_probabilities() {
+        let mut metric: LogLoss = LogLoss::new();
+        metric.update(&arr2(&[[0.0]]), &arr2(&[[1.0]])).unwrap();
+        assert!(metric.result().is_finite());
+    }
+
+    #[test]
+    fn get_name_should_return_metric_name() {
+        assert_eq!(LogLoss::new().get_name(), "log_loss");
+    }
+}
@@ -1 +1,16 @@
+#[allow(clippy::module_inception)]
 pub mod accuracy;
+pub mod average_precision;
+pub mod binary_accuracy;
+pub mod categorical_accuracy;
+pub mod confusion_matrix;
+pub mod f1_score;
+pub mod log_loss;
+pub mod precision;
+pub mod ranking;
+pub mod recall;
+pub mod regression;
+pub mod regression_score;
+pub mod roc_auc;
+pub mod sparse_categorical_accuracy;
+pub mod top_k_accuracy;
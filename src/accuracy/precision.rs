@@ -0,0 +1,66 @@
+use crate::accuracy::accuracy::Metric;
+use crate::accuracy::confusion_matrix::{Average, ConfusionMatrix};
+use crate::utils::error::MlError;
+use ndarray::Array2;
+
+#[derive(Debug)]
+pub struct Precision {
+    average: Average,
+    confusion_matrix: ConfusionMatrix,
+}
+
+impl Precision {
+    pub fn build(average: Average) -> Precision {
+        Precision {
+            average,
+            confusion_matrix: ConfusionMatrix::new(),
+        }
+    }
+}
+
+impl Metric for Precision {
+    fn update(&mut self, y_pred: &Array2<f64>, y_true: &Array2<f64>) -> Result<(), MlError> {
+        self.confusion_matrix.update(y_pred, y_true)
+    }
+
+    fn result(&self) -> f64 {
+        self.confusion_matrix.precision(self.average)
+    }
+
+    fn reset(&mut self) {
+        self.confusion_matrix.reset();
+    }
+
+    fn get_name(&self) -> String {
+        format!("precision_{}", self.average.get_name())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::arr2;
+
+    #[test]
+    fn precision_should_compute_macro_precision() {
+        let y_pred: Array2<f64> = arr2(&[[1.0, 0.0], [1.0, 0.0], [0.0, 1.0], [1.0, 0.0]]);
+        let y_true: Array2<f64> = arr2(&[[1.0, 0.0], [0.0, 1.0], [0.0, 1.0], [1.0, 0.0]]);
+        let mut metric: Precision = Precision::build(Average::Macro);
+        metric.update(&y_pred, &y_true).unwrap();
+        assert!((metric.result() - (2.0 / 3.0 + 1.0) / 2.0).powf(2.0) < 0.00001);
+        metric.reset();
+        assert_eq!(metric.result(), 0.0);
+    }
+
+    #[test]
+    fn get_name_should_return_metric_name() {
+        assert_eq!(
+            Precision::build(Average::Micro).get_name(),
+            "precision_micro"
+        );
+        assert_eq!(
+            Precision::build(Average::Weighted).get_name(),
+            "precision_weighted"
+        );
+    }
+}
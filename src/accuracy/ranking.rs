@@ -0,0 +1,209 @@
+use crate::accuracy::accuracy::check_shapes;
+use crate::utils::error::MlError;
+use ndarray::{Array2, ArrayView1, Axis};
+
+pub type Curve = (String, Vec<f64>, Vec<f64>);
+
+pub type CurveFunction = fn(&ArrayView1<f64>, &ArrayView1<f64>) -> (Vec<f64>, Vec<f64>);
+
+/// Keeps every batch seen since the last reset, ranking metrics need all the scores at once.
+#[derive(Debug, Default)]
+pub struct PredictionBuffer {
+    y_pred: Array2<f64>,
+    y_true: Array2<f64>,
+}
+
+impl PredictionBuffer {
+    pub fn new() -> PredictionBuffer {
+        PredictionBuffer {
+            y_pred: Array2::zeros((0, 0)),
+            y_true: Array2::zeros((0, 0)),
+        }
+    }
+
+    pub fn push(&mut self, y_pred: &Array2<f64>, y_true: &Array2<f64>) -> Result<(), MlError> {
+        check_shapes(y_pred, y_true)?;
+        if y_pred.iter().any(|p| p.is_nan()) {
+            return Err(MlError::Numeric(
+                "predictions can not be ranked, they contain NaN".to_string(),
+            ));
+        }
+        if self.y_pred.is_empty() {
+            self.y_pred = y_pred.clone();
+            self.y_true = y_true.clone();
+        } else {
+            self.y_pred.append(Axis(0), y_pred.view())?;
+            self.y_true.append(Axis(0), y_true.view())?;
+        }
+        Ok(())
+    }
+
+    pub fn clear(&mut self) {
+        self.y_pred = Array2::zeros((0, 0));
+        self.y_true = Array2::zeros((0, 0));
+    }
+
+    pub fn get_y_pred(&self) -> &Array2<f64> {
+        &self.y_pred
+    }
+
+    pub fn get_y_true(&self) -> &Array2<f64> {
+        &self.y_true
+    }
+}
+
+fn sorted_labels(scores: &ArrayView1<f64>, labels: &ArrayView1<f64>) -> Vec<(f64, bool)> {
+    let mut pairs: Vec<(f64, bool)> = scores
+        .iter()
+        .zip(labels.iter())
+        .map(|(&score, &label)| (score, label >= 0.5))
+        .collect();
+    pairs.sort_by(|a, b| b.0.total_cmp(&a.0));
+    pairs
+}
+
+/// Cumulated (false positives, true positives) at each distinct threshold, by decreasing score.
+fn cumulated_counts(scores: &ArrayView1<f64>, labels: &ArrayView1<f64>) -> Vec<(f64, f64)> {
+    let pairs: Vec<(f64, bool)> = sorted_labels(scores, labels);
+    let mut counts: Vec<(f64, f64)> = vec![];
+    let (mut false_positives, mut true_positives): (f64, f64) = (0.0, 0.0);
+    for i in 0..pairs.len() {
+        if pairs[i].1 {
+            true_positives += 1.0;
+        } else {
+            false_positives += 1.0;
+        }
+        if i == pairs.len() - 1 || pairs[i + 1].0 != pairs[i].0 {
+            counts.push((false_positives, true_positives));
+        }
+    }
+    counts
+}
+
+pub fn roc_curve(scores: &ArrayView1<f64>, labels: &ArrayView1<f64>) -> (Vec<f64>, Vec<f64>) {
+    let counts: Vec<(f64, f64)> = cumulated_counts(scores, labels);
+    let (negatives, positives): (f64, f64) = *counts.last().unwrap_or(&(0.0, 0.0));
+    let mut false_positive_rates: Vec<f64> = vec![0.0];
+    let mut true_positive_rates: Vec<f64> = vec![0.0];
+    for (false_positives, true_positives) in counts {
+        false_positive_rates.push(false_positives / negatives);
+        true_positive_rates.push(true_positives / positives);
+    }
+    (false_positive_rates, true_positive_rates)
+}
+
+pub fn precision_recall_curve(
+    scores: &ArrayView1<f64>,
+    labels: &ArrayView1<f64>,
+) -> (Vec<f64>, Vec<f64>) {
+    let counts: Vec<(f64, f64)> = cumulated_counts(scores, labels);
+    let positives: f64 = counts.last().unwrap_or(&(0.0, 0.0)).1;
+    let mut recalls: Vec<f64> = vec![0.0];
+    let mut precisions: Vec<f64> = vec![1.0];
+    for (false_positives, true_positives) in counts {
+        recalls.push(true_positives / positives);
+        precisions.push(true_positives / (true_positives + false_positives));
+    }
+    (recalls, precisions)
+}
+
+/// Trapezoidal area under the curve.
+pub fn auc(x: &[f64], y: &[f64]) -> f64 {
+    (1..x.len())
+        .map(|i| (x[i] - x[i - 1]) * (y[i] + y[i - 1]) / 2.0)
+        .sum()
+}
+
+/// Step-wise area under the precision recall curve, sum of (R_n - R_n-1) * P_n.
+pub fn average_precision(recalls: &[f64], precisions: &[f64]) -> f64 {
+    (1..recalls.len())
+        .map(|i| (recalls[i] - recalls[i - 1]) * precisions[i])
+        .sum()
+}
+
+fn has_both_classes(labels: &ArrayView1<f64>) -> bool {
+    labels.iter().any(|&label| label >= 0.5) && labels.iter().any(|&label| label < 0.5)
+}
+
+/// Computes one curve per class, a single column is considered as a binary classification.
+pub fn one_vs_rest_curves(
+    y_pred: &Array2<f64>,
+    y_true: &Array2<f64>,
+    curve_function: CurveFunction,
+) -> Vec<Curve> {
+    let class_number: usize = y_pred.shape()[1];
+    (0..class_number)
+        .filter(|&c| has_both_classes(&y_true.index_axis(Axis(1), c)))
+        .map(|c| {
+            let (x, y): (Vec<f64>, Vec<f64>) = curve_function(
+                &y_pred.index_axis(Axis(1), c),
+                &y_true.index_axis(Axis(1), c),
+            );
+            let name: String = if class_number == 1 {
+                "positive".to_string()
+            } else {
+                format!("class {}", c)
+            };
+            (name, x, y)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::{arr1, arr2, Array1};
+
+    #[test]
+    fn prediction_buffer_should_stack_batches() {
+        let mut buffer: PredictionBuffer = PredictionBuffer::new();
+        buffer
+            .push(&arr2(&[[0.2], [0.7]]), &arr2(&[[0.0], [1.0]]))
+            .unwrap();
+        buffer.push(&arr2(&[[0.9]]), &arr2(&[[1.0]])).unwrap();
+        assert_eq!(buffer.get_y_pred(), arr2(&[[0.2], [0.7], [0.9]]));
+        assert_eq!(buffer.get_y_true(), arr2(&[[0.0], [1.0], [1.0]]));
+        buffer.clear();
+        assert!(buffer.get_y_pred().is_empty());
+    }
+
+    #[test]
+    fn roc_curve_should_compute_rates() {
+        let scores: Array1<f64> = arr1(&[0.1, 0.4, 0.35, 0.8]);
+        let labels: Array1<f64> = arr1(&[0.0, 0.0, 1.0, 1.0]);
+        let (false_positive_rates, true_positive_rates) = roc_curve(&scores.view(), &labels.view());
+        assert_eq!(false_positive_rates, vec![0.0, 0.0, 0.5, 0.5, 1.0]);
+        assert_eq!(true_positive_rates, vec![0.0, 0.5, 0.5, 1.0, 1.0]);
+        assert_eq!(auc(&false_positive_rates, &true_positive_rates), 0.75);
+    }
+
+    #[test]
+    fn precision_recall_curve_should_compute_average_precision() {
+        let scores: Array1<f64> = arr1(&[0.1, 0.4, 0.35, 0.8]);
+        let labels: Array1<f64> = arr1(&[0.0, 0.0, 1.0, 1.0]);
+        let (recalls, precisions) = precision_recall_curve(&scores.view(), &labels.view());
+        assert_eq!(recalls, vec![0.0, 0.5, 0.5, 1.0, 1.0]);
+        assert_eq!(precisions, vec![1.0, 1.0, 0.5, 2.0 / 3.0, 0.5]);
+        assert!((average_precision(&recalls, &precisions) - 0.8333333333).powf(2.0) < 0.00001);
+    }
+
+    #[test]
+    fn roc_curve_should_merge_tied_scores() {
+        let scores: Array1<f64> = arr1(&[0.5, 0.5, 0.5, 0.5]);
+        let labels: Array1<f64> = arr1(&[0.0, 1.0, 0.0, 1.0]);
+        let (false_positive_rates, true_positive_rates) = roc_curve(&scores.view(), &labels.view());
+        assert_eq!(false_positive_rates, vec![0.0, 1.0]);
+        assert_eq!(true_positive_rates, vec![0.0, 1.0]);
+        assert_eq!(auc(&false_positive_rates, &true_positive_rates), 0.5);
+    }
+
+    #[test]
+    fn one_vs_rest_curves_should_skip_classes_without_positive() {
+        let y_pred: Array2<f64> = arr2(&[[0.8, 0.1, 0.1], [0.3, 0.6, 0.1], [0.2, 0.2, 0.6]]);
+        let y_true: Array2<f64> = arr2(&[[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [1.0, 0.0, 0.0]]);
+        let curves: Vec<Curve> = one_vs_rest_curves(&y_pred, &y_true, roc_curve);
+        assert_eq!(curves.len(), 2);
+        assert_eq!(curves[0].0, "class 0");
+        assert_eq!(curves[1].0, "class 1");
+    }
+}
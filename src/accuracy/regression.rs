@@ -0,0 +1,253 @@
+use crate::accuracy::accuracy::check_shapes;
+use crate::utils::error::MlError;
+use ndarray::{Array1, Array2, Axis};
+
+const EPSILON: f64 = 1e-10;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RegressionMetric {
+    R2,
+    Mae,
+    Rmse,
+    Mape,
+    ExplainedVariance,
+}
+
+impl RegressionMetric {
+    pub fn get_name(&self) -> String {
+        match self {
+            RegressionMetric::R2 => "r2".to_string(),
+            RegressionMetric::Mae => "mae".to_string(),
+            RegressionMetric::Rmse => "rmse".to_string(),
+            RegressionMetric::Mape => "mape".to_string(),
+            RegressionMetric::ExplainedVariance => "explained_variance".to_string(),
+        }
+    }
+}
+
+/// Mean and sum of squared deviations from the mean per column. Batches are merged with Chan's
+/// parallel update, so large offsets do not cancel out like with a sum of squares.
+#[derive(Debug, Default)]
+struct RunningMoments {
+    mean: Array1<f64>,
+    squared_deviations: Array1<f64>,
+}
+
+impl RunningMoments {
+    fn new(columns: usize) -> RunningMoments {
+        RunningMoments {
+            mean: Array1::zeros(columns),
+            squared_deviations: Array1::zeros(columns),
+        }
+    }
+
+    /// Merges the non-empty batch `x` into the moments of the `count` rows seen so far.
+    fn update(&mut self, count: f64, x: &Array2<f64>) {
+        let batch_count: f64 = x.shape()[0] as f64;
+        let total: f64 = count + batch_count;
+        let batch_mean: Array1<f64> = x.sum_axis(Axis(0)) / batch_count;
+        let batch_squared_deviations: Array1<f64> =
+            (x - &batch_mean).mapv(|d| d * d).sum_axis(Axis(0));
+        let delta: Array1<f64> = batch_mean - &self.mean;
+        self.mean += &(&delta * (batch_count / total));
+        self.squared_deviations +=
+            &(batch_squared_deviations + delta.mapv(|d| d * d) * (count * batch_count / total));
+    }
+
+    fn variance(&self, count: f64) -> Array1<f64> {
+        &self.squared_deviations / count
+    }
+}
+
+/// Running statistics per output column, enough to compute every regression metric without
+/// keeping the predictions.
+#[derive(Debug, Default)]
+pub struct RegressionStatistics {
+    count: f64,
+    y_true: RunningMoments,
+    error: RunningMoments,
+    sum_error_square: Array1<f64>,
+    sum_absolute_error: Array1<f64>,
+    sum_absolute_percentage_error: Array1<f64>,
+}
+
+impl RegressionStatistics {
+    pub fn new() -> RegressionStatistics {
+        RegressionStatistics::with_columns(0)
+    }
+
+    fn with_columns(columns: usize) -> RegressionStatistics {
+        RegressionStatistics {
+            count: 0.0,
+            y_true: RunningMoments::new(columns),
+            error: RunningMoments::new(columns),
+            sum_error_square: Array1::zeros(columns),
+            sum_absolute_error: Array1::zeros(columns),
+            sum_absolute_percentage_error: Array1::zeros(columns),
+        }
+    }
+
+    pub fn update(&mut self, y_pred: &Array2<f64>, y_true: &Array2<f64>) -> Result<(), MlError> {
+        check_shapes(y_pred, y_true)?;
+        if self.sum_error_square.len() != y_true.shape()[1] {
+            *self = RegressionStatistics::with_columns(y_true.shape()[1]);
+        }
+        if y_true.shape()[0] == 0 {
+            return Ok(());
+        }
+        let error: Array2<f64> = y_true - y_pred;
+        self.y_true.update(self.count, y_true);
+        self.error.update(self.count, &error);
+        self.count += y_true.shape()[0] as f64;
+        self.sum_error_square += &error.mapv(|e| e * e).sum_axis(Axis(0));
+        self.sum_absolute_error += &error.mapv(f64::abs).sum_axis(Axis(0));
+        self.sum_absolute_percentage_error +=
+            &(error.mapv(f64::abs) / y_true.mapv(|y| y.abs().max(EPSILON))).sum_axis(Axis(0));
+        Ok(())
+    }
+
+    pub fn reset(&mut self) {
+        *self = RegressionStatistics::new();
+    }
+
+    fn uniform_average(&self, scores: Array1<f64>) -> f64 {
+        if self.count == 0.0 {
+            return 0.0;
+        }
+        scores.mean().unwrap_or(0.0)
+    }
+
+    pub fn mean_absolute_error(&self) -> f64 {
+        self.uniform_average(&self.sum_absolute_error / self.count)
+    }
+
+    pub fn root_mean_squared_error(&self) -> f64 {
+        self.uniform_average(&self.sum_error_square / self.count)
+            .sqrt()
+    }
+
+    pub fn mean_absolute_percentage_error(&self) -> f64 {
+        self.uniform_average(&self.sum_absolute_percentage_error / self.count)
+    }
+
+    pub fn r2_score(&self) -> f64 {
+        let total_variance: Array1<f64> = self.y_true.variance(self.count);
+        let residual_variance: Array1<f64> = &self.sum_error_square / self.count;
+        self.uniform_average(RegressionStatistics::variance_ratio(
+            &residual_variance,
+            &total_variance,
+        ))
+    }
+
+    pub fn explained_variance(&self) -> f64 {
+        let total_variance: Array1<f64> = self.y_true.variance(self.count);
+        let error_variance: Array1<f64> = self.error.variance(self.count);
+        self.uniform_average(RegressionStatistics::variance_ratio(
+            &error_variance,
+            &total_variance,
+        ))
+    }
+
+    pub fn result(&self, metric: RegressionMetric) -> f64 {
+        match metric {
+            RegressionMetric::R2 => self.r2_score(),
+            RegressionMetric::Mae => self.mean_absolute_error(),
+            RegressionMetric::Rmse => self.root_mean_squared_error(),
+            RegressionMetric::Mape => self.mean_absolute_percentage_error(),
+            RegressionMetric::ExplainedVariance => self.explained_variance(),
+        }
+    }
+
+    fn variance_ratio(numerator: &Array1<f64>, denominator: &Array1<f64>) -> Array1<f64> {
+        numerator
+            .iter()
+            .zip(denominator.iter())
+            .map(|(&n, &d)| {
+                if d.abs() < EPSILON {
+                    if n.abs() < EPSILON {
+                        1.0
+                    } else {
+                        0.0
+                    }
+                } else {
+                    1.0 - n / d
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::arr2;
+
+    fn generate_test_statistics() -> RegressionStatistics {
+        let mut statistics: RegressionStatistics = RegressionStatistics::new();
+        statistics
+            .update(&arr2(&[[2.5], [0.0]]), &arr2(&[[3.0], [-0.5]]))
+            .unwrap();
+        statistics
+            .update(&arr2(&[[2.0], [8.0]]), &arr2(&[[2.0], [7.0]]))
+            .unwrap();
+        statistics
+    }
+
+    #[test]
+    fn regression_statistics_should_compute_errors() {
+        let statistics: RegressionStatistics = generate_test_statistics();
+        assert!((statistics.mean_absolute_error() - 0.5).powf(2.0) < 0.00001);
+        assert!((statistics.root_mean_squared_error() - 0.375_f64.sqrt()).powf(2.0) < 0.00001);
+        assert!(
+            (statistics.mean_absolute_percentage_error() - 0.3273809523809524).powf(2.0) < 0.00001
+        );
+    }
+
+    #[test]
+    fn regression_statistics_should_compute_scores() {
+        let statistics: RegressionStatistics = generate_test_statistics();
+        assert!((statistics.r2_score() - 0.9486081370449679).powf(2.0) < 0.00001);
+        assert!((statistics.explained_variance() - 0.9571734475374732).powf(2.0) < 0.00001);
+    }
+
+    #[test]
+    fn regression_statistics_should_average_columns() {
+        let mut statistics: RegressionStatistics = RegressionStatistics::new();
+        statistics
+            .update(
+                &arr2(&[[1.0, 2.0], [2.0, 2.0]]),
+                &arr2(&[[1.0, 1.0], [2.0, 3.0]]),
+            )
+            .unwrap();
+        assert!((statistics.mean_absolute_error() - 0.5).powf(2.0) < 0.00001);
+        assert!((statistics.r2_score() - 0.5).powf(2.0) < 0.00001);
+    }
+
+    #[test]
+    fn regression_statistics_should_keep_precision_for_offset_targets() {
+        let mut statistics: RegressionStatistics = RegressionStatistics::new();
+        let offset: f64 = 1e9;
+        statistics
+            .update(
+                &arr2(&[[2.5], [0.0]]).mapv(|y| y + offset),
+                &arr2(&[[3.0], [-0.5]]).mapv(|y| y + offset),
+            )
+            .unwrap();
+        statistics
+            .update(
+                &arr2(&[[2.0], [8.0]]).mapv(|y| y + offset),
+                &arr2(&[[2.0], [7.0]]).mapv(|y| y + offset),
+            )
+            .unwrap();
+        assert!((statistics.r2_score() - 0.9486081370449679).powf(2.0) < 0.00001);
+        assert!((statistics.explained_variance() - 0.9571734475374732).powf(2.0) < 0.00001);
+    }
+
+    #[test]
+    fn reset_should_clear_statistics() {
+        let mut statistics: RegressionStatistics = generate_test_statistics();
+        statistics.reset();
+        assert_eq!(statistics.mean_absolute_error(), 0.0);
+        assert_eq!(statistics.r2_score(), 0.0);
+    }
+}
@@ -0,0 +1,84 @@
+use crate::accuracy::accuracy::Metric;
+use crate::accuracy::regression::{RegressionMetric, RegressionStatistics};
+use crate::utils::error::MlError;
+use ndarray::Array2;
+
+#[derive(Debug)]
+pub struct RegressionScore {
+    metric: RegressionMetric,
+    statistics: RegressionStatistics,
+}
+
+impl RegressionScore {
+    pub fn build(metric: RegressionMetric) -> RegressionScore {
+        RegressionScore {
+            metric,
+            statistics: RegressionStatistics::new(),
+        }
+    }
+}
+
+impl Metric for RegressionScore {
+    fn update(&mut self, y_pred: &Array2<f64>, y_true: &Array2<f64>) -> Result<(), MlError> {
+        self.statistics.update(y_pred, y_true)
+    }
+
+    fn result(&self) -> f64 {
+        self.statistics.result(self.metric)
+    }
+
+    fn reset(&mut self) {
+        self.statistics.reset();
+    }
+
+    fn get_name(&self) -> String {
+        self.metric.get_name()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::arr2;
+
+    #[test]
+    fn regression_score_should_compute_every_metric() {
+        let expected: [(RegressionMetric, f64); 5] = [
+            (RegressionMetric::R2, 0.9486081370449679),
+            (RegressionMetric::Mae, 0.5),
+            (RegressionMetric::Rmse, 0.375_f64.sqrt()),
+            (RegressionMetric::Mape, 0.3273809523809524),
+            (RegressionMetric::ExplainedVariance, 0.9571734475374732),
+        ];
+        for (metric, value) in expected {
+            let mut score: RegressionScore = RegressionScore::build(metric);
+            score
+                .update(
+                    &arr2(&[[2.5], [0.0], [2.0], [8.0]]),
+                    &arr2(&[[3.0], [-0.5], [2.0], [7.0]]),
+                )
+                .unwrap();
+            assert!((score.result() - value).powf(2.0) < 0.00001);
+            score.reset();
+            assert_eq!(score.result(), 0.0);
+        }
+    }
+
+    #[test]
+    fn get_name_should_return_metric_name() {
+        let names: Vec<String> = [
+            RegressionMetric::R2,
+            RegressionMetric::Mae,
+            RegressionMetric::Rmse,
+            RegressionMetric::Mape,
+            RegressionMetric::ExplainedVariance,
+        ]
+        .iter()
+        .map(|&metric| RegressionScore::build(metric).get_name())
+        .collect();
+        assert_eq!(
+            names,
+            vec!["r2", "mae", "rmse", "mape", "explained_variance"]
+        );
+    }
+}
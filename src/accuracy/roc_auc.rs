@@ -0,0 +1,84 @@
+use crate::accuracy::accuracy::Metric;
+use crate::accuracy::ranking::{auc, one_vs_rest_curves, roc_curve, Curve, PredictionBuffer};
+use crate::utils::error::MlError;
+use ndarray::Array2;
+
+/// Area under the ROC curve, averaged over classes (one vs rest) when arrays are one hot encoded.
+#[derive(Debug, Default)]
+pub struct RocAuc {
+    buffer: PredictionBuffer,
+}
+
+impl RocAuc {
+    pub fn new() -> RocAuc {
+        RocAuc {
+            buffer: PredictionBuffer::new(),
+        }
+    }
+
+    pub fn curves(&self) -> Vec<Curve> {
+        one_vs_rest_curves(
+            self.buffer.get_y_pred(),
+            self.buffer.get_y_true(),
+            roc_curve,
+        )
+    }
+}
+
+impl Metric for RocAuc {
+    fn update(&mut self, y_pred: &Array2<f64>, y_true: &Array2<f64>) -> Result<(), MlError> {
+        self.buffer.push(y_pred, y_true)
+    }
+
+    fn result(&self) -> f64 {
+        let curves: Vec<Curve> = self.curves();
+        if curves.is_empty() {
+            return 0.0;
+        }
+        curves.iter().map(|(_, x, y)| auc(x, y)).sum::<f64>() / curves.len() as f64
+    }
+
+    fn reset(&mut self) {
+        self.buffer.clear();
+    }
+
+    fn get_name(&self) -> String {
+        "roc_auc".to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::arr2;
+
+    #[test]
+    fn roc_auc_should_compute_binary_auc() {
+        let mut metric: RocAuc = RocAuc::new();
+        metric
+            .update(&arr2(&[[0.1], [0.4]]), &arr2(&[[0.0], [0.0]]))
+            .unwrap();
+        metric
+            .update(&arr2(&[[0.35], [0.8]]), &arr2(&[[1.0], [1.0]]))
+            .unwrap();
+        assert_eq!(metric.result(), 0.75);
+        assert_eq!(metric.curves()[0].0, "positive");
+        metric.reset();
+        assert_eq!(metric.result(), 0.0);
+    }
+
+    #[test]
+    fn roc_auc_should_average_one_vs_rest() {
+        let y_pred: Array2<f64> = arr2(&[[0.9, 0.1], [0.6, 0.4], [0.3, 0.7], [0.2, 0.8]]);
+        let y_true: Array2<f64> = arr2(&[[1.0, 0.0], [0.0, 1.0], [1.0, 0.0], [0.0, 1.0]]);
+        let mut metric: RocAuc = RocAuc::new();
+        metric.update(&y_pred, &y_true).unwrap();
+        assert_eq!(metric.curves().len(), 2);
+        assert_eq!(metric.result(), 0.75);
+    }
+
+    #[test]
+    fn get_name_should_return_metric_name() {
+        assert_eq!(RocAuc::new().get_name(), "roc_auc");
+    }
+}
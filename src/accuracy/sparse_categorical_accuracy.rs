@@ -0,0 +1,155 @@
+use crate::accuracy::accuracy::{argmax_rows, Metric};
+use crate::utils::error::MlError;
+use ndarray::{Array1, Array2};
+
+/// Accuracy against class indices given in a single column. `fit` requires targets shaped like
+/// the network output, so the metric is meant for `evaluate` with the labels of a classifier.
+#[derive(Debug, Default)]
+pub struct SparseCategoricalAccuracy {
+    correct: usize,
+    total: usize,
+}
+
+impl SparseCategoricalAccuracy {
+    pub fn new() -> SparseCategoricalAccuracy {
+        SparseCategoricalAccuracy {
+            correct: 0,
+            total: 0,
+        }
+    }
+}
+
+impl Metric for SparseCategoricalAccuracy {
+    fn update(&mut self, y_pred: &Array2<f64>, y_true: &Array2<f64>) -> Result<(), MlError> {
+        if y_true.shape()[1] != 1 {
+            return Err(MlError::ShapeMismatch(format!(
+                "true array must have only one column, actually: {}",
+                y_true.shape()[1]
+            )));
+        }
+        if y_pred.shape()[0] != y_true.shape()[0] {
+            return Err(MlError::ShapeMismatch(format!(
+                "arrays must have the same row length: {} != {}",
+                y_pred.shape()[0],
+                y_true.shape()[0]
+            )));
+        }
+        let classes: usize = y_pred.shape()[1];
+        if let Some(label) = y_true
+            .iter()
+            .find(|&&label| !(label >= 0.0 && label < classes as f64 && label.fract() == 0.0))
+        {
+            return Err(MlError::InvalidInput(format!(
+                "labels must be integers in [0, {}), actually: {}",
+                classes, label
+            )));
+        }
+        let pred_argmax: Array1<usize> = argmax_rows(y_pred)?;
+        for i in 0..pred_argmax.len() {
+            if pred_argmax[i] == y_true[[i, 0]] as usize {
+                self.correct += 1;
+            }
+        }
+        self.total += pred_argmax.len();
+        Ok(())
+    }
+
+    fn result(&self) -> f64 {
+        if self.total == 0 {
+            return 0.0;
+        }
+        self.correct as f64 / self.total as f64
+    }
+
+    fn reset(&mut self) {
+        self.correct = 0;
+        self.total = 0;
+    }
+
+    fn get_name(&self) -> String {
+        "sparse_categorical_accuracy".to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::activation::softmax::Softmax;
+    use crate::layer::activation_layer::ActivationLayer;
+    use crate::layer::fc_layer::FCLayer;
+    use crate::loss::categorical_cross_entropy::CategoricalCrossEntropy;
+    use crate::network::mlp::Mlp;
+    use ndarray::arr2;
+
+    #[test]
+    fn sparse_categorical_accuracy_should_compute_accuracy() {
+        let y_pred: Array2<f64> = arr2(&[
+            [0.1, 0.2, 0.7],
+            [0.0, 0.9, 0.1],
+            [0.8, 0.1, 0.1],
+            [0.3, 0.3, 0.4],
+        ]);
+        let y_true: Array2<f64> = arr2(&[[1.0], [1.0], [0.0], [0.0]]);
+        let mut metric: SparseCategoricalAccuracy = SparseCategoricalAccuracy::new();
+        metric.update(&y_pred, &y_true).unwrap();
+        assert_eq!(metric.result(), 0.5);
+    }
+
+    #[test]
+    fn sparse_categorical_accuracy_should_fail_when_true_array_is_one_hot() {
+        let y_pred: Array2<f64> = arr2(&[[0.1, 0.2, 0.7]]);
+        let y_true: Array2<f64> = arr2(&[[0.0, 0.0, 1.0]]);
+        let error: MlError = SparseCategoricalAccuracy::new()
+            .update(&y_pred, &y_true)
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "shape mismatch: true array must have only one column, actually: 3"
+        );
+    }
+
+    #[test]
+    fn sparse_categorical_accuracy_should_fail_when_labels_are_not_class_indices() {
+        let y_pred: Array2<f64> = arr2(&[[0.1, 0.2, 0.7], [0.0, 0.9, 0.1]]);
+        for label in [-1.0, 0.5, 3.0, f64::NAN] {
+            let y_true: Array2<f64> = arr2(&[[2.0], [label]]);
+            assert!(matches!(
+                SparseCategoricalAccuracy::new().update(&y_pred, &y_true),
+                Err(MlError::InvalidInput(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn evaluate_should_compare_network_predictions_with_labels() {
+        let x: Array2<f64> = arr2(&[[1.0, 0.0], [0.0, 1.0], [2.0, 1.0]]);
+        let labels: Array2<f64> = arr2(&[[0.0], [1.0], [1.0]]);
+        let mut mlp: Mlp = Mlp::build(Box::new(CategoricalCrossEntropy));
+        mlp.add_layer(Box::new(
+            FCLayer::from_json(
+                "{\"weights\":[1.0,0.0,0.0,1.0],\"bias\":[0.0,0.0],\"shape\":[2,2]}",
+            )
+            .unwrap(),
+        ));
+        mlp.add_layer(Box::new(ActivationLayer::build(Box::new(Softmax))));
+
+        let result: Vec<(String, f64)> = mlp
+            .evaluate(
+                &x,
+                &labels,
+                &mut [Box::new(SparseCategoricalAccuracy::new())],
+            )
+            .unwrap();
+
+        assert_eq!(result[0].0, "sparse_categorical_accuracy");
+        assert!((result[0].1 - 2.0 / 3.0).powf(2.0) < 0.00001);
+    }
+
+    #[test]
+    fn get_name_should_return_metric_name() {
+        assert_eq!(
+            SparseCategoricalAccuracy::new().get_name(),
+            "sparse_categorical_accuracy"
+        );
+    }
+}
@@ -0,0 +1,104 @@
+use crate::accuracy::accuracy::{argmax_rows, check_shapes, Metric};
+use crate::utils::error::MlError;
+use ndarray::{Array1, Array2, Axis};
+
+#[derive(Debug)]
+pub struct TopKAccuracy {
+    k: usize,
+    correct: usize,
+    total: usize,
+}
+
+impl TopKAccuracy {
+    pub fn build(k: usize) -> TopKAccuracy {
+        TopKAccuracy {
+            k,
+            correct: 0,
+            total: 0,
+        }
+    }
+}
+
+impl Metric for TopKAccuracy {
+    fn update(&mut self, y_pred: &Array2<f64>, y_true: &Array2<f64>) -> Result<(), MlError> {
+        check_shapes(y_pred, y_true)?;
+        if y_pred.iter().any(|p| !p.is_finite()) {
+            return Err(MlError::Numeric(
+                "predictions can not be ranked, they contain non-finite values".to_string(),
+            ));
+        }
+        let true_classes: Array1<usize> = argmax_rows(y_true)?;
+        for (pred_row, &true_class) in y_pred.axis_iter(Axis(0)).zip(true_classes.iter()) {
+            let true_score: f64 = pred_row[true_class];
+            let rank: usize = pred_row.iter().filter(|&&p| p > true_score).count();
+            if rank < self.k {
+                self.correct += 1;
+            }
+        }
+        self.total += y_pred.shape()[0];
+        Ok(())
+    }
+
+    fn result(&self) -> f64 {
+        if self.total == 0 {
+            return 0.0;
+        }
+        self.correct as f64 / self.total as f64
+    }
+
+    fn reset(&mut self) {
+        self.correct = 0;
+        self.total = 0;
+    }
+
+    fn get_name(&self) -> String {
+        format!("top_{}_accuracy", self.k)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::arr2;
+
+    #[test]
+    fn top_k_accuracy_should_compute_accuracy() {
+        let y_pred: Array2<f64> = arr2(&[
+            [0.1, 0.2, 0.7],
+            [0.5, 0.4, 0.1],
+            [0.1, 0.3, 0.6],
+            [0.2, 0.7, 0.1],
+        ]);
+        let y_true: Array2<f64> = arr2(&[
+            [0.0, 1.0, 0.0],
+            [0.0, 1.0, 0.0],
+            [1.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0],
+        ]);
+        let mut top_1: TopKAccuracy = TopKAccuracy::build(1);
+        let mut top_2: TopKAccuracy = TopKAccuracy::build(2);
+        top_1.update(&y_pred, &y_true).unwrap();
+        top_2.update(&y_pred, &y_true).unwrap();
+        assert_eq!(top_1.result(), 0.25);
+        assert_eq!(top_2.result(), 0.75);
+    }
+
+    #[test]
+    fn top_k_accuracy_should_fail_when_predictions_are_not_finite() {
+        let y_true: Array2<f64> = arr2(&[[0.0, 1.0, 0.0]]);
+        for score in [f64::NAN, f64::INFINITY] {
+            let y_pred: Array2<f64> = arr2(&[[0.1, score, 0.7]]);
+            let mut metric: TopKAccuracy = TopKAccuracy::build(1);
+            assert!(matches!(
+                metric.update(&y_pred, &y_true),
+                Err(MlError::Numeric(_))
+            ));
+            assert_eq!(metric.result(), 0.0);
+        }
+    }
+
+    #[test]
+    fn get_name_should_return_metric_name() {
+        assert_eq!(TopKAccuracy::build(3).get_name(), "top_3_accuracy");
+    }
+}
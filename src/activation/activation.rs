@@ -2,57 +2,68 @@ use crate::activation::relu::Relu;
 use crate::activation::sigmoid::Sigmoid;
 use crate::activation::softmax::Softmax;
 use crate::activation::tanh::Tanh;
+use crate::utils::error::MlError;
+use crate::utils::float::MlFloat;
 use ndarray::Array2;
-use std::io::{Error, ErrorKind};
 
-pub trait Activation {
-    fn function(&self, x: &Array2<f64>) -> Array2<f64>;
+pub trait Activation<F: MlFloat = f64>: Send + Sync {
+    fn function(&self, x: &Array2<F>) -> Array2<F>;
 
-    fn derivative(&self, x: &Array2<f64>) -> Array2<f64>;
+    fn derivative(&self, x: &Array2<F>) -> Array2<F>;
+
+    /// Error of the input `x` from the error `y` of the output. The default multiplies `y` by the
+    /// derivative, which only holds for functions applied to each element independently.
+    fn backward(&self, x: &Array2<F>, y: &Array2<F>) -> Array2<F> {
+        self.derivative(x) * y
+    }
 
     fn get_name(&self) -> String;
+
+    fn clone_activation(&self) -> Box<dyn Activation<F>>;
 }
 
-pub fn from_string(name: String) -> Result<Box<dyn Activation>, Error> {
+pub fn from_string<F: MlFloat>(name: String) -> Result<Box<dyn Activation<F>>, MlError> {
     match name.to_uppercase().as_str() {
         "TANH" => Ok(Box::new(Tanh)),
         "RELU" => Ok(Box::new(Relu)),
         "SIGMOID" => Ok(Box::new(Sigmoid)),
         "SOFTMAX" => Ok(Box::new(Softmax)),
-        _ => Err(Error::new(
-            ErrorKind::InvalidInput,
-            format!("unknown activation '{}'", name),
-        )),
+        _ => Err(MlError::unknown_component("activation", &name)),
     }
 }
 
 #[cfg(test)]
+#[allow(clippy::unused_unit)]
 mod tests {
     use super::*;
 
     #[test]
     fn from_string_should_return_activation() -> () {
         assert_eq!(
-            from_string("Tanh".to_string()).unwrap().get_name(),
+            from_string::<f64>("Tanh".to_string()).unwrap().get_name(),
             "Tanh".to_string()
         );
         assert_eq!(
-            from_string("Relu".to_string()).unwrap().get_name(),
+            from_string::<f64>("Relu".to_string()).unwrap().get_name(),
             "Relu".to_string()
         );
         assert_eq!(
-            from_string("Sigmoid".to_string()).unwrap().get_name(),
+            from_string::<f32>("Sigmoid".to_string())
+                .unwrap()
+                .get_name(),
             "Sigmoid".to_string()
         );
         assert_eq!(
-            from_string("Softmax".to_string()).unwrap().get_name(),
+            from_string::<f64>("Softmax".to_string())
+                .unwrap()
+                .get_name(),
             "Softmax".to_string()
         );
     }
 
     #[test]
-    #[should_panic(expected = "unknown activation 'Unknown'")]
     fn from_string_should_raise_error_when_name_is_unknown() -> () {
-        from_string("Unknown".to_string()).unwrap();
+        let error: MlError = from_string::<f64>("Unknown".to_string()).err().unwrap();
+        assert_eq!(error.to_string(), "unknown activation 'Unknown'");
     }
 }
@@ -1,3 +1,4 @@
+#[allow(clippy::module_inception)]
 pub mod activation;
 pub mod relu;
 pub mod sigmoid;
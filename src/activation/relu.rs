@@ -1,26 +1,33 @@
 use crate::activation::activation::Activation;
+use crate::utils::float::MlFloat;
 use ndarray::Array2;
 
 #[derive(Debug)]
 pub struct Relu;
 
-impl Activation for Relu {
-    fn function(&self, x: &Array2<f64>) -> Array2<f64> {
-        x.map(|&x| if x > 0.0 { x } else { 0.0 })
+impl<F: MlFloat> Activation<F> for Relu {
+    fn function(&self, x: &Array2<F>) -> Array2<F> {
+        x.map(|&x| if x > F::zero() { x } else { F::zero() })
     }
 
-    fn derivative(&self, x: &Array2<f64>) -> Array2<f64> {
-        x.map(|&x| if x > 0.0 { 1.0 } else { 0.0 })
+    fn derivative(&self, x: &Array2<F>) -> Array2<F> {
+        x.map(|&x| if x > F::zero() { F::one() } else { F::zero() })
     }
 
     fn get_name(&self) -> String {
         "Relu".to_string()
     }
+
+    fn clone_activation(&self) -> Box<dyn Activation<F>> {
+        Box::new(Relu)
+    }
 }
 
 #[cfg(test)]
+#[allow(clippy::unused_unit)]
 mod tests {
     use super::*;
+    use crate::utils::gradient_check::{check_activation, GradientCheck};
     use ndarray::arr2;
 
     #[test]
@@ -41,8 +48,15 @@ mod tests {
         assert_eq!(output, target);
     }
 
+    #[test]
+    fn activation_relu_backward_should_pass_gradient_check() {
+        let input: Array2<f64> = arr2(&[[1.0, -2.0, 3.0, 0.5], [-0.5, 0.25, -1.5, 2.5]]);
+        let check: GradientCheck = check_activation(&Relu, &input, 0.0001).unwrap();
+        assert!(check.relative_error < 0.0001);
+    }
+
     #[test]
     fn get_name_should_return_struct_name() -> () {
-        assert_eq!(Relu.get_name(), "Relu");
+        assert_eq!(Activation::<f64>::get_name(&Relu), "Relu");
     }
 }
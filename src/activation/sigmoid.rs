@@ -1,27 +1,34 @@
 use crate::activation::activation::Activation;
+use crate::utils::float::MlFloat;
 use ndarray::Array2;
 
 #[derive(Debug)]
 pub struct Sigmoid;
 
-impl Activation for Sigmoid {
-    fn function(&self, x: &Array2<f64>) -> Array2<f64> {
-        x.map(|&x| 1.0 / (1.0 + (x * -1.0).exp()))
+impl<F: MlFloat> Activation<F> for Sigmoid {
+    fn function(&self, x: &Array2<F>) -> Array2<F> {
+        x.map(|&x| F::one() / (F::one() + (-x).exp()))
     }
 
-    fn derivative(&self, x: &Array2<f64>) -> Array2<f64> {
-        let sigmoid: Array2<f64> = self.function(x);
-        &sigmoid * (1.0 - &sigmoid)
+    fn derivative(&self, x: &Array2<F>) -> Array2<F> {
+        let sigmoid: Array2<F> = self.function(x);
+        sigmoid.mapv(|s| s * (F::one() - s))
     }
 
     fn get_name(&self) -> String {
         "Sigmoid".to_string()
     }
+
+    fn clone_activation(&self) -> Box<dyn Activation<F>> {
+        Box::new(Sigmoid)
+    }
 }
 
 #[cfg(test)]
+#[allow(clippy::unused_unit)]
 mod tests {
     use super::*;
+    use crate::utils::gradient_check::{check_activation, GradientCheck};
     use ndarray::arr2;
 
     #[test]
@@ -58,8 +65,15 @@ mod tests {
         }
     }
 
+    #[test]
+    fn activation_sigmoid_backward_should_pass_gradient_check() {
+        let input: Array2<f64> = arr2(&[[1.0, -2.0, 3.0, 0.5], [-0.5, 0.25, -1.5, 2.5]]);
+        let check: GradientCheck = check_activation(&Sigmoid, &input, 0.0001).unwrap();
+        assert!(check.relative_error < 0.0001);
+    }
+
     #[test]
     fn get_name_should_return_struct_name() -> () {
-        assert_eq!(Sigmoid.get_name(), "Sigmoid");
+        assert_eq!(Activation::<f64>::get_name(&Sigmoid), "Sigmoid");
     }
 }
@@ -1,35 +1,49 @@
 use crate::activation::activation::Activation;
-use ndarray::Array2;
+use crate::utils::float::MlFloat;
+use ndarray::{Array2, Axis};
 
 #[derive(Debug)]
 pub struct Softmax;
 
-impl Activation for Softmax {
-    fn function(&self, x: &Array2<f64>) -> Array2<f64> {
-        if x.shape()[0] != 1 {
-            panic!(
-                "array must have only one row to apply softmax, actually {}",
-                x.shape()[0]
-            )
-        }
-        let exp_sum: f64 = x.map(|&x| x.exp()).sum();
-        x.map(|&x| x.exp() / exp_sum)
+impl<F: MlFloat> Activation<F> for Softmax {
+    fn function(&self, x: &Array2<F>) -> Array2<F> {
+        let mut output: Array2<F> = x.clone();
+        output.axis_iter_mut(Axis(0)).for_each(|mut row| {
+            let max: F = row.fold(F::neg_infinity(), |max, &x| max.max(x));
+            row.mapv_inplace(|x| (x - max).exp());
+            let exp_sum: F = row.sum();
+            row.mapv_inplace(|x| x / exp_sum);
+        });
+        output
+    }
+
+    /// Diagonal of the Jacobian of each row, `backward` applies the full Jacobian.
+    fn derivative(&self, x: &Array2<F>) -> Array2<F> {
+        let softmax: Array2<F> = self.function(x);
+        softmax.mapv(|s| s * (F::one() - s))
     }
 
-    fn derivative(&self, x: &Array2<f64>) -> Array2<f64> {
-        let eye: Array2<f64> = Array2::eye(x.shape()[0]);
-        let softmax: Array2<f64> = self.function(x);
-        &softmax * (eye - softmax.t())
+    /// Product of `y` with the Jacobian of each row, `s * (y - sum(y * s))`.
+    fn backward(&self, x: &Array2<F>, y: &Array2<F>) -> Array2<F> {
+        let softmax: Array2<F> = self.function(x);
+        let dot: Array2<F> = (&softmax * y).sum_axis(Axis(1)).insert_axis(Axis(1));
+        &softmax * &(y - &dot)
     }
 
     fn get_name(&self) -> String {
         "Softmax".to_string()
     }
+
+    fn clone_activation(&self) -> Box<dyn Activation<F>> {
+        Box::new(Softmax)
+    }
 }
 
 #[cfg(test)]
+#[allow(clippy::unused_unit)]
 mod tests {
     use super::*;
+    use crate::utils::gradient_check::{check_activation, GradientCheck};
     use ndarray::arr2;
 
     #[test]
@@ -47,38 +61,63 @@ mod tests {
     }
 
     #[test]
-    #[should_panic(expected = "array must have only one row to apply softmax, actually 3")]
-    fn activation_softmax_function_should_panic_when_array_has_not_one_row() -> () {
+    fn activation_softmax_function_should_apply_on_each_row() {
         let softmax: Softmax = Softmax;
-        let input: Array2<f64> = arr2(&[
-            [1.0, 2.0, 3.0, 6.0],
-            [1.0, 2.0, 3.0, 6.0],
-            [1.0, 2.0, 3.0, 6.0],
+        let input: Array2<f64> = arr2(&[[1.0, 2.0, 3.0, 6.0], [0.0, 0.0, 0.0, 0.0]]);
+        let output: Array2<f64> = softmax.function(&input);
+        let target: Array2<f64> = arr2(&[
+            [0.00626879, 0.01704033, 0.04632042, 0.93037047],
+            [0.25, 0.25, 0.25, 0.25],
         ]);
-        softmax.function(&input);
+        assert_eq!(output.shape(), target.shape());
+        let output_vec: Vec<f64> = output.into_raw_vec();
+        let target_vec: Vec<f64> = target.into_raw_vec();
+        for i in 0..8 {
+            assert!((output_vec[i] - target_vec[i]).powf(2.0) < 0.00001)
+        }
     }
 
     #[test]
     fn activation_softmax_derivative() -> () {
         let softmax: Softmax = Softmax;
-        let input: Array2<f64> = arr2(&[[0.00626879, 0.01704033, 0.04632042, 0.93037047]]);
+        let input: Array2<f64> = arr2(&[[1.0, 2.0, 3.0, 6.0]]);
         let output: Array2<f64> = softmax.derivative(&input);
+        let target: Array2<f64> = arr2(&[[0.00622949, 0.01674996, 0.04417484, 0.06478126]]);
+        assert_eq!(output.shape(), target.shape());
+        for (o, t) in output.iter().zip(target.iter()) {
+            assert!((o - t).powf(2.0) < 0.00001)
+        }
+    }
+
+    #[test]
+    fn activation_softmax_backward_should_apply_jacobian_on_each_row() {
+        let softmax: Softmax = Softmax;
+        let x: Array2<f64> = arr2(&[[1.0, 2.0, 3.0], [0.0, 0.0, 0.0]]);
+        let y: Array2<f64> = arr2(&[[1.0, 0.0, 0.0], [0.5, 0.5, 0.5]]);
+        let output: Array2<f64> = softmax.backward(&x, &y);
+        let s: Array2<f64> = softmax.function(&x);
         let target: Array2<f64> = arr2(&[
-            [0.14727423, 0.14886917, 0.15329252, 0.3710727],
-            [0.14692532, 0.14851649, 0.15292935, 0.3701936],
-            [0.14595769, 0.14753837, 0.15192218, 0.36775555],
-            [0.09831691, 0.09938166, 0.10233458, 0.24771967],
+            [
+                s[[0, 0]] * (1.0 - s[[0, 0]]),
+                -s[[0, 0]] * s[[0, 1]],
+                -s[[0, 0]] * s[[0, 2]],
+            ],
+            [0.0, 0.0, 0.0],
         ]);
-        assert_eq!(output.shape(), target.shape());
-        let output_vec: Vec<f64> = output.into_raw_vec();
-        let target_vec: Vec<f64> = target.into_raw_vec();
-        for i in 0..16 {
-            assert!((output_vec[i] - target_vec[i]).powf(2.0) < 0.00001)
+        for (o, t) in output.iter().zip(target.iter()) {
+            assert!((o - t).powf(2.0) < 0.00001)
         }
     }
 
+    #[test]
+    fn activation_softmax_backward_should_pass_gradient_check() {
+        let input: Array2<f64> = arr2(&[[1.0, 2.0, 3.0, 6.0], [0.5, -0.5, 0.0, 0.25]]);
+        let check: GradientCheck = check_activation(&Softmax, &input, 0.0001).unwrap();
+        assert!(check.relative_error < 0.0001);
+    }
+
     #[test]
     fn get_name_should_return_struct_name() -> () {
-        assert_eq!(Softmax.get_name(), "Softmax");
+        assert_eq!(Activation::<f64>::get_name(&Softmax), "Softmax");
     }
 }
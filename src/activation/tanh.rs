@@ -1,26 +1,33 @@
 use crate::activation::activation::Activation;
+use crate::utils::float::MlFloat;
 use ndarray::Array2;
 
 #[derive(Debug)]
 pub struct Tanh;
 
-impl Activation for Tanh {
-    fn function(&self, x: &Array2<f64>) -> Array2<f64> {
+impl<F: MlFloat> Activation<F> for Tanh {
+    fn function(&self, x: &Array2<F>) -> Array2<F> {
         x.map(|x| x.tanh())
     }
 
-    fn derivative(&self, x: &Array2<f64>) -> Array2<f64> {
-        x.map(|x| 1.0 - x.tanh().powf(2.0))
+    fn derivative(&self, x: &Array2<F>) -> Array2<F> {
+        x.map(|x| F::one() - x.tanh().powi(2))
     }
 
     fn get_name(&self) -> String {
         "Tanh".to_string()
     }
+
+    fn clone_activation(&self) -> Box<dyn Activation<F>> {
+        Box::new(Tanh)
+    }
 }
 
 #[cfg(test)]
+#[allow(clippy::unused_unit)]
 mod tests {
     use super::*;
+    use crate::utils::gradient_check::{check_activation, GradientCheck};
     use ndarray::arr2;
 
     #[test]
@@ -57,8 +64,15 @@ mod tests {
         }
     }
 
+    #[test]
+    fn activation_tanh_backward_should_pass_gradient_check() {
+        let input: Array2<f64> = arr2(&[[1.0, -2.0, 3.0, 0.5], [-0.5, 0.25, -1.5, 2.5]]);
+        let check: GradientCheck = check_activation(&Tanh, &input, 0.0001).unwrap();
+        assert!(check.relative_error < 0.0001);
+    }
+
     #[test]
     fn get_name_should_return_struct_name() -> () {
-        assert_eq!(Tanh.get_name(), "Tanh");
+        assert_eq!(Activation::<f64>::get_name(&Tanh), "Tanh");
     }
 }
@@ -0,0 +1,410 @@
+use crate::autograd::tape::{Gradients, Tape, Var};
+use crate::layer::layer::Layer;
+use crate::utils::error::{check_shapes, MlError};
+use crate::utils::float::MlFloat;
+use ndarray::Array2;
+use serde::{Deserialize, Serialize};
+
+/// Forward pass of a layer recorded on a `Tape`, `AutogradLayer` derives its backward pass.
+pub trait LayerFunction<F: MlFloat = f64>: Send + Sync {
+    /// Records the output of the batch `x` given the parameters, in the order the layer holds
+    /// them.
+    fn forward(&self, tape: &mut Tape<F>, x: Var, parameters: &[Var]) -> Result<Var, MlError>;
+
+    /// Checks the function accepts samples of `input_shape` and returns the shape of its output
+    /// samples.
+    fn compile(&mut self, input_shape: &[usize]) -> Result<Vec<usize>, MlError>;
+
+    fn get_name(&self) -> String;
+
+    fn clone_function(&self) -> Box<dyn LayerFunction<F>>;
+}
+
+/// Layer defined by its forward pass only. It is slower than the hand-written layers since
+/// every operation is recorded, but new layers do not need a backward pass.
+///
+/// Functions can not be deserialized by name, so the layer is not known by
+/// `layer::from_string`; use `from_json` with the function instead.
+pub struct AutogradLayer<F: MlFloat = f64> {
+    function: Box<dyn LayerFunction<F>>,
+    parameters: Vec<Array2<F>>,
+    gradients: Vec<Array2<F>>,
+    /// Tape of the last forward pass with its input, parameters and output.
+    recording: Option<(Tape<F>, Var, Vec<Var>, Var)>,
+    input_shape: Vec<usize>,
+    output_shape: Vec<usize>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AutogradLayerModel {
+    function: String,
+    parameters: Vec<((usize, usize), Vec<f64>)>,
+    input_shape: Vec<usize>,
+    output_shape: Vec<usize>,
+}
+
+impl<F: MlFloat> AutogradLayer<F> {
+    pub fn build(
+        function: Box<dyn LayerFunction<F>>,
+        parameters: Vec<Array2<F>>,
+    ) -> AutogradLayer<F> {
+        AutogradLayer {
+            function,
+            gradients: parameters
+                .iter()
+                .map(|parameter| Array2::zeros(parameter.raw_dim()))
+                .collect(),
+            parameters,
+            recording: None,
+            input_shape: vec![],
+            output_shape: vec![],
+        }
+    }
+
+    pub fn from_json(
+        json_str: &str,
+        function: Box<dyn LayerFunction<F>>,
+    ) -> Result<AutogradLayer<F>, MlError> {
+        let model: AutogradLayerModel = serde_json::from_str(json_str)?;
+        if model.function != function.get_name() {
+            return Err(MlError::InvalidInput(format!(
+                "expected function {}, actually: {}",
+                function.get_name(),
+                model.function
+            )));
+        }
+        let mut parameters: Vec<Array2<F>> = vec![];
+        for (shape, values) in model.parameters {
+            parameters.push(Array2::from_shape_vec(
+                shape,
+                values.into_iter().map(F::cast).collect(),
+            )?);
+        }
+        let mut layer: AutogradLayer<F> = AutogradLayer::build(function, parameters);
+        if !model.input_shape.is_empty() {
+            layer.compile(&model.input_shape)?;
+        }
+        Ok(layer)
+    }
+
+    pub fn get_parameters(&self) -> &[Array2<F>] {
+        &self.parameters
+    }
+
+    fn record(&self, x: &Array2<F>) -> Result<(Tape<F>, Var, Vec<Var>, Var), MlError> {
+        let mut tape: Tape<F> = Tape::new();
+        let input: Var = tape.variable(x.clone());
+        let parameters: Vec<Var> = self
+            .parameters
+            .iter()
+            .map(|parameter| tape.variable(parameter.clone()))
+            .collect();
+        let output: Var = self.function.forward(&mut tape, input, &parameters)?;
+        Ok((tape, input, parameters, output))
+    }
+}
+
+impl<F: MlFloat> Layer<F> for AutogradLayer<F> {
+    fn forward_propagation(&mut self, x: &Array2<F>) -> Result<Array2<F>, MlError> {
+        let recording: (Tape<F>, Var, Vec<Var>, Var) = self.record(x)?;
+        let output: Array2<F> = recording.0.value(recording.3).clone();
+        self.recording = Some(recording);
+        Ok(output)
+    }
+
+    fn predict(&self, x: &Array2<F>) -> Result<Array2<F>, MlError> {
+        let (tape, _, _, output): (Tape<F>, Var, Vec<Var>, Var) = self.record(x)?;
+        Ok(tape.value(output).clone())
+    }
+
+    fn compute_gradients(&mut self, y: &Array2<F>) -> Result<Array2<F>, MlError> {
+        let (tape, input, parameters, output) = self.recording.as_ref().ok_or_else(|| {
+            MlError::InvalidInput("forward propagation must run before gradients".to_string())
+        })?;
+        let gradients: Gradients<F> = tape.backward_with(*output, y.clone())?;
+        self.gradients = parameters
+            .iter()
+            .zip(self.parameters.iter())
+            .map(|(&var, parameter)| match gradients.get(var) {
+                Some(gradient) => gradient.clone(),
+                None => Array2::zeros(parameter.raw_dim()),
+            })
+            .collect();
+        Ok(match gradients.get(*input) {
+            Some(gradient) => gradient.clone(),
+            None => Array2::zeros(tape.value(*input).raw_dim()),
+        })
+    }
+
+    fn apply_gradients(&mut self, learning_rate: F) {
+        for (parameter, gradient) in self.parameters.iter_mut().zip(self.gradients.iter()) {
+            *parameter = &*parameter - &(gradient * learning_rate);
+        }
+    }
+
+    fn get_gradients(&self) -> Vec<Array2<F>> {
+        self.gradients.clone()
+    }
+
+    fn set_gradients(&mut self, gradients: Vec<Array2<F>>) -> Result<(), MlError> {
+        if gradients.len() != self.parameters.len() {
+            return Err(MlError::InvalidInput(format!(
+                "{} expects {} gradients, actually: {}",
+                self.function.get_name(),
+                self.parameters.len(),
+                gradients.len()
+            )));
+        }
+        for (gradient, parameter) in gradients.iter().zip(self.parameters.iter()) {
+            check_shapes(gradient.shape(), parameter.shape())?;
+        }
+        self.gradients = gradients;
+        Ok(())
+    }
+
+    fn get_parameters(&self) -> Vec<Array2<F>> {
+        self.parameters.clone()
+    }
+
+    fn set_parameters(&mut self, parameters: Vec<Array2<F>>) -> Result<(), MlError> {
+        if parameters.len() != self.parameters.len() {
+            return Err(MlError::InvalidInput(format!(
+                "{} expects {} parameters, actually: {}",
+                self.function.get_name(),
+                self.parameters.len(),
+                parameters.len()
+            )));
+        }
+        for (parameter, current) in parameters.iter().zip(self.parameters.iter()) {
+            check_shapes(parameter.shape(), current.shape())?;
+        }
+        self.parameters = parameters;
+        Ok(())
+    }
+
+    fn compile(&mut self, input_shape: &[usize]) -> Result<Vec<usize>, MlError> {
+        let output_shape: Vec<usize> = self.function.compile(input_shape)?;
+        self.input_shape = input_shape.to_vec();
+        self.output_shape = output_shape.clone();
+        Ok(output_shape)
+    }
+
+    fn count_parameters(&self) -> (usize, usize) {
+        (
+            self.parameters
+                .iter()
+                .map(|parameter| parameter.len())
+                .sum(),
+            0,
+        )
+    }
+
+    fn clone_layer(&self) -> Box<dyn Layer<F>> {
+        Box::new(AutogradLayer {
+            function: self.function.clone_function(),
+            parameters: self.parameters.clone(),
+            gradients: self.gradients.clone(),
+            recording: self.recording.clone(),
+            input_shape: self.input_shape.clone(),
+            output_shape: self.output_shape.clone(),
+        })
+    }
+
+    fn get_shape(&self) -> (usize, usize) {
+        (
+            self.input_shape.iter().product(),
+            self.output_shape.iter().product(),
+        )
+    }
+
+    fn get_name(&self) -> String {
+        self.function.get_name()
+    }
+
+    fn to_json(&self) -> Result<String, MlError> {
+        let model: AutogradLayerModel = AutogradLayerModel {
+            function: self.function.get_name(),
+            parameters: self
+                .parameters
+                .iter()
+                .map(|parameter| {
+                    (
+                        parameter.dim(),
+                        parameter.iter().map(|&p| p.to_f64_lossy()).collect(),
+                    )
+                })
+                .collect(),
+            input_shape: self.input_shape.clone(),
+            output_shape: self.output_shape.clone(),
+        };
+        Ok(serde_json::to_string(&model)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::activation::tanh::Tanh;
+    use crate::layer::activation_layer::ActivationLayer;
+    use crate::layer::fc_layer::FCLayer;
+    use crate::loss::loss::Loss;
+    use crate::loss::mse::Mse;
+    use crate::network::mlp::Mlp;
+    use crate::utils::gradient_check::{check_layer, max_relative_error, GradientCheck};
+    use ndarray::arr2;
+
+    /// `tanh(x . weights + bias)`
+    struct Dense {
+        input_size: usize,
+        output_size: usize,
+    }
+
+    impl LayerFunction for Dense {
+        fn forward(&self, tape: &mut Tape, x: Var, parameters: &[Var]) -> Result<Var, MlError> {
+            let product: Var = tape.matmul(x, parameters[0])?;
+            let sum: Var = tape.add(product, parameters[1])?;
+            Ok(tape.tanh(sum))
+        }
+
+        fn compile(&mut self, input_shape: &[usize]) -> Result<Vec<usize>, MlError> {
+            check_shapes(input_shape, &[self.input_size])?;
+            Ok(vec![self.output_size])
+        }
+
+        fn get_name(&self) -> String {
+            "Dense".to_string()
+        }
+
+        fn clone_function(&self) -> Box<dyn LayerFunction> {
+            Box::new(Dense {
+                input_size: self.input_size,
+                output_size: self.output_size,
+            })
+        }
+    }
+
+    fn generate_test_autograd_layer() -> AutogradLayer {
+        let mut layer: AutogradLayer = AutogradLayer::build(
+            Box::new(Dense {
+                input_size: 2,
+                output_size: 3,
+            }),
+            vec![
+                arr2(&[[0.0, 1.0, 0.0], [0.5, 1.0, 0.5]]),
+                arr2(&[[1.0, 1.0, 0.25]]),
+            ],
+        );
+        layer.compile(&[2]).unwrap();
+        layer
+    }
+
+    #[test]
+    fn autograd_layer_should_match_hand_written_layers() {
+        let mut layer: AutogradLayer = generate_test_autograd_layer();
+        let mut fc_layer: FCLayer = FCLayer::from_json(
+            "{\"weights\":[0.0,1.0,0.0,0.5,1.0,0.5],\"bias\":[1.0,1.0,0.25],\"shape\":[2,3]}",
+        )
+        .unwrap();
+        let mut activation_layer: ActivationLayer = ActivationLayer::build(Box::new(Tanh));
+        activation_layer.compile(&[3]).unwrap();
+        let x: Array2<f64> = arr2(&[[0.5, -1.0], [1.0, 2.0]]);
+        let y: Array2<f64> = arr2(&[[0.1, -0.2, 0.3], [1.0, 0.5, -1.5]]);
+
+        let output: Array2<f64> = layer.forward_propagation(&x).unwrap();
+        let expected: Array2<f64> = activation_layer
+            .forward_propagation(&fc_layer.forward_propagation(&x).unwrap())
+            .unwrap();
+        assert_eq!(output, expected);
+        assert_eq!(layer.predict(&x).unwrap(), expected);
+
+        let input_error: Array2<f64> = layer.compute_gradients(&y).unwrap();
+        let expected_error: Array2<f64> = fc_layer
+            .compute_gradients(&activation_layer.compute_gradients(&y).unwrap())
+            .unwrap();
+        for (gradient, expected) in layer.get_gradients().iter().zip(fc_layer.get_gradients()) {
+            for (g, e) in gradient.iter().zip(expected.iter()) {
+                assert!((g - e).powf(2.0) < 0.00001);
+            }
+        }
+        for (g, e) in input_error.iter().zip(expected_error.iter()) {
+            assert!((g - e).powf(2.0) < 0.00001);
+        }
+    }
+
+    #[test]
+    fn compute_gradients_should_pass_gradient_check() {
+        let mut layer: AutogradLayer = generate_test_autograd_layer();
+        let x: Array2<f64> = arr2(&[[0.5, -1.0], [1.0, 2.0]]);
+        let checks: Vec<GradientCheck> = check_layer(&mut layer, &x, 0.0001).unwrap();
+        assert_eq!(checks.len(), 3);
+        assert!(max_relative_error(&checks) < 0.0001);
+    }
+
+    #[test]
+    fn compute_gradients_should_fail_before_forward_propagation() {
+        let mut layer: AutogradLayer = generate_test_autograd_layer();
+        assert!(matches!(
+            layer.compute_gradients(&Array2::zeros((1, 3))),
+            Err(MlError::InvalidInput(_))
+        ));
+        assert!(matches!(
+            layer.set_gradients(vec![]),
+            Err(MlError::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn backward_propagation_should_decrease_loss() {
+        let x: Array2<f64> = arr2(&[[0.0, 0.0], [0.0, 1.0], [1.0, 0.0], [1.0, 1.0]]);
+        let y: Array2<f64> = arr2(&[
+            [0.0, 0.5, 0.0],
+            [1.0, 0.5, 0.0],
+            [1.0, 0.5, 1.0],
+            [0.0, 0.5, 1.0],
+        ]);
+        let mut layer: AutogradLayer = generate_test_autograd_layer();
+        let before: f64 = Mse.function(&y, &layer.predict(&x).unwrap()).unwrap();
+        for _ in 0..200 {
+            let output: Array2<f64> = layer.forward_propagation(&x).unwrap();
+            layer
+                .backward_propagation(&Mse.derivative(&y, &output).unwrap(), 0.5)
+                .unwrap();
+        }
+        assert!(Mse.function(&y, &layer.predict(&x).unwrap()).unwrap() < before);
+    }
+
+    #[test]
+    fn mlp_should_count_autograd_layer_parameters() {
+        let mut mlp: Mlp = Mlp::build(Box::new(Mse));
+        mlp.add_layer(Box::new(generate_test_autograd_layer()));
+        mlp.add_layer(Box::new(FCLayer::build(3, 1)));
+        mlp.compile(&[2]).unwrap();
+        assert_eq!(mlp.summary().unwrap().get_trainable_params(), 9 + 4);
+    }
+
+    #[test]
+    fn from_json_should_deserialize_parameters() {
+        let layer: AutogradLayer = generate_test_autograd_layer();
+        let function: Box<dyn LayerFunction> = Box::new(Dense {
+            input_size: 2,
+            output_size: 3,
+        });
+        let result: AutogradLayer =
+            AutogradLayer::from_json(&layer.to_json().unwrap(), function).unwrap();
+        assert_eq!(result.get_parameters(), layer.get_parameters());
+        assert_eq!(result.get_shape(), (2, 3));
+        assert_eq!(result.get_name(), "Dense");
+        let json: String = layer.to_json().unwrap().replace("Dense", "Conv");
+        let function: Box<dyn LayerFunction> = Box::new(Dense {
+            input_size: 2,
+            output_size: 3,
+        });
+        assert_eq!(
+            AutogradLayer::from_json(&json, function)
+                .err()
+                .unwrap()
+                .to_string(),
+            "invalid input: expected function Dense, actually: Conv"
+        );
+    }
+}
@@ -0,0 +1,131 @@
+use crate::autograd::tape::{Gradients, Tape, Var};
+use crate::loss::loss::Loss;
+use crate::utils::error::{check_shapes, MlError};
+use crate::utils::float::MlFloat;
+use ndarray::Array2;
+
+/// Loss recorded on a `Tape`, `AutogradLoss` derives its derivative.
+pub trait LossFunction<F: MlFloat = f64>: Send + Sync {
+    /// Records the loss of the predictions as a `(1, 1)` value.
+    fn forward(&self, tape: &mut Tape<F>, y_true: Var, y_pred: Var) -> Result<Var, MlError>;
+
+    fn get_name(&self) -> String;
+}
+
+/// Loss defined by its function only, the derivative with respect to the predictions is
+/// computed from the tape.
+pub struct AutogradLoss<F: MlFloat = f64> {
+    function: Box<dyn LossFunction<F>>,
+}
+
+impl<F: MlFloat> AutogradLoss<F> {
+    pub fn build(function: Box<dyn LossFunction<F>>) -> AutogradLoss<F> {
+        AutogradLoss { function }
+    }
+
+    fn record(
+        &self,
+        y_true: &Array2<F>,
+        y_pred: &Array2<F>,
+    ) -> Result<(Tape<F>, Var, Var), MlError> {
+        check_shapes(y_true.shape(), y_pred.shape())?;
+        let mut tape: Tape<F> = Tape::new();
+        let y_true: Var = tape.variable(y_true.clone());
+        let y_pred: Var = tape.variable(y_pred.clone());
+        let loss: Var = self.function.forward(&mut tape, y_true, y_pred)?;
+        check_shapes(tape.value(loss).shape(), &[1, 1])?;
+        Ok((tape, y_pred, loss))
+    }
+}
+
+impl<F: MlFloat> Loss<F> for AutogradLoss<F> {
+    fn function(&self, y_true: &Array2<F>, y_pred: &Array2<F>) -> Result<F, MlError> {
+        let (tape, _, loss): (Tape<F>, Var, Var) = self.record(y_true, y_pred)?;
+        Ok(tape.value(loss)[[0, 0]])
+    }
+
+    fn derivative(&self, y_true: &Array2<F>, y_pred: &Array2<F>) -> Result<Array2<F>, MlError> {
+        let (tape, y_pred, loss): (Tape<F>, Var, Var) = self.record(y_true, y_pred)?;
+        let gradients: Gradients<F> = tape.backward(loss)?;
+        Ok(match gradients.get(y_pred) {
+            Some(gradient) => gradient.clone(),
+            None => Array2::zeros(tape.value(y_pred).raw_dim()),
+        })
+    }
+
+    fn get_name(&self) -> String {
+        self.function.get_name()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::loss::mse::Mse;
+    use crate::utils::gradient_check::{check_loss, GradientCheck};
+    use ndarray::arr2;
+
+    struct SquaredError;
+
+    impl LossFunction for SquaredError {
+        fn forward(&self, tape: &mut Tape, y_true: Var, y_pred: Var) -> Result<Var, MlError> {
+            let difference: Var = tape.sub(y_pred, y_true)?;
+            let squared: Var = tape.powi(difference, 2);
+            Ok(tape.mean(squared))
+        }
+
+        fn get_name(&self) -> String {
+            "squared_error".to_string()
+        }
+    }
+
+    struct Constant;
+
+    impl LossFunction for Constant {
+        fn forward(&self, tape: &mut Tape, _y_true: Var, y_pred: Var) -> Result<Var, MlError> {
+            Ok(tape.tanh(y_pred))
+        }
+
+        fn get_name(&self) -> String {
+            "constant".to_string()
+        }
+    }
+
+    #[test]
+    fn autograd_loss_should_match_mse() {
+        let loss: AutogradLoss = AutogradLoss::build(Box::new(SquaredError));
+        let y_true: Array2<f64> = arr2(&[[0.0, 1.0, 0.5], [1.0, 0.0, 0.25]]);
+        let y_pred: Array2<f64> = arr2(&[[0.2, 0.7, 0.5], [0.4, 0.3, 1.0]]);
+        let value: f64 = loss.function(&y_true, &y_pred).unwrap();
+        assert!((value - Mse.function(&y_true, &y_pred).unwrap()).powf(2.0) < 0.00001);
+        let derivative: Array2<f64> = loss.derivative(&y_true, &y_pred).unwrap();
+        let expected: Array2<f64> = Mse.derivative(&y_true, &y_pred).unwrap();
+        for (d, e) in derivative.iter().zip(expected.iter()) {
+            assert!((d - e).powf(2.0) < 0.00001);
+        }
+        assert_eq!(loss.get_name(), "squared_error");
+    }
+
+    #[test]
+    fn derivative_should_pass_gradient_check() {
+        let loss: AutogradLoss = AutogradLoss::build(Box::new(SquaredError));
+        let y_true: Array2<f64> = arr2(&[[0.0, 1.0, 0.5], [1.0, 0.0, 0.25]]);
+        let y_pred: Array2<f64> = arr2(&[[0.2, 0.7, 0.5], [0.4, 0.3, 1.0]]);
+        let check: GradientCheck = check_loss(&loss, &y_true, &y_pred, 0.0001).unwrap();
+        assert!(check.relative_error < 0.0001);
+    }
+
+    #[test]
+    fn autograd_loss_should_check_shapes() {
+        let loss: AutogradLoss = AutogradLoss::build(Box::new(SquaredError));
+        assert!(matches!(
+            loss.function(&Array2::zeros((1, 2)), &Array2::zeros((1, 3))),
+            Err(MlError::ShapeMismatch(_))
+        ));
+        let loss: AutogradLoss = AutogradLoss::build(Box::new(Constant));
+        assert!(matches!(
+            loss.derivative(&Array2::zeros((1, 2)), &Array2::zeros((1, 2))),
+            Err(MlError::ShapeMismatch(_))
+        ));
+    }
+}
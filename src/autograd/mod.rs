@@ -0,0 +1,3 @@
+pub mod autograd_layer;
+pub mod autograd_loss;
+pub mod tape;
@@ -0,0 +1,582 @@
+use crate::layer::convolution::Convolution;
+use crate::utils::error::{check_shapes, MlError};
+use crate::utils::float::MlFloat;
+use ndarray::{Array2, Axis};
+
+/// Handle on a value recorded on a `Tape`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Var(usize);
+
+#[derive(Debug, Clone)]
+enum Operation<F: MlFloat> {
+    Variable,
+    MatMul(Var, Var),
+    Add(Var, Var),
+    Sub(Var, Var),
+    Mul(Var, Var),
+    Div(Var, Var),
+    Scale(Var, F),
+    /// Elementwise function, holds its derivative evaluated at the input.
+    Elementwise(Var, Array2<F>),
+    Sum(Var),
+    SumAxis(Var),
+    Reshape(Var),
+    Transpose(Var),
+    Conv2d {
+        input: Var,
+        kernels: Var,
+        columns: Array2<F>,
+        convolution: Convolution,
+    },
+}
+
+#[derive(Debug, Clone)]
+struct Node<F: MlFloat> {
+    value: Array2<F>,
+    operation: Operation<F>,
+}
+
+/// Records the operations of a forward pass over `Array2` values so the gradients of every value
+/// can be computed in reverse order. Binary operations broadcast `(1, n)`, `(m, 1)` and `(1, 1)`
+/// operands like ndarray does.
+#[derive(Debug, Clone, Default)]
+pub struct Tape<F: MlFloat = f64> {
+    nodes: Vec<Node<F>>,
+}
+
+/// Gradients of a value of the tape with respect to every value it depends on.
+#[derive(Debug)]
+pub struct Gradients<F: MlFloat = f64> {
+    gradients: Vec<Option<Array2<F>>>,
+}
+
+impl<F: MlFloat> Gradients<F> {
+    /// `None` when the output does not depend on the value.
+    pub fn get(&self, var: Var) -> Option<&Array2<F>> {
+        self.gradients
+            .get(var.0)
+            .and_then(|gradient| gradient.as_ref())
+    }
+}
+
+impl<F: MlFloat> Tape<F> {
+    pub fn new() -> Tape<F> {
+        Tape { nodes: vec![] }
+    }
+
+    fn push(&mut self, value: Array2<F>, operation: Operation<F>) -> Var {
+        self.nodes.push(Node { value, operation });
+        Var(self.nodes.len() - 1)
+    }
+
+    /// Records an input or a parameter.
+    pub fn variable(&mut self, value: Array2<F>) -> Var {
+        self.push(value, Operation::Variable)
+    }
+
+    pub fn value(&self, var: Var) -> &Array2<F> {
+        &self.nodes[var.0].value
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    pub fn matmul(&mut self, a: Var, b: Var) -> Result<Var, MlError> {
+        let (left, right): (&Array2<F>, &Array2<F>) = (self.value(a), self.value(b));
+        if left.shape()[1] != right.shape()[0] {
+            return Err(MlError::ShapeMismatch(format!(
+                "can not multiply matrices of shapes {:?} and {:?}",
+                left.shape(),
+                right.shape()
+            )));
+        }
+        let value: Array2<F> = left.dot(right);
+        Ok(self.push(value, Operation::MatMul(a, b)))
+    }
+
+    pub fn add(&mut self, a: Var, b: Var) -> Result<Var, MlError> {
+        self.check_broadcast(a, b)?;
+        let value: Array2<F> = self.value(a) + self.value(b);
+        Ok(self.push(value, Operation::Add(a, b)))
+    }
+
+    pub fn sub(&mut self, a: Var, b: Var) -> Result<Var, MlError> {
+        self.check_broadcast(a, b)?;
+        let value: Array2<F> = self.value(a) - self.value(b);
+        Ok(self.push(value, Operation::Sub(a, b)))
+    }
+
+    pub fn mul(&mut self, a: Var, b: Var) -> Result<Var, MlError> {
+        self.check_broadcast(a, b)?;
+        let value: Array2<F> = self.value(a) * self.value(b);
+        Ok(self.push(value, Operation::Mul(a, b)))
+    }
+
+    pub fn div(&mut self, a: Var, b: Var) -> Result<Var, MlError> {
+        self.check_broadcast(a, b)?;
+        let value: Array2<F> = self.value(a) / self.value(b);
+        Ok(self.push(value, Operation::Div(a, b)))
+    }
+
+    pub fn scale(&mut self, a: Var, factor: F) -> Var {
+        let value: Array2<F> = self.value(a) * factor;
+        self.push(value, Operation::Scale(a, factor))
+    }
+
+    /// Applies `function` to every element, `derivative` is evaluated at the same input.
+    pub fn elementwise(
+        &mut self,
+        a: Var,
+        function: impl Fn(F) -> F,
+        derivative: impl Fn(F) -> F,
+    ) -> Var {
+        let input: &Array2<F> = self.value(a);
+        let value: Array2<F> = input.mapv(function);
+        let derivative: Array2<F> = input.mapv(derivative);
+        self.push(value, Operation::Elementwise(a, derivative))
+    }
+
+    pub fn exp(&mut self, a: Var) -> Var {
+        self.elementwise(a, |x| x.exp(), |x| x.exp())
+    }
+
+    pub fn ln(&mut self, a: Var) -> Var {
+        self.elementwise(a, |x| x.ln(), |x| x.recip())
+    }
+
+    pub fn powi(&mut self, a: Var, n: i32) -> Var {
+        self.elementwise(a, |x| x.powi(n), |x| F::cast(n as f64) * x.powi(n - 1))
+    }
+
+    pub fn tanh(&mut self, a: Var) -> Var {
+        self.elementwise(a, |x| x.tanh(), |x| F::one() - x.tanh().powi(2))
+    }
+
+    pub fn sigmoid(&mut self, a: Var) -> Var {
+        let sigmoid = |x: F| F::one() / (F::one() + (-x).exp());
+        self.elementwise(a, sigmoid, |x| sigmoid(x) * (F::one() - sigmoid(x)))
+    }
+
+    pub fn relu(&mut self, a: Var) -> Var {
+        self.elementwise(
+            a,
+            |x| x.max(F::zero()),
+            |x| if x > F::zero() { F::one() } else { F::zero() },
+        )
+    }
+
+    /// Softmax of every row, recorded as `exp(x - max) / sum(exp(x - max))`.
+    pub fn softmax(&mut self, a: Var) -> Result<Var, MlError> {
+        let max: Array2<F> = self
+            .value(a)
+            .fold_axis(Axis(1), F::neg_infinity(), |max, &x| max.max(x))
+            .insert_axis(Axis(1));
+        let max: Var = self.variable(max);
+        let shifted: Var = self.sub(a, max)?;
+        let exp: Var = self.exp(shifted);
+        let sum: Var = self.sum_axis(exp, Axis(1));
+        self.div(exp, sum)
+    }
+
+    /// Sum of all the elements as a `(1, 1)` value.
+    pub fn sum(&mut self, a: Var) -> Var {
+        let value: Array2<F> = Array2::from_elem((1, 1), self.value(a).sum());
+        self.push(value, Operation::Sum(a))
+    }
+
+    /// Sum along `axis`, which is kept with a length of 1.
+    pub fn sum_axis(&mut self, a: Var, axis: Axis) -> Var {
+        let value: Array2<F> = self.value(a).sum_axis(axis).insert_axis(axis);
+        self.push(value, Operation::SumAxis(a))
+    }
+
+    pub fn mean(&mut self, a: Var) -> Var {
+        let len: usize = self.value(a).len().max(1);
+        let sum: Var = self.sum(a);
+        self.scale(sum, F::cast(1.0 / len as f64))
+    }
+
+    /// Reshapes in row-major order.
+    pub fn reshape(&mut self, a: Var, shape: (usize, usize)) -> Result<Var, MlError> {
+        let value: Array2<F> = self
+            .value(a)
+            .as_standard_layout()
+            .into_owned()
+            .into_shape(shape)?;
+        Ok(self.push(value, Operation::Reshape(a)))
+    }
+
+    pub fn transpose(&mut self, a: Var) -> Var {
+        let value: Array2<F> = self.value(a).t().to_owned();
+        self.push(value, Operation::Transpose(a))
+    }
+
+    /// Convolution with a stride of 1 and no padding of the `(h, w, c)` samples of `input`, one
+    /// per row, by the `(kernel_size * kernel_size * c, n)` `kernels`. The output samples have
+    /// the shape `(h - kernel_size + 1, w - kernel_size + 1, n)`.
+    pub fn conv2d(
+        &mut self,
+        input: Var,
+        kernels: Var,
+        input_shape: (usize, usize, usize),
+        kernel_size: usize,
+    ) -> Result<Var, MlError> {
+        let mut convolution: Convolution = Convolution::build(2, kernel_size, input_shape.2);
+        convolution.compile(&[input_shape.0, input_shape.1, input_shape.2])?;
+        let columns: Array2<F> = convolution.image_to_columns(self.value(input))?;
+        let kernel_values: &Array2<F> = self.value(kernels);
+        check_shapes(
+            &[kernel_values.shape()[0]],
+            &[kernel_size * kernel_size * input_shape.2],
+        )?;
+        let batch: usize = self.value(input).shape()[0];
+        let value: Array2<F> = columns.dot(kernel_values).into_shape((
+            batch,
+            columns.shape()[0] / batch.max(1) * kernel_values.shape()[1],
+        ))?;
+        Ok(self.push(
+            value,
+            Operation::Conv2d {
+                input,
+                kernels,
+                columns,
+                convolution,
+            },
+        ))
+    }
+
+    fn check_broadcast(&self, a: Var, b: Var) -> Result<(), MlError> {
+        let (left, right): (&[usize], &[usize]) = (self.value(a).shape(), self.value(b).shape());
+        if left
+            .iter()
+            .zip(right.iter())
+            .all(|(&l, &r)| l == r || l == 1 || r == 1)
+        {
+            Ok(())
+        } else {
+            Err(MlError::ShapeMismatch(format!(
+                "can not broadcast shapes {:?} and {:?}",
+                left, right
+            )))
+        }
+    }
+
+    /// Gradients of the sum of the elements of `output`.
+    pub fn backward(&self, output: Var) -> Result<Gradients<F>, MlError> {
+        let seed: Array2<F> = Array2::ones(self.value(output).raw_dim());
+        self.backward_with(output, seed)
+    }
+
+    /// Gradients of `output` given the gradient `seed` of a value computed from it.
+    pub fn backward_with(&self, output: Var, seed: Array2<F>) -> Result<Gradients<F>, MlError> {
+        check_shapes(seed.shape(), self.value(output).shape())?;
+        let mut gradients: Vec<Option<Array2<F>>> = vec![None; output.0 + 1];
+        gradients[output.0] = Some(seed);
+        for index in (0..=output.0).rev() {
+            let gradient: Array2<F> = match gradients[index].take() {
+                Some(gradient) => gradient,
+                None => continue,
+            };
+            for (var, input_gradient) in self.input_gradients(index, &gradient)? {
+                accumulate(&mut gradients, var, input_gradient);
+            }
+            gradients[index] = Some(gradient);
+        }
+        Ok(Gradients { gradients })
+    }
+
+    /// Gradients of the inputs of the node `index` from the gradient of its value.
+    fn input_gradients(
+        &self,
+        index: usize,
+        gradient: &Array2<F>,
+    ) -> Result<Vec<(Var, Array2<F>)>, MlError> {
+        let gradients: Vec<(Var, Array2<F>)> = match &self.nodes[index].operation {
+            Operation::Variable => vec![],
+            Operation::MatMul(a, b) => vec![
+                (*a, gradient.dot(&self.value(*b).t())),
+                (*b, self.value(*a).t().dot(gradient)),
+            ],
+            Operation::Add(a, b) => vec![
+                (*a, self.unbroadcast(gradient.clone(), *a)),
+                (*b, self.unbroadcast(gradient.clone(), *b)),
+            ],
+            Operation::Sub(a, b) => vec![
+                (*a, self.unbroadcast(gradient.clone(), *a)),
+                (*b, self.unbroadcast(gradient.mapv(|g| -g), *b)),
+            ],
+            Operation::Mul(a, b) => vec![
+                (*a, self.unbroadcast(gradient * self.value(*b), *a)),
+                (*b, self.unbroadcast(gradient * self.value(*a), *b)),
+            ],
+            Operation::Div(a, b) => {
+                let (left, right): (&Array2<F>, &Array2<F>) = (self.value(*a), self.value(*b));
+                vec![
+                    (*a, self.unbroadcast(gradient / right, *a)),
+                    (
+                        *b,
+                        self.unbroadcast(
+                            (gradient * left).mapv(|g| -g) / &right.mapv(|x| x * x),
+                            *b,
+                        ),
+                    ),
+                ]
+            }
+            Operation::Scale(a, factor) => vec![(*a, gradient * *factor)],
+            Operation::Elementwise(a, derivative) => vec![(*a, gradient * derivative)],
+            Operation::Sum(a) | Operation::SumAxis(a) => {
+                let shape = self.value(*a).raw_dim();
+                vec![(*a, gradient.broadcast(shape).unwrap().to_owned())]
+            }
+            Operation::Reshape(a) => vec![(
+                *a,
+                gradient
+                    .as_standard_layout()
+                    .into_owned()
+                    .into_shape(self.value(*a).raw_dim())?,
+            )],
+            Operation::Transpose(a) => vec![(*a, gradient.t().to_owned())],
+            Operation::Conv2d {
+                input,
+                kernels,
+                columns,
+                convolution,
+            } => {
+                let kernel_values: &Array2<F> = self.value(*kernels);
+                let batch: usize = self.value(*input).shape()[0];
+                let y_columns: Array2<F> = gradient
+                    .as_standard_layout()
+                    .into_owned()
+                    .into_shape((columns.shape()[0], kernel_values.shape()[1]))?;
+                vec![
+                    (
+                        *input,
+                        convolution.columns_to_image(&y_columns.dot(&kernel_values.t()), batch)?,
+                    ),
+                    (*kernels, columns.t().dot(&y_columns)),
+                ]
+            }
+        };
+        Ok(gradients)
+    }
+
+    /// Sums the gradient over the axes `var` was broadcast along.
+    fn unbroadcast(&self, mut gradient: Array2<F>, var: Var) -> Array2<F> {
+        let shape: &[usize] = self.value(var).shape();
+        for (axis, &length) in shape.iter().enumerate() {
+            if length == 1 && gradient.shape()[axis] != 1 {
+                gradient = gradient.sum_axis(Axis(axis)).insert_axis(Axis(axis));
+            }
+        }
+        gradient
+    }
+}
+
+fn accumulate<F: MlFloat>(gradients: &mut [Option<Array2<F>>], var: Var, gradient: Array2<F>) {
+    match &mut gradients[var.0] {
+        Some(sum) => *sum += &gradient,
+        slot => *slot = Some(gradient),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::activation::activation::Activation;
+    use crate::activation::softmax::Softmax;
+    use crate::layer::conv_layer::ConvLayer;
+    use crate::layer::layer::Layer;
+    use ndarray::arr2;
+
+    /// Checks the gradient of `function` at `x` against central finite differences.
+    fn check_gradient(x: Array2<f64>, function: impl Fn(&mut Tape, Var) -> Result<Var, MlError>) {
+        let mut tape: Tape = Tape::new();
+        let input: Var = tape.variable(x.clone());
+        let output: Var = function(&mut tape, input).unwrap();
+        let gradients: Gradients = tape.backward(output).unwrap();
+        let analytic: &Array2<f64> = gradients.get(input).unwrap();
+        assert_eq!(analytic.shape(), x.shape());
+
+        let evaluate = |x: Array2<f64>| -> f64 {
+            let mut tape: Tape = Tape::new();
+            let input: Var = tape.variable(x);
+            let output: Var = function(&mut tape, input).unwrap();
+            tape.value(output).sum()
+        };
+        let epsilon: f64 = 0.0001;
+        for (index, &gradient) in analytic.indexed_iter() {
+            let mut x_plus: Array2<f64> = x.clone();
+            x_plus[index] += epsilon;
+            let mut x_minus: Array2<f64> = x.clone();
+            x_minus[index] -= epsilon;
+            let numeric: f64 = (evaluate(x_plus) - evaluate(x_minus)) / (2.0 * epsilon);
+            assert!((numeric - gradient).powf(2.0) < 0.00001);
+        }
+    }
+
+    fn generate_test_input() -> Array2<f64> {
+        arr2(&[[0.5, -1.0, 2.0], [1.5, 0.25, -0.75]])
+    }
+
+    #[test]
+    fn backward_should_differentiate_matmul() {
+        let mut tape: Tape = Tape::new();
+        let a: Var = tape.variable(arr2(&[[1.0, 2.0], [3.0, 4.0]]));
+        let b: Var = tape.variable(arr2(&[[0.5], [-1.0]]));
+        let output: Var = tape.matmul(a, b).unwrap();
+        assert_eq!(tape.value(output), arr2(&[[-1.5], [-2.5]]));
+        let gradients: Gradients = tape.backward(output).unwrap();
+        assert_eq!(gradients.get(a).unwrap(), arr2(&[[0.5, -1.0], [0.5, -1.0]]));
+        assert_eq!(gradients.get(b).unwrap(), arr2(&[[4.0], [6.0]]));
+        assert!(matches!(tape.matmul(b, b), Err(MlError::ShapeMismatch(_))));
+    }
+
+    #[test]
+    fn backward_should_sum_broadcast_gradients() {
+        let mut tape: Tape = Tape::new();
+        let x: Var = tape.variable(generate_test_input());
+        let bias: Var = tape.variable(arr2(&[[1.0, 2.0, 3.0]]));
+        let output: Var = tape.add(x, bias).unwrap();
+        let gradients: Gradients = tape.backward(output).unwrap();
+        assert_eq!(gradients.get(bias).unwrap(), arr2(&[[2.0, 2.0, 2.0]]));
+        assert_eq!(gradients.get(x).unwrap(), Array2::<f64>::ones((2, 3)));
+        let column: Var = tape.variable(arr2(&[[1.0], [2.0], [3.0]]));
+        assert!(matches!(
+            tape.mul(x, column),
+            Err(MlError::ShapeMismatch(_))
+        ));
+    }
+
+    #[test]
+    fn backward_should_differentiate_binary_operations() {
+        let other: Array2<f64> = arr2(&[[2.0, -0.5, 1.5]]);
+        check_gradient(generate_test_input(), |tape, x| {
+            let other: Var = tape.variable(other.clone());
+            let sum: Var = tape.add(x, other)?;
+            let difference: Var = tape.sub(sum, x)?;
+            let product: Var = tape.mul(difference, x)?;
+            let squared: Var = tape.mul(product, x)?;
+            tape.div(squared, other)
+        });
+        check_gradient(arr2(&[[2.0, -0.5, 1.5]]), |tape, x| {
+            let other: Var = tape.variable(generate_test_input());
+            let quotient: Var = tape.div(other, x)?;
+            tape.sub(quotient, x)
+        });
+    }
+
+    #[test]
+    fn backward_should_differentiate_elementwise_functions() {
+        check_gradient(generate_test_input(), |tape, x| Ok(tape.tanh(x)));
+        check_gradient(generate_test_input(), |tape, x| Ok(tape.sigmoid(x)));
+        check_gradient(generate_test_input(), |tape, x| Ok(tape.relu(x)));
+        check_gradient(generate_test_input(), |tape, x| Ok(tape.exp(x)));
+        check_gradient(generate_test_input(), |tape, x| {
+            let scaled: Var = tape.scale(x, -3.0);
+            Ok(tape.powi(scaled, 3))
+        });
+        check_gradient(generate_test_input().mapv(f64::abs), |tape, x| {
+            Ok(tape.ln(x))
+        });
+    }
+
+    #[test]
+    fn backward_should_differentiate_reductions_and_reshapes() {
+        check_gradient(generate_test_input(), |tape, x| {
+            let squared: Var = tape.mul(x, x)?;
+            Ok(tape.mean(squared))
+        });
+        check_gradient(generate_test_input(), |tape, x| {
+            let rows: Var = tape.sum_axis(x, Axis(1));
+            let squared: Var = tape.mul(rows, rows)?;
+            let columns: Var = tape.sum_axis(x, Axis(0));
+            let product: Var = tape.mul(squared, columns)?;
+            Ok(tape.sum(product))
+        });
+        check_gradient(generate_test_input(), |tape, x| {
+            let reshaped: Var = tape.reshape(x, (3, 2))?;
+            let weights: Var = tape.variable(arr2(&[[1.0, 2.0, 3.0], [4.0, 5.0, 6.0]]));
+            let product: Var = tape.matmul(reshaped, weights)?;
+            let transposed: Var = tape.transpose(product);
+            tape.mul(transposed, transposed)
+        });
+    }
+
+    #[test]
+    fn softmax_should_match_activation_backward() {
+        let x: Array2<f64> = generate_test_input();
+        let y: Array2<f64> = arr2(&[[0.1, -0.4, 0.8], [1.0, 0.5, -2.0]]);
+        let mut tape: Tape = Tape::new();
+        let input: Var = tape.variable(x.clone());
+        let output: Var = tape.softmax(input).unwrap();
+        let gradients: Gradients = tape.backward_with(output, y.clone()).unwrap();
+
+        let expected_output: Array2<f64> = Softmax.function(&x);
+        let expected_gradient: Array2<f64> = Softmax.backward(&x, &y);
+        for (e, r) in expected_output.iter().zip(tape.value(output).iter()) {
+            assert!((e - r).powf(2.0) < 0.00001);
+        }
+        for (e, r) in expected_gradient
+            .iter()
+            .zip(gradients.get(input).unwrap().iter())
+        {
+            assert!((e - r).powf(2.0) < 0.00001);
+        }
+    }
+
+    #[test]
+    fn conv2d_should_match_conv_layer() {
+        let kernels: Array2<f64> =
+            Array2::from_shape_fn((8, 2), |(i, j)| (i * 2 + j) as f64 / 16.0);
+        let mut layer: ConvLayer = ConvLayer::from_json(&format!(
+            "{{\"kernels\":{:?},\"bias\":[0.0,0.0],\"kernel_size\":2,\"kernel_num\":2,\"input_shape\":[3,3,2]}}",
+            kernels.iter().collect::<Vec<&f64>>()
+        ))
+        .unwrap();
+        let x: Array2<f64> = Array2::from_shape_fn((2, 18), |(b, i)| (b * 18 + i) as f64 / 10.0);
+        let y: Array2<f64> = Array2::from_shape_fn((2, 8), |(b, i)| (b + i) as f64 / 4.0 - 1.0);
+
+        let mut tape: Tape = Tape::new();
+        let input: Var = tape.variable(x.clone());
+        let kernel_var: Var = tape.variable(kernels.clone());
+        let output: Var = tape.conv2d(input, kernel_var, (3, 3, 2), 2).unwrap();
+        let gradients: Gradients = tape.backward_with(output, y.clone()).unwrap();
+        assert_eq!(tape.value(output), layer.forward_propagation(&x).unwrap());
+        assert_eq!(
+            gradients.get(input).unwrap(),
+            layer.compute_gradients(&y).unwrap()
+        );
+        assert_eq!(gradients.get(kernel_var).unwrap(), layer.get_gradients()[0]);
+
+        check_gradient(x.clone(), |tape, x| {
+            let kernels: Var = tape.variable(kernels.clone());
+            let output: Var = tape.conv2d(x, kernels, (3, 3, 2), 2)?;
+            tape.mul(output, output)
+        });
+        check_gradient(kernels, |tape, kernels| {
+            let x: Var = tape.variable(x.clone());
+            let output: Var = tape.conv2d(x, kernels, (3, 3, 2), 2)?;
+            tape.mul(output, output)
+        });
+    }
+
+    #[test]
+    fn backward_with_should_check_seed_shape() {
+        let mut tape: Tape = Tape::new();
+        let x: Var = tape.variable(generate_test_input());
+        let output: Var = tape.tanh(x);
+        assert!(matches!(
+            tape.backward_with(output, Array2::zeros((1, 3))),
+            Err(MlError::ShapeMismatch(_))
+        ));
+        let unused: Var = tape.variable(generate_test_input());
+        let gradients: Gradients = tape.backward(output).unwrap();
+        assert!(gradients.get(unused).is_none());
+        assert_eq!(tape.len(), 3);
+    }
+}
@@ -1,13 +1,14 @@
 use crate::activation;
 use crate::activation::activation::Activation;
 use crate::layer::layer::Layer;
+use crate::utils::error::{check_shapes, MlError};
+use crate::utils::float::MlFloat;
 use ndarray::{Array, Array2};
 use serde::{Deserialize, Serialize};
-use std::error::Error;
 
-pub struct ActivationLayer {
-    input: Array2<f64>,
-    activation: Box<dyn Activation>,
+pub struct ActivationLayer<F: MlFloat = f64> {
+    input: Array2<F>,
+    activation: Box<dyn Activation<F>>,
     shape: (usize, usize),
 }
 
@@ -17,22 +18,19 @@ pub struct ActivationLayerModel {
     shape: (usize, usize),
 }
 
-impl ActivationLayer {
-    pub fn build(
-        activation: Box<dyn Activation>,
-        input_size: usize,
-        output_size: usize,
-    ) -> ActivationLayer {
+impl<F: MlFloat> ActivationLayer<F> {
+    /// The shape is inferred from the previous layer when the network is compiled.
+    pub fn build(activation: Box<dyn Activation<F>>) -> ActivationLayer<F> {
         ActivationLayer {
             input: Array::zeros((0, 0)),
             activation,
-            shape: (input_size, output_size),
+            shape: (0, 0),
         }
     }
 
-    pub fn from_json(json_str: &str) -> Result<ActivationLayer, Box<dyn Error>> {
+    pub fn from_json(json_str: &str) -> Result<ActivationLayer<F>, MlError> {
         let model: ActivationLayerModel = serde_json::from_str(json_str)?;
-        let layer: ActivationLayer = ActivationLayer {
+        let layer: ActivationLayer<F> = ActivationLayer {
             input: Array::zeros((0, 0)),
             activation: activation::activation::from_string(model.activation)?,
             shape: model.shape,
@@ -41,14 +39,33 @@ impl ActivationLayer {
     }
 }
 
-impl Layer for ActivationLayer {
-    fn forward_propagation(&mut self, x: &Array2<f64>) -> Array2<f64> {
+impl<F: MlFloat> Layer<F> for ActivationLayer<F> {
+    fn forward_propagation(&mut self, x: &Array2<F>) -> Result<Array2<F>, MlError> {
         self.input = (*x).clone();
-        self.activation.function(x)
+        self.predict(x)
     }
 
-    fn backward_propagation(&mut self, y: &Array2<f64>, _learning_rate: f64) -> Array2<f64> {
-        self.activation.derivative(&self.input) * y
+    fn predict(&self, x: &Array2<F>) -> Result<Array2<F>, MlError> {
+        Ok(self.activation.function(x))
+    }
+
+    fn compute_gradients(&mut self, y: &Array2<F>) -> Result<Array2<F>, MlError> {
+        check_shapes(y.shape(), self.input.shape())?;
+        Ok(self.activation.backward(&self.input, y))
+    }
+
+    fn compile(&mut self, input_shape: &[usize]) -> Result<Vec<usize>, MlError> {
+        let size: usize = input_shape.iter().product();
+        self.shape = (size, size);
+        Ok(input_shape.to_vec())
+    }
+
+    fn clone_layer(&self) -> Box<dyn Layer<F>> {
+        Box::new(ActivationLayer {
+            input: self.input.clone(),
+            activation: self.activation.clone_activation(),
+            shape: self.shape,
+        })
     }
 
     fn get_shape(&self) -> (usize, usize) {
@@ -59,7 +76,7 @@ impl Layer for ActivationLayer {
         "ActivationLayer".to_string()
     }
 
-    fn to_json(&self) -> Result<String, Box<dyn Error>> {
+    fn to_json(&self) -> Result<String, MlError> {
         let model: ActivationLayerModel = ActivationLayerModel {
             activation: self.activation.get_name(),
             shape: self.shape,
@@ -69,9 +86,14 @@ impl Layer for ActivationLayer {
 }
 
 #[cfg(test)]
+#[allow(clippy::unused_unit)]
 mod tests {
     use super::*;
+    use crate::activation::relu::Relu;
+    use crate::activation::sigmoid::Sigmoid;
+    use crate::activation::softmax::Softmax;
     use crate::activation::tanh::Tanh;
+    use crate::utils::gradient_check::{check_layer, max_relative_error, GradientCheck};
     use ndarray::arr2;
 
     fn generate_test_activation_layer() -> ActivationLayer {
@@ -84,32 +106,69 @@ mod tests {
 
     #[test]
     fn build_should_initialize_layer() -> () {
-        let layer: ActivationLayer = ActivationLayer::build(Box::new(Tanh), 3, 2);
+        let layer: ActivationLayer = ActivationLayer::build(Box::new(Tanh));
         assert_eq!(layer.input.len(), 0);
-        assert_eq!(layer.shape, (3, 2));
+        assert_eq!(layer.shape, (0, 0));
+    }
+
+    #[test]
+    fn compile_should_infer_shape_from_input() {
+        let mut layer: ActivationLayer = ActivationLayer::build(Box::new(Tanh));
+        assert_eq!(layer.compile(&[3]).unwrap(), vec![3]);
+        assert_eq!(layer.get_shape(), (3, 3));
     }
 
     #[test]
     fn forward_propagation_should_apply_weights_and_bias() -> () {
         let mut layer: ActivationLayer = generate_test_activation_layer();
-        let result: Array2<f64> = layer.forward_propagation(&arr2(&[[0.5, 1.0]]));
+        let result: Array2<f64> = layer.forward_propagation(&arr2(&[[0.5, 1.0]])).unwrap();
         assert_eq!(layer.input, arr2(&[[0.5, 1.0]]));
         let result_vec: Vec<f64> = result.into_raw_vec();
         assert!((result_vec[0] - 0.46211715726000974).powf(2.0) < 0.00001);
         assert!((result_vec[1] - 0.7615941559557649).powf(2.0) < 0.00001);
     }
 
+    #[test]
+    fn predict_should_apply_activation_on_batch() {
+        let layer: ActivationLayer = generate_test_activation_layer();
+        let result: Array2<f64> = layer.predict(&arr2(&[[0.5, 1.0], [0.0, 0.0]])).unwrap();
+        assert_eq!(layer.input, arr2(&[[1.0, 0.5, 0.5]]));
+        assert_eq!(result.shape(), &[2, 2]);
+        assert!((result[[0, 0]] - 0.46211715726000974).powf(2.0) < 0.00001);
+        assert!((result[[0, 1]] - 0.7615941559557649).powf(2.0) < 0.00001);
+        assert_eq!(result.row(1).sum(), 0.0);
+    }
+
     #[test]
     fn backward_propagation_should_return_input_error() -> () {
         let mut layer: ActivationLayer = generate_test_activation_layer();
-        layer.forward_propagation(&arr2(&[[0.9, 0.5]]));
-        let result: Array2<f64> = layer.backward_propagation(&arr2(&[[1.0, 1.0]]), 0.0);
+        layer.forward_propagation(&arr2(&[[0.9, 0.5]])).unwrap();
+        let result: Array2<f64> = layer
+            .backward_propagation(&arr2(&[[1.0, 1.0]]), 0.0)
+            .unwrap();
         assert_eq!(result.shape(), &[1, 2]);
         let result_vec: Vec<f64> = result.into_raw_vec();
         assert!((result_vec[0] - 0.4869173611483415).powf(2.0) < 0.00001);
         assert!((result_vec[1] - 0.7864477329659274).powf(2.0) < 0.00001);
     }
 
+    #[test]
+    fn compute_gradients_should_pass_gradient_check() {
+        let activations: Vec<Box<dyn Activation>> = vec![
+            Box::new(Tanh),
+            Box::new(Relu),
+            Box::new(Sigmoid),
+            Box::new(Softmax),
+        ];
+        let x: Array2<f64> = arr2(&[[0.5, -1.0, 2.0], [1.5, 0.25, -0.75]]);
+        for activation in activations {
+            let mut layer: ActivationLayer = ActivationLayer::build(activation);
+            layer.compile(&[3]).unwrap();
+            let checks: Vec<GradientCheck> = check_layer(&mut layer, &x, 0.0001).unwrap();
+            assert!(max_relative_error(&checks) < 0.0001);
+        }
+    }
+
     #[test]
     fn get_shape_should_return_layer_dim() -> () {
         let layer: ActivationLayer = generate_test_activation_layer();
@@ -135,7 +194,7 @@ mod tests {
     fn from_json_should_deserialize_layer() -> () {
         let target_layer: ActivationLayer = generate_test_activation_layer();
         let json_str: &str = "{\"activation\":\"Tanh\",\"shape\":[2,3]}";
-        let output_layer = ActivationLayer::from_json(json_str).unwrap();
+        let output_layer: ActivationLayer = ActivationLayer::from_json(json_str).unwrap();
         assert_eq!(target_layer.shape, output_layer.shape);
         assert_eq!(target_layer.activation.get_name(), "Tanh");
         assert_eq!(&[0, 0], output_layer.input.shape());
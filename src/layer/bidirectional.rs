@@ -0,0 +1,463 @@
+use crate::layer::layer;
+use crate::layer::layer::Layer;
+use crate::utils::error::{check_shapes, MlError};
+use crate::utils::float::MlFloat;
+use ndarray::{concatenate, s, Array2, Array3, Axis};
+use serde::{Deserialize, Serialize};
+
+/// How the outputs of both directions are merged, concatenated features or their sum.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum MergeMode {
+    Concat,
+    Sum,
+}
+
+/// Runs a recurrent layer over the sequence and another one over the reversed sequence. When
+/// the layers return sequences, the output of the backward one is reversed back so both outputs
+/// are aligned on the same timesteps before being merged.
+pub struct Bidirectional<F: MlFloat = f64> {
+    forward_layer: Box<dyn Layer<F>>,
+    backward_layer: Box<dyn Layer<F>>,
+    merge_mode: MergeMode,
+    input_shape: Vec<usize>,
+    /// Output shape of each direction.
+    layer_output_shape: Vec<usize>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BidirectionalModel {
+    forward_layer: (String, String),
+    backward_layer: (String, String),
+    merge_mode: MergeMode,
+    input_shape: Vec<usize>,
+    layer_output_shape: Vec<usize>,
+}
+
+/// Reverses the order of the timesteps of a batch of sequences flattened in rows.
+fn reverse_timesteps<F: MlFloat>(x: &Array2<F>, timesteps: usize) -> Result<Array2<F>, MlError> {
+    let (batch, columns): (usize, usize) = x.dim();
+    let sequences: Array3<F> =
+        x.as_standard_layout()
+            .into_owned()
+            .into_shape((batch, timesteps, columns / timesteps))?;
+    Ok(sequences
+        .slice(s![.., ..;-1, ..])
+        .as_standard_layout()
+        .into_owned()
+        .into_shape((batch, columns))?)
+}
+
+impl<F: MlFloat> Bidirectional<F> {
+    /// Both directions are initialized and trained separately, `compile` checks that they have
+    /// the same output shape.
+    pub fn build(
+        forward_layer: Box<dyn Layer<F>>,
+        backward_layer: Box<dyn Layer<F>>,
+        merge_mode: MergeMode,
+    ) -> Bidirectional<F> {
+        Bidirectional {
+            forward_layer,
+            backward_layer,
+            merge_mode,
+            input_shape: vec![],
+            layer_output_shape: vec![],
+        }
+    }
+
+    pub fn from_json(json_str: &str) -> Result<Bidirectional<F>, MlError> {
+        let model: BidirectionalModel = serde_json::from_str(json_str)?;
+        let (forward_name, forward_json): (String, String) = model.forward_layer;
+        let (backward_name, backward_json): (String, String) = model.backward_layer;
+        Ok(Bidirectional {
+            forward_layer: layer::from_string(forward_name, forward_json.as_str())?,
+            backward_layer: layer::from_string(backward_name, backward_json.as_str())?,
+            merge_mode: model.merge_mode,
+            input_shape: model.input_shape,
+            layer_output_shape: model.layer_output_shape,
+        })
+    }
+
+    fn timesteps(&self) -> usize {
+        self.input_shape.first().copied().unwrap_or(0)
+    }
+
+    /// Timesteps in the output of each direction, one when only the last states are returned.
+    fn output_timesteps(&self) -> usize {
+        if self.layer_output_shape.len() > 1 {
+            self.layer_output_shape[0]
+        } else {
+            1
+        }
+    }
+
+    fn check_input(&self, x: &Array2<F>) -> Result<(), MlError> {
+        if self.input_shape.is_empty() {
+            return Err(MlError::ShapeMismatch(
+                "Bidirectional must be compiled before use".to_string(),
+            ));
+        }
+        check_shapes(&x.shape()[1..], &[self.input_shape.iter().product()])
+    }
+
+    fn merge(
+        &self,
+        forward_output: Array2<F>,
+        backward_output: Array2<F>,
+    ) -> Result<Array2<F>, MlError> {
+        let backward_output: Array2<F> = if self.layer_output_shape.len() > 1 {
+            reverse_timesteps(&backward_output, self.output_timesteps())?
+        } else {
+            backward_output
+        };
+        match self.merge_mode {
+            MergeMode::Sum => Ok(forward_output + backward_output),
+            MergeMode::Concat => {
+                let (batch, columns): (usize, usize) = forward_output.dim();
+                let shape: (usize, usize, usize) = (
+                    batch,
+                    self.output_timesteps(),
+                    columns / self.output_timesteps(),
+                );
+                let forward_output: Array3<F> = forward_output
+                    .as_standard_layout()
+                    .into_owned()
+                    .into_shape(shape)?;
+                let backward_output: Array3<F> = backward_output
+                    .as_standard_layout()
+                    .into_owned()
+                    .into_shape(shape)?;
+                Ok(
+                    concatenate(Axis(2), &[forward_output.view(), backward_output.view()])?
+                        .as_standard_layout()
+                        .into_owned()
+                        .into_shape((batch, 2 * columns))?,
+                )
+            }
+        }
+    }
+
+    /// Splits the output error into the errors of both directions, in their own time order.
+    fn split(&self, y: &Array2<F>) -> Result<(Array2<F>, Array2<F>), MlError> {
+        let (forward_error, backward_error): (Array2<F>, Array2<F>) = match self.merge_mode {
+            MergeMode::Sum => (y.clone(), y.clone()),
+            MergeMode::Concat => {
+                let (batch, columns): (usize, usize) = y.dim();
+                let size: usize = columns / (2 * self.output_timesteps());
+                let errors: Array3<F> = y.as_standard_layout().into_owned().into_shape((
+                    batch,
+                    self.output_timesteps(),
+                    2 * size,
+                ))?;
+                (
+                    errors
+                        .slice(s![.., .., ..size])
+                        .as_standard_layout()
+                        .into_owned()
+                        .into_shape((batch, columns / 2))?,
+                    errors
+                        .slice(s![.., .., size..])
+                        .as_standard_layout()
+                        .into_owned()
+                        .into_shape((batch, columns / 2))?,
+                )
+            }
+        };
+        if self.layer_output_shape.len() > 1 {
+            Ok((
+                forward_error,
+                reverse_timesteps(&backward_error, self.output_timesteps())?,
+            ))
+        } else {
+            Ok((forward_error, backward_error))
+        }
+    }
+
+    fn output_shape(&self) -> Vec<usize> {
+        let mut output_shape: Vec<usize> = self.layer_output_shape.clone();
+        if self.merge_mode == MergeMode::Concat {
+            if let Some(size) = output_shape.last_mut() {
+                *size *= 2;
+            }
+        }
+        output_shape
+    }
+}
+
+impl<F: MlFloat> Layer<F> for Bidirectional<F> {
+    fn forward_propagation(&mut self, x: &Array2<F>) -> Result<Array2<F>, MlError> {
+        self.check_input(x)?;
+        let forward_output: Array2<F> = self.forward_layer.forward_propagation(x)?;
+        let backward_output: Array2<F> = self
+            .backward_layer
+            .forward_propagation(&reverse_timesteps(x, self.timesteps())?)?;
+        self.merge(forward_output, backward_output)
+    }
+
+    fn predict(&self, x: &Array2<F>) -> Result<Array2<F>, MlError> {
+        self.check_input(x)?;
+        let forward_output: Array2<F> = self.forward_layer.predict(x)?;
+        let backward_output: Array2<F> = self
+            .backward_layer
+            .predict(&reverse_timesteps(x, self.timesteps())?)?;
+        self.merge(forward_output, backward_output)
+    }
+
+    fn compute_gradients(&mut self, y: &Array2<F>) -> Result<Array2<F>, MlError> {
+        check_shapes(&y.shape()[1..], &[self.output_shape().iter().product()])?;
+        let (forward_error, backward_error): (Array2<F>, Array2<F>) = self.split(y)?;
+        let input_error: Array2<F> = self.forward_layer.compute_gradients(&forward_error)?;
+        let backward_input_error: Array2<F> =
+            self.backward_layer.compute_gradients(&backward_error)?;
+        Ok(input_error + reverse_timesteps(&backward_input_error, self.timesteps())?)
+    }
+
+    fn apply_gradients(&mut self, learning_rate: F) {
+        self.forward_layer.apply_gradients(learning_rate);
+        self.backward_layer.apply_gradients(learning_rate);
+    }
+
+    /// Gradients of the forward direction followed by the ones of the backward direction.
+    fn get_gradients(&self) -> Vec<Array2<F>> {
+        let mut gradients: Vec<Array2<F>> = self.forward_layer.get_gradients();
+        gradients.extend(self.backward_layer.get_gradients());
+        gradients
+    }
+
+    fn set_gradients(&mut self, gradients: Vec<Array2<F>>) -> Result<(), MlError> {
+        let count: usize = self.forward_layer.get_gradients().len();
+        let expected: usize = count + self.backward_layer.get_gradients().len();
+        if gradients.len() != expected {
+            return Err(MlError::InvalidInput(format!(
+                "Bidirectional expects {} gradients, actually: {}",
+                expected,
+                gradients.len()
+            )));
+        }
+        let mut gradients: Vec<Array2<F>> = gradients;
+        let backward_gradients: Vec<Array2<F>> = gradients.split_off(count);
+        self.forward_layer.set_gradients(gradients)?;
+        self.backward_layer.set_gradients(backward_gradients)
+    }
+
+    /// Parameters of the forward direction followed by the ones of the backward direction.
+    fn get_parameters(&self) -> Vec<Array2<F>> {
+        let mut parameters: Vec<Array2<F>> = self.forward_layer.get_parameters();
+        parameters.extend(self.backward_layer.get_parameters());
+        parameters
+    }
+
+    fn set_parameters(&mut self, parameters: Vec<Array2<F>>) -> Result<(), MlError> {
+        let count: usize = self.forward_layer.get_parameters().len();
+        let expected: usize = count + self.backward_layer.get_parameters().len();
+        if parameters.len() != expected {
+            return Err(MlError::InvalidInput(format!(
+                "Bidirectional expects {} parameters, actually: {}",
+                expected,
+                parameters.len()
+            )));
+        }
+        let mut parameters: Vec<Array2<F>> = parameters;
+        let backward_parameters: Vec<Array2<F>> = parameters.split_off(count);
+        self.forward_layer.set_parameters(parameters)?;
+        self.backward_layer.set_parameters(backward_parameters)
+    }
+
+    fn compile(&mut self, input_shape: &[usize]) -> Result<Vec<usize>, MlError> {
+        if input_shape.len() != 2 || input_shape[0] == 0 {
+            return Err(MlError::ShapeMismatch(format!(
+                "expected input shape [timesteps, features], actually: {:?}",
+                input_shape
+            )));
+        }
+        let layer_output_shape: Vec<usize> = self.forward_layer.compile(input_shape)?;
+        check_shapes(
+            &self.backward_layer.compile(input_shape)?,
+            &layer_output_shape,
+        )?;
+        self.input_shape = input_shape.to_vec();
+        self.layer_output_shape = layer_output_shape;
+        Ok(self.output_shape())
+    }
+
+    fn is_stateful(&self) -> bool {
+        self.forward_layer.is_stateful() || self.backward_layer.is_stateful()
+    }
+
+    fn count_parameters(&self) -> (usize, usize) {
+        let (trainable, frozen): (usize, usize) = self.forward_layer.count_parameters();
+        let (backward_trainable, backward_frozen): (usize, usize) =
+            self.backward_layer.count_parameters();
+        (trainable + backward_trainable, frozen + backward_frozen)
+    }
+
+    fn clone_layer(&self) -> Box<dyn Layer<F>> {
+        Box::new(Bidirectional {
+            forward_layer: self.forward_layer.clone_layer(),
+            backward_layer: self.backward_layer.clone_layer(),
+            merge_mode: self.merge_mode,
+            input_shape: self.input_shape.clone(),
+            layer_output_shape: self.layer_output_shape.clone(),
+        })
+    }
+
+    fn get_shape(&self) -> (usize, usize) {
+        (
+            self.input_shape.iter().product(),
+            self.output_shape().iter().product(),
+        )
+    }
+
+    fn get_name(&self) -> String {
+        "Bidirectional".to_string()
+    }
+
+    fn to_json(&self) -> Result<String, MlError> {
+        let model: BidirectionalModel = BidirectionalModel {
+            forward_layer: (self.forward_layer.get_name(), self.forward_layer.to_json()?),
+            backward_layer: (
+                self.backward_layer.get_name(),
+                self.backward_layer.to_json()?,
+            ),
+            merge_mode: self.merge_mode,
+            input_shape: self.input_shape.clone(),
+            layer_output_shape: self.layer_output_shape.clone(),
+        };
+        Ok(serde_json::to_string(&model)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layer::gru_layer::GruLayer;
+    use crate::layer::rnn_layer::RnnLayer;
+    use crate::utils::gradient_check::{check_layer, max_relative_error, GradientCheck};
+    use ndarray::arr2;
+
+    fn generate_test_rnn_layer(return_sequences: bool, seed: u64) -> RnnLayer {
+        let mut rnn_layer: RnnLayer = RnnLayer::build_with_seed(2, 3, seed);
+        rnn_layer.set_return_sequences(return_sequences);
+        rnn_layer
+    }
+
+    fn generate_test_bidirectional(return_sequences: bool, merge_mode: MergeMode) -> Bidirectional {
+        let mut bidirectional: Bidirectional = Bidirectional::build(
+            Box::new(generate_test_rnn_layer(return_sequences, 1)),
+            Box::new(generate_test_rnn_layer(return_sequences, 2)),
+            merge_mode,
+        );
+        bidirectional.compile(&[4, 2]).unwrap();
+        bidirectional
+    }
+
+    #[test]
+    fn reverse_timesteps_should_reverse_blocks_of_features() {
+        let x: Array2<f64> = arr2(&[[1.0, 2.0, 3.0, 4.0, 5.0, 6.0]]);
+        assert_eq!(
+            reverse_timesteps(&x, 3).unwrap(),
+            arr2(&[[5.0, 6.0, 3.0, 4.0, 1.0, 2.0]])
+        );
+    }
+
+    #[test]
+    fn compile_should_merge_output_shapes() {
+        let mut bidirectional: Bidirectional =
+            generate_test_bidirectional(false, MergeMode::Concat);
+        assert_eq!(bidirectional.compile(&[4, 2]).unwrap(), vec![6]);
+        let mut bidirectional: Bidirectional = generate_test_bidirectional(true, MergeMode::Concat);
+        assert_eq!(bidirectional.compile(&[4, 2]).unwrap(), vec![4, 6]);
+        let mut bidirectional: Bidirectional = generate_test_bidirectional(true, MergeMode::Sum);
+        assert_eq!(bidirectional.compile(&[4, 2]).unwrap(), vec![4, 3]);
+        assert_eq!(bidirectional.get_shape(), (8, 12));
+        assert!(matches!(
+            bidirectional.compile(&[8]),
+            Err(MlError::ShapeMismatch(_))
+        ));
+        let mut bidirectional: Bidirectional = Bidirectional::build(
+            Box::new(generate_test_rnn_layer(true, 1)),
+            Box::new(generate_test_rnn_layer(false, 2)),
+            MergeMode::Sum,
+        );
+        assert!(matches!(
+            bidirectional.compile(&[4, 2]),
+            Err(MlError::ShapeMismatch(_))
+        ));
+    }
+
+    #[test]
+    fn predict_should_run_layer_over_reversed_sequence() {
+        let bidirectional: Bidirectional = generate_test_bidirectional(true, MergeMode::Concat);
+        let mut forward_layer: RnnLayer = generate_test_rnn_layer(true, 1);
+        forward_layer.compile(&[4, 2]).unwrap();
+        let mut backward_layer: RnnLayer = generate_test_rnn_layer(true, 2);
+        backward_layer.compile(&[4, 2]).unwrap();
+        let x: Array2<f64> =
+            Array2::from_shape_fn((2, 8), |(b, i)| ((b * 8 + i) % 5) as f64 / 4.0 - 0.5);
+
+        let output: Array2<f64> = bidirectional.predict(&x).unwrap();
+        let forward_output: Array2<f64> = forward_layer.predict(&x).unwrap();
+        let backward_output: Array2<f64> = reverse_timesteps(
+            &backward_layer
+                .predict(&reverse_timesteps(&x, 4).unwrap())
+                .unwrap(),
+            4,
+        )
+        .unwrap();
+        for t in 0..4 {
+            assert_eq!(
+                output.slice(s![.., 6 * t..6 * t + 3]),
+                forward_output.slice(s![.., 3 * t..3 * t + 3])
+            );
+            assert_eq!(
+                output.slice(s![.., 6 * t + 3..6 * t + 6]),
+                backward_output.slice(s![.., 3 * t..3 * t + 3])
+            );
+        }
+    }
+
+    #[test]
+    fn compute_gradients_should_pass_gradient_check() {
+        let x: Array2<f64> =
+            Array2::from_shape_fn((2, 8), |(b, i)| ((b * 8 + i) % 5) as f64 / 4.0 - 0.5);
+        for return_sequences in [false, true] {
+            for merge_mode in [MergeMode::Concat, MergeMode::Sum] {
+                let mut bidirectional: Bidirectional =
+                    generate_test_bidirectional(return_sequences, merge_mode);
+                let checks: Vec<GradientCheck> =
+                    check_layer(&mut bidirectional, &x, 0.0001).unwrap();
+                assert_eq!(checks.len(), 7);
+                assert!(max_relative_error(&checks) < 0.0001);
+            }
+        }
+    }
+
+    #[test]
+    fn from_string_should_deserialize_both_directions() {
+        let mut bidirectional: Bidirectional = Bidirectional::build(
+            Box::new(GruLayer::build_with_seed(2, 3, 1)),
+            Box::new(GruLayer::build_with_seed(2, 3, 2)),
+            MergeMode::Sum,
+        );
+        bidirectional.compile(&[4, 2]).unwrap();
+        let x: Array2<f64> =
+            Array2::from_shape_fn((2, 8), |(b, i)| ((b * 8 + i) % 5) as f64 / 4.0 - 0.5);
+        bidirectional.forward_propagation(&x).unwrap();
+        bidirectional
+            .backward_propagation(&Array2::ones((2, 3)), 0.1)
+            .unwrap();
+
+        let result: Box<dyn Layer<f64>> = layer::from_string(
+            "Bidirectional".to_string(),
+            &bidirectional.to_json().unwrap(),
+        )
+        .unwrap();
+        assert_eq!(result.get_name(), "Bidirectional");
+        assert_eq!(result.count_parameters(), bidirectional.count_parameters());
+        let expected: Array2<f64> = bidirectional.predict(&x).unwrap();
+        let actual: Array2<f64> = result.predict(&x).unwrap();
+        assert!(expected
+            .iter()
+            .zip(actual.iter())
+            .all(|(e, a)| (e - a).powf(2.0) < 0.00001));
+    }
+}
@@ -0,0 +1,81 @@
+use crate::layer::conv_layer::ConvolutionLayer;
+
+/// Convolution over sequences of shape `(length, c)`, such as audio or sensor signals.
+pub type Conv1dLayer<F = f64> = ConvolutionLayer<F, 1>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layer::layer::{self, Layer};
+    use crate::utils::error::MlError;
+    use crate::utils::gradient_check::{check_layer, max_relative_error, GradientCheck};
+    use ndarray::{arr2, Array2};
+
+    fn generate_test_conv1d_layer() -> Conv1dLayer {
+        Conv1dLayer::from_json(
+            "{\"kernels\":[1.0,-1.0],\"bias\":[0.5],\"kernel_size\":2,\"kernel_num\":1,\"input_shape\":[0,1]}",
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn forward_propagation_should_apply_kernel_along_sequence() {
+        let mut layer: Conv1dLayer = generate_test_conv1d_layer();
+        assert_eq!(layer.compile(&[5, 1]).unwrap(), vec![4, 1]);
+        let output: Array2<f64> = layer
+            .forward_propagation(&arr2(&[[1.0, 3.0, 2.0, 5.0, 4.0]]))
+            .unwrap();
+        assert_eq!(output, arr2(&[[-1.5, 1.5, -2.5, 1.5]]));
+    }
+
+    #[test]
+    fn forward_propagation_should_use_stride_padding_and_dilation() {
+        let mut layer: Conv1dLayer = generate_test_conv1d_layer();
+        layer.set_stride(2).unwrap();
+        layer.set_padding(1);
+        layer.set_dilation(2).unwrap();
+        // padded sequence 0 1 3 2 5 4 0, windows (0, 3), (3, 5) and (5, 0)
+        assert_eq!(layer.compile(&[5, 1]).unwrap(), vec![3, 1]);
+        let output: Array2<f64> = layer
+            .forward_propagation(&arr2(&[[1.0, 3.0, 2.0, 5.0, 4.0]]))
+            .unwrap();
+        assert_eq!(output, arr2(&[[-2.5, -1.5, 5.5]]));
+        assert!(matches!(
+            layer.compile(&[0, 1]),
+            Err(MlError::ShapeMismatch(_))
+        ));
+    }
+
+    #[test]
+    fn compute_gradients_should_pass_gradient_check() {
+        let mut layer: Conv1dLayer = Conv1dLayer::build_with_seed(3, 2, 2, 3);
+        layer.set_stride(2).unwrap();
+        layer.set_padding(2);
+        layer.set_dilation(2).unwrap();
+        assert_eq!(layer.compile(&[6, 2]).unwrap(), vec![3, 2]);
+        assert_eq!(layer.count_parameters(), (3 * 2 * 2 + 2, 0));
+        let x: Array2<f64> =
+            Array2::from_shape_fn((2, 12), |(b, i)| ((b * 12 + i) % 5) as f64 - 2.0);
+        let checks: Vec<GradientCheck> = check_layer(&mut layer, &x, 0.0001).unwrap();
+        assert_eq!(checks.len(), 3);
+        assert!(max_relative_error(&checks) < 0.0001);
+    }
+
+    #[test]
+    fn from_string_should_deserialize_layer() {
+        let mut conv1d_layer: Conv1dLayer = Conv1dLayer::build_with_seed(3, 2, 2, 3);
+        conv1d_layer.set_stride(2).unwrap();
+        conv1d_layer.compile(&[7, 2]).unwrap();
+        let result: Box<dyn Layer<f64>> =
+            layer::from_string("Conv1dLayer".to_string(), &conv1d_layer.to_json().unwrap())
+                .unwrap();
+        assert_eq!(result.get_name(), "Conv1dLayer");
+        assert_eq!(result.get_shape(), (14, 6));
+        assert!(matches!(
+            Conv1dLayer::<f64>::from_json(
+                "{\"kernels\":[1.0],\"bias\":[0.0],\"kernel_size\":1,\"kernel_num\":1,\"input_shape\":[2,2,1]}"
+            ),
+            Err(MlError::Serialization(_))
+        ));
+    }
+}
@@ -0,0 +1,63 @@
+use crate::layer::conv_layer::ConvolutionLayer;
+
+/// Convolution over volumes of shape `(d, h, w, c)`, such as medical scans.
+pub type Conv3dLayer<F = f64> = ConvolutionLayer<F, 3>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layer::layer::{self, Layer};
+    use crate::utils::error::MlError;
+    use crate::utils::gradient_check::{check_layer, max_relative_error, GradientCheck};
+    use ndarray::{arr2, Array2};
+
+    #[test]
+    fn forward_propagation_should_sum_cube() {
+        let mut layer: Conv3dLayer = Conv3dLayer::from_json(
+            "{\"kernels\":[1.0,1.0,1.0,1.0,1.0,1.0,1.0,1.0],\"bias\":[0.0],\"kernel_size\":2,\"kernel_num\":1,\"input_shape\":[0,0,0,1]}",
+        )
+        .unwrap();
+        assert_eq!(layer.compile(&[2, 2, 3, 1]).unwrap(), vec![1, 1, 2, 1]);
+        let x: Array2<f64> = Array2::from_shape_fn((1, 12), |(_, i)| i as f64);
+        // first cube holds 0 1 3 4 6 7 9 10, the second one every value shifted by one
+        assert_eq!(
+            layer.forward_propagation(&x).unwrap(),
+            arr2(&[[40.0, 48.0]])
+        );
+    }
+
+    #[test]
+    fn compute_gradients_should_pass_gradient_check() {
+        let mut layer: Conv3dLayer = Conv3dLayer::build_with_seed(2, 2, 2, 5);
+        layer.set_stride(2).unwrap();
+        layer.set_padding(1);
+        assert_eq!(layer.compile(&[3, 2, 3, 2]).unwrap(), vec![2, 2, 2, 2]);
+        assert_eq!(layer.count_parameters(), (8 * 2 * 2 + 2, 0));
+        assert!(matches!(
+            layer.compile(&[3, 2, 3, 1]),
+            Err(MlError::ShapeMismatch(_))
+        ));
+        let x: Array2<f64> =
+            Array2::from_shape_fn((2, 36), |(b, i)| ((b * 36 + i) % 7) as f64 / 3.0);
+        let checks: Vec<GradientCheck> = check_layer(&mut layer, &x, 0.0001).unwrap();
+        assert_eq!(checks.len(), 3);
+        assert!(max_relative_error(&checks) < 0.0001);
+    }
+
+    #[test]
+    fn from_string_should_deserialize_layer() {
+        let mut conv3d_layer: Conv3dLayer = Conv3dLayer::build_with_seed(2, 3, 1, 5);
+        conv3d_layer.set_dilation(2).unwrap();
+        conv3d_layer.compile(&[4, 4, 4, 1]).unwrap();
+        let result: Box<dyn Layer<f64>> =
+            layer::from_string("Conv3dLayer".to_string(), &conv3d_layer.to_json().unwrap())
+                .unwrap();
+        assert_eq!(result.get_name(), "Conv3dLayer");
+        assert_eq!(result.get_shape(), (64, 24));
+        let x: Array2<f64> = Array2::from_shape_fn((1, 64), |(_, i)| i as f64 / 64.0);
+        assert_eq!(
+            result.predict(&x).unwrap(),
+            conv3d_layer.predict(&x).unwrap()
+        );
+    }
+}
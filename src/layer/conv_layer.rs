@@ -1,196 +1,347 @@
-use ndarray::{s, Array, Array1, Array2, Array3, ArrayView2, ArrayView3, Axis};
+use crate::layer::convolution::Convolution;
+use crate::layer::layer::Layer;
+use crate::utils::error::{check_shapes, MlError};
+use crate::utils::float::MlFloat;
+use ndarray::{Array, Array2, Axis};
+use ndarray_rand::rand::rngs::StdRng;
+use ndarray_rand::rand::SeedableRng;
 use ndarray_rand::rand_distr::Uniform;
 use ndarray_rand::RandomExt;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug)]
-pub struct ConvLayer<'a> {
-    input: Option<&'a ArrayView2<'a, f64>>,
+/// Convolution over samples of `N` spatial axes followed by the channels, each row of a batch is
+/// one sample flattened in row-major order. The kernels span `kernel_size` values on every
+/// spatial axis, the stride and the dilation default to 1 and the samples are not padded.
+#[derive(Debug, Clone)]
+pub struct ConvolutionLayer<F: MlFloat, const N: usize> {
+    /// Input windows of the last forward pass, one row per output position.
+    columns: Array2<F>,
+    kernel_num: usize,
+    /// One column of `kernel_size^N * c` weights per kernel.
+    kernels: Array2<F>,
+    bias: Array2<F>,
+    kernels_gradient: Array2<F>,
+    bias_gradient: Array2<F>,
+    convolution: Convolution,
+}
+
+/// Convolution over images of shape `(h, w, c)`.
+pub type ConvLayer<F = f64> = ConvolutionLayer<F, 2>;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ConvolutionLayerModel {
+    kernels: Vec<f64>,
+    bias: Vec<f64>,
     kernel_size: usize,
     kernel_num: usize,
-    kernels: Array3<f64>,
+    input_shape: Vec<usize>,
+    #[serde(default = "default_one")]
+    stride: usize,
+    #[serde(default)]
+    padding: usize,
+    #[serde(default = "default_one")]
+    dilation: usize,
 }
 
-impl<'a> ConvLayer<'a> {
-    pub fn build(kernel_size: usize, kernel_num: usize) -> ConvLayer<'a> {
-        ConvLayer {
-            input: None,
+/// Layers saved before the stride and the dilation were recorded always used 1.
+fn default_one() -> usize {
+    1
+}
+
+impl<F: MlFloat, const N: usize> ConvolutionLayer<F, N> {
+    /// The spatial axes of the samples are inferred when the network is compiled.
+    pub fn build(kernel_size: usize, kernel_num: usize, channels: usize) -> ConvolutionLayer<F, N> {
+        let size: usize = kernel_size.pow(N as u32) * channels;
+        ConvolutionLayer::build_with_kernels(
             kernel_size,
-            kernel_num,
-            kernels: Array::random(
-                (kernel_num, kernel_size, kernel_size),
-                Uniform::new(0.0, 1.0),
-            ) / kernel_size.pow(2) as f64,
-        }
+            Array::random((size, kernel_num), Uniform::new(F::zero(), F::one()))
+                / F::cast(size as f64),
+            Array::zeros((1, kernel_num)),
+        )
     }
 
-    fn image_to_patches(
-        &mut self,
-        x: &'a ArrayView2<'a, f64>,
-    ) -> Vec<(ArrayView2<f64>, usize, usize)> {
-        self.input = Some(x);
-        let shape: &[usize] = x.shape();
-        let mut patches_buffer: Vec<(ArrayView2<f64>, usize, usize)> = vec![];
-        for h in 0..(shape[0] - self.kernel_size + 1) {
-            for w in 0..(shape[1] - self.kernel_size + 1) {
-                patches_buffer.push((
-                    x.slice(s![h..(h + self.kernel_size), w..(w + self.kernel_size)]),
-                    h,
-                    w,
-                ));
-            }
+    pub fn build_with_seed(
+        kernel_size: usize,
+        kernel_num: usize,
+        channels: usize,
+        seed: u64,
+    ) -> ConvolutionLayer<F, N> {
+        let mut rng: StdRng = StdRng::seed_from_u64(seed);
+        let size: usize = kernel_size.pow(N as u32) * channels;
+        ConvolutionLayer::build_with_kernels(
+            kernel_size,
+            Array::random_using(
+                (size, kernel_num),
+                Uniform::new(F::zero(), F::one()),
+                &mut rng,
+            ) / F::cast(size as f64),
+            Array::zeros((1, kernel_num)),
+        )
+    }
+
+    fn build_with_kernels(
+        kernel_size: usize,
+        kernels: Array2<F>,
+        bias: Array2<F>,
+    ) -> ConvolutionLayer<F, N> {
+        let channels: usize = kernels.shape()[0] / kernel_size.pow(N as u32).max(1);
+        ConvolutionLayer {
+            columns: Array::zeros((0, kernels.shape()[0])),
+            kernel_num: kernels.shape()[1],
+            kernels_gradient: Array::zeros(kernels.raw_dim()),
+            bias_gradient: Array::zeros(bias.raw_dim()),
+            kernels,
+            bias,
+            convolution: Convolution::build(N, kernel_size, channels),
         }
-        patches_buffer
     }
 
-    pub fn forward_propagation(&mut self, x: &'a ArrayView2<'a, f64>) -> Array3<f64> {
-        let shape: &[usize] = x.shape();
-        let mut convolution_buffer: Vec<f64> = vec![];
-        let kernel_buffer: Array3<f64> = self.kernels.clone();
-        for (patch, _, _) in self.image_to_patches(x) {
-            let product: Array3<f64> = &patch * &kernel_buffer;
-            let sum: Array1<f64> = product.sum_axis(Axis(2)).sum_axis(Axis(1));
-            convolution_buffer.append(&mut sum.into_raw_vec());
+    pub fn from_json(json_str: &str) -> Result<ConvolutionLayer<F, N>, MlError> {
+        let model: ConvolutionLayerModel = serde_json::from_str(json_str)?;
+        if model.input_shape.len() != N + 1 {
+            return Err(MlError::Serialization(format!(
+                "expected an input shape of {} axes, actually: {:?}",
+                N + 1,
+                model.input_shape
+            )));
         }
-        Array3::from_shape_vec(
+        let kernels: Array2<F> = Array2::from_shape_vec(
             (
-                shape[0] - self.kernel_size + 1,
-                shape[1] - self.kernel_size + 1,
-                self.kernel_num,
+                model.kernel_size.pow(N as u32) * model.input_shape[N],
+                model.kernel_num,
             ),
-            convolution_buffer,
+            model.kernels.into_iter().map(F::cast).collect(),
+        )?;
+        let bias: Array2<F> = Array2::from_shape_vec(
+            (1, model.kernel_num),
+            model.bias.into_iter().map(F::cast).collect(),
+        )?;
+        let mut layer: ConvolutionLayer<F, N> =
+            ConvolutionLayer::build_with_kernels(model.kernel_size, kernels, bias);
+        layer.convolution.input_shape = model.input_shape;
+        layer.set_stride(model.stride)?;
+        layer.set_padding(model.padding);
+        layer.set_dilation(model.dilation)?;
+        Ok(layer)
+    }
+
+    /// Distance between two positions of the kernels, to set before compiling the network.
+    pub fn set_stride(&mut self, stride: usize) -> Result<(), MlError> {
+        self.convolution.set_stride(stride)
+    }
+
+    /// Number of zeros added on both sides of every spatial axis, to set before compiling the
+    /// network.
+    pub fn set_padding(&mut self, padding: usize) {
+        self.convolution.padding = padding;
+    }
+
+    /// Distance between two values of a kernel, to set before compiling the network.
+    pub fn set_dilation(&mut self, dilation: usize) -> Result<(), MlError> {
+        self.convolution.set_dilation(dilation)
+    }
+
+    fn output_shape(&self) -> Vec<usize> {
+        let mut output_shape: Vec<usize> = self.convolution.output_shape();
+        output_shape.push(self.kernel_num);
+        output_shape
+    }
+
+    fn image_to_columns(&self, x: &Array2<F>) -> Result<Array2<F>, MlError> {
+        self.convolution.image_to_columns(x)
+    }
+
+    fn columns_to_image(&self, columns: &Array2<F>, batch: usize) -> Result<Array2<F>, MlError> {
+        self.convolution.columns_to_image(columns, batch)
+    }
+
+    fn convolve(&self, columns: &Array2<F>, batch: usize) -> Result<Array2<F>, MlError> {
+        let size: usize = self.output_shape().iter().product();
+        Ok((columns.dot(&self.kernels) + &self.bias).into_shape((batch, size))?)
+    }
+}
+
+impl<F: MlFloat, const N: usize> Layer<F> for ConvolutionLayer<F, N> {
+    fn forward_propagation(&mut self, x: &Array2<F>) -> Result<Array2<F>, MlError> {
+        self.columns = self.image_to_columns(x)?;
+        self.convolve(&self.columns, x.shape()[0])
+    }
+
+    fn predict(&self, x: &Array2<F>) -> Result<Array2<F>, MlError> {
+        self.convolve(&self.image_to_columns(x)?, x.shape()[0])
+    }
+
+    fn compute_gradients(&mut self, y: &Array2<F>) -> Result<Array2<F>, MlError> {
+        let positions: usize = self.convolution.positions();
+        let n: usize = self.kernel_num;
+        let batch: usize = self.columns.shape()[0] / positions.max(1);
+        check_shapes(y.shape(), &[batch, positions * n])?;
+        let y_columns: Array2<F> = y
+            .as_standard_layout()
+            .into_owned()
+            .into_shape((batch * positions, n))?;
+        self.kernels_gradient = self.columns.t().dot(&y_columns);
+        self.bias_gradient = y_columns.sum_axis(Axis(0)).insert_axis(Axis(0));
+        self.columns_to_image(&y_columns.dot(&self.kernels.t()), batch)
+    }
+
+    fn apply_gradients(&mut self, learning_rate: F) {
+        self.kernels = &self.kernels - &(&self.kernels_gradient * learning_rate);
+        self.bias = &self.bias - &(&self.bias_gradient * learning_rate);
+    }
+
+    fn get_gradients(&self) -> Vec<Array2<F>> {
+        vec![self.kernels_gradient.clone(), self.bias_gradient.clone()]
+    }
+
+    fn set_gradients(&mut self, gradients: Vec<Array2<F>>) -> Result<(), MlError> {
+        if gradients.len() != 2 {
+            return Err(MlError::InvalidInput(format!(
+                "{} expects 2 gradients, actually: {}",
+                Layer::<F>::get_name(self),
+                gradients.len()
+            )));
+        }
+        check_shapes(gradients[0].shape(), self.kernels.shape())?;
+        check_shapes(gradients[1].shape(), self.bias.shape())?;
+        self.kernels_gradient = gradients[0].clone();
+        self.bias_gradient = gradients[1].clone();
+        Ok(())
+    }
+
+    fn get_parameters(&self) -> Vec<Array2<F>> {
+        vec![self.kernels.clone(), self.bias.clone()]
+    }
+
+    fn set_parameters(&mut self, parameters: Vec<Array2<F>>) -> Result<(), MlError> {
+        if parameters.len() != 2 {
+            return Err(MlError::InvalidInput(format!(
+                "{} expects 2 parameters, actually: {}",
+                Layer::<F>::get_name(self),
+                parameters.len()
+            )));
+        }
+        check_shapes(parameters[0].shape(), self.kernels.shape())?;
+        check_shapes(parameters[1].shape(), self.bias.shape())?;
+        self.kernels = parameters[0].clone();
+        self.bias = parameters[1].clone();
+        Ok(())
+    }
+
+    fn compile(&mut self, input_shape: &[usize]) -> Result<Vec<usize>, MlError> {
+        let channels: usize = self.convolution.channels();
+        if input_shape.last() != Some(&channels) {
+            return Err(MlError::ShapeMismatch(format!(
+                "expected input shape with {} channels, actually: {:?}",
+                channels, input_shape
+            )));
+        }
+        self.convolution.compile(input_shape)?;
+        Ok(self.output_shape())
+    }
+
+    fn count_parameters(&self) -> (usize, usize) {
+        (self.kernels.len() + self.bias.len(), 0)
+    }
+
+    fn clone_layer(&self) -> Box<dyn Layer<F>> {
+        Box::new(self.clone())
+    }
+
+    fn get_shape(&self) -> (usize, usize) {
+        (
+            self.convolution.input_size(),
+            self.output_shape().iter().product(),
         )
-        .unwrap()
     }
 
-    pub fn backward_propagation(
-        &mut self,
-        y: &ArrayView3<'a, f64>,
-        learning_rate: f64,
-    ) -> Array3<f64> {
-        let kernel_num: usize = self.kernel_num.clone();
-        let mut kernel_error: Array3<f64> =
-            Array3::zeros((self.kernel_num, self.kernel_size, self.kernel_size));
-        for (patch, h, w) in self.image_to_patches(self.input.unwrap()) {
-            for k in 0..kernel_num {
-                let product: Array2<f64> = &patch * y[[h, w, k]];
-                for i in 0..product.shape()[0] {
-                    for j in 0..product.shape()[1] {
-                        kernel_error[[k, i, j]] += product[[i, j]];
-                    }
-                }
-            }
+    fn get_name(&self) -> String {
+        match N {
+            1 => "Conv1dLayer".to_string(),
+            3 => "Conv3dLayer".to_string(),
+            _ => "ConvLayer".to_string(),
         }
-        self.kernels = &self.kernels - (learning_rate * &kernel_error);
-        kernel_error
+    }
+
+    fn to_json(&self) -> Result<String, MlError> {
+        let model: ConvolutionLayerModel = ConvolutionLayerModel {
+            kernels: self.kernels.iter().map(|&k| k.to_f64_lossy()).collect(),
+            bias: self.bias.iter().map(|&b| b.to_f64_lossy()).collect(),
+            kernel_size: self.convolution.kernel_size,
+            kernel_num: self.kernel_num,
+            input_shape: self.convolution.input_shape.clone(),
+            stride: self.convolution.stride,
+            padding: self.convolution.padding,
+            dilation: self.convolution.dilation,
+        };
+        Ok(serde_json::to_string(&model)?)
     }
 }
 
 #[cfg(test)]
+#[allow(clippy::unused_unit)]
 mod tests {
     use super::*;
+    use crate::utils::gradient_check::{check_layer, max_relative_error, GradientCheck};
     use ndarray::arr2;
 
-    fn generate_test_conv_layer<'a>() -> ConvLayer<'a> {
-        ConvLayer {
-            input: None,
-            kernel_size: 2,
-            kernel_num: 2,
-            kernels: Array3::from_shape_vec(
-                (2, 2, 2),
-                vec![1.0, 0.5, 1.5, 2.5, 0.5, 3.5, 4.0, 1.5],
-            )
-            .unwrap(),
-        }
+    fn generate_test_conv_layer() -> ConvLayer {
+        let mut layer: ConvLayer = ConvLayer::build_with_kernels(
+            2,
+            arr2(&[[1.0, 0.5], [0.5, 3.5], [1.5, 4.0], [2.5, 1.5]]),
+            Array2::zeros((1, 2)),
+        );
+        layer.compile(&[4, 4, 1]).unwrap();
+        layer
+    }
+
+    fn generate_test_image() -> Array2<f64> {
+        Array2::from_shape_vec(
+            (1, 16),
+            vec![
+                6.0, 6.0, 7.0, 11.0, 4.0, 6.0, 7.0, 9.0, 2.0, 5.0, 7.0, 9.0, 6.0, 6.0, 7.0, 9.0,
+            ],
+        )
+        .unwrap()
     }
 
     #[test]
     fn build_should_initialize_layer() -> () {
-        let layer: ConvLayer = ConvLayer::build(2, 2);
-        assert!(layer.input.is_none());
-        assert_eq!(layer.kernel_size, 2);
-        assert_eq!(layer.kernel_num, 2);
-        assert_eq!(layer.kernels.shape(), &[2, 2, 2]);
+        let layer: ConvLayer = ConvLayer::build(2, 3, 4);
+        assert_eq!(layer.columns.len(), 0);
+        assert_eq!(layer.convolution.kernel_size, 2);
+        assert_eq!(layer.kernel_num, 3);
+        assert_eq!(layer.kernels.shape(), &[16, 3]);
+        assert_eq!(layer.bias.shape(), &[1, 3]);
+        assert_eq!(layer.convolution.input_shape, vec![0, 0, 4]);
     }
 
     #[test]
-    fn image_to_patches_should_return_vec_of_pattern() -> () {
-        let mut layer: ConvLayer = generate_test_conv_layer();
-        let x: Array2<f64> = arr2(&[
-            [6.0, 6.0, 7.0, 11.0],
-            [4.0, 6.0, 7.0, 9.0],
-            [2.0, 5.0, 7.0, 9.0],
-            [6.0, 6.0, 7.0, 9.0],
-        ]);
-        let x_view: ArrayView2<f64> = x.view();
-        let output: Vec<(ArrayView2<f64>, usize, usize)> = layer.image_to_patches(&x_view);
-        let target: Vec<(Array2<f64>, usize, usize)> = vec![
-            (
-                Array2::from_shape_vec((2, 2), vec![6.0, 6.0, 4.0, 6.0]).unwrap(),
-                0,
-                0,
-            ),
-            (
-                Array2::from_shape_vec((2, 2), vec![6.0, 7.0, 6.0, 7.0]).unwrap(),
-                0,
-                1,
-            ),
-            (
-                Array2::from_shape_vec((2, 2), vec![7.0, 11.0, 7.0, 9.0]).unwrap(),
-                0,
-                2,
-            ),
-            (
-                Array2::from_shape_vec((2, 2), vec![4.0, 6.0, 2.0, 5.0]).unwrap(),
-                1,
-                0,
-            ),
-            (
-                Array2::from_shape_vec((2, 2), vec![6.0, 7.0, 5.0, 7.0]).unwrap(),
-                1,
-                1,
-            ),
-            (
-                Array2::from_shape_vec((2, 2), vec![7.0, 9.0, 7.0, 9.0]).unwrap(),
-                1,
-                2,
-            ),
-            (
-                Array2::from_shape_vec((2, 2), vec![2.0, 5.0, 6.0, 6.0]).unwrap(),
-                2,
-                0,
-            ),
-            (
-                Array2::from_shape_vec((2, 2), vec![5.0, 7.0, 6.0, 7.0]).unwrap(),
-                2,
-                1,
-            ),
-            (
-                Array2::from_shape_vec((2, 2), vec![7.0, 9.0, 7.0, 9.0]).unwrap(),
-                2,
-                2,
-            ),
-        ];
-        assert_eq!(output.len(), target.len());
-        for i in 0..target.len() {
-            assert_eq!(output[i].0, target[i].0.view());
-            assert_eq!(output[i].1, target[i].1);
-            assert_eq!(output[i].2, target[i].2);
-        }
+    fn image_to_columns_should_return_patches() {
+        let layer: ConvLayer = generate_test_conv_layer();
+        let columns: Array2<f64> = layer.image_to_columns(&generate_test_image()).unwrap();
+        assert_eq!(
+            columns,
+            arr2(&[
+                [6.0, 6.0, 4.0, 6.0],
+                [6.0, 7.0, 6.0, 7.0],
+                [7.0, 11.0, 7.0, 9.0],
+                [4.0, 6.0, 2.0, 5.0],
+                [6.0, 7.0, 5.0, 7.0],
+                [7.0, 9.0, 7.0, 9.0],
+                [2.0, 5.0, 6.0, 6.0],
+                [5.0, 7.0, 6.0, 7.0],
+                [7.0, 9.0, 7.0, 9.0],
+            ])
+        );
     }
 
     #[test]
     fn forward_propagation_apply_kernel() -> () {
         let mut layer: ConvLayer = generate_test_conv_layer();
-        let x: Array2<f64> = arr2(&[
-            [6.0, 6.0, 7.0, 11.0],
-            [4.0, 6.0, 7.0, 9.0],
-            [2.0, 5.0, 7.0, 9.0],
-            [6.0, 6.0, 7.0, 9.0],
-        ]);
-        let x_view: ArrayView2<f64> = x.view();
-        let output: Array3<f64> = layer.forward_propagation(&x_view);
-        let target: Array3<f64> = Array3::from_shape_vec(
-            (3, 3, 2),
+        let output: Array2<f64> = layer.forward_propagation(&generate_test_image()).unwrap();
+        let target: Array2<f64> = Array2::from_shape_vec(
+            (1, 18),
             vec![
                 30.0, 49.0, 36.0, 62.0, 45.5, 83.5, 22.5, 38.5, 34.5, 58.0, 44.5, 76.5, 28.5, 51.5,
                 35.0, 61.5, 44.5, 76.5,
@@ -203,45 +354,101 @@ mod tests {
     #[test]
     fn backward_propagation_should_correct_kernel() -> () {
         let mut layer: ConvLayer = generate_test_conv_layer();
-        let x: Array2<f64> = arr2(&[
-            [6.0, 6.0, 7.0, 11.0],
-            [4.0, 6.0, 7.0, 9.0],
-            [2.0, 5.0, 7.0, 9.0],
-            [6.0, 6.0, 7.0, 9.0],
-        ]);
-        let x_view: ArrayView2<f64> = x.view();
-        layer.forward_propagation(&x_view);
-        let error: Array3<f64> = Array3::from_shape_vec(
-            (3, 3, 2),
+        layer.forward_propagation(&generate_test_image()).unwrap();
+        let error: Array2<f64> = Array2::from_shape_vec(
+            (1, 18),
             vec![
                 0.1, 0.2, 0.3, 0.4, 0.9, 1.0, 0.5, 0.6, 0.7, 0.8, 1.1, 1.2, 1.5, 1.6, 1.7, 1.8,
                 1.9, 2.0,
             ],
         )
         .unwrap();
-        let error_view: ArrayView3<f64> = error.view();
-        let target: Array3<f64> = Array3::from_shape_vec(
-            (2, 2, 2),
-            vec![47.4, 66.9, 53.2, 66.1, 52.4, 73.6, 58.2, 72.6],
-        )
-        .unwrap();
-        let output: Array3<f64> = layer.backward_propagation(&error_view, 0.1);
-        assert_eq!(target.shape(), output.shape());
-        let target_vec: Vec<f64> = target.into_raw_vec();
-        let output_vec: Vec<f64> = output.into_raw_vec();
-        for i in 0..8 {
-            assert!((target_vec[i] - output_vec[i]).powf(2.0) < 0.00001);
+        let input_error: Array2<f64> = layer.backward_propagation(&error, 0.1).unwrap();
+
+        let target_gradient: Array2<f64> =
+            arr2(&[[47.4, 52.4], [66.9, 73.6], [53.2, 58.2], [66.1, 72.6]]);
+        for (target, output) in target_gradient.iter().zip(layer.kernels_gradient.iter()) {
+            assert!((target - output).powf(2.0) < 0.00001);
         }
-        let target_kernels: Array3<f64> = Array3::from_shape_vec(
-            (2, 2, 2),
-            vec![-3.74, -6.19, -3.82, -4.11, -4.74, -3.86, -1.82, -5.76],
-        )
-        .unwrap();
-        assert_eq!(target_kernels.shape(), layer.kernels.shape());
-        let target_kernels_vec: Vec<f64> = target_kernels.into_raw_vec();
-        let output_kernels_vec: Vec<f64> = layer.kernels.into_raw_vec();
-        for i in 0..8 {
-            assert!((target_kernels_vec[i] - output_kernels_vec[i]).powf(2.0) < 0.00001);
+        let target_kernels: Array2<f64> = arr2(&[
+            [-3.74, -4.74],
+            [-6.19, -3.86],
+            [-3.82, -1.82],
+            [-4.11, -5.76],
+        ]);
+        for (target, output) in target_kernels.iter().zip(layer.kernels.iter()) {
+            assert!((target - output).powf(2.0) < 0.00001);
         }
+        assert!((layer.bias[[0, 0]] + 0.87).powf(2.0) < 0.00001);
+        assert!((layer.bias[[0, 1]] + 0.96).powf(2.0) < 0.00001);
+        // first pixel only sees the first patch: 0.1 * 1.0 + 0.2 * 0.5
+        assert_eq!(input_error.shape(), &[1, 16]);
+        assert!((input_error[[0, 0]] - 0.2).powf(2.0) < 0.00001);
+    }
+
+    #[test]
+    fn compute_gradients_should_pass_gradient_check() {
+        let mut layer: ConvLayer = ConvLayer::build_with_seed(2, 2, 2, 7);
+        layer.compile(&[3, 3, 2]).unwrap();
+        let x: Array2<f64> = Array2::from_shape_fn((2, 18), |(b, i)| (b * 18 + i) as f64 / 10.0);
+        let checks: Vec<GradientCheck> = check_layer(&mut layer, &x, 0.0001).unwrap();
+        assert_eq!(checks.len(), 3);
+        assert!(max_relative_error(&checks) < 0.0001);
+    }
+
+    #[test]
+    fn compute_gradients_should_pass_gradient_check_with_stride_padding_and_dilation() {
+        let mut layer: ConvLayer = ConvLayer::build_with_seed(2, 2, 2, 7);
+        layer.set_stride(2).unwrap();
+        layer.set_padding(1);
+        layer.set_dilation(2).unwrap();
+        assert_eq!(layer.compile(&[4, 5, 2]).unwrap(), vec![2, 3, 2]);
+        let x: Array2<f64> = Array2::from_shape_fn((2, 40), |(b, i)| (b * 40 + i) as f64 / 20.0);
+        let checks: Vec<GradientCheck> = check_layer(&mut layer, &x, 0.0001).unwrap();
+        assert_eq!(checks.len(), 3);
+        assert!(max_relative_error(&checks) < 0.0001);
+    }
+
+    #[test]
+    fn set_stride_should_reject_zero() {
+        let mut layer: ConvLayer = ConvLayer::build(2, 2, 1);
+        assert!(matches!(layer.set_stride(0), Err(MlError::InvalidInput(_))));
+        assert!(matches!(
+            layer.set_dilation(0),
+            Err(MlError::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn compile_should_return_output_shape() {
+        let mut layer: ConvLayer = ConvLayer::build(3, 4, 2);
+        assert_eq!(layer.compile(&[5, 6, 2]).unwrap(), vec![3, 4, 4]);
+        assert_eq!(layer.get_shape(), (60, 48));
+        assert!(matches!(
+            layer.compile(&[5, 6, 3]),
+            Err(MlError::ShapeMismatch(_))
+        ));
+        assert!(matches!(
+            layer.compile(&[2, 6, 2]),
+            Err(MlError::ShapeMismatch(_))
+        ));
+    }
+
+    #[test]
+    fn predict_should_fail_when_shapes_do_not_fit() {
+        let layer: ConvLayer = generate_test_conv_layer();
+        assert!(matches!(
+            layer.predict(&Array2::zeros((1, 15))),
+            Err(MlError::ShapeMismatch(_))
+        ));
+    }
+
+    #[test]
+    fn from_json_should_deserialize_layer() {
+        let layer: ConvLayer = generate_test_conv_layer();
+        let result: ConvLayer<f32> = ConvLayer::from_json(&layer.to_json().unwrap()).unwrap();
+        assert_eq!(result.convolution.input_shape, vec![4, 4, 1]);
+        assert_eq!(result.kernels, layer.kernels.mapv(|k| k as f32));
+        assert_eq!(result.get_name(), "ConvLayer");
     }
 }
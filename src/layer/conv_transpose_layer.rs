@@ -0,0 +1,449 @@
+use crate::layer::convolution::Convolution;
+use crate::layer::layer::Layer;
+use crate::utils::error::{check_shapes, MlError};
+use crate::utils::float::MlFloat;
+use ndarray::{Array, Array2, Axis};
+use ndarray_rand::rand::rngs::StdRng;
+use ndarray_rand::rand::SeedableRng;
+use ndarray_rand::rand_distr::Uniform;
+use ndarray_rand::RandomExt;
+use serde::{Deserialize, Serialize};
+
+/// Transposed convolution over feature maps of shape `(h, w, c)`, each row of a batch is one
+/// sample flattened in row-major order. Every input value spreads a `kernel_size x kernel_size`
+/// patch in the output, the patches are `stride` apart and `padding` values are cropped on every
+/// side, so the output has the shape `((h - 1) * stride - 2 * padding + kernel_size, ..., n)`.
+/// It is the gradient of a `ConvLayer` with the same kernels.
+#[derive(Debug, Clone)]
+pub struct ConvTransposeLayer<F: MlFloat = f64> {
+    /// Input of the last forward pass, one row per input position.
+    rows: Array2<F>,
+    kernel_num: usize,
+    /// One column of `kernel_size * kernel_size * n` weights per input channel.
+    kernels: Array2<F>,
+    bias: Array2<F>,
+    kernels_gradient: Array2<F>,
+    bias_gradient: Array2<F>,
+    input_shape: Vec<usize>,
+    /// Convolution from the output back to the input.
+    convolution: Convolution,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ConvTransposeLayerModel {
+    kernels: Vec<f64>,
+    bias: Vec<f64>,
+    kernel_size: usize,
+    kernel_num: usize,
+    input_shape: Vec<usize>,
+    stride: usize,
+    padding: usize,
+}
+
+impl<F: MlFloat> ConvTransposeLayer<F> {
+    /// The height and width of the feature maps are inferred when the network is compiled.
+    pub fn build(kernel_size: usize, kernel_num: usize, channels: usize) -> ConvTransposeLayer<F> {
+        let size: usize = kernel_size * kernel_size * kernel_num;
+        ConvTransposeLayer::build_with_kernels(
+            kernel_size,
+            Array::random((size, channels), Uniform::new(F::zero(), F::one()))
+                / F::cast(size as f64),
+            Array::zeros((1, kernel_num)),
+        )
+    }
+
+    pub fn build_with_seed(
+        kernel_size: usize,
+        kernel_num: usize,
+        channels: usize,
+        seed: u64,
+    ) -> ConvTransposeLayer<F> {
+        let mut rng: StdRng = StdRng::seed_from_u64(seed);
+        let size: usize = kernel_size * kernel_size * kernel_num;
+        ConvTransposeLayer::build_with_kernels(
+            kernel_size,
+            Array::random_using(
+                (size, channels),
+                Uniform::new(F::zero(), F::one()),
+                &mut rng,
+            ) / F::cast(size as f64),
+            Array::zeros((1, kernel_num)),
+        )
+    }
+
+    fn build_with_kernels(
+        kernel_size: usize,
+        kernels: Array2<F>,
+        bias: Array2<F>,
+    ) -> ConvTransposeLayer<F> {
+        let kernel_num: usize = bias.shape()[1];
+        ConvTransposeLayer {
+            rows: Array::zeros((0, kernels.shape()[1])),
+            kernel_num,
+            kernels_gradient: Array::zeros(kernels.raw_dim()),
+            bias_gradient: Array::zeros(bias.raw_dim()),
+            input_shape: vec![0, 0, kernels.shape()[1]],
+            kernels,
+            bias,
+            convolution: Convolution::build(2, kernel_size, kernel_num),
+        }
+    }
+
+    pub fn from_json(json_str: &str) -> Result<ConvTransposeLayer<F>, MlError> {
+        let model: ConvTransposeLayerModel = serde_json::from_str(json_str)?;
+        if model.input_shape.len() != 3 {
+            return Err(MlError::Serialization(format!(
+                "expected an input shape of 3 axes, actually: {:?}",
+                model.input_shape
+            )));
+        }
+        let kernels: Array2<F> = Array2::from_shape_vec(
+            (
+                model.kernel_size * model.kernel_size * model.kernel_num,
+                model.input_shape[2],
+            ),
+            model.kernels.into_iter().map(F::cast).collect(),
+        )?;
+        let bias: Array2<F> = Array2::from_shape_vec(
+            (1, model.kernel_num),
+            model.bias.into_iter().map(F::cast).collect(),
+        )?;
+        let mut layer: ConvTransposeLayer<F> =
+            ConvTransposeLayer::build_with_kernels(model.kernel_size, kernels, bias);
+        layer.set_stride(model.stride)?;
+        layer.set_padding(model.padding);
+        if model.input_shape[..2] != [0, 0] {
+            layer.compile(&model.input_shape)?;
+        }
+        Ok(layer)
+    }
+
+    /// Distance between the patches of two neighbouring input values, to set before compiling the
+    /// network.
+    pub fn set_stride(&mut self, stride: usize) -> Result<(), MlError> {
+        self.convolution.set_stride(stride)
+    }
+
+    /// Number of values cropped on every side of the output, to set before compiling the network.
+    pub fn set_padding(&mut self, padding: usize) {
+        self.convolution.padding = padding;
+    }
+
+    fn positions(&self) -> usize {
+        self.input_shape[0] * self.input_shape[1]
+    }
+
+    /// Adds the bias to every position of the output feature maps.
+    fn add_bias(&self, output: Array2<F>) -> Result<Array2<F>, MlError> {
+        let (batch, size): (usize, usize) = (output.shape()[0], output.shape()[1]);
+        let pixels: Array2<F> = output
+            .into_shape((batch * size / self.kernel_num.max(1), self.kernel_num))?
+            + &self.bias;
+        Ok(pixels.into_shape((batch, size))?)
+    }
+
+    fn spread(&self, rows: &Array2<F>, batch: usize) -> Result<Array2<F>, MlError> {
+        let output: Array2<F> = self
+            .convolution
+            .columns_to_image(&rows.dot(&self.kernels.t()), batch)?;
+        self.add_bias(output)
+    }
+
+    fn to_rows(&self, x: &Array2<F>) -> Result<Array2<F>, MlError> {
+        let size: usize = self.input_shape.iter().product();
+        if x.shape()[1] != size {
+            return Err(MlError::ShapeMismatch(format!(
+                "ConvTransposeLayer expects {} input columns, actually: {}",
+                size,
+                x.shape()[1]
+            )));
+        }
+        Ok(x.as_standard_layout()
+            .into_owned()
+            .into_shape((x.shape()[0] * self.positions(), self.input_shape[2]))?)
+    }
+}
+
+impl<F: MlFloat> Layer<F> for ConvTransposeLayer<F> {
+    fn forward_propagation(&mut self, x: &Array2<F>) -> Result<Array2<F>, MlError> {
+        self.rows = self.to_rows(x)?;
+        self.spread(&self.rows, x.shape()[0])
+    }
+
+    fn predict(&self, x: &Array2<F>) -> Result<Array2<F>, MlError> {
+        self.spread(&self.to_rows(x)?, x.shape()[0])
+    }
+
+    fn compute_gradients(&mut self, y: &Array2<F>) -> Result<Array2<F>, MlError> {
+        let batch: usize = self.rows.shape()[0] / self.positions().max(1);
+        check_shapes(y.shape(), &[batch, self.convolution.input_size()])?;
+        let y_columns: Array2<F> = self.convolution.image_to_columns(y)?;
+        self.kernels_gradient = y_columns.t().dot(&self.rows);
+        self.bias_gradient = y
+            .as_standard_layout()
+            .into_owned()
+            .into_shape((y.len() / self.kernel_num.max(1), self.kernel_num))?
+            .sum_axis(Axis(0))
+            .insert_axis(Axis(0));
+        Ok(y_columns
+            .dot(&self.kernels)
+            .into_shape((batch, self.positions() * self.input_shape[2]))?)
+    }
+
+    fn apply_gradients(&mut self, learning_rate: F) {
+        self.kernels = &self.kernels - &(&self.kernels_gradient * learning_rate);
+        self.bias = &self.bias - &(&self.bias_gradient * learning_rate);
+    }
+
+    fn get_gradients(&self) -> Vec<Array2<F>> {
+        vec![self.kernels_gradient.clone(), self.bias_gradient.clone()]
+    }
+
+    fn set_gradients(&mut self, gradients: Vec<Array2<F>>) -> Result<(), MlError> {
+        if gradients.len() != 2 {
+            return Err(MlError::InvalidInput(format!(
+                "ConvTransposeLayer expects 2 gradients, actually: {}",
+                gradients.len()
+            )));
+        }
+        check_shapes(gradients[0].shape(), self.kernels.shape())?;
+        check_shapes(gradients[1].shape(), self.bias.shape())?;
+        self.kernels_gradient = gradients[0].clone();
+        self.bias_gradient = gradients[1].clone();
+        Ok(())
+    }
+
+    fn get_parameters(&self) -> Vec<Array2<F>> {
+        vec![self.kernels.clone(), self.bias.clone()]
+    }
+
+    fn set_parameters(&mut self, parameters: Vec<Array2<F>>) -> Result<(), MlError> {
+        if parameters.len() != 2 {
+            return Err(MlError::InvalidInput(format!(
+                "ConvTransposeLayer expects 2 parameters, actually: {}",
+                parameters.len()
+            )));
+        }
+        check_shapes(parameters[0].shape(), self.kernels.shape())?;
+        check_shapes(parameters[1].shape(), self.bias.shape())?;
+        self.kernels = parameters[0].clone();
+        self.bias = parameters[1].clone();
+        Ok(())
+    }
+
+    fn compile(&mut self, input_shape: &[usize]) -> Result<Vec<usize>, MlError> {
+        let channels: usize = self.input_shape[2];
+        let (k, s, p): (usize, usize, usize) = (
+            self.convolution.kernel_size,
+            self.convolution.stride,
+            self.convolution.padding,
+        );
+        let output_size = |size: usize| ((size - 1) * s + k).checked_sub(2 * p);
+        match *input_shape {
+            [h, w, c] if c == channels && h > 0 && w > 0 => {
+                match (output_size(h), output_size(w)) {
+                    (Some(oh), Some(ow)) if oh > 0 && ow > 0 => {
+                        let output_shape: Vec<usize> = vec![oh, ow, self.kernel_num];
+                        self.convolution.compile(&output_shape)?;
+                        self.input_shape = input_shape.to_vec();
+                        Ok(output_shape)
+                    }
+                    _ => Err(MlError::ShapeMismatch(format!(
+                        "padding {} crops the whole output of input shape {:?}",
+                        p, input_shape
+                    ))),
+                }
+            }
+            _ => Err(MlError::ShapeMismatch(format!(
+                "expected input shape [h, w, {}], actually: {:?}",
+                channels, input_shape
+            ))),
+        }
+    }
+
+    fn count_parameters(&self) -> (usize, usize) {
+        (self.kernels.len() + self.bias.len(), 0)
+    }
+
+    fn clone_layer(&self) -> Box<dyn Layer<F>> {
+        Box::new(self.clone())
+    }
+
+    fn get_shape(&self) -> (usize, usize) {
+        (
+            self.input_shape.iter().product(),
+            self.convolution.input_size(),
+        )
+    }
+
+    fn get_name(&self) -> String {
+        "ConvTransposeLayer".to_string()
+    }
+
+    fn to_json(&self) -> Result<String, MlError> {
+        let model: ConvTransposeLayerModel = ConvTransposeLayerModel {
+            kernels: self.kernels.iter().map(|&k| k.to_f64_lossy()).collect(),
+            bias: self.bias.iter().map(|&b| b.to_f64_lossy()).collect(),
+            kernel_size: self.convolution.kernel_size,
+            kernel_num: self.kernel_num,
+            input_shape: self.input_shape.clone(),
+            stride: self.convolution.stride,
+            padding: self.convolution.padding,
+        };
+        Ok(serde_json::to_string(&model)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layer::conv_layer::ConvLayer;
+    use crate::layer::flatten_layer::FlattenLayer;
+    use crate::layer::layer;
+    use crate::loss::mse::Mse;
+    use crate::network::sequential::Sequential;
+    use crate::report::report::Report;
+    use crate::utils::gradient_check::{check_layer, max_relative_error, GradientCheck};
+    use ndarray::{arr2, Array4};
+    use std::fs;
+
+    #[test]
+    fn forward_propagation_should_spread_kernel_with_stride() {
+        let mut layer: ConvTransposeLayer = ConvTransposeLayer::build_with_kernels(
+            2,
+            arr2(&[[1.0], [2.0], [3.0], [4.0]]),
+            arr2(&[[0.5]]),
+        );
+        layer.set_stride(2).unwrap();
+        assert_eq!(layer.compile(&[1, 2, 1]).unwrap(), vec![2, 4, 1]);
+        let output: Array2<f64> = layer.forward_propagation(&arr2(&[[1.0, 10.0]])).unwrap();
+        assert_eq!(
+            output,
+            arr2(&[[1.5, 2.5, 10.5, 20.5, 3.5, 4.5, 30.5, 40.5]])
+        );
+    }
+
+    #[test]
+    fn forward_propagation_should_sum_overlaps_and_crop_padding() {
+        let mut layer: ConvTransposeLayer =
+            ConvTransposeLayer::build_with_kernels(3, Array2::ones((9, 1)), arr2(&[[0.0]]));
+        layer.set_padding(1);
+        assert_eq!(layer.compile(&[2, 2, 1]).unwrap(), vec![2, 2, 1]);
+        let output: Array2<f64> = layer
+            .forward_propagation(&arr2(&[[1.0, 2.0, 3.0, 4.0]]))
+            .unwrap();
+        // every output value is covered by the patches of the four inputs
+        assert_eq!(output, arr2(&[[10.0, 10.0, 10.0, 10.0]]));
+    }
+
+    #[test]
+    fn forward_propagation_should_be_adjoint_of_convolution() {
+        let kernels: Vec<f64> = (0..54).map(|i| (i % 5) as f64 - 2.0).collect();
+        let json: String = format!(
+            "{{\"kernels\":{:?},\"bias\":[0.0,0.0,0.0],\"kernel_size\":3,\"kernel_num\":3,\"input_shape\":[5,5,2],\"stride\":2,\"padding\":1}}",
+            kernels
+        );
+        let conv_layer: ConvLayer = ConvLayer::from_json(&json).unwrap();
+        let mut conv_transpose_layer: ConvTransposeLayer = ConvTransposeLayer::from_json(
+            &format!(
+                "{{\"kernels\":{:?},\"bias\":[0.0,0.0],\"kernel_size\":3,\"kernel_num\":2,\"input_shape\":[3,3,3],\"stride\":2,\"padding\":1}}",
+                kernels
+            ),
+        )
+        .unwrap();
+        assert_eq!(conv_transpose_layer.get_shape(), (27, 50));
+        let x: Array2<f64> = Array2::from_shape_fn((1, 50), |(_, i)| (i % 7) as f64 - 3.0);
+        let y: Array2<f64> = Array2::from_shape_fn((1, 27), |(_, i)| (i % 4) as f64 - 1.5);
+        let convolved: f64 = (&conv_layer.predict(&x).unwrap() * &y).sum();
+        let transposed: f64 = (&x * &conv_transpose_layer.forward_propagation(&y).unwrap()).sum();
+        assert!((convolved - transposed).powf(2.0) < 0.00001);
+    }
+
+    #[test]
+    fn compute_gradients_should_pass_gradient_check() {
+        let mut layer: ConvTransposeLayer = ConvTransposeLayer::build_with_seed(3, 2, 2, 4);
+        layer.set_stride(2).unwrap();
+        layer.set_padding(1);
+        assert_eq!(layer.compile(&[2, 3, 2]).unwrap(), vec![3, 5, 2]);
+        let x: Array2<f64> =
+            Array2::from_shape_fn((2, 12), |(b, i)| ((b * 12 + i) % 5) as f64 - 2.0);
+        let checks: Vec<GradientCheck> = check_layer(&mut layer, &x, 0.0001).unwrap();
+        assert_eq!(checks.len(), 3);
+        assert!(max_relative_error(&checks) < 0.0001);
+    }
+
+    #[test]
+    fn conv_transpose_layer_should_decode_strided_convolution() {
+        let images: Array4<f64> = Array4::from_shape_fn((32, 4, 4, 1), |(b, i, j, _)| {
+            ((b * 7 + i * 5 + j * 3) % 11) as f64 / 10.0
+        });
+        let y: Array2<f64> = images.clone().into_shape((32, 16)).unwrap();
+        let mut encoder: ConvLayer = ConvLayer::build_with_seed(2, 4, 1, 1);
+        encoder.set_stride(2).unwrap();
+        let mut decoder: ConvTransposeLayer = ConvTransposeLayer::build_with_seed(2, 1, 4, 2);
+        decoder.set_stride(2).unwrap();
+        let mut network: Sequential = Sequential::build(Box::new(Mse));
+        network.add_layer(Box::new(encoder));
+        network.add_layer(Box::new(decoder));
+        network.add_layer(Box::new(FlattenLayer::new()));
+        assert_eq!(network.compile(&[4, 4, 1]).unwrap(), vec![16]);
+        let error = |network: &Sequential| {
+            (&network.predict(&images).unwrap() - &y)
+                .mapv(|e| e * e)
+                .mean()
+                .unwrap()
+        };
+        let initial_error: f64 = error(&network);
+
+        network
+            .fit(
+                &images,
+                &y,
+                None,
+                None,
+                200,
+                0.5,
+                8,
+                &mut [],
+                &mut Report::build("./test_report_conv_transpose_1"),
+                1000,
+            )
+            .unwrap();
+        fs::remove_dir_all("./test_report_conv_transpose_1").ok();
+        assert!(error(&network) < initial_error / 10.0);
+    }
+
+    #[test]
+    fn compile_should_reject_padding_cropping_everything() {
+        let mut layer: ConvTransposeLayer = ConvTransposeLayer::build(2, 2, 1);
+        layer.set_padding(1);
+        assert!(matches!(
+            layer.compile(&[1, 1, 1]),
+            Err(MlError::ShapeMismatch(_))
+        ));
+        assert!(matches!(
+            layer.compile(&[3, 3, 2]),
+            Err(MlError::ShapeMismatch(_))
+        ));
+    }
+
+    #[test]
+    fn from_string_should_deserialize_layer() {
+        let mut conv_transpose_layer: ConvTransposeLayer =
+            ConvTransposeLayer::build_with_seed(2, 3, 2, 4);
+        conv_transpose_layer.set_stride(2).unwrap();
+        conv_transpose_layer.compile(&[2, 2, 2]).unwrap();
+        let result: Box<dyn Layer<f64>> = layer::from_string(
+            "ConvTransposeLayer".to_string(),
+            &conv_transpose_layer.to_json().unwrap(),
+        )
+        .unwrap();
+        assert_eq!(result.get_shape(), (8, 48));
+        assert_eq!(result.count_parameters(), (2 * 2 * 3 * 2 + 3, 0));
+        let x: Array2<f64> = Array2::from_shape_fn((2, 8), |(b, i)| (b * 8 + i) as f64 / 8.0);
+        assert_eq!(
+            result.predict(&x).unwrap(),
+            conv_transpose_layer.predict(&x).unwrap()
+        );
+    }
+}
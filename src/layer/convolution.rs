@@ -0,0 +1,255 @@
+use crate::utils::error::MlError;
+use crate::utils::float::MlFloat;
+use ndarray::Array2;
+
+/// Geometry of a window sliding over samples of shape `(d_1, ..., d_n, c)` flattened in rows,
+/// shared by the convolution and pooling layers. The window covers `kernel_size` values per
+/// spatial axis spaced by `dilation`, it moves by `stride` over the samples padded with `padding`
+/// zeros on both sides of every spatial axis.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Convolution {
+    /// Spatial axes followed by the channels.
+    pub(crate) input_shape: Vec<usize>,
+    pub(crate) kernel_size: usize,
+    pub(crate) stride: usize,
+    pub(crate) padding: usize,
+    pub(crate) dilation: usize,
+}
+
+impl Convolution {
+    /// Stride and dilation of 1 without padding, the spatial axes are inferred when the network
+    /// is compiled.
+    pub(crate) fn build(dimensions: usize, kernel_size: usize, channels: usize) -> Convolution {
+        let mut input_shape: Vec<usize> = vec![0; dimensions];
+        input_shape.push(channels);
+        Convolution {
+            input_shape,
+            kernel_size,
+            stride: 1,
+            padding: 0,
+            dilation: 1,
+        }
+    }
+
+    pub(crate) fn set_stride(&mut self, stride: usize) -> Result<(), MlError> {
+        if stride == 0 {
+            return Err(MlError::InvalidInput(
+                "stride must be at least 1".to_string(),
+            ));
+        }
+        self.stride = stride;
+        Ok(())
+    }
+
+    pub(crate) fn set_dilation(&mut self, dilation: usize) -> Result<(), MlError> {
+        if dilation == 0 {
+            return Err(MlError::InvalidInput(
+                "dilation must be at least 1".to_string(),
+            ));
+        }
+        self.dilation = dilation;
+        Ok(())
+    }
+
+    pub(crate) fn dimensions(&self) -> usize {
+        self.input_shape.len().saturating_sub(1)
+    }
+
+    pub(crate) fn channels(&self) -> usize {
+        self.input_shape.last().copied().unwrap_or(0)
+    }
+
+    pub(crate) fn input_size(&self) -> usize {
+        self.input_shape.iter().product()
+    }
+
+    /// Number of values in a window of one channel.
+    pub(crate) fn kernel_volume(&self) -> usize {
+        self.kernel_size.pow(self.dimensions() as u32)
+    }
+
+    /// Distance along an axis between the first and the last value of a window, both included.
+    pub(crate) fn span(&self) -> usize {
+        self.dilation * self.kernel_size.saturating_sub(1) + 1
+    }
+
+    /// Spatial axes of the output.
+    pub(crate) fn output_shape(&self) -> Vec<usize> {
+        self.input_shape[..self.dimensions()]
+            .iter()
+            .map(|&d| {
+                (d + 2 * self.padding)
+                    .checked_sub(self.span())
+                    .map_or(0, |rest| rest / self.stride.max(1) + 1)
+            })
+            .collect()
+    }
+
+    /// Number of positions of the window over a sample.
+    pub(crate) fn positions(&self) -> usize {
+        self.output_shape().iter().product()
+    }
+
+    /// Replaces the spatial axes and the channels by those of `input_shape` and returns the
+    /// spatial axes of the output.
+    pub(crate) fn compile(&mut self, input_shape: &[usize]) -> Result<Vec<usize>, MlError> {
+        if self.kernel_size == 0
+            || input_shape.len() != self.input_shape.len()
+            || input_shape[..self.dimensions()]
+                .iter()
+                .any(|&d| d + 2 * self.padding < self.span())
+        {
+            return Err(MlError::ShapeMismatch(format!(
+                "expected input shape of {} axes followed by the channels, each axis >= {} once padded, actually: {:?}",
+                self.dimensions(),
+                self.span(),
+                input_shape
+            )));
+        }
+        self.input_shape = input_shape.to_vec();
+        Ok(self.output_shape())
+    }
+
+    /// Input position of every value of every window, `None` in the padding. The windows are
+    /// ordered by output position and their values in row-major order.
+    pub(crate) fn windows(&self) -> Vec<Option<usize>> {
+        let dimensions: usize = self.dimensions();
+        let axes: &[usize] = &self.input_shape[..dimensions];
+        let output_shape: Vec<usize> = self.output_shape();
+        let kernel_shape: Vec<usize> = vec![self.kernel_size; dimensions];
+        let mut windows: Vec<Option<usize>> =
+            Vec::with_capacity(self.positions() * self.kernel_volume());
+        for position in 0..self.positions() {
+            let origin: Vec<usize> = unravel(position, &output_shape);
+            for offset in 0..self.kernel_volume() {
+                let shift: Vec<usize> = unravel(offset, &kernel_shape);
+                let index: Option<usize> = (0..dimensions).try_fold(0, |index, axis| {
+                    (origin[axis] * self.stride + shift[axis] * self.dilation)
+                        .checked_sub(self.padding)
+                        .filter(|&coordinate| coordinate < axes[axis])
+                        .map(|coordinate| index * axes[axis] + coordinate)
+                });
+                windows.push(index);
+            }
+        }
+        windows
+    }
+
+    /// Copies every window of a batch in a row, each window value holding all the channels. The
+    /// rows of a sample are ordered by output position.
+    pub(crate) fn image_to_columns<F: MlFloat>(&self, x: &Array2<F>) -> Result<Array2<F>, MlError> {
+        if x.shape()[1] != self.input_size() {
+            return Err(MlError::ShapeMismatch(format!(
+                "convolution expects {} input columns, actually: {}",
+                self.input_size(),
+                x.shape()[1]
+            )));
+        }
+        let (batch, positions, c): (usize, usize, usize) =
+            (x.shape()[0], self.positions(), self.channels());
+        let volume: usize = self.kernel_volume().max(1);
+        let mut columns: Array2<F> = Array2::zeros((batch * positions, volume * c));
+        for (p, window) in self.windows().chunks(volume).enumerate() {
+            for (o, index) in window.iter().enumerate() {
+                if let Some(index) = index {
+                    for b in 0..batch {
+                        for ch in 0..c {
+                            columns[[b * positions + p, o * c + ch]] = x[[b, index * c + ch]];
+                        }
+                    }
+                }
+            }
+        }
+        Ok(columns)
+    }
+
+    /// Sums the window rows back at their position in the samples, the inverse of
+    /// `image_to_columns` for gradients.
+    pub(crate) fn columns_to_image<F: MlFloat>(
+        &self,
+        columns: &Array2<F>,
+        batch: usize,
+    ) -> Result<Array2<F>, MlError> {
+        let (positions, c): (usize, usize) = (self.positions(), self.channels());
+        let volume: usize = self.kernel_volume().max(1);
+        if columns.shape() != [batch * positions, volume * c] {
+            return Err(MlError::ShapeMismatch(format!(
+                "convolution expects columns of shape {:?}, actually: {:?}",
+                [batch * positions, volume * c],
+                columns.shape()
+            )));
+        }
+        let mut images: Array2<F> = Array2::zeros((batch, self.input_size()));
+        for (p, window) in self.windows().chunks(volume).enumerate() {
+            for (o, index) in window.iter().enumerate() {
+                if let Some(index) = index {
+                    for b in 0..batch {
+                        for ch in 0..c {
+                            images[[b, index * c + ch]] += columns[[b * positions + p, o * c + ch]];
+                        }
+                    }
+                }
+            }
+        }
+        Ok(images)
+    }
+}
+
+/// Coordinates of the row-major `index` in an array of `shape`.
+fn unravel(mut index: usize, shape: &[usize]) -> Vec<usize> {
+    let mut coordinates: Vec<usize> = vec![0; shape.len()];
+    for axis in (0..shape.len()).rev() {
+        coordinates[axis] = index % shape[axis];
+        index /= shape[axis];
+    }
+    coordinates
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::arr2;
+
+    #[test]
+    fn output_shape_should_account_for_stride_padding_and_dilation() {
+        let mut convolution: Convolution = Convolution::build(2, 3, 1);
+        assert_eq!(convolution.compile(&[7, 8, 1]).unwrap(), vec![5, 6]);
+        convolution.stride = 2;
+        assert_eq!(convolution.output_shape(), vec![3, 3]);
+        convolution.padding = 1;
+        assert_eq!(convolution.output_shape(), vec![4, 4]);
+        convolution.dilation = 3;
+        assert_eq!(convolution.output_shape(), vec![2, 2]);
+        assert!(matches!(
+            convolution.compile(&[4, 8, 1]),
+            Err(MlError::ShapeMismatch(_))
+        ));
+        assert!(matches!(
+            convolution.compile(&[7, 8]),
+            Err(MlError::ShapeMismatch(_))
+        ));
+    }
+
+    #[test]
+    fn windows_should_skip_padding() {
+        let mut convolution: Convolution = Convolution::build(1, 2, 1);
+        convolution.stride = 2;
+        convolution.padding = 1;
+        convolution.dilation = 2;
+        convolution.compile(&[4, 1]).unwrap();
+        assert_eq!(convolution.windows(), vec![None, Some(1), Some(1), Some(3)]);
+    }
+
+    #[test]
+    fn columns_to_image_should_sum_overlapping_windows() {
+        let mut convolution: Convolution = Convolution::build(1, 2, 2);
+        convolution.compile(&[3, 2]).unwrap();
+        let x: Array2<f64> = arr2(&[[1.0, 2.0, 3.0, 4.0, 5.0, 6.0]]);
+        let columns: Array2<f64> = convolution.image_to_columns(&x).unwrap();
+        assert_eq!(columns, arr2(&[[1.0, 2.0, 3.0, 4.0], [3.0, 4.0, 5.0, 6.0]]));
+        assert_eq!(
+            convolution.columns_to_image(&columns, 1).unwrap(),
+            arr2(&[[1.0, 2.0, 6.0, 8.0, 5.0, 6.0]])
+        );
+    }
+}
@@ -0,0 +1,174 @@
+use crate::layer::layer::Layer;
+use crate::layer::zero_padding2d::pad_images;
+use crate::utils::error::{check_shapes, MlError};
+use crate::utils::float::MlFloat;
+use ndarray::{s, Array2, ArrayView4};
+use serde::{Deserialize, Serialize};
+
+/// Rows and columns at the border of images, `((top, bottom), (left, right))`.
+pub type Margins = ((usize, usize), (usize, usize));
+
+/// Keeps the `(h - top - bottom, w - left - right, c)` center of `(h, w, c)` images flattened in
+/// the rows of `x`.
+pub(crate) fn crop_images<F: MlFloat>(
+    x: &Array2<F>,
+    input_shape: (usize, usize, usize),
+    margins: Margins,
+) -> Result<Array2<F>, MlError> {
+    let (h, w, c): (usize, usize, usize) = input_shape;
+    let ((top, bottom), (left, right)): Margins = margins;
+    check_shapes(&x.shape()[1..], &[h * w * c])?;
+    let batch: usize = x.shape()[0];
+    let x = x.as_standard_layout();
+    let images: ArrayView4<F> = x.view().into_shape((batch, h, w, c))?;
+    let cropped = images.slice(s![.., top..(h - bottom), left..(w - right), ..]);
+    Ok(cropped
+        .as_standard_layout()
+        .into_owned()
+        .into_shape((batch, (h - top - bottom) * (w - left - right) * c))?)
+}
+
+/// Removes rows and columns at the border of feature maps of shape `(h, w, c)`.
+#[derive(Debug, Clone)]
+pub struct Cropping2D {
+    cropping: Margins,
+    input_shape: (usize, usize, usize),
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Cropping2DModel {
+    cropping: Margins,
+    input_shape: (usize, usize, usize),
+}
+
+impl Cropping2D {
+    /// Removes `((top, bottom), (left, right))` rows and columns, the shape of the feature maps
+    /// is inferred when the network is compiled.
+    pub fn build(cropping: Margins) -> Cropping2D {
+        Cropping2D {
+            cropping,
+            input_shape: (0, 0, 0),
+        }
+    }
+
+    pub fn from_json(json_str: &str) -> Result<Cropping2D, MlError> {
+        let model: Cropping2DModel = serde_json::from_str(json_str)?;
+        let mut layer: Cropping2D = Cropping2D::build(model.cropping);
+        layer.input_shape = model.input_shape;
+        Ok(layer)
+    }
+
+    fn output_shape(&self) -> (usize, usize, usize) {
+        let (h, w, c): (usize, usize, usize) = self.input_shape;
+        let ((top, bottom), (left, right)): Margins = self.cropping;
+        (
+            h.saturating_sub(top + bottom),
+            w.saturating_sub(left + right),
+            c,
+        )
+    }
+}
+
+impl<F: MlFloat> Layer<F> for Cropping2D {
+    fn forward_propagation(&mut self, x: &Array2<F>) -> Result<Array2<F>, MlError> {
+        self.predict(x)
+    }
+
+    fn predict(&self, x: &Array2<F>) -> Result<Array2<F>, MlError> {
+        crop_images(x, self.input_shape, self.cropping)
+    }
+
+    /// Pads the errors with zeros, the cropped values have no effect on the output.
+    fn compute_gradients(&mut self, y: &Array2<F>) -> Result<Array2<F>, MlError> {
+        pad_images(y, self.output_shape(), self.cropping)
+    }
+
+    fn compile(&mut self, input_shape: &[usize]) -> Result<Vec<usize>, MlError> {
+        let ((top, bottom), (left, right)): Margins = self.cropping;
+        match *input_shape {
+            [h, w, c] if h > top + bottom && w > left + right => {
+                self.input_shape = (h, w, c);
+                let (oh, ow, c): (usize, usize, usize) = self.output_shape();
+                Ok(vec![oh, ow, c])
+            }
+            _ => Err(MlError::ShapeMismatch(format!(
+                "expected input shape [h, w, c] with h > {} and w > {}, actually: {:?}",
+                top + bottom,
+                left + right,
+                input_shape
+            ))),
+        }
+    }
+
+    fn clone_layer(&self) -> Box<dyn Layer<F>> {
+        Box::new(self.clone())
+    }
+
+    fn get_shape(&self) -> (usize, usize) {
+        let (h, w, c): (usize, usize, usize) = self.input_shape;
+        let (oh, ow, c_out): (usize, usize, usize) = self.output_shape();
+        (h * w * c, oh * ow * c_out)
+    }
+
+    fn get_name(&self) -> String {
+        "Cropping2D".to_string()
+    }
+
+    fn to_json(&self) -> Result<String, MlError> {
+        let model: Cropping2DModel = Cropping2DModel {
+            cropping: self.cropping,
+            input_shape: self.input_shape,
+        };
+        Ok(serde_json::to_string(&model)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layer::layer;
+    use crate::utils::gradient_check::{check_layer, max_relative_error, GradientCheck};
+    use ndarray::arr2;
+
+    #[test]
+    fn forward_propagation_should_keep_center() {
+        let mut cropping: Cropping2D = Cropping2D::build(((1, 0), (0, 1)));
+        assert_eq!(
+            Layer::<f64>::compile(&mut cropping, &[3, 3, 1]).unwrap(),
+            vec![2, 2, 1]
+        );
+        let output: Array2<f64> = cropping
+            .forward_propagation(&arr2(&[[1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0]]))
+            .unwrap();
+        assert_eq!(output, arr2(&[[4.0, 5.0, 7.0, 8.0]]));
+        let input_error: Array2<f64> = cropping.compute_gradients(&output).unwrap();
+        assert_eq!(
+            input_error,
+            arr2(&[[0.0, 0.0, 0.0, 4.0, 5.0, 0.0, 7.0, 8.0, 0.0]])
+        );
+        assert!(matches!(
+            Layer::<f64>::compile(&mut cropping, &[1, 3, 1]),
+            Err(MlError::ShapeMismatch(_))
+        ));
+    }
+
+    #[test]
+    fn compute_gradients_should_pass_gradient_check() {
+        let mut cropping: Cropping2D = Cropping2D::build(((1, 1), (2, 0)));
+        Layer::<f64>::compile(&mut cropping, &[4, 3, 2]).unwrap();
+        let x: Array2<f64> = Array2::from_shape_fn((2, 24), |(b, i)| (b * 24 + i) as f64 / 10.0);
+        let checks: Vec<GradientCheck> = check_layer(&mut cropping, &x, 0.0001).unwrap();
+        assert!(max_relative_error(&checks) < 0.0001);
+    }
+
+    #[test]
+    fn from_string_should_deserialize_layer() {
+        let mut cropping: Cropping2D = Cropping2D::build(((1, 1), (2, 0)));
+        Layer::<f64>::compile(&mut cropping, &[4, 3, 2]).unwrap();
+        let json: String = Layer::<f64>::to_json(&cropping).unwrap();
+        assert_eq!(json, "{\"cropping\":[[1,1],[2,0]],\"input_shape\":[4,3,2]}");
+        let result: Box<dyn Layer<f64>> =
+            layer::from_string("Cropping2D".to_string(), &json).unwrap();
+        assert_eq!(result.get_shape(), (24, 4));
+    }
+}
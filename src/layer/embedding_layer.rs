@@ -0,0 +1,534 @@
+use crate::layer::layer::Layer;
+use crate::utils::error::{check_shapes, MlError};
+use crate::utils::float::MlFloat;
+use ndarray::{concatenate, Array, Array2, ArrayView2, Axis};
+use ndarray_rand::rand::rngs::StdRng;
+use ndarray_rand::rand::SeedableRng;
+use ndarray_rand::rand_distr::Uniform;
+use ndarray_rand::RandomExt;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs::read_to_string;
+
+/// Maps sequences of integer ids, stored as floats, to learnable vectors. Samples of shape
+/// `(sequence_length)` become samples of shape `(sequence_length, embedding_size)`.
+///
+/// Only the rows of the ids seen in the last batch get a gradient and are updated. Ids equal to
+/// the padding index are mapped to zeros and never update their row.
+#[derive(Debug, Clone)]
+pub struct EmbeddingLayer<F: MlFloat = f64> {
+    ids: Vec<usize>,
+    /// One row per id of the vocabulary.
+    embeddings: Array2<F>,
+    /// Ids of the rows with a gradient, in the order of the rows of `gradient`.
+    gradient_ids: Vec<usize>,
+    gradient: Array2<F>,
+    padding_idx: Option<usize>,
+    sequence_length: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EmbeddingLayerModel {
+    embeddings: Vec<f64>,
+    shape: (usize, usize),
+    padding_idx: Option<usize>,
+    sequence_length: usize,
+}
+
+impl<F: MlFloat> EmbeddingLayer<F> {
+    pub fn build(vocabulary_size: usize, embedding_size: usize) -> EmbeddingLayer<F> {
+        EmbeddingLayer::build_with_embeddings(
+            Array::random(
+                (vocabulary_size, embedding_size),
+                Uniform::new(F::zero(), F::one()),
+            ) - F::cast(0.5),
+        )
+    }
+
+    pub fn build_with_seed(
+        vocabulary_size: usize,
+        embedding_size: usize,
+        seed: u64,
+    ) -> EmbeddingLayer<F> {
+        let mut rng: StdRng = StdRng::seed_from_u64(seed);
+        EmbeddingLayer::build_with_embeddings(
+            Array::random_using(
+                (vocabulary_size, embedding_size),
+                Uniform::new(F::zero(), F::one()),
+                &mut rng,
+            ) - F::cast(0.5),
+        )
+    }
+
+    /// The sequence length is inferred when the network is compiled.
+    pub fn build_with_embeddings(embeddings: Array2<F>) -> EmbeddingLayer<F> {
+        EmbeddingLayer {
+            ids: vec![],
+            gradient_ids: vec![],
+            gradient: Array2::zeros((0, embeddings.shape()[1])),
+            embeddings,
+            padding_idx: None,
+            sequence_length: 0,
+        }
+    }
+
+    /// Reads pretrained vectors from a GloVe or word2vec text file, one word per line followed by
+    /// its values. The optional word2vec header line with the number of words and the vector
+    /// size is skipped. Returns the layer and the id of every word, in the order of the file.
+    pub fn from_pretrained(
+        path: &str,
+    ) -> Result<(EmbeddingLayer<F>, HashMap<String, usize>), MlError> {
+        let content: String = read_to_string(path)?;
+        let mut vocabulary: HashMap<String, usize> = HashMap::new();
+        let mut values: Vec<F> = vec![];
+        let mut embedding_size: Option<usize> = None;
+        for (index, line) in content.lines().enumerate() {
+            let tokens: Vec<&str> = line.split_whitespace().collect();
+            let is_header: bool = index == 0
+                && tokens.len() == 2
+                && tokens.iter().all(|token| token.parse::<usize>().is_ok());
+            if tokens.is_empty() || is_header {
+                continue;
+            }
+            let vector: Vec<f64> = tokens[1..]
+                .iter()
+                .map(|token| token.parse::<f64>())
+                .collect::<Result<Vec<f64>, _>>()
+                .map_err(|error| {
+                    MlError::Serialization(format!("{} line {}: {}", path, index + 1, error))
+                })?;
+            if vector.is_empty() || *embedding_size.get_or_insert(vector.len()) != vector.len() {
+                return Err(MlError::Serialization(format!(
+                    "{} line {}: expected {} values, actually: {}",
+                    path,
+                    index + 1,
+                    embedding_size.map_or("some".to_string(), |size| size.to_string()),
+                    vector.len()
+                )));
+            }
+            if vocabulary.contains_key(tokens[0]) {
+                continue;
+            }
+            vocabulary.insert(tokens[0].to_string(), vocabulary.len());
+            values.extend(vector.into_iter().map(F::cast));
+        }
+        let embedding_size: usize = embedding_size
+            .ok_or_else(|| MlError::Serialization(format!("{} holds no vectors", path)))?;
+        let embeddings: Array2<F> =
+            Array2::from_shape_vec((vocabulary.len(), embedding_size), values)?;
+        Ok((
+            EmbeddingLayer::build_with_embeddings(embeddings),
+            vocabulary,
+        ))
+    }
+
+    pub fn from_json(json_str: &str) -> Result<EmbeddingLayer<F>, MlError> {
+        let model: EmbeddingLayerModel = serde_json::from_str(json_str)?;
+        let embeddings: Array2<F> = Array2::from_shape_vec(
+            model.shape,
+            model.embeddings.into_iter().map(F::cast).collect(),
+        )?;
+        let mut layer: EmbeddingLayer<F> = EmbeddingLayer::build_with_embeddings(embeddings);
+        layer.set_padding_idx(model.padding_idx)?;
+        layer.sequence_length = model.sequence_length;
+        Ok(layer)
+    }
+
+    /// Id masked in the input, usually the one of the padding token.
+    pub fn set_padding_idx(&mut self, padding_idx: Option<usize>) -> Result<(), MlError> {
+        if let Some(id) = padding_idx {
+            if id >= self.vocabulary_size() {
+                return Err(MlError::InvalidInput(format!(
+                    "padding index {} is outside of a vocabulary of {} ids",
+                    id,
+                    self.vocabulary_size()
+                )));
+            }
+        }
+        self.padding_idx = padding_idx;
+        Ok(())
+    }
+
+    pub fn get_embeddings(&self) -> &Array2<F> {
+        &self.embeddings
+    }
+
+    fn vocabulary_size(&self) -> usize {
+        self.embeddings.shape()[0]
+    }
+
+    fn embedding_size(&self) -> usize {
+        self.embeddings.shape()[1]
+    }
+
+    fn parse_ids(&self, x: &Array2<F>) -> Result<Vec<usize>, MlError> {
+        check_shapes(&x.shape()[1..], &[self.sequence_length])?;
+        x.iter()
+            .map(|&value| {
+                let id: f64 = value.to_f64_lossy().round();
+                if id >= 0.0 && id < self.vocabulary_size() as f64 {
+                    Ok(id as usize)
+                } else {
+                    Err(MlError::InvalidInput(format!(
+                        "EmbeddingLayer expects ids in [0, {}), actually: {}",
+                        self.vocabulary_size(),
+                        value.to_f64_lossy()
+                    )))
+                }
+            })
+            .collect()
+    }
+
+    fn lookup(&self, ids: &[usize], batch: usize) -> Result<Array2<F>, MlError> {
+        let embedding_size: usize = self.embedding_size();
+        let rows: Array2<F> = Array2::from_shape_fn((ids.len(), embedding_size), |(i, j)| {
+            if Some(ids[i]) == self.padding_idx {
+                F::zero()
+            } else {
+                self.embeddings[[ids[i], j]]
+            }
+        });
+        Ok(rows.into_shape((batch, self.sequence_length * embedding_size))?)
+    }
+}
+
+impl<F: MlFloat> Layer<F> for EmbeddingLayer<F> {
+    fn forward_propagation(&mut self, x: &Array2<F>) -> Result<Array2<F>, MlError> {
+        let ids: Vec<usize> = self.parse_ids(x)?;
+        let output: Array2<F> = self.lookup(&ids, x.shape()[0])?;
+        self.ids = ids;
+        Ok(output)
+    }
+
+    fn predict(&self, x: &Array2<F>) -> Result<Array2<F>, MlError> {
+        self.lookup(&self.parse_ids(x)?, x.shape()[0])
+    }
+
+    /// Ids are not differentiable, the returned input error is zero.
+    fn compute_gradients(&mut self, y: &Array2<F>) -> Result<Array2<F>, MlError> {
+        let embedding_size: usize = self.embedding_size();
+        let batch: usize = self.ids.len() / self.sequence_length.max(1);
+        check_shapes(y.shape(), &[batch, self.sequence_length * embedding_size])?;
+        let errors: Array2<F> = y
+            .as_standard_layout()
+            .into_owned()
+            .into_shape((self.ids.len(), embedding_size))?;
+        let mut rows: BTreeMap<usize, Array2<F>> = BTreeMap::new();
+        for (id, error) in self.ids.iter().zip(errors.rows()) {
+            if Some(*id) != self.padding_idx {
+                *rows
+                    .entry(*id)
+                    .or_insert_with(|| Array2::zeros((1, embedding_size))) += &error;
+            }
+        }
+        self.gradient_ids = rows.keys().copied().collect();
+        let views: Vec<ArrayView2<F>> = rows.values().map(|row| row.view()).collect();
+        self.gradient = if views.is_empty() {
+            Array2::zeros((0, embedding_size))
+        } else {
+            concatenate(Axis(0), &views)?
+        };
+        Ok(Array2::zeros((batch, self.sequence_length)))
+    }
+
+    fn apply_gradients(&mut self, learning_rate: F) {
+        for (id, gradient) in self.gradient_ids.iter().zip(self.gradient.rows()) {
+            self.embeddings
+                .row_mut(*id)
+                .scaled_add(-learning_rate, &gradient);
+        }
+    }
+
+    /// The sparse gradient expanded to the shape of the embedding matrix.
+    fn get_gradients(&self) -> Vec<Array2<F>> {
+        let mut gradient: Array2<F> = Array2::zeros(self.embeddings.raw_dim());
+        for (id, row) in self.gradient_ids.iter().zip(self.gradient.rows()) {
+            gradient.row_mut(*id).assign(&row);
+        }
+        vec![gradient]
+    }
+
+    /// Only the non-zero rows of the gradient are kept.
+    fn set_gradients(&mut self, gradients: Vec<Array2<F>>) -> Result<(), MlError> {
+        if gradients.len() != 1 {
+            return Err(MlError::InvalidInput(format!(
+                "EmbeddingLayer expects 1 gradient, actually: {}",
+                gradients.len()
+            )));
+        }
+        check_shapes(gradients[0].shape(), self.embeddings.shape())?;
+        self.gradient_ids = gradients[0]
+            .rows()
+            .into_iter()
+            .enumerate()
+            .filter(|(_, row)| row.iter().any(|&g| g != F::zero()))
+            .map(|(id, _)| id)
+            .collect();
+        self.gradient = gradients[0].select(Axis(0), &self.gradient_ids);
+        Ok(())
+    }
+
+    fn get_parameters(&self) -> Vec<Array2<F>> {
+        vec![self.embeddings.clone()]
+    }
+
+    fn set_parameters(&mut self, parameters: Vec<Array2<F>>) -> Result<(), MlError> {
+        if parameters.len() != 1 {
+            return Err(MlError::InvalidInput(format!(
+                "EmbeddingLayer expects 1 parameter, actually: {}",
+                parameters.len()
+            )));
+        }
+        check_shapes(parameters[0].shape(), self.embeddings.shape())?;
+        self.embeddings = parameters[0].clone();
+        Ok(())
+    }
+
+    fn compile(&mut self, input_shape: &[usize]) -> Result<Vec<usize>, MlError> {
+        match input_shape {
+            &[sequence_length] if sequence_length > 0 => {
+                self.sequence_length = sequence_length;
+                Ok(vec![sequence_length, self.embedding_size()])
+            }
+            _ => Err(MlError::ShapeMismatch(format!(
+                "expected input shape [sequence_length], actually: {:?}",
+                input_shape
+            ))),
+        }
+    }
+
+    fn count_parameters(&self) -> (usize, usize) {
+        (self.embeddings.len(), 0)
+    }
+
+    fn clone_layer(&self) -> Box<dyn Layer<F>> {
+        Box::new(self.clone())
+    }
+
+    fn get_shape(&self) -> (usize, usize) {
+        (
+            self.sequence_length,
+            self.sequence_length * self.embedding_size(),
+        )
+    }
+
+    fn get_name(&self) -> String {
+        "EmbeddingLayer".to_string()
+    }
+
+    fn to_json(&self) -> Result<String, MlError> {
+        let model: EmbeddingLayerModel = EmbeddingLayerModel {
+            embeddings: self.embeddings.iter().map(|&w| w.to_f64_lossy()).collect(),
+            shape: (self.vocabulary_size(), self.embedding_size()),
+            padding_idx: self.padding_idx,
+            sequence_length: self.sequence_length,
+        };
+        Ok(serde_json::to_string(&model)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::activation::sigmoid::Sigmoid;
+    use crate::layer::activation_layer::ActivationLayer;
+    use crate::layer::fc_layer::FCLayer;
+    use crate::layer::flatten_layer::FlattenLayer;
+    use crate::layer::layer;
+    use crate::loss::mse::Mse;
+    use crate::network::mlp::Mlp;
+    use crate::network::network::Network;
+    use crate::report::report::Report;
+    use crate::utils::gradient_check::{check_layer, max_relative_error, GradientCheck};
+    use ndarray::{arr2, s};
+    use std::fs;
+
+    fn generate_test_embedding_layer() -> EmbeddingLayer {
+        let mut embedding_layer: EmbeddingLayer = EmbeddingLayer::build_with_embeddings(arr2(&[
+            [0.0, 0.0],
+            [1.0, 2.0],
+            [3.0, 4.0],
+            [5.0, 6.0],
+        ]));
+        embedding_layer.compile(&[3]).unwrap();
+        embedding_layer
+    }
+
+    #[test]
+    fn forward_propagation_should_look_up_embeddings() {
+        let mut embedding_layer: EmbeddingLayer = generate_test_embedding_layer();
+        let x: Array2<f64> = arr2(&[[1.0, 3.0, 1.0], [2.0, 0.0, 0.0]]);
+        let result: Array2<f64> = embedding_layer.forward_propagation(&x).unwrap();
+        assert_eq!(
+            result,
+            arr2(&[
+                [1.0, 2.0, 5.0, 6.0, 1.0, 2.0],
+                [3.0, 4.0, 0.0, 0.0, 0.0, 0.0]
+            ])
+        );
+        assert_eq!(embedding_layer.predict(&x).unwrap(), result);
+    }
+
+    #[test]
+    fn forward_propagation_should_fail_on_unknown_ids() {
+        let mut embedding_layer: EmbeddingLayer = generate_test_embedding_layer();
+        assert!(matches!(
+            embedding_layer.forward_propagation(&arr2(&[[1.0, 4.0, 0.0]])),
+            Err(MlError::InvalidInput(_))
+        ));
+        assert!(matches!(
+            embedding_layer.forward_propagation(&arr2(&[[1.0, -1.0, 0.0]])),
+            Err(MlError::InvalidInput(_))
+        ));
+        assert!(matches!(
+            embedding_layer.forward_propagation(&arr2(&[[1.0, 2.0]])),
+            Err(MlError::ShapeMismatch(_))
+        ));
+    }
+
+    #[test]
+    fn backward_propagation_should_only_update_rows_of_batch() {
+        let mut embedding_layer: EmbeddingLayer = generate_test_embedding_layer();
+        embedding_layer.set_padding_idx(Some(2)).unwrap();
+        let x: Array2<f64> = arr2(&[[1.0, 2.0, 1.0]]);
+        let output: Array2<f64> = embedding_layer.forward_propagation(&x).unwrap();
+        assert_eq!(output, arr2(&[[1.0, 2.0, 0.0, 0.0, 1.0, 2.0]]));
+
+        let input_error: Array2<f64> = embedding_layer
+            .backward_propagation(&arr2(&[[1.0, 1.0, 1.0, 1.0, 0.5, -1.0]]), 0.5)
+            .unwrap();
+        assert_eq!(input_error, Array2::<f64>::zeros((1, 3)));
+        assert_eq!(embedding_layer.gradient_ids, vec![1]);
+        assert_eq!(
+            embedding_layer.embeddings,
+            arr2(&[[0.0, 0.0], [0.25, 2.0], [3.0, 4.0], [5.0, 6.0]])
+        );
+        assert_eq!(
+            embedding_layer.get_gradients()[0],
+            arr2(&[[0.0, 0.0], [1.5, 0.0], [0.0, 0.0], [0.0, 0.0]])
+        );
+    }
+
+    #[test]
+    fn compute_gradients_should_pass_gradient_check() {
+        let mut embedding_layer: EmbeddingLayer = EmbeddingLayer::build_with_seed(5, 3, 1);
+        embedding_layer.set_padding_idx(Some(0)).unwrap();
+        embedding_layer.compile(&[4]).unwrap();
+        let x: Array2<f64> = arr2(&[[1.0, 3.0, 0.0, 1.0], [4.0, 2.0, 3.0, 0.0]]);
+        let checks: Vec<GradientCheck> = check_layer(&mut embedding_layer, &x, 0.0001).unwrap();
+        assert_eq!(checks.len(), 2);
+        assert!(max_relative_error(&checks) < 0.0001);
+        assert_eq!(checks[1].analytic.row(0).sum(), 0.0);
+    }
+
+    #[test]
+    fn mlp_should_learn_text_classifier_on_embeddings() {
+        // the label is 1 when the sequence holds the token 1, 0 pads the sequences
+        let x: Array2<f64> = arr2(&[
+            [1.0, 2.0, 0.0],
+            [3.0, 4.0, 2.0],
+            [4.0, 1.0, 3.0],
+            [2.0, 2.0, 0.0],
+            [3.0, 3.0, 1.0],
+            [4.0, 0.0, 0.0],
+        ]);
+        let y: Array2<f64> = arr2(&[[1.0], [0.0], [1.0], [0.0], [1.0], [0.0]]);
+        let mut embedding_layer: EmbeddingLayer = EmbeddingLayer::build_with_seed(5, 4, 1);
+        embedding_layer.set_padding_idx(Some(0)).unwrap();
+        let padding: Array2<f64> = embedding_layer.embeddings.slice(s![0..1, ..]).to_owned();
+        let mut mlp: Mlp = Mlp::build(Box::new(Mse));
+        mlp.add_layer(Box::new(embedding_layer));
+        mlp.add_layer(Box::new(FlattenLayer::new()));
+        mlp.add_layer(Box::new(FCLayer::build_with_seed(12, 1, 2)));
+        mlp.add_layer(Box::new(ActivationLayer::build(Box::new(Sigmoid))));
+        assert_eq!(mlp.compile(&[3]).unwrap(), vec![1]);
+        let before: f64 = mlp.compute_loss(&x, &y).unwrap();
+
+        mlp.fit(
+            &x,
+            &y,
+            None,
+            None,
+            200,
+            0.5,
+            2,
+            &mut [],
+            &mut Report::build("./test_report_embedding_1"),
+            1000,
+        )
+        .unwrap();
+        fs::remove_dir_all("./test_report_embedding_1").ok();
+        assert!(mlp.compute_loss(&x, &y).unwrap() < before / 4.0);
+        let model: serde_json::Value = serde_json::from_str(&mlp.to_json().unwrap()).unwrap();
+        let trained: EmbeddingLayer =
+            EmbeddingLayer::from_json(model["layers"][0][1].as_str().unwrap()).unwrap();
+        assert!(trained
+            .embeddings
+            .row(0)
+            .iter()
+            .zip(padding.iter())
+            .all(|(t, p)| (t - p).powf(2.0) < 0.00001));
+    }
+
+    #[test]
+    fn from_pretrained_should_read_glove_and_word2vec_files() {
+        fs::write(
+            "./test_glove.txt",
+            "the 0.1 0.2 0.3\ncat -1.0 0.5 2\n\ndog 1e-1 0 0\n",
+        )
+        .unwrap();
+        fs::write(
+            "./test_word2vec.txt",
+            "2 3\nthe 0.1 0.2 0.3\ncat -1.0 0.5 2\n",
+        )
+        .unwrap();
+        fs::write("./test_bad_vectors.txt", "the 0.1 0.2 0.3\ncat -1.0 0.5\n").unwrap();
+
+        let glove = EmbeddingLayer::<f64>::from_pretrained("./test_glove.txt");
+        let word2vec = EmbeddingLayer::<f32>::from_pretrained("./test_word2vec.txt");
+        let bad_vectors = EmbeddingLayer::<f64>::from_pretrained("./test_bad_vectors.txt");
+        fs::remove_file("./test_glove.txt").unwrap();
+        fs::remove_file("./test_word2vec.txt").unwrap();
+        fs::remove_file("./test_bad_vectors.txt").unwrap();
+
+        let (embedding_layer, vocabulary): (EmbeddingLayer, HashMap<String, usize>) =
+            glove.unwrap();
+        assert_eq!(vocabulary.len(), 3);
+        assert_eq!(vocabulary["dog"], 2);
+        assert_eq!(
+            embedding_layer.get_embeddings().row(1).to_vec(),
+            vec![-1.0, 0.5, 2.0]
+        );
+        let (embedding_layer, vocabulary): (EmbeddingLayer<f32>, HashMap<String, usize>) =
+            word2vec.unwrap();
+        assert_eq!(vocabulary["cat"], 1);
+        assert_eq!(embedding_layer.get_embeddings().shape(), &[2, 3]);
+        assert!(matches!(bad_vectors, Err(MlError::Serialization(_))));
+        assert!(matches!(
+            EmbeddingLayer::<f64>::from_pretrained("./test_missing_vectors.txt"),
+            Err(MlError::Io(_))
+        ));
+    }
+
+    #[test]
+    fn from_string_should_deserialize_layer() {
+        let mut embedding_layer: EmbeddingLayer = generate_test_embedding_layer();
+        embedding_layer.set_padding_idx(Some(0)).unwrap();
+        let json: String = embedding_layer.to_json().unwrap();
+        assert_eq!(
+            json,
+            "{\"embeddings\":[0.0,0.0,1.0,2.0,3.0,4.0,5.0,6.0],\"shape\":[4,2],\
+             \"padding_idx\":0,\"sequence_length\":3}"
+        );
+        let result: Box<dyn Layer<f64>> =
+            layer::from_string("EmbeddingLayer".to_string(), &json).unwrap();
+        assert_eq!(result.get_shape(), (3, 6));
+        assert_eq!(result.count_parameters(), (8, 0));
+        assert!(matches!(
+            EmbeddingLayer::<f64>::from_json(
+                "{\"embeddings\":[0.0,1.0],\"shape\":[1,2],\"padding_idx\":1,\"sequence_length\":3}"
+            ),
+            Err(MlError::InvalidInput(_))
+        ));
+    }
+}
@@ -1,15 +1,20 @@
 use crate::layer::layer::Layer;
-use ndarray::{Array, Array2};
+use crate::utils::error::{check_shapes, MlError};
+use crate::utils::float::MlFloat;
+use ndarray::{Array, Array2, Axis};
+use ndarray_rand::rand::rngs::StdRng;
+use ndarray_rand::rand::SeedableRng;
 use ndarray_rand::rand_distr::Uniform;
 use ndarray_rand::RandomExt;
 use serde::{Deserialize, Serialize};
-use std::error::Error;
 
-#[derive(Debug)]
-pub struct FCLayer {
-    input: Array2<f64>,
-    weights: Array2<f64>,
-    bias: Array2<f64>,
+#[derive(Debug, Clone)]
+pub struct FCLayer<F: MlFloat = f64> {
+    input: Array2<F>,
+    weights: Array2<F>,
+    bias: Array2<F>,
+    weights_gradient: Array2<F>,
+    bias_gradient: Array2<F>,
     shape: (usize, usize),
 }
 
@@ -20,42 +25,140 @@ pub struct FCLayerModel {
     shape: (usize, usize),
 }
 
-impl FCLayer {
-    pub fn build(input_size: usize, output_size: usize) -> FCLayer {
+impl<F: MlFloat> FCLayer<F> {
+    pub fn build(input_size: usize, output_size: usize) -> FCLayer<F> {
+        FCLayer::build_with_weights(
+            Array::random((input_size, output_size), Uniform::new(F::zero(), F::one()))
+                - F::cast(0.5),
+            Array::random((1, output_size), Uniform::new(F::zero(), F::one())) - F::cast(0.5),
+        )
+    }
+
+    pub fn build_with_seed(input_size: usize, output_size: usize, seed: u64) -> FCLayer<F> {
+        let mut rng: StdRng = StdRng::seed_from_u64(seed);
+        FCLayer::build_with_weights(
+            Array::random_using(
+                (input_size, output_size),
+                Uniform::new(F::zero(), F::one()),
+                &mut rng,
+            ) - F::cast(0.5),
+            Array::random_using(
+                (1, output_size),
+                Uniform::new(F::zero(), F::one()),
+                &mut rng,
+            ) - F::cast(0.5),
+        )
+    }
+
+    fn build_with_weights(weights: Array2<F>, bias: Array2<F>) -> FCLayer<F> {
+        let shape: (usize, usize) = (weights.shape()[0], weights.shape()[1]);
         FCLayer {
-            input: Array::zeros((1, input_size)),
-            weights: Array::random((input_size, output_size), Uniform::new(0.0, 1.0)) - 0.5,
-            bias: Array::random((1, output_size), Uniform::new(0.0, 1.0)) - 0.5,
-            shape: (input_size, output_size),
+            input: Array::zeros((1, shape.0)),
+            weights_gradient: Array::zeros(shape),
+            bias_gradient: Array::zeros((1, shape.1)),
+            weights,
+            bias,
+            shape,
         }
     }
 
-    pub fn from_json(json_str: &str) -> Result<FCLayer, Box<dyn Error>> {
+    pub fn from_json(json_str: &str) -> Result<FCLayer<F>, MlError> {
         let model: FCLayerModel = serde_json::from_str(json_str)?;
-        let weights: Array2<f64> = Array2::from_shape_vec(model.shape, model.weights)?;
-        let bias: Array2<f64> = Array2::from_shape_vec([1, model.shape.1], model.bias)?;
-        let layer: FCLayer = FCLayer {
-            input: Array::zeros((1, model.shape.0)),
-            weights,
-            bias,
-            shape: model.shape,
-        };
-        Ok(layer)
+        let weights: Array2<F> = Array2::from_shape_vec(
+            model.shape,
+            model.weights.into_iter().map(F::cast).collect(),
+        )?;
+        let bias: Array2<F> = Array2::from_shape_vec(
+            [1, model.shape.1],
+            model.bias.into_iter().map(F::cast).collect(),
+        )?;
+        Ok(FCLayer::build_with_weights(weights, bias))
     }
 }
 
-impl Layer for FCLayer {
-    fn forward_propagation(&mut self, x: &Array2<f64>) -> Array2<f64> {
+impl<F: MlFloat> Layer<F> for FCLayer<F> {
+    fn forward_propagation(&mut self, x: &Array2<F>) -> Result<Array2<F>, MlError> {
+        let output: Array2<F> = self.predict(x)?;
         self.input = (*x).clone();
-        self.input.dot(&self.weights) + &self.bias
+        Ok(output)
     }
 
-    fn backward_propagation(&mut self, y: &Array2<f64>, learning_rate: f64) -> Array2<f64> {
-        let input_error: Array2<f64> = y.dot(&self.weights.t());
-        let weight_error: Array2<f64> = self.input.t().dot(y);
-        self.weights = &self.weights - (learning_rate * weight_error);
-        self.bias = &self.bias - (learning_rate * y);
-        input_error
+    fn predict(&self, x: &Array2<F>) -> Result<Array2<F>, MlError> {
+        if x.shape()[1] != self.shape.0 {
+            return Err(MlError::ShapeMismatch(format!(
+                "FCLayer expects {} input columns, actually: {}",
+                self.shape.0,
+                x.shape()[1]
+            )));
+        }
+        Ok(x.dot(&self.weights) + &self.bias)
+    }
+
+    fn compute_gradients(&mut self, y: &Array2<F>) -> Result<Array2<F>, MlError> {
+        check_shapes(y.shape(), &[self.input.shape()[0], self.shape.1])?;
+        self.weights_gradient = self.input.t().dot(y);
+        self.bias_gradient = y.sum_axis(Axis(0)).insert_axis(Axis(0));
+        Ok(y.dot(&self.weights.t()))
+    }
+
+    fn apply_gradients(&mut self, learning_rate: F) {
+        self.weights = &self.weights - &(&self.weights_gradient * learning_rate);
+        self.bias = &self.bias - &(&self.bias_gradient * learning_rate);
+    }
+
+    fn get_gradients(&self) -> Vec<Array2<F>> {
+        vec![self.weights_gradient.clone(), self.bias_gradient.clone()]
+    }
+
+    fn set_gradients(&mut self, gradients: Vec<Array2<F>>) -> Result<(), MlError> {
+        if gradients.len() != 2 {
+            return Err(MlError::InvalidInput(format!(
+                "FCLayer expects 2 gradients, actually: {}",
+                gradients.len()
+            )));
+        }
+        check_shapes(gradients[0].shape(), self.weights.shape())?;
+        check_shapes(gradients[1].shape(), self.bias.shape())?;
+        self.weights_gradient = gradients[0].clone();
+        self.bias_gradient = gradients[1].clone();
+        Ok(())
+    }
+
+    fn get_parameters(&self) -> Vec<Array2<F>> {
+        vec![self.weights.clone(), self.bias.clone()]
+    }
+
+    fn set_parameters(&mut self, parameters: Vec<Array2<F>>) -> Result<(), MlError> {
+        if parameters.len() != 2 {
+            return Err(MlError::InvalidInput(format!(
+                "FCLayer expects 2 parameters, actually: {}",
+                parameters.len()
+            )));
+        }
+        check_shapes(parameters[0].shape(), self.weights.shape())?;
+        check_shapes(parameters[1].shape(), self.bias.shape())?;
+        self.weights = parameters[0].clone();
+        self.bias = parameters[1].clone();
+        Ok(())
+    }
+
+    fn compile(&mut self, input_shape: &[usize]) -> Result<Vec<usize>, MlError> {
+        if input_shape != [self.shape.0] {
+            return Err(MlError::ShapeMismatch(format!(
+                "expected input shape {:?}, actually: {:?}",
+                [self.shape.0],
+                input_shape
+            )));
+        }
+        Ok(vec![self.shape.1])
+    }
+
+    fn count_parameters(&self) -> (usize, usize) {
+        (self.weights.len() + self.bias.len(), 0)
+    }
+
+    fn clone_layer(&self) -> Box<dyn Layer<F>> {
+        Box::new(self.clone())
     }
 
     fn get_shape(&self) -> (usize, usize) {
@@ -66,10 +169,10 @@ impl Layer for FCLayer {
         "FCLayer".to_string()
     }
 
-    fn to_json(&self) -> Result<String, Box<dyn Error>> {
+    fn to_json(&self) -> Result<String, MlError> {
         let model: FCLayerModel = FCLayerModel {
-            weights: self.weights.clone().into_raw_vec(),
-            bias: self.bias.clone().into_raw_vec(),
+            weights: self.weights.iter().map(|&w| w.to_f64_lossy()).collect(),
+            bias: self.bias.iter().map(|&b| b.to_f64_lossy()).collect(),
             shape: self.shape,
         };
         Ok(serde_json::to_string(&model)?)
@@ -77,8 +180,10 @@ impl Layer for FCLayer {
 }
 
 #[cfg(test)]
+#[allow(clippy::unused_unit)]
 mod tests {
     use super::*;
+    use crate::utils::gradient_check::{check_layer, max_relative_error, GradientCheck};
     use ndarray::arr2;
 
     fn generate_test_fc_layer() -> FCLayer {
@@ -86,6 +191,8 @@ mod tests {
             input: arr2(&[[1.0, 0.5]]),
             weights: arr2(&[[0.0, 1.0, 0.0], [0.5, 1.0, 0.5]]),
             bias: arr2(&[[1.0, 1.0, 0.25]]),
+            weights_gradient: Array2::zeros((2, 3)),
+            bias_gradient: Array2::zeros((1, 3)),
             shape: (2, 3),
         }
     }
@@ -102,20 +209,116 @@ mod tests {
     #[test]
     fn forward_propagation_should_apply_weights_and_bias() -> () {
         let mut layer: FCLayer = generate_test_fc_layer();
-        let result: Array2<f64> = layer.forward_propagation(&arr2(&[[1.0, 1.0]]));
+        let result: Array2<f64> = layer.forward_propagation(&arr2(&[[1.0, 1.0]])).unwrap();
         assert_eq!(layer.input, arr2(&[[1.0, 1.0]]));
         assert_eq!(result, arr2(&[[1.5, 3.0, 0.75]]));
     }
 
+    #[test]
+    fn predict_should_apply_weights_and_bias_on_batch() {
+        let layer: FCLayer = generate_test_fc_layer();
+        let result: Array2<f64> = layer.predict(&arr2(&[[1.0, 1.0], [0.0, 0.0]])).unwrap();
+        assert_eq!(layer.input, arr2(&[[1.0, 0.5]]));
+        assert_eq!(result, arr2(&[[1.5, 3.0, 0.75], [1.0, 1.0, 0.25]]));
+    }
+
     #[test]
     fn backward_propagation_should_correct_weights_and_bias() -> () {
         let mut layer: FCLayer = generate_test_fc_layer();
-        let result: Array2<f64> = layer.backward_propagation(&arr2(&[[1.0, 0.0, 0.0]]), 0.5);
+        let result: Array2<f64> = layer
+            .backward_propagation(&arr2(&[[1.0, 0.0, 0.0]]), 0.5)
+            .unwrap();
         assert_eq!(layer.bias, arr2(&[[0.5, 1.0, 0.25]]));
         assert_eq!(layer.weights, arr2(&[[-0.5, 1.0, 0.0], [0.25, 1.0, 0.5]]));
         assert_eq!(result, arr2(&[[0.0, 0.5]]));
     }
 
+    #[test]
+    fn build_with_seed_should_be_reproducible() {
+        let layer: FCLayer = FCLayer::build_with_seed(2, 3, 42);
+        let same_layer: FCLayer = FCLayer::build_with_seed(2, 3, 42);
+        let other_layer: FCLayer = FCLayer::build_with_seed(2, 3, 7);
+        assert_eq!(layer.weights, same_layer.weights);
+        assert_eq!(layer.bias, same_layer.bias);
+        assert!(layer.weights != other_layer.weights);
+    }
+
+    #[test]
+    fn compute_gradients_should_sum_batch_without_updating() {
+        let mut layer: FCLayer = generate_test_fc_layer();
+        layer
+            .forward_propagation(&arr2(&[[1.0, 0.5], [0.0, 1.0]]))
+            .unwrap();
+        let result: Array2<f64> = layer
+            .compute_gradients(&arr2(&[[1.0, 0.0, 0.0], [0.0, 1.0, 0.0]]))
+            .unwrap();
+        assert_eq!(result, arr2(&[[0.0, 0.5], [1.0, 1.0]]));
+        assert_eq!(
+            layer.get_gradients(),
+            vec![
+                arr2(&[[1.0, 0.0, 0.0], [0.5, 1.0, 0.0]]),
+                arr2(&[[1.0, 1.0, 0.0]])
+            ]
+        );
+        assert_eq!(layer.weights, arr2(&[[0.0, 1.0, 0.0], [0.5, 1.0, 0.5]]));
+        layer.apply_gradients(1.0);
+        assert_eq!(layer.weights, arr2(&[[-1.0, 1.0, 0.0], [0.0, 0.0, 0.5]]));
+        assert_eq!(layer.bias, arr2(&[[0.0, 0.0, 0.25]]));
+    }
+
+    #[test]
+    fn compute_gradients_should_pass_gradient_check() {
+        let mut layer: FCLayer = FCLayer::build_with_seed(3, 2, 1);
+        layer.compile(&[3]).unwrap();
+        let x: Array2<f64> = arr2(&[[0.5, -1.0, 2.0], [1.5, 0.25, -0.75]]);
+        let checks: Vec<GradientCheck> = check_layer(&mut layer, &x, 0.0001).unwrap();
+        assert_eq!(checks.len(), 3);
+        assert!(max_relative_error(&checks) < 0.0001);
+    }
+
+    #[test]
+    fn from_json_should_cast_weights_to_f32() {
+        let json_str: &str =
+            "{\"weights\":[0.0,1.0,0.0,0.5,1.0,0.5],\"bias\":[1.0,1.0,0.25],\"shape\":[2,3]}";
+        let layer: FCLayer<f32> = FCLayer::from_json(json_str).unwrap();
+        let result: Array2<f32> = layer.predict(&arr2(&[[1.0_f32, 1.0]])).unwrap();
+        assert_eq!(result, arr2(&[[1.5_f32, 3.0, 0.75]]));
+        assert_eq!(layer.to_json().unwrap(), json_str);
+    }
+
+    #[test]
+    fn propagation_should_fail_when_shapes_do_not_fit() {
+        let mut layer: FCLayer = generate_test_fc_layer();
+        assert!(matches!(
+            layer.forward_propagation(&arr2(&[[1.0, 1.0, 1.0]])),
+            Err(MlError::ShapeMismatch(_))
+        ));
+        assert!(matches!(
+            layer.compute_gradients(&arr2(&[[1.0, 0.0]])),
+            Err(MlError::ShapeMismatch(_))
+        ));
+        assert!(matches!(
+            layer.set_gradients(vec![arr2(&[[1.0]])]),
+            Err(MlError::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn compile_should_return_output_shape() {
+        let mut layer: FCLayer = generate_test_fc_layer();
+        assert_eq!(layer.compile(&[2]).unwrap(), vec![3]);
+        assert_eq!(
+            layer.compile(&[3]).unwrap_err().to_string(),
+            "shape mismatch: expected input shape [2], actually: [3]"
+        );
+    }
+
+    #[test]
+    fn count_parameters_should_count_weights_and_bias() {
+        let layer: FCLayer = FCLayer::build(2, 3);
+        assert_eq!(layer.count_parameters(), (9, 0));
+    }
+
     #[test]
     fn get_shape_should_return_layer_dim() -> () {
         let layer: FCLayer = generate_test_fc_layer();
@@ -1,86 +1,131 @@
-use ndarray::{Array2, Array3};
+use crate::layer::layer::Layer;
+use crate::utils::error::{check_shapes, MlError};
+use crate::utils::float::MlFloat;
+use ndarray::Array2;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug)]
+/// Turns multi-dimensional samples into vectors. Samples are already stored flattened in the
+/// rows of a batch, only the shape seen by the next layers changes.
+#[derive(Debug, Clone, Default)]
 pub struct FlattenLayer {
-    input_shape: (usize, usize, usize),
+    input_shape: Vec<usize>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FlattenLayerModel {
+    input_shape: Vec<usize>,
 }
 
 impl FlattenLayer {
     pub fn new() -> FlattenLayer {
         FlattenLayer {
-            input_shape: (0, 0, 0),
+            input_shape: vec![],
         }
     }
 
-    pub fn forward_propagation(&mut self, x: &Array3<f64>) -> Array2<f64> {
-        let x_clone: Array3<f64> = (*x).clone();
-        let shape: &[usize] = x_clone.shape();
-        self.input_shape = (shape[0], shape[1], shape[2]);
-        Array2::from_shape_vec((1, x_clone.len()), x_clone.into_raw_vec()).unwrap()
+    pub fn from_json(json_str: &str) -> Result<FlattenLayer, MlError> {
+        let model: FlattenLayerModel = serde_json::from_str(json_str)?;
+        Ok(FlattenLayer {
+            input_shape: model.input_shape,
+        })
     }
 
-    pub fn backward_propagation(&self, y: Array2<f64>) -> Array3<f64> {
-        Array3::from_shape_vec(self.input_shape, y.into_raw_vec()).unwrap()
+    fn size(&self) -> usize {
+        self.input_shape.iter().product()
+    }
+}
+
+impl<F: MlFloat> Layer<F> for FlattenLayer {
+    fn forward_propagation(&mut self, x: &Array2<F>) -> Result<Array2<F>, MlError> {
+        self.predict(x)
+    }
+
+    fn predict(&self, x: &Array2<F>) -> Result<Array2<F>, MlError> {
+        check_shapes(&x.shape()[1..], &[self.size()])?;
+        Ok(x.clone())
+    }
+
+    fn compute_gradients(&mut self, y: &Array2<F>) -> Result<Array2<F>, MlError> {
+        check_shapes(&y.shape()[1..], &[self.size()])?;
+        Ok(y.clone())
+    }
+
+    fn compile(&mut self, input_shape: &[usize]) -> Result<Vec<usize>, MlError> {
+        self.input_shape = input_shape.to_vec();
+        Ok(vec![self.size()])
+    }
+
+    fn clone_layer(&self) -> Box<dyn Layer<F>> {
+        Box::new(self.clone())
+    }
+
+    fn get_shape(&self) -> (usize, usize) {
+        (self.size(), self.size())
+    }
+
+    fn get_name(&self) -> String {
+        "FlattenLayer".to_string()
+    }
+
+    fn to_json(&self) -> Result<String, MlError> {
+        let model: FlattenLayerModel = FlattenLayerModel {
+            input_shape: self.input_shape.clone(),
+        };
+        Ok(serde_json::to_string(&model)?)
     }
 }
 
 #[cfg(test)]
+#[allow(clippy::unused_unit)]
 mod tests {
     use super::*;
+    use crate::utils::gradient_check::{check_layer, max_relative_error, GradientCheck};
+    use ndarray::arr2;
 
     #[test]
     fn new_should_initialize_layer() -> () {
         let layer: FlattenLayer = FlattenLayer::new();
-        assert_eq!(layer.input_shape, (0, 0, 0))
+        assert!(layer.input_shape.is_empty())
     }
 
     #[test]
-    fn forward_propagation_should_flatten_input() -> () {
+    fn compile_should_flatten_shape() {
         let mut layer: FlattenLayer = FlattenLayer::new();
-        let input: Array3<f64> = Array3::from_shape_vec(
-            (3, 3, 2),
-            vec![
-                30.0, 49.0, 36.0, 62.0, 45.5, 83.5, 22.5, 38.5, 34.5, 58.0, 44.5, 76.5, 28.5, 51.5,
-                35.0, 61.5, 44.5, 76.5,
-            ],
-        )
-        .unwrap();
-        let flatten: Array2<f64> = layer.forward_propagation(&input);
-        assert_eq!(layer.input_shape, (3, 3, 2));
         assert_eq!(
-            flatten,
-            Array2::from_shape_vec(
-                (1, 18),
-                vec![
-                    30.0, 49.0, 36.0, 62.0, 45.5, 83.5, 22.5, 38.5, 34.5, 58.0, 44.5, 76.5, 28.5,
-                    51.5, 35.0, 61.5, 44.5, 76.5,
-                ]
-            )
-            .unwrap()
-        )
+            Layer::<f64>::compile(&mut layer, &[3, 3, 2]).unwrap(),
+            vec![18]
+        );
+        assert_eq!(layer.input_shape, vec![3, 3, 2]);
+    }
+
+    #[test]
+    fn propagation_should_keep_rows() {
+        let mut layer: FlattenLayer = FlattenLayer::new();
+        Layer::<f64>::compile(&mut layer, &[2, 1, 2]).unwrap();
+        let x: Array2<f64> = arr2(&[[30.0, 49.0, 36.0, 62.0], [45.5, 83.5, 22.5, 38.5]]);
+        assert_eq!(layer.forward_propagation(&x).unwrap(), x);
+        assert_eq!(layer.backward_propagation(&x, 0.1).unwrap(), x);
+        assert!(matches!(
+            layer.predict(&arr2(&[[1.0, 2.0]])),
+            Err(MlError::ShapeMismatch(_))
+        ));
+    }
+
+    #[test]
+    fn compute_gradients_should_pass_gradient_check() {
+        let mut layer: FlattenLayer = FlattenLayer::new();
+        Layer::<f64>::compile(&mut layer, &[2, 2, 1]).unwrap();
+        let x: Array2<f64> = arr2(&[[0.5, -1.0, 2.0, 1.5], [0.25, -0.75, 1.0, 0.0]]);
+        let checks: Vec<GradientCheck> = check_layer(&mut layer, &x, 0.0001).unwrap();
+        assert!(max_relative_error(&checks) < 0.0001);
     }
 
     #[test]
-    fn backward_propagation_should_reshape_input() -> () {
+    fn from_json_should_deserialize_layer() {
         let mut layer: FlattenLayer = FlattenLayer::new();
-        let input: Array3<f64> = Array3::from_shape_vec(
-            (3, 3, 2),
-            vec![
-                30.0, 49.0, 36.0, 62.0, 45.5, 83.5, 22.5, 38.5, 34.5, 58.0, 44.5, 76.5, 28.5, 51.5,
-                35.0, 61.5, 44.5, 76.5,
-            ],
-        )
-        .unwrap();
-        layer.forward_propagation(&input);
-        let error_input: Array2<f64> = Array2::from_shape_vec(
-            (1, 18),
-            vec![
-                30.0, 49.0, 36.0, 62.0, 45.5, 83.5, 22.5, 38.5, 34.5, 58.0, 44.5, 76.5, 28.5, 51.5,
-                35.0, 61.5, 44.5, 76.5,
-            ],
-        )
-        .unwrap();
-        let output: Array3<f64> = layer.backward_propagation(error_input);
-        assert_eq!(output, input);
+        Layer::<f64>::compile(&mut layer, &[3, 3, 2]).unwrap();
+        let result: FlattenLayer =
+            FlattenLayer::from_json(&Layer::<f64>::to_json(&layer).unwrap()).unwrap();
+        assert_eq!(result.input_shape, vec![3, 3, 2]);
     }
 }
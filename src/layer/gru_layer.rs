@@ -0,0 +1,129 @@
+use crate::activation::activation::Activation;
+use crate::activation::sigmoid::Sigmoid;
+use crate::layer::recurrent_layer::{gate, Cell, RecurrentLayer, RecurrentWeights, StepOutput};
+use crate::utils::error::MlError;
+use crate::utils::float::MlFloat;
+use ndarray::{concatenate, s, Array2, Axis};
+
+/// Gated recurrent unit with update, reset and candidate gates in this order. The reset gate is
+/// applied to the state before its product with the recurrent weights of the candidate.
+#[derive(Debug, Clone)]
+pub struct GruCell;
+
+impl Cell for GruCell {
+    const GATES: usize = 3;
+    const STATES: usize = 1;
+    const NAME: &'static str = "GruLayer";
+
+    fn forward<F: MlFloat>(
+        weights: &RecurrentWeights<F>,
+        x: &Array2<F>,
+        states: &[Array2<F>],
+    ) -> Result<StepOutput<F>, MlError> {
+        let h: &Array2<F> = &states[0];
+        let units: usize = h.shape()[1];
+        let x_linear: Array2<F> = x.dot(&weights.input) + &weights.bias;
+        let h_linear: Array2<F> = h.dot(&weights.recurrent.slice(s![.., 0..2 * units]));
+        let update_gate: Array2<F> =
+            Sigmoid.function(&(&gate(&x_linear, 0, units) + &gate(&h_linear, 0, units)));
+        let reset_gate: Array2<F> =
+            Sigmoid.function(&(&gate(&x_linear, 1, units) + &gate(&h_linear, 1, units)));
+        let candidate: Array2<F> = (&gate(&x_linear, 2, units)
+            + &(&reset_gate * h).dot(&gate(&weights.recurrent, 2, units)))
+            .mapv(F::tanh);
+        let new_h: Array2<F> = &update_gate.mapv(|z| F::one() - z) * &candidate + &update_gate * h;
+        Ok((vec![new_h], vec![update_gate, reset_gate, candidate]))
+    }
+
+    fn backward<F: MlFloat>(
+        weights: &RecurrentWeights<F>,
+        gradients: &mut RecurrentWeights<F>,
+        x: &Array2<F>,
+        states: &[Array2<F>],
+        cache: &[Array2<F>],
+        errors: &[Array2<F>],
+    ) -> Result<(Array2<F>, Vec<Array2<F>>), MlError> {
+        let (update_gate, reset_gate, candidate) = (&cache[0], &cache[1], &cache[2]);
+        let (h, dh): (&Array2<F>, &Array2<F>) = (&states[0], &errors[0]);
+        let units: usize = h.shape()[1];
+        let sigmoid_derivative = |s: F| s * (F::one() - s);
+
+        let d_candidate: Array2<F> =
+            dh * &update_gate.mapv(|z| F::one() - z) * &candidate.mapv(|n| F::one() - n * n);
+        let d_update: Array2<F> = dh * &(h - candidate) * &update_gate.mapv(sigmoid_derivative);
+        let d_reset_h: Array2<F> = d_candidate.dot(&gate(&weights.recurrent, 2, units).t());
+        let d_reset: Array2<F> = &d_reset_h * h * &reset_gate.mapv(sigmoid_derivative);
+        let dz: Array2<F> = concatenate(
+            Axis(1),
+            &[d_update.view(), d_reset.view(), d_candidate.view()],
+        )?;
+
+        gradients.input += &x.t().dot(&dz);
+        gradients.bias += &dz.sum_axis(Axis(0)).insert_axis(Axis(0));
+        gradients
+            .recurrent
+            .slice_mut(s![.., 0..2 * units])
+            .scaled_add(F::one(), &h.t().dot(&dz.slice(s![.., 0..2 * units])));
+        gradients
+            .recurrent
+            .slice_mut(s![.., 2 * units..3 * units])
+            .scaled_add(F::one(), &(reset_gate * h).t().dot(&d_candidate));
+
+        let h_error: Array2<F> = dh * update_gate
+            + &d_reset_h * reset_gate
+            + dz.slice(s![.., 0..2 * units])
+                .dot(&weights.recurrent.slice(s![.., 0..2 * units]).t());
+        Ok((dz.dot(&weights.input.t()), vec![h_error]))
+    }
+}
+
+/// Gated recurrent unit layer.
+pub type GruLayer<F = f64> = RecurrentLayer<F, GruCell>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layer::layer;
+    use crate::layer::layer::Layer;
+    use crate::utils::gradient_check::{check_layer, max_relative_error, GradientCheck};
+    use ndarray::arr2;
+
+    #[test]
+    fn forward_propagation_should_interpolate_state_and_candidate() {
+        let mut gru_layer: GruLayer = GruLayer::from_json(
+            "{\"input_weights\":[0.0,0.0,1.0],\"recurrent_weights\":[0.0,0.0,1.0],\
+             \"bias\":[0.0,0.0,0.0],\"features\":1,\"units\":1,\"timesteps\":2,\
+             \"return_sequences\":true,\"stateful\":false,\"truncation\":null}",
+        )
+        .unwrap();
+        let output: Array2<f64> = gru_layer.forward_propagation(&arr2(&[[1.0, 0.5]])).unwrap();
+        // update and reset gates are 0.5 with null weights
+        let h_1: f64 = 0.5 * 1.0_f64.tanh();
+        let h_2: f64 = 0.5 * (0.5 + 0.5 * h_1).tanh() + 0.5 * h_1;
+        assert!((output[[0, 0]] - h_1).powf(2.0) < 0.00001);
+        assert!((output[[0, 1]] - h_2).powf(2.0) < 0.00001);
+    }
+
+    #[test]
+    fn compute_gradients_should_pass_gradient_check() {
+        let x: Array2<f64> =
+            Array2::from_shape_fn((2, 8), |(b, i)| ((b * 8 + i) % 5) as f64 / 4.0 - 0.5);
+        for return_sequences in [false, true] {
+            let mut gru_layer: GruLayer = GruLayer::build_with_seed(2, 3, 1);
+            gru_layer.set_return_sequences(return_sequences);
+            gru_layer.compile(&[4, 2]).unwrap();
+            let checks: Vec<GradientCheck> = check_layer(&mut gru_layer, &x, 0.0001).unwrap();
+            assert!(max_relative_error(&checks) < 0.0001);
+        }
+    }
+
+    #[test]
+    fn from_string_should_deserialize_layer() {
+        let mut gru_layer: GruLayer = GruLayer::build_with_seed(2, 3, 1);
+        gru_layer.compile(&[4, 2]).unwrap();
+        let result: Box<dyn Layer<f64>> =
+            layer::from_string("GruLayer".to_string(), &gru_layer.to_json().unwrap()).unwrap();
+        assert_eq!(result.get_name(), "GruLayer");
+        assert_eq!(result.count_parameters(), (2 * 9 + 3 * 9 + 9, 0));
+    }
+}
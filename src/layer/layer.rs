@@ -1,33 +1,134 @@
 use crate::layer::activation_layer::ActivationLayer;
+use crate::layer::bidirectional::Bidirectional;
+use crate::layer::conv1d_layer::Conv1dLayer;
+use crate::layer::conv3d_layer::Conv3dLayer;
+use crate::layer::conv_layer::ConvLayer;
+use crate::layer::conv_transpose_layer::ConvTransposeLayer;
+use crate::layer::cropping2d::Cropping2D;
+use crate::layer::embedding_layer::EmbeddingLayer;
 use crate::layer::fc_layer::FCLayer;
+use crate::layer::flatten_layer::FlattenLayer;
+use crate::layer::gru_layer::GruLayer;
+use crate::layer::layer_norm_layer::LayerNormLayer;
+use crate::layer::lstm_layer::LstmLayer;
+use crate::layer::multi_head_attention::MultiHeadAttention;
+use crate::layer::permute_layer::PermuteLayer;
+use crate::layer::pooling1d_layer::{AvgPooling1d, MaxPooling1d};
+use crate::layer::pooling_layer::{AvgPoolingLayer, MaxPoolingLayer};
+use crate::layer::positional_encoding_layer::PositionalEncodingLayer;
+use crate::layer::repeat_vector::RepeatVector;
+use crate::layer::reshape_layer::ReshapeLayer;
+use crate::layer::residual_block::ResidualBlock;
+use crate::layer::rnn_layer::RnnLayer;
+use crate::layer::time_distributed::TimeDistributed;
+use crate::layer::transformer_encoder_block::TransformerEncoderBlock;
+use crate::layer::up_sampling_layer::UpSamplingLayer;
+use crate::layer::zero_padding2d::ZeroPadding2D;
+use crate::utils::error::MlError;
+use crate::utils::float::MlFloat;
 use ndarray::Array2;
-use std::error::Error;
-use std::io;
 
-pub trait Layer {
-    fn forward_propagation(&mut self, x: &Array2<f64>) -> Array2<f64>;
+/// Batches hold one sample per row. Samples with more than one dimension are flattened in
+/// row-major order, layers get their shape back from `compile`.
+pub trait Layer<F: MlFloat = f64>: Send + Sync {
+    fn forward_propagation(&mut self, x: &Array2<F>) -> Result<Array2<F>, MlError>;
 
-    fn backward_propagation(&mut self, y: &Array2<f64>, learning_rate: f64) -> Array2<f64>;
+    /// Forward pass without caching the input, so a trained layer can be shared between threads.
+    fn predict(&self, x: &Array2<F>) -> Result<Array2<F>, MlError>;
+
+    fn backward_propagation(
+        &mut self,
+        y: &Array2<F>,
+        learning_rate: F,
+    ) -> Result<Array2<F>, MlError> {
+        let input_error: Array2<F> = self.compute_gradients(y)?;
+        self.apply_gradients(learning_rate);
+        Ok(input_error)
+    }
+
+    /// Stores the parameter gradients for the output error `y` and returns the input error.
+    fn compute_gradients(&mut self, y: &Array2<F>) -> Result<Array2<F>, MlError>;
+
+    fn apply_gradients(&mut self, _learning_rate: F) {}
+
+    fn get_gradients(&self) -> Vec<Array2<F>> {
+        vec![]
+    }
+
+    fn set_gradients(&mut self, _gradients: Vec<Array2<F>>) -> Result<(), MlError> {
+        Ok(())
+    }
+
+    /// Trainable arrays of the layer, in the same order as its gradients.
+    fn get_parameters(&self) -> Vec<Array2<F>> {
+        vec![]
+    }
+
+    /// Replaces the trainable arrays, they must have the shapes returned by `get_parameters`.
+    fn set_parameters(&mut self, _parameters: Vec<Array2<F>>) -> Result<(), MlError> {
+        Ok(())
+    }
+
+    /// Checks the layer accepts samples of `input_shape`, infers its own shape from it and
+    /// returns the shape of its output samples.
+    fn compile(&mut self, input_shape: &[usize]) -> Result<Vec<usize>, MlError>;
+
+    /// Whether the layer carries states from one batch to the next, which clones of the layer
+    /// would lose.
+    fn is_stateful(&self) -> bool {
+        false
+    }
+
+    /// Number of trainable and non-trainable parameters.
+    fn count_parameters(&self) -> (usize, usize) {
+        (0, 0)
+    }
+
+    fn clone_layer(&self) -> Box<dyn Layer<F>>;
 
     fn get_shape(&self) -> (usize, usize);
 
     fn get_name(&self) -> String;
 
-    fn to_json(&self) -> Result<String, Box<dyn Error>>;
+    fn to_json(&self) -> Result<String, MlError>;
 }
 
-pub fn from_string(name: String, json_str: &str) -> Result<Box<dyn Layer>, Box<dyn Error>> {
+pub fn from_string<F: MlFloat>(name: String, json_str: &str) -> Result<Box<dyn Layer<F>>, MlError> {
     match name.to_uppercase().as_str() {
         "FCLAYER" => Ok(Box::new(FCLayer::from_json(json_str)?)),
         "ACTIVATIONLAYER" => Ok(Box::new(ActivationLayer::from_json(json_str)?)),
-        _ => Err(Box::new(io::Error::new(
-            io::ErrorKind::InvalidInput,
-            format!("unknown layer '{}'", name),
-        ))),
+        "CONVLAYER" => Ok(Box::new(ConvLayer::from_json(json_str)?)),
+        "CONV1DLAYER" => Ok(Box::new(Conv1dLayer::from_json(json_str)?)),
+        "CONV3DLAYER" => Ok(Box::new(Conv3dLayer::from_json(json_str)?)),
+        "MAXPOOLINGLAYER" => Ok(Box::new(MaxPoolingLayer::from_json(json_str)?)),
+        "AVGPOOLINGLAYER" => Ok(Box::new(AvgPoolingLayer::from_json(json_str)?)),
+        "MAXPOOLING1D" => Ok(Box::new(MaxPooling1d::from_json(json_str)?)),
+        "AVGPOOLING1D" => Ok(Box::new(AvgPooling1d::from_json(json_str)?)),
+        "CONVTRANSPOSELAYER" => Ok(Box::new(ConvTransposeLayer::from_json(json_str)?)),
+        "UPSAMPLINGLAYER" => Ok(Box::new(UpSamplingLayer::from_json(json_str)?)),
+        "FLATTENLAYER" => Ok(Box::new(FlattenLayer::from_json(json_str)?)),
+        "RESHAPELAYER" => Ok(Box::new(ReshapeLayer::from_json(json_str)?)),
+        "PERMUTELAYER" => Ok(Box::new(PermuteLayer::from_json(json_str)?)),
+        "REPEATVECTOR" => Ok(Box::new(RepeatVector::from_json(json_str)?)),
+        "CROPPING2D" => Ok(Box::new(Cropping2D::from_json(json_str)?)),
+        "ZEROPADDING2D" => Ok(Box::new(ZeroPadding2D::from_json(json_str)?)),
+        "RESIDUALBLOCK" => Ok(Box::new(ResidualBlock::from_json(json_str)?)),
+        "RNNLAYER" => Ok(Box::new(RnnLayer::from_json(json_str)?)),
+        "LSTMLAYER" => Ok(Box::new(LstmLayer::from_json(json_str)?)),
+        "GRULAYER" => Ok(Box::new(GruLayer::from_json(json_str)?)),
+        "BIDIRECTIONAL" => Ok(Box::new(Bidirectional::from_json(json_str)?)),
+        "TIMEDISTRIBUTED" => Ok(Box::new(TimeDistributed::from_json(json_str)?)),
+        "EMBEDDINGLAYER" => Ok(Box::new(EmbeddingLayer::from_json(json_str)?)),
+        "LAYERNORMLAYER" => Ok(Box::new(LayerNormLayer::from_json(json_str)?)),
+        "MULTIHEADATTENTION" => Ok(Box::new(MultiHeadAttention::from_json(json_str)?)),
+        "POSITIONALENCODINGLAYER" => Ok(Box::new(PositionalEncodingLayer::from_json(json_str)?)),
+        "TRANSFORMERENCODERBLOCK" => Ok(Box::new(TransformerEncoderBlock::from_json(json_str)?)),
+        _ => Err(MlError::unknown_component("layer", &name)),
     }
 }
 
 #[cfg(test)]
+#[allow(clippy::unused_unit)]
 mod tests {
     use super::*;
 
@@ -37,22 +138,31 @@ mod tests {
             "{\"weights\":[0.0,1.0,0.0,0.5,1.0,0.5],\"bias\":[1.0,1.0,0.25],\"shape\":[2,3]}";
         let activation_layer_str: &str = "{\"activation\":\"Tanh\",\"shape\":[2,3]}";
         assert_eq!(
-            from_string("FCLayer".to_string(), fc_layer_str)
+            from_string::<f64>("FCLayer".to_string(), fc_layer_str)
                 .unwrap()
                 .get_name(),
             "FCLayer".to_string()
         );
         assert_eq!(
-            from_string("ActivationLayer".to_string(), activation_layer_str)
+            from_string::<f32>("ActivationLayer".to_string(), activation_layer_str)
                 .unwrap()
                 .get_name(),
             "ActivationLayer".to_string()
         );
+        assert_eq!(
+            from_string::<f64>(
+                "MaxPoolingLayer".to_string(),
+                "{\"kernel_size\":2,\"input_shape\":[4,4,1]}"
+            )
+            .unwrap()
+            .get_shape(),
+            (16, 4)
+        );
     }
 
     #[test]
-    #[should_panic(expected = "unknown layer 'Unknown'")]
     fn from_string_should_raise_error_when_name_is_unknown() -> () {
-        from_string("Unknown".to_string(), "").unwrap();
+        let error: MlError = from_string::<f64>("Unknown".to_string(), "").err().unwrap();
+        assert_eq!(error.to_string(), "unknown layer 'Unknown'");
     }
 }
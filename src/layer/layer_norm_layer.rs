@@ -0,0 +1,266 @@
+use crate::layer::layer::Layer;
+use crate::utils::error::{check_shapes, MlError};
+use crate::utils::float::MlFloat;
+use ndarray::{Array2, Axis};
+use serde::{Deserialize, Serialize};
+
+/// Normalizes the last axis of the samples to a zero mean and a unit variance, then scales and
+/// shifts it with learned `gain` and `bias`. Samples of shape `(timesteps, size)` are normalized
+/// at every timestep.
+#[derive(Debug, Clone)]
+pub struct LayerNormLayer<F: MlFloat = f64> {
+    /// Normalized input of the last forward pass, one row per normalized vector.
+    normalized: Array2<F>,
+    /// Inverse of the standard deviation of each row of `normalized`.
+    inverse_deviation: Array2<F>,
+    gain: Array2<F>,
+    bias: Array2<F>,
+    gain_gradient: Array2<F>,
+    bias_gradient: Array2<F>,
+    epsilon: F,
+    input_shape: Vec<usize>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LayerNormLayerModel {
+    gain: Vec<f64>,
+    bias: Vec<f64>,
+    epsilon: f64,
+    input_shape: Vec<usize>,
+}
+
+impl<F: MlFloat> LayerNormLayer<F> {
+    /// Normalizes vectors of `size` values, starting as the identity on normalized inputs.
+    pub fn build(size: usize) -> LayerNormLayer<F> {
+        LayerNormLayer {
+            normalized: Array2::zeros((0, size)),
+            inverse_deviation: Array2::zeros((0, 1)),
+            gain: Array2::ones((1, size)),
+            bias: Array2::zeros((1, size)),
+            gain_gradient: Array2::zeros((1, size)),
+            bias_gradient: Array2::zeros((1, size)),
+            epsilon: F::cast(1e-5),
+            input_shape: vec![],
+        }
+    }
+
+    pub fn from_json(json_str: &str) -> Result<LayerNormLayer<F>, MlError> {
+        let model: LayerNormLayerModel = serde_json::from_str(json_str)?;
+        let size: usize = model.gain.len();
+        let mut layer: LayerNormLayer<F> = LayerNormLayer::build(size);
+        layer.gain =
+            Array2::from_shape_vec((1, size), model.gain.into_iter().map(F::cast).collect())?;
+        layer.bias =
+            Array2::from_shape_vec((1, size), model.bias.into_iter().map(F::cast).collect())?;
+        layer.epsilon = F::cast(model.epsilon);
+        layer.input_shape = model.input_shape;
+        Ok(layer)
+    }
+
+    fn size(&self) -> usize {
+        self.gain.shape()[1]
+    }
+
+    /// Returns the normalized rows of `x` and the inverse of their standard deviation.
+    fn normalize(&self, x: &Array2<F>) -> Result<(Array2<F>, Array2<F>), MlError> {
+        check_shapes(&x.shape()[1..], &[self.input_shape.iter().product()])?;
+        let rows: Array2<F> = x
+            .as_standard_layout()
+            .into_owned()
+            .into_shape((x.len() / self.size(), self.size()))?;
+        let size: F = F::cast(self.size() as f64);
+        let mean: Array2<F> = rows.sum_axis(Axis(1)).insert_axis(Axis(1)) / size;
+        let centered: Array2<F> = rows - &mean;
+        let variance: Array2<F> = centered
+            .mapv(|c| c * c)
+            .sum_axis(Axis(1))
+            .insert_axis(Axis(1))
+            / size;
+        let inverse_deviation: Array2<F> = variance.mapv(|v| F::one() / (v + self.epsilon).sqrt());
+        Ok((centered * &inverse_deviation, inverse_deviation))
+    }
+
+    fn scale(&self, normalized: &Array2<F>, batch: usize) -> Result<Array2<F>, MlError> {
+        let output: Array2<F> = normalized * &self.gain + &self.bias;
+        Ok(output.into_shape((batch, self.input_shape.iter().product()))?)
+    }
+}
+
+impl<F: MlFloat> Layer<F> for LayerNormLayer<F> {
+    fn forward_propagation(&mut self, x: &Array2<F>) -> Result<Array2<F>, MlError> {
+        let (normalized, inverse_deviation): (Array2<F>, Array2<F>) = self.normalize(x)?;
+        let output: Array2<F> = self.scale(&normalized, x.shape()[0])?;
+        self.normalized = normalized;
+        self.inverse_deviation = inverse_deviation;
+        Ok(output)
+    }
+
+    fn predict(&self, x: &Array2<F>) -> Result<Array2<F>, MlError> {
+        let (normalized, _): (Array2<F>, Array2<F>) = self.normalize(x)?;
+        self.scale(&normalized, x.shape()[0])
+    }
+
+    fn compute_gradients(&mut self, y: &Array2<F>) -> Result<Array2<F>, MlError> {
+        let columns: usize = self.input_shape.iter().product();
+        check_shapes(
+            y.shape(),
+            &[self.normalized.len() / columns.max(1), columns],
+        )?;
+        let errors: Array2<F> = y
+            .as_standard_layout()
+            .into_owned()
+            .into_shape(self.normalized.raw_dim())?;
+        self.gain_gradient = (&errors * &self.normalized)
+            .sum_axis(Axis(0))
+            .insert_axis(Axis(0));
+        self.bias_gradient = errors.sum_axis(Axis(0)).insert_axis(Axis(0));
+
+        let size: F = F::cast(self.size() as f64);
+        let normalized_error: Array2<F> = errors * &self.gain;
+        let mean_error: Array2<F> = normalized_error.sum_axis(Axis(1)).insert_axis(Axis(1)) / size;
+        let mean_projection: Array2<F> = (&normalized_error * &self.normalized)
+            .sum_axis(Axis(1))
+            .insert_axis(Axis(1))
+            / size;
+        let input_error: Array2<F> =
+            (normalized_error - mean_error - &self.normalized * &mean_projection)
+                * &self.inverse_deviation;
+        Ok(input_error.into_shape(y.raw_dim())?)
+    }
+
+    fn apply_gradients(&mut self, learning_rate: F) {
+        self.gain = &self.gain - &(&self.gain_gradient * learning_rate);
+        self.bias = &self.bias - &(&self.bias_gradient * learning_rate);
+    }
+
+    fn get_gradients(&self) -> Vec<Array2<F>> {
+        vec![self.gain_gradient.clone(), self.bias_gradient.clone()]
+    }
+
+    fn set_gradients(&mut self, gradients: Vec<Array2<F>>) -> Result<(), MlError> {
+        if gradients.len() != 2 {
+            return Err(MlError::InvalidInput(format!(
+                "LayerNormLayer expects 2 gradients, actually: {}",
+                gradients.len()
+            )));
+        }
+        check_shapes(gradients[0].shape(), self.gain.shape())?;
+        check_shapes(gradients[1].shape(), self.bias.shape())?;
+        self.gain_gradient = gradients[0].clone();
+        self.bias_gradient = gradients[1].clone();
+        Ok(())
+    }
+
+    fn get_parameters(&self) -> Vec<Array2<F>> {
+        vec![self.gain.clone(), self.bias.clone()]
+    }
+
+    fn set_parameters(&mut self, parameters: Vec<Array2<F>>) -> Result<(), MlError> {
+        if parameters.len() != 2 {
+            return Err(MlError::InvalidInput(format!(
+                "LayerNormLayer expects 2 parameters, actually: {}",
+                parameters.len()
+            )));
+        }
+        check_shapes(parameters[0].shape(), self.gain.shape())?;
+        check_shapes(parameters[1].shape(), self.bias.shape())?;
+        self.gain = parameters[0].clone();
+        self.bias = parameters[1].clone();
+        Ok(())
+    }
+
+    fn compile(&mut self, input_shape: &[usize]) -> Result<Vec<usize>, MlError> {
+        if input_shape.last() != Some(&self.size()) {
+            return Err(MlError::ShapeMismatch(format!(
+                "expected input shape [..., {}], actually: {:?}",
+                self.size(),
+                input_shape
+            )));
+        }
+        self.input_shape = input_shape.to_vec();
+        Ok(input_shape.to_vec())
+    }
+
+    fn count_parameters(&self) -> (usize, usize) {
+        (self.gain.len() + self.bias.len(), 0)
+    }
+
+    fn clone_layer(&self) -> Box<dyn Layer<F>> {
+        Box::new(self.clone())
+    }
+
+    fn get_shape(&self) -> (usize, usize) {
+        let size: usize = self.input_shape.iter().product();
+        (size, size)
+    }
+
+    fn get_name(&self) -> String {
+        "LayerNormLayer".to_string()
+    }
+
+    fn to_json(&self) -> Result<String, MlError> {
+        let model: LayerNormLayerModel = LayerNormLayerModel {
+            gain: self.gain.iter().map(|&g| g.to_f64_lossy()).collect(),
+            bias: self.bias.iter().map(|&b| b.to_f64_lossy()).collect(),
+            epsilon: self.epsilon.to_f64_lossy(),
+            input_shape: self.input_shape.clone(),
+        };
+        Ok(serde_json::to_string(&model)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layer::layer;
+    use crate::utils::gradient_check::{check_layer, max_relative_error, GradientCheck};
+    use ndarray::arr2;
+
+    #[test]
+    fn forward_propagation_should_normalize_every_timestep() {
+        let mut layer_norm_layer: LayerNormLayer = LayerNormLayer::build(2);
+        assert_eq!(layer_norm_layer.compile(&[2, 2]).unwrap(), vec![2, 2]);
+        let result: Array2<f64> = layer_norm_layer
+            .forward_propagation(&arr2(&[[1.0, 3.0, 4.0, 0.0]]))
+            .unwrap();
+        let expected: Array2<f64> = arr2(&[[-1.0, 1.0, 1.0, -1.0]]);
+        assert!(result
+            .iter()
+            .zip(expected.iter())
+            .all(|(r, e)| (r - e).powf(2.0) < 0.00001));
+        assert!(matches!(
+            layer_norm_layer.compile(&[2, 3]),
+            Err(MlError::ShapeMismatch(_))
+        ));
+    }
+
+    #[test]
+    fn compute_gradients_should_pass_gradient_check() {
+        let mut layer_norm_layer: LayerNormLayer = LayerNormLayer::build(3);
+        layer_norm_layer.compile(&[2, 3]).unwrap();
+        layer_norm_layer.gain = arr2(&[[0.5, 1.5, -1.0]]);
+        layer_norm_layer.bias = arr2(&[[0.1, 0.0, -0.2]]);
+        let x: Array2<f64> = arr2(&[
+            [0.5, -1.0, 2.0, 1.0, 1.5, 0.0],
+            [0.0, 0.3, -0.4, 2.0, 1.0, 3.0],
+        ]);
+        let checks: Vec<GradientCheck> = check_layer(&mut layer_norm_layer, &x, 0.0001).unwrap();
+        assert_eq!(checks.len(), 3);
+        assert!(max_relative_error(&checks) < 0.0001);
+    }
+
+    #[test]
+    fn from_string_should_deserialize_layer() {
+        let mut layer_norm_layer: LayerNormLayer = LayerNormLayer::build(3);
+        layer_norm_layer.compile(&[3]).unwrap();
+        let json: String = layer_norm_layer.to_json().unwrap();
+        assert_eq!(
+            json,
+            "{\"gain\":[1.0,1.0,1.0],\"bias\":[0.0,0.0,0.0],\"epsilon\":0.00001,\"input_shape\":[3]}"
+        );
+        let result: Box<dyn Layer<f64>> =
+            layer::from_string("LayerNormLayer".to_string(), &json).unwrap();
+        assert_eq!(result.get_shape(), (3, 3));
+        assert_eq!(result.count_parameters(), (6, 0));
+    }
+}
@@ -0,0 +1,157 @@
+use crate::activation::activation::Activation;
+use crate::activation::sigmoid::Sigmoid;
+use crate::layer::recurrent_layer::{gate, Cell, RecurrentLayer, RecurrentWeights, StepOutput};
+use crate::utils::error::MlError;
+use crate::utils::float::MlFloat;
+use ndarray::{concatenate, s, Array2, Axis};
+
+/// Long short-term memory cell with input, forget, cell and output gates in this order. The
+/// states are the output `h` and the cell `c`.
+#[derive(Debug, Clone)]
+pub struct LstmCell;
+
+impl Cell for LstmCell {
+    const GATES: usize = 4;
+    const STATES: usize = 2;
+    const NAME: &'static str = "LstmLayer";
+
+    /// The forget gate starts open so the cell keeps its memory at the beginning of training.
+    fn initial_bias<F: MlFloat>(units: usize) -> Array2<F> {
+        let mut bias: Array2<F> = Array2::zeros((1, 4 * units));
+        bias.slice_mut(s![.., units..2 * units]).fill(F::one());
+        bias
+    }
+
+    fn forward<F: MlFloat>(
+        weights: &RecurrentWeights<F>,
+        x: &Array2<F>,
+        states: &[Array2<F>],
+    ) -> Result<StepOutput<F>, MlError> {
+        let units: usize = states[0].shape()[1];
+        let z: Array2<F> = weights.linear(x, &states[0]);
+        let input_gate: Array2<F> = Sigmoid.function(&gate(&z, 0, units).to_owned());
+        let forget_gate: Array2<F> = Sigmoid.function(&gate(&z, 1, units).to_owned());
+        let cell_gate: Array2<F> = gate(&z, 2, units).mapv(F::tanh);
+        let output_gate: Array2<F> = Sigmoid.function(&gate(&z, 3, units).to_owned());
+        let c: Array2<F> = &forget_gate * &states[1] + &input_gate * &cell_gate;
+        let c_tanh: Array2<F> = c.mapv(F::tanh);
+        let h: Array2<F> = &output_gate * &c_tanh;
+        Ok((
+            vec![h, c],
+            vec![input_gate, forget_gate, cell_gate, output_gate, c_tanh],
+        ))
+    }
+
+    fn backward<F: MlFloat>(
+        weights: &RecurrentWeights<F>,
+        gradients: &mut RecurrentWeights<F>,
+        x: &Array2<F>,
+        states: &[Array2<F>],
+        cache: &[Array2<F>],
+        errors: &[Array2<F>],
+    ) -> Result<(Array2<F>, Vec<Array2<F>>), MlError> {
+        let (input_gate, forget_gate, cell_gate, output_gate, c_tanh) =
+            (&cache[0], &cache[1], &cache[2], &cache[3], &cache[4]);
+        let sigmoid_derivative = |s: F| s * (F::one() - s);
+        let dc: Array2<F> =
+            &errors[1] + &(&errors[0] * output_gate * &c_tanh.mapv(|c| F::one() - c * c));
+        let dz: Array2<F> = concatenate(
+            Axis(1),
+            &[
+                (&dc * cell_gate * &input_gate.mapv(sigmoid_derivative)).view(),
+                (&dc * &states[1] * &forget_gate.mapv(sigmoid_derivative)).view(),
+                (&dc * input_gate * &cell_gate.mapv(|g| F::one() - g * g)).view(),
+                (&errors[0] * c_tanh * &output_gate.mapv(sigmoid_derivative)).view(),
+            ],
+        )?;
+        gradients.accumulate(x, &states[0], &dz);
+        Ok((
+            dz.dot(&weights.input.t()),
+            vec![dz.dot(&weights.recurrent.t()), dc * forget_gate],
+        ))
+    }
+}
+
+/// Long short-term memory layer.
+pub type LstmLayer<F = f64> = RecurrentLayer<F, LstmCell>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layer::fc_layer::FCLayer;
+    use crate::layer::layer;
+    use crate::layer::layer::Layer;
+    use crate::loss::mse::Mse;
+    use crate::network::sequential::Sequential;
+    use crate::report::report::Report;
+    use crate::utils::gradient_check::{check_layer, max_relative_error, GradientCheck};
+    use ndarray::{Array3, Array4};
+    use ndarray_rand::rand::rngs::StdRng;
+    use ndarray_rand::rand::{Rng, SeedableRng};
+    use std::fs;
+
+    #[test]
+    fn build_should_open_forget_gate() {
+        let lstm_layer: LstmLayer = LstmLayer::build_with_seed(2, 3, 1);
+        assert_eq!(lstm_layer.count_parameters(), (2 * 12 + 3 * 12 + 12, 0));
+        let json: String = lstm_layer.to_json().unwrap();
+        assert!(json.contains("\"bias\":[0.0,0.0,0.0,1.0,1.0,1.0,0.0,0.0,0.0,0.0,0.0,0.0]"));
+    }
+
+    #[test]
+    fn compute_gradients_should_pass_gradient_check() {
+        let x: Array2<f64> =
+            Array2::from_shape_fn((2, 8), |(b, i)| ((b * 8 + i) % 5) as f64 / 4.0 - 0.5);
+        for return_sequences in [false, true] {
+            let mut lstm_layer: LstmLayer = LstmLayer::build_with_seed(2, 3, 1);
+            lstm_layer.set_return_sequences(return_sequences);
+            lstm_layer.compile(&[4, 2]).unwrap();
+            let checks: Vec<GradientCheck> = check_layer(&mut lstm_layer, &x, 0.0001).unwrap();
+            assert!(max_relative_error(&checks) < 0.0001);
+        }
+    }
+
+    #[test]
+    fn lstm_should_learn_to_sum_sequences() {
+        let mut rng: StdRng = StdRng::seed_from_u64(3);
+        let x: Array3<f64> = Array3::from_shape_fn((32, 4, 1), |_| rng.gen_range(-0.5..0.5));
+        let y: Array2<f64> = x.sum_axis(Axis(1));
+        let mut network: Sequential = Sequential::build(Box::new(Mse));
+        network.add_layer(Box::new(LstmLayer::build_with_seed(1, 8, 1)));
+        network.add_layer(Box::new(FCLayer::build_with_seed(8, 1, 2)));
+        network.compile(&[4, 1]).unwrap();
+        let before: f64 = network.compute_loss(&x, &y).unwrap();
+
+        network
+            .fit(
+                &x,
+                &y,
+                None,
+                None,
+                60,
+                0.1,
+                4,
+                &mut [],
+                &mut Report::build("./test_report_lstm_1"),
+                100,
+            )
+            .unwrap();
+        fs::remove_dir_all("./test_report_lstm_1").ok();
+        assert!(network.compute_loss(&x, &y).unwrap() < before / 4.0);
+        assert!(matches!(
+            network.predict(&Array4::<f64>::zeros((1, 4, 1, 1))),
+            Err(MlError::ShapeMismatch(_))
+        ));
+    }
+
+    #[test]
+    fn from_string_should_deserialize_layer() {
+        let mut lstm_layer: LstmLayer = LstmLayer::build_with_seed(2, 3, 1);
+        lstm_layer.set_return_sequences(true);
+        lstm_layer.compile(&[4, 2]).unwrap();
+        let result: Box<dyn Layer<f64>> =
+            layer::from_string("LstmLayer".to_string(), &lstm_layer.to_json().unwrap()).unwrap();
+        assert_eq!(result.get_name(), "LstmLayer");
+        assert_eq!(result.get_shape(), (8, 12));
+    }
+}
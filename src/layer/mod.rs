@@ -1,6 +1,30 @@
 pub mod activation_layer;
+pub mod bidirectional;
+pub mod conv1d_layer;
+pub mod conv3d_layer;
 pub mod conv_layer;
+pub mod conv_transpose_layer;
+pub mod convolution;
+pub mod cropping2d;
+pub mod embedding_layer;
 pub mod fc_layer;
 pub mod flatten_layer;
+pub mod gru_layer;
+#[allow(clippy::module_inception)]
 pub mod layer;
-pub mod max_pooling_layer;
+pub mod layer_norm_layer;
+pub mod lstm_layer;
+pub mod multi_head_attention;
+pub mod permute_layer;
+pub mod pooling1d_layer;
+pub mod pooling_layer;
+pub mod positional_encoding_layer;
+pub mod recurrent_layer;
+pub mod repeat_vector;
+pub mod reshape_layer;
+pub mod residual_block;
+pub mod rnn_layer;
+pub mod time_distributed;
+pub mod transformer_encoder_block;
+pub mod up_sampling_layer;
+pub mod zero_padding2d;
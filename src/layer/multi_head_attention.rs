@@ -0,0 +1,629 @@
+use crate::layer::layer::Layer;
+use crate::utils::error::{check_shapes, MlError};
+use crate::utils::float::MlFloat;
+use ndarray::{s, Array, Array2, ArrayView2, Axis};
+use ndarray_rand::rand::rngs::StdRng;
+use ndarray_rand::rand::SeedableRng;
+use ndarray_rand::rand_distr::Uniform;
+use ndarray_rand::RandomExt;
+use serde::{Deserialize, Serialize};
+
+/// Returns `softmax(query . key^T / sqrt(d_k)) . value` and the attention weights, one row per
+/// query. A `true` in the mask prevents the query of its row from attending to the key of its
+/// column, a query with every key masked gets a zero output.
+pub fn scaled_dot_product_attention<F: MlFloat>(
+    query: ArrayView2<F>,
+    key: ArrayView2<F>,
+    value: ArrayView2<F>,
+    mask: Option<&Array2<bool>>,
+) -> Result<(Array2<F>, Array2<F>), MlError> {
+    check_shapes(&[query.shape()[1]], &[key.shape()[1]])?;
+    check_shapes(&[key.shape()[0]], &[value.shape()[0]])?;
+    if let Some(mask) = mask {
+        check_shapes(mask.shape(), &[query.shape()[0], key.shape()[0]])?;
+    }
+    let scale: F = F::one() / F::cast(query.shape()[1] as f64).sqrt();
+    let mut weights: Array2<F> = query.dot(&key.t()) * scale;
+    for (i, mut row) in weights.rows_mut().into_iter().enumerate() {
+        let allowed = |j: usize| mask.map_or(true, |mask| !mask[[i, j]]);
+        let max: F = row
+            .iter()
+            .enumerate()
+            .filter(|(j, _)| allowed(*j))
+            .fold(F::neg_infinity(), |max, (_, &score)| max.max(score));
+        for (j, weight) in row.iter_mut().enumerate() {
+            *weight = if allowed(j) {
+                (*weight - max).exp()
+            } else {
+                F::zero()
+            };
+        }
+        let sum: F = row.sum();
+        if sum > F::zero() {
+            row.mapv_inplace(|weight| weight / sum);
+        }
+    }
+    Ok((weights.dot(&value), weights))
+}
+
+/// Errors of the query, key and value of `scaled_dot_product_attention` given the attention
+/// weights it returned and the error of its output.
+pub fn scaled_dot_product_attention_backward<F: MlFloat>(
+    query: ArrayView2<F>,
+    key: ArrayView2<F>,
+    value: ArrayView2<F>,
+    weights: &Array2<F>,
+    output_error: ArrayView2<F>,
+) -> (Array2<F>, Array2<F>, Array2<F>) {
+    let scale: F = F::one() / F::cast(query.shape()[1] as f64).sqrt();
+    let weights_error: Array2<F> = output_error.dot(&value.t());
+    let projection: Array2<F> = (&weights_error * weights)
+        .sum_axis(Axis(1))
+        .insert_axis(Axis(1));
+    let scores_error: Array2<F> = (weights_error - projection) * weights * scale;
+    (
+        scores_error.dot(&key),
+        scores_error.t().dot(&query),
+        weights.t().dot(&output_error),
+    )
+}
+
+/// Mask preventing every timestep from attending to the following ones.
+pub fn causal_mask(timesteps: usize) -> Array2<bool> {
+    Array2::from_shape_fn((timesteps, timesteps), |(i, j)| j > i)
+}
+
+#[derive(Debug, Clone)]
+struct Projection<F: MlFloat> {
+    weights: Array2<F>,
+    bias: Array2<F>,
+}
+
+impl<F: MlFloat> Projection<F> {
+    fn apply(&self, x: &Array2<F>) -> Array2<F> {
+        x.dot(&self.weights) + &self.bias
+    }
+
+    fn zeros(&self) -> Projection<F> {
+        Projection {
+            weights: Array2::zeros(self.weights.raw_dim()),
+            bias: Array2::zeros(self.bias.raw_dim()),
+        }
+    }
+}
+
+/// Values of the last forward pass, every array holds one row per timestep of every sample.
+#[derive(Debug, Clone)]
+struct AttentionCache<F: MlFloat> {
+    input: Array2<F>,
+    query: Array2<F>,
+    key: Array2<F>,
+    value: Array2<F>,
+    /// Attention weights of every head of every sample.
+    weights: Vec<Array2<F>>,
+    heads_output: Array2<F>,
+}
+
+/// Self-attention over samples of shape `(timesteps, model_size)`. The input is projected to
+/// queries, keys and values that are split in `heads` blocks of columns attending separately,
+/// the outputs of the heads are concatenated and projected back to `model_size`.
+#[derive(Debug, Clone)]
+pub struct MultiHeadAttention<F: MlFloat = f64> {
+    /// Projections of the queries, keys, values and output in this order.
+    projections: Vec<Projection<F>>,
+    gradients: Vec<Projection<F>>,
+    heads: usize,
+    timesteps: usize,
+    mask: Option<Array2<bool>>,
+    cache: Option<AttentionCache<F>>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MultiHeadAttentionModel {
+    /// Weights and bias of the queries, keys, values and output projections.
+    projections: Vec<(Vec<f64>, Vec<f64>)>,
+    model_size: usize,
+    heads: usize,
+    timesteps: usize,
+    mask: Option<Vec<bool>>,
+}
+
+fn to_rows<F: MlFloat>(x: &Array2<F>, rows: usize, columns: usize) -> Result<Array2<F>, MlError> {
+    Ok(x.as_standard_layout()
+        .into_owned()
+        .into_shape((rows, columns))?)
+}
+
+impl<F: MlFloat> MultiHeadAttention<F> {
+    /// The number of timesteps is inferred when the network is compiled.
+    pub fn build(model_size: usize, heads: usize) -> Result<MultiHeadAttention<F>, MlError> {
+        let projections: Vec<Projection<F>> = (0..4)
+            .map(|_| Projection {
+                weights: Array::random((model_size, model_size), Uniform::new(F::zero(), F::one()))
+                    - F::cast(0.5),
+                bias: Array2::zeros((1, model_size)),
+            })
+            .collect();
+        MultiHeadAttention::build_with_projections(projections, heads)
+    }
+
+    pub fn build_with_seed(
+        model_size: usize,
+        heads: usize,
+        seed: u64,
+    ) -> Result<MultiHeadAttention<F>, MlError> {
+        let mut rng: StdRng = StdRng::seed_from_u64(seed);
+        let projections: Vec<Projection<F>> = (0..4)
+            .map(|_| Projection {
+                weights: Array::random_using(
+                    (model_size, model_size),
+                    Uniform::new(F::zero(), F::one()),
+                    &mut rng,
+                ) - F::cast(0.5),
+                bias: Array2::zeros((1, model_size)),
+            })
+            .collect();
+        MultiHeadAttention::build_with_projections(projections, heads)
+    }
+
+    fn build_with_projections(
+        projections: Vec<Projection<F>>,
+        heads: usize,
+    ) -> Result<MultiHeadAttention<F>, MlError> {
+        let model_size: usize = projections[0].weights.shape()[0];
+        if heads == 0 || model_size % heads != 0 {
+            return Err(MlError::InvalidInput(format!(
+                "model size {} is not divisible in {} heads",
+                model_size, heads
+            )));
+        }
+        Ok(MultiHeadAttention {
+            gradients: projections.iter().map(|p| p.zeros()).collect(),
+            projections,
+            heads,
+            timesteps: 0,
+            mask: None,
+            cache: None,
+        })
+    }
+
+    pub fn from_json(json_str: &str) -> Result<MultiHeadAttention<F>, MlError> {
+        let model: MultiHeadAttentionModel = serde_json::from_str(json_str)?;
+        if model.projections.len() != 4 {
+            return Err(MlError::Serialization(format!(
+                "MultiHeadAttention expects 4 projections, actually: {}",
+                model.projections.len()
+            )));
+        }
+        let size: usize = model.model_size;
+        let projections: Vec<Projection<F>> = model
+            .projections
+            .into_iter()
+            .map(|(weights, bias)| {
+                Ok(Projection {
+                    weights: Array2::from_shape_vec(
+                        (size, size),
+                        weights.into_iter().map(F::cast).collect(),
+                    )?,
+                    bias: Array2::from_shape_vec(
+                        (1, size),
+                        bias.into_iter().map(F::cast).collect(),
+                    )?,
+                })
+            })
+            .collect::<Result<Vec<Projection<F>>, MlError>>()?;
+        let mut layer: MultiHeadAttention<F> =
+            MultiHeadAttention::build_with_projections(projections, model.heads)?;
+        layer.timesteps = model.timesteps;
+        layer.mask = model
+            .mask
+            .map(|mask| Array2::from_shape_vec((model.timesteps, model.timesteps), mask))
+            .transpose()?;
+        Ok(layer)
+    }
+
+    /// Square mask over the timesteps, see `scaled_dot_product_attention`.
+    pub fn set_mask(&mut self, mask: Option<Array2<bool>>) -> Result<(), MlError> {
+        if let Some(mask) = &mask {
+            if mask.shape()[0] != mask.shape()[1]
+                || (self.timesteps > 0 && mask.shape()[0] != self.timesteps)
+            {
+                return Err(MlError::ShapeMismatch(format!(
+                    "expected a mask over {} timesteps, actually: {:?}",
+                    self.timesteps,
+                    mask.shape()
+                )));
+            }
+        }
+        self.mask = mask;
+        Ok(())
+    }
+
+    fn model_size(&self) -> usize {
+        self.projections[0].weights.shape()[0]
+    }
+
+    fn head_columns(&self, head: usize) -> std::ops::Range<usize> {
+        let head_size: usize = self.model_size() / self.heads;
+        head * head_size..(head + 1) * head_size
+    }
+
+    fn attend(&self, x: &Array2<F>) -> Result<(Array2<F>, AttentionCache<F>), MlError> {
+        let (timesteps, size): (usize, usize) = (self.timesteps, self.model_size());
+        if timesteps == 0 {
+            return Err(MlError::ShapeMismatch(
+                "MultiHeadAttention must be compiled before use".to_string(),
+            ));
+        }
+        check_shapes(&x.shape()[1..], &[timesteps * size])?;
+        let batch: usize = x.shape()[0];
+        let input: Array2<F> = to_rows(x, batch * timesteps, size)?;
+        let query: Array2<F> = self.projections[0].apply(&input);
+        let key: Array2<F> = self.projections[1].apply(&input);
+        let value: Array2<F> = self.projections[2].apply(&input);
+        let mut heads_output: Array2<F> = Array2::zeros((batch * timesteps, size));
+        let mut weights: Vec<Array2<F>> = Vec::with_capacity(batch * self.heads);
+        for sample in 0..batch {
+            let rows = sample * timesteps..(sample + 1) * timesteps;
+            for head in 0..self.heads {
+                let columns = self.head_columns(head);
+                let (output, head_weights): (Array2<F>, Array2<F>) = scaled_dot_product_attention(
+                    query.slice(s![rows.clone(), columns.clone()]),
+                    key.slice(s![rows.clone(), columns.clone()]),
+                    value.slice(s![rows.clone(), columns.clone()]),
+                    self.mask.as_ref(),
+                )?;
+                heads_output
+                    .slice_mut(s![rows.clone(), columns])
+                    .assign(&output);
+                weights.push(head_weights);
+            }
+        }
+        let output: Array2<F> = to_rows(
+            &self.projections[3].apply(&heads_output),
+            batch,
+            timesteps * size,
+        )?;
+        Ok((
+            output,
+            AttentionCache {
+                input,
+                query,
+                key,
+                value,
+                weights,
+                heads_output,
+            },
+        ))
+    }
+
+    /// Attention weights of the last forward pass, one `(timesteps, timesteps)` array per head of
+    /// every sample.
+    pub fn get_attention_weights(&self) -> Vec<Array2<F>> {
+        self.cache
+            .as_ref()
+            .map_or(vec![], |cache| cache.weights.clone())
+    }
+}
+
+impl<F: MlFloat> Layer<F> for MultiHeadAttention<F> {
+    fn forward_propagation(&mut self, x: &Array2<F>) -> Result<Array2<F>, MlError> {
+        let (output, cache): (Array2<F>, AttentionCache<F>) = self.attend(x)?;
+        self.cache = Some(cache);
+        Ok(output)
+    }
+
+    fn predict(&self, x: &Array2<F>) -> Result<Array2<F>, MlError> {
+        Ok(self.attend(x)?.0)
+    }
+
+    fn compute_gradients(&mut self, y: &Array2<F>) -> Result<Array2<F>, MlError> {
+        let (timesteps, size): (usize, usize) = (self.timesteps, self.model_size());
+        let cache: &AttentionCache<F> = self.cache.as_ref().ok_or_else(|| {
+            MlError::InvalidInput(
+                "MultiHeadAttention computes gradients after a forward propagation".to_string(),
+            )
+        })?;
+        let batch: usize = cache.input.shape()[0] / timesteps;
+        check_shapes(y.shape(), &[batch, timesteps * size])?;
+        let output_error: Array2<F> = to_rows(y, batch * timesteps, size)?;
+        let heads_error: Array2<F> = output_error.dot(&self.projections[3].weights.t());
+        let mut query_error: Array2<F> = Array2::zeros((batch * timesteps, size));
+        let mut key_error: Array2<F> = Array2::zeros((batch * timesteps, size));
+        let mut value_error: Array2<F> = Array2::zeros((batch * timesteps, size));
+        for sample in 0..batch {
+            let rows = sample * timesteps..(sample + 1) * timesteps;
+            for head in 0..self.heads {
+                let columns = self.head_columns(head);
+                let (query_head_error, key_head_error, value_head_error) =
+                    scaled_dot_product_attention_backward(
+                        cache.query.slice(s![rows.clone(), columns.clone()]),
+                        cache.key.slice(s![rows.clone(), columns.clone()]),
+                        cache.value.slice(s![rows.clone(), columns.clone()]),
+                        &cache.weights[sample * self.heads + head],
+                        heads_error.slice(s![rows.clone(), columns.clone()]),
+                    );
+                query_error
+                    .slice_mut(s![rows.clone(), columns.clone()])
+                    .assign(&query_head_error);
+                key_error
+                    .slice_mut(s![rows.clone(), columns.clone()])
+                    .assign(&key_head_error);
+                value_error
+                    .slice_mut(s![rows.clone(), columns])
+                    .assign(&value_head_error);
+            }
+        }
+
+        let inputs: [&Array2<F>; 4] = [
+            &cache.input,
+            &cache.input,
+            &cache.input,
+            &cache.heads_output,
+        ];
+        let errors: [&Array2<F>; 4] = [&query_error, &key_error, &value_error, &output_error];
+        self.gradients = inputs
+            .iter()
+            .zip(errors.iter())
+            .map(|(input, error)| Projection {
+                weights: input.t().dot(*error),
+                bias: error.sum_axis(Axis(0)).insert_axis(Axis(0)),
+            })
+            .collect();
+        let input_error: Array2<F> = query_error.dot(&self.projections[0].weights.t())
+            + key_error.dot(&self.projections[1].weights.t())
+            + value_error.dot(&self.projections[2].weights.t());
+        to_rows(&input_error, batch, timesteps * size)
+    }
+
+    fn apply_gradients(&mut self, learning_rate: F) {
+        for (projection, gradient) in self.projections.iter_mut().zip(self.gradients.iter()) {
+            projection.weights = &projection.weights - &(&gradient.weights * learning_rate);
+            projection.bias = &projection.bias - &(&gradient.bias * learning_rate);
+        }
+    }
+
+    /// Weights and bias gradients of the queries, keys, values and output projections.
+    fn get_gradients(&self) -> Vec<Array2<F>> {
+        self.gradients
+            .iter()
+            .flat_map(|gradient| [gradient.weights.clone(), gradient.bias.clone()])
+            .collect()
+    }
+
+    fn set_gradients(&mut self, gradients: Vec<Array2<F>>) -> Result<(), MlError> {
+        if gradients.len() != 8 {
+            return Err(MlError::InvalidInput(format!(
+                "MultiHeadAttention expects 8 gradients, actually: {}",
+                gradients.len()
+            )));
+        }
+        for (projection, pair) in self.projections.iter().zip(gradients.chunks(2)) {
+            check_shapes(pair[0].shape(), projection.weights.shape())?;
+            check_shapes(pair[1].shape(), projection.bias.shape())?;
+        }
+        self.gradients = gradients
+            .chunks(2)
+            .map(|pair| Projection {
+                weights: pair[0].clone(),
+                bias: pair[1].clone(),
+            })
+            .collect();
+        Ok(())
+    }
+
+    /// Weights and bias of the queries, keys, values and output projections.
+    fn get_parameters(&self) -> Vec<Array2<F>> {
+        self.projections
+            .iter()
+            .flat_map(|projection| [projection.weights.clone(), projection.bias.clone()])
+            .collect()
+    }
+
+    fn set_parameters(&mut self, parameters: Vec<Array2<F>>) -> Result<(), MlError> {
+        if parameters.len() != 8 {
+            return Err(MlError::InvalidInput(format!(
+                "MultiHeadAttention expects 8 parameters, actually: {}",
+                parameters.len()
+            )));
+        }
+        for (projection, pair) in self.projections.iter().zip(parameters.chunks(2)) {
+            check_shapes(pair[0].shape(), projection.weights.shape())?;
+            check_shapes(pair[1].shape(), projection.bias.shape())?;
+        }
+        self.projections = parameters
+            .chunks(2)
+            .map(|pair| Projection {
+                weights: pair[0].clone(),
+                bias: pair[1].clone(),
+            })
+            .collect();
+        Ok(())
+    }
+
+    fn compile(&mut self, input_shape: &[usize]) -> Result<Vec<usize>, MlError> {
+        match input_shape {
+            &[timesteps, size] if timesteps > 0 && size == self.model_size() => {
+                if let Some(mask) = &self.mask {
+                    check_shapes(mask.shape(), &[timesteps, timesteps])?;
+                }
+                self.timesteps = timesteps;
+                Ok(input_shape.to_vec())
+            }
+            _ => Err(MlError::ShapeMismatch(format!(
+                "expected input shape [timesteps, {}], actually: {:?}",
+                self.model_size(),
+                input_shape
+            ))),
+        }
+    }
+
+    fn count_parameters(&self) -> (usize, usize) {
+        (
+            self.projections
+                .iter()
+                .map(|projection| projection.weights.len() + projection.bias.len())
+                .sum(),
+            0,
+        )
+    }
+
+    fn clone_layer(&self) -> Box<dyn Layer<F>> {
+        Box::new(self.clone())
+    }
+
+    fn get_shape(&self) -> (usize, usize) {
+        let size: usize = self.timesteps * self.model_size();
+        (size, size)
+    }
+
+    fn get_name(&self) -> String {
+        "MultiHeadAttention".to_string()
+    }
+
+    fn to_json(&self) -> Result<String, MlError> {
+        let model: MultiHeadAttentionModel = MultiHeadAttentionModel {
+            projections: self
+                .projections
+                .iter()
+                .map(|projection| {
+                    (
+                        projection
+                            .weights
+                            .iter()
+                            .map(|&w| w.to_f64_lossy())
+                            .collect(),
+                        projection.bias.iter().map(|&b| b.to_f64_lossy()).collect(),
+                    )
+                })
+                .collect(),
+            model_size: self.model_size(),
+            heads: self.heads,
+            timesteps: self.timesteps,
+            mask: self
+                .mask
+                .as_ref()
+                .map(|mask| mask.iter().copied().collect()),
+        };
+        Ok(serde_json::to_string(&model)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layer::layer;
+    use crate::utils::gradient_check::{check_layer, max_relative_error, GradientCheck};
+    use ndarray::arr2;
+
+    fn generate_test_input() -> Array2<f64> {
+        Array2::from_shape_fn((2, 12), |(b, i)| ((b * 12 + i) % 7) as f64 / 3.0 - 1.0)
+    }
+
+    #[test]
+    fn scaled_dot_product_attention_should_weight_values() {
+        let query: Array2<f64> = arr2(&[[1.0, 0.0], [0.0, 0.0]]);
+        let key: Array2<f64> = arr2(&[[2.0_f64.ln() * 2.0_f64.sqrt(), 0.0], [0.0, 1.0]]);
+        let value: Array2<f64> = arr2(&[[3.0, 0.0], [0.0, 3.0]]);
+        let (output, weights): (Array2<f64>, Array2<f64>) =
+            scaled_dot_product_attention(query.view(), key.view(), value.view(), None).unwrap();
+        // exp(ln 2) = 2 against exp(0) = 1 for the first query, uniform for the second
+        let expected_weights: Array2<f64> = arr2(&[[2.0 / 3.0, 1.0 / 3.0], [0.5, 0.5]]);
+        assert!(weights
+            .iter()
+            .zip(expected_weights.iter())
+            .all(|(w, e)| (w - e).powf(2.0) < 0.00001));
+        assert!((output[[0, 0]] - 2.0).powf(2.0) < 0.00001);
+        assert!((output[[1, 1]] - 1.5).powf(2.0) < 0.00001);
+    }
+
+    #[test]
+    fn scaled_dot_product_attention_should_ignore_masked_keys() {
+        let x: Array2<f64> = arr2(&[[1.0, 0.5], [-1.0, 2.0], [0.3, 0.3]]);
+        let (output, weights): (Array2<f64>, Array2<f64>) =
+            scaled_dot_product_attention(x.view(), x.view(), x.view(), Some(&causal_mask(3)))
+                .unwrap();
+        assert_eq!(weights.row(0).to_vec(), vec![1.0, 0.0, 0.0]);
+        assert_eq!(weights[[1, 2]], 0.0);
+        assert!((weights.row(2).sum() - 1.0).powf(2.0) < 0.00001);
+        assert_eq!(output.row(0), x.row(0));
+
+        let mask: Array2<bool> = Array2::from_elem((3, 3), true);
+        let (output, _): (Array2<f64>, Array2<f64>) =
+            scaled_dot_product_attention(x.view(), x.view(), x.view(), Some(&mask)).unwrap();
+        assert_eq!(output, Array2::<f64>::zeros((3, 2)));
+    }
+
+    #[test]
+    fn build_should_fail_when_heads_do_not_divide_model_size() {
+        assert!(matches!(
+            MultiHeadAttention::<f64>::build(6, 4),
+            Err(MlError::InvalidInput(_))
+        ));
+        let mut attention: MultiHeadAttention = MultiHeadAttention::build(6, 3).unwrap();
+        assert_eq!(attention.compile(&[4, 6]).unwrap(), vec![4, 6]);
+        assert_eq!(attention.count_parameters(), (4 * 6 * 6 + 4 * 6, 0));
+        assert!(matches!(
+            attention.set_mask(Some(causal_mask(3))),
+            Err(MlError::ShapeMismatch(_))
+        ));
+    }
+
+    #[test]
+    fn forward_propagation_should_not_look_ahead_with_causal_mask() {
+        let mut attention: MultiHeadAttention =
+            MultiHeadAttention::build_with_seed(4, 2, 1).unwrap();
+        attention.compile(&[3, 4]).unwrap();
+        attention.set_mask(Some(causal_mask(3))).unwrap();
+        let x: Array2<f64> = generate_test_input();
+        let output: Array2<f64> = attention.forward_propagation(&x).unwrap();
+        assert_eq!(attention.get_attention_weights().len(), 4);
+
+        let mut changed: Array2<f64> = x.clone();
+        changed.slice_mut(s![.., 8..12]).fill(5.0);
+        let changed_output: Array2<f64> = attention.predict(&changed).unwrap();
+        assert_eq!(
+            output.slice(s![.., 0..8]),
+            changed_output.slice(s![.., 0..8])
+        );
+        assert_ne!(
+            output.slice(s![.., 8..12]),
+            changed_output.slice(s![.., 8..12])
+        );
+    }
+
+    #[test]
+    fn compute_gradients_should_pass_gradient_check() {
+        for mask in [None, Some(causal_mask(3))] {
+            let mut attention: MultiHeadAttention =
+                MultiHeadAttention::build_with_seed(4, 2, 1).unwrap();
+            attention.compile(&[3, 4]).unwrap();
+            attention.set_mask(mask).unwrap();
+            let checks: Vec<GradientCheck> =
+                check_layer(&mut attention, &generate_test_input(), 0.0001).unwrap();
+            assert_eq!(checks.len(), 9);
+            assert!(max_relative_error(&checks) < 0.0001);
+        }
+    }
+
+    #[test]
+    fn from_string_should_deserialize_layer() {
+        let mut attention: MultiHeadAttention =
+            MultiHeadAttention::build_with_seed(4, 2, 1).unwrap();
+        attention.compile(&[3, 4]).unwrap();
+        attention.set_mask(Some(causal_mask(3))).unwrap();
+        let result: Box<dyn Layer<f64>> = layer::from_string(
+            "MultiHeadAttention".to_string(),
+            &attention.to_json().unwrap(),
+        )
+        .unwrap();
+        assert_eq!(result.get_shape(), (12, 12));
+        let x: Array2<f64> = generate_test_input();
+        let expected: Array2<f64> = attention.predict(&x).unwrap();
+        let actual: Array2<f64> = result.predict(&x).unwrap();
+        assert!(expected
+            .iter()
+            .zip(actual.iter())
+            .all(|(e, a)| (e - a).powf(2.0) < 0.00001));
+    }
+}
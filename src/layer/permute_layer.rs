@@ -0,0 +1,198 @@
+use crate::layer::layer::Layer;
+use crate::utils::error::{check_shapes, MlError};
+use crate::utils::float::MlFloat;
+use ndarray::{Array2, IxDyn};
+use serde::{Deserialize, Serialize};
+
+/// Reorders the axes of the samples, axis `i` of the output is axis `axes[i]` of the input. The
+/// axes of the samples are numbered from 0, the batch axis is never moved.
+#[derive(Debug, Clone)]
+pub struct PermuteLayer {
+    axes: Vec<usize>,
+    input_shape: Vec<usize>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PermuteLayerModel {
+    axes: Vec<usize>,
+    input_shape: Vec<usize>,
+}
+
+impl PermuteLayer {
+    pub fn build(axes: &[usize]) -> Result<PermuteLayer, MlError> {
+        let mut sorted: Vec<usize> = axes.to_vec();
+        sorted.sort_unstable();
+        if sorted.iter().enumerate().any(|(i, &axis)| i != axis) {
+            return Err(MlError::InvalidInput(format!(
+                "axes must be a permutation of 0..{}, actually: {:?}",
+                axes.len(),
+                axes
+            )));
+        }
+        Ok(PermuteLayer {
+            axes: axes.to_vec(),
+            input_shape: vec![0; axes.len()],
+        })
+    }
+
+    pub fn from_json(json_str: &str) -> Result<PermuteLayer, MlError> {
+        let model: PermuteLayerModel = serde_json::from_str(json_str)?;
+        let mut layer: PermuteLayer = PermuteLayer::build(&model.axes)?;
+        Layer::<f64>::compile(&mut layer, &model.input_shape)?;
+        Ok(layer)
+    }
+
+    fn size(&self) -> usize {
+        self.input_shape.iter().product()
+    }
+
+    fn output_shape(&self) -> Vec<usize> {
+        self.axes
+            .iter()
+            .map(|&axis| self.input_shape[axis])
+            .collect()
+    }
+
+    /// Moves the axes of the samples of `shape` flattened in the rows of `x` in the order of
+    /// `axes`.
+    fn permute<F: MlFloat>(
+        x: &Array2<F>,
+        shape: &[usize],
+        axes: &[usize],
+    ) -> Result<Array2<F>, MlError> {
+        let batch: usize = x.shape()[0];
+        let dimensions: Vec<usize> = [&[batch], shape].concat();
+        let order: Vec<usize> = [vec![0], axes.iter().map(|&axis| axis + 1).collect()].concat();
+        let x = x.as_standard_layout();
+        let samples = x.view().into_shape(IxDyn(&dimensions))?;
+        Ok(samples
+            .permuted_axes(IxDyn(&order))
+            .as_standard_layout()
+            .into_owned()
+            .into_shape((batch, x.shape()[1]))?)
+    }
+}
+
+impl<F: MlFloat> Layer<F> for PermuteLayer {
+    fn forward_propagation(&mut self, x: &Array2<F>) -> Result<Array2<F>, MlError> {
+        self.predict(x)
+    }
+
+    fn predict(&self, x: &Array2<F>) -> Result<Array2<F>, MlError> {
+        check_shapes(&x.shape()[1..], &[self.size()])?;
+        PermuteLayer::permute(x, &self.input_shape, &self.axes)
+    }
+
+    /// Moves the axes of the errors back in the order of the input.
+    fn compute_gradients(&mut self, y: &Array2<F>) -> Result<Array2<F>, MlError> {
+        check_shapes(&y.shape()[1..], &[self.size()])?;
+        let mut inverse: Vec<usize> = vec![0; self.axes.len()];
+        for (i, &axis) in self.axes.iter().enumerate() {
+            inverse[axis] = i;
+        }
+        PermuteLayer::permute(y, &self.output_shape(), &inverse)
+    }
+
+    fn compile(&mut self, input_shape: &[usize]) -> Result<Vec<usize>, MlError> {
+        if input_shape.len() != self.axes.len() {
+            return Err(MlError::ShapeMismatch(format!(
+                "expected input shape of {} axes, actually: {:?}",
+                self.axes.len(),
+                input_shape
+            )));
+        }
+        self.input_shape = input_shape.to_vec();
+        Ok(self.output_shape())
+    }
+
+    fn clone_layer(&self) -> Box<dyn Layer<F>> {
+        Box::new(self.clone())
+    }
+
+    fn get_shape(&self) -> (usize, usize) {
+        (self.size(), self.size())
+    }
+
+    fn get_name(&self) -> String {
+        "PermuteLayer".to_string()
+    }
+
+    fn to_json(&self) -> Result<String, MlError> {
+        let model: PermuteLayerModel = PermuteLayerModel {
+            axes: self.axes.clone(),
+            input_shape: self.input_shape.clone(),
+        };
+        Ok(serde_json::to_string(&model)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layer::layer;
+    use crate::utils::gradient_check::{check_layer, max_relative_error, GradientCheck};
+    use ndarray::arr2;
+
+    #[test]
+    fn build_should_reject_invalid_axes() {
+        assert!(matches!(
+            PermuteLayer::build(&[0, 0]),
+            Err(MlError::InvalidInput(_))
+        ));
+        assert!(matches!(
+            PermuteLayer::build(&[1, 2]),
+            Err(MlError::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn forward_propagation_should_transpose_samples() {
+        let mut permute_layer: PermuteLayer = PermuteLayer::build(&[1, 0]).unwrap();
+        assert_eq!(
+            Layer::<f64>::compile(&mut permute_layer, &[2, 3]).unwrap(),
+            vec![3, 2]
+        );
+        let output: Array2<f64> = permute_layer
+            .forward_propagation(&arr2(&[
+                [1.0, 2.0, 3.0, 4.0, 5.0, 6.0],
+                [0.0, 1.0, 0.0, 2.0, 0.0, 3.0],
+            ]))
+            .unwrap();
+        assert_eq!(
+            output,
+            arr2(&[
+                [1.0, 4.0, 2.0, 5.0, 3.0, 6.0],
+                [0.0, 2.0, 1.0, 0.0, 0.0, 3.0]
+            ])
+        );
+        assert!(matches!(
+            Layer::<f64>::compile(&mut permute_layer, &[2, 3, 1]),
+            Err(MlError::ShapeMismatch(_))
+        ));
+    }
+
+    #[test]
+    fn compute_gradients_should_pass_gradient_check() {
+        let mut permute_layer: PermuteLayer = PermuteLayer::build(&[2, 0, 1]).unwrap();
+        assert_eq!(
+            Layer::<f64>::compile(&mut permute_layer, &[2, 3, 4]).unwrap(),
+            vec![4, 2, 3]
+        );
+        let x: Array2<f64> = Array2::from_shape_fn((2, 24), |(b, i)| (b * 24 + i) as f64 / 10.0);
+        let checks: Vec<GradientCheck> = check_layer(&mut permute_layer, &x, 0.0001).unwrap();
+        assert!(max_relative_error(&checks) < 0.0001);
+        let output: Array2<f64> = permute_layer.forward_propagation(&x).unwrap();
+        assert_eq!(permute_layer.compute_gradients(&output).unwrap(), x);
+    }
+
+    #[test]
+    fn from_string_should_deserialize_layer() {
+        let mut permute_layer: PermuteLayer = PermuteLayer::build(&[1, 0]).unwrap();
+        Layer::<f64>::compile(&mut permute_layer, &[2, 5]).unwrap();
+        let json: String = Layer::<f64>::to_json(&permute_layer).unwrap();
+        assert_eq!(json, "{\"axes\":[1,0],\"input_shape\":[2,5]}");
+        let result: Box<dyn Layer<f64>> =
+            layer::from_string("PermuteLayer".to_string(), &json).unwrap();
+        assert_eq!(result.get_shape(), (10, 10));
+    }
+}
@@ -0,0 +1,136 @@
+use crate::layer::pooling_layer::{Avg, Max, PoolingLayer};
+
+/// Max pooling over sequences of shape `(length, c)`.
+pub type MaxPooling1d = PoolingLayer<Max, 1>;
+
+/// Average pooling over sequences of shape `(length, c)`.
+pub type AvgPooling1d = PoolingLayer<Avg, 1>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layer::layer::{self, Layer};
+    use crate::utils::error::MlError;
+    use crate::utils::gradient_check::{check_layer, max_relative_error, GradientCheck};
+    use ndarray::{arr2, Array2};
+
+    #[test]
+    fn max_pooling_should_pool_channels_separately() {
+        let mut max_pooling: MaxPooling1d = MaxPooling1d::build(2);
+        assert_eq!(
+            Layer::<f64>::compile(&mut max_pooling, &[5, 2]).unwrap(),
+            vec![2, 2]
+        );
+        let output: Array2<f64> = max_pooling
+            .forward_propagation(&arr2(&[[1.0, 8.0, 4.0, 2.0, 3.0, 1.0, 2.0, 5.0, 9.0, 9.0]]))
+            .unwrap();
+        assert_eq!(output, arr2(&[[4.0, 8.0, 3.0, 5.0]]));
+        let input_error: Array2<f64> = max_pooling
+            .compute_gradients(&arr2(&[[1.0, 2.0, 3.0, 4.0]]))
+            .unwrap();
+        assert_eq!(
+            input_error,
+            arr2(&[[0.0, 2.0, 1.0, 0.0, 3.0, 0.0, 0.0, 4.0, 0.0, 0.0]])
+        );
+    }
+
+    #[test]
+    fn max_pooling_should_skip_padding() {
+        let mut max_pooling: MaxPooling1d = MaxPooling1d::build(2);
+        max_pooling.set_stride(1).unwrap();
+        max_pooling.set_padding(1);
+        max_pooling.set_dilation(2).unwrap();
+        assert_eq!(
+            Layer::<f64>::compile(&mut max_pooling, &[4, 1]).unwrap(),
+            vec![4, 1]
+        );
+        let output: Array2<f64> = max_pooling
+            .forward_propagation(&arr2(&[[-1.0, -4.0, -2.0, -3.0]]))
+            .unwrap();
+        assert_eq!(output, arr2(&[[-4.0, -1.0, -3.0, -2.0]]));
+        max_pooling.set_padding(3);
+        assert!(matches!(
+            Layer::<f64>::compile(&mut max_pooling, &[4, 1]),
+            Err(MlError::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn avg_pooling_should_average_channels_separately() {
+        let mut avg_pooling: AvgPooling1d = AvgPooling1d::build(2);
+        assert_eq!(
+            Layer::<f64>::compile(&mut avg_pooling, &[4, 2]).unwrap(),
+            vec![2, 2]
+        );
+        let output: Array2<f64> = avg_pooling
+            .forward_propagation(&arr2(&[[1.0, 8.0, 4.0, 2.0, 3.0, 1.0, 2.0, 5.0]]))
+            .unwrap();
+        assert_eq!(output, arr2(&[[2.5, 5.0, 2.5, 3.0]]));
+    }
+
+    #[test]
+    fn avg_pooling_should_leave_padding_out() {
+        let mut avg_pooling: AvgPooling1d = AvgPooling1d::build(3);
+        avg_pooling.set_stride(2).unwrap();
+        avg_pooling.set_padding(1);
+        assert_eq!(
+            Layer::<f64>::compile(&mut avg_pooling, &[5, 1]).unwrap(),
+            vec![3, 1]
+        );
+        let output: Array2<f64> = avg_pooling
+            .forward_propagation(&arr2(&[[2.0, 4.0, 6.0, 8.0, 10.0]]))
+            .unwrap();
+        assert_eq!(output, arr2(&[[3.0, 6.0, 9.0]]));
+        let input_error: Array2<f64> = avg_pooling
+            .compute_gradients(&arr2(&[[2.0, 3.0, 4.0]]))
+            .unwrap();
+        assert_eq!(input_error, arr2(&[[1.0, 2.0, 1.0, 3.0, 2.0]]));
+    }
+
+    #[test]
+    fn compute_gradients_should_pass_gradient_check() {
+        let mut max_pooling: MaxPooling1d = MaxPooling1d::build(3);
+        max_pooling.set_stride(2).unwrap();
+        max_pooling.set_padding(1);
+        Layer::<f64>::compile(&mut max_pooling, &[6, 2]).unwrap();
+        let x: Array2<f64> =
+            Array2::from_shape_fn((2, 12), |(b, i)| ((b * 12 + i) * 7 % 11) as f64);
+        let checks: Vec<GradientCheck> = check_layer(&mut max_pooling, &x, 0.0001).unwrap();
+        assert_eq!(checks.len(), 1);
+        assert!(max_relative_error(&checks) < 0.0001);
+
+        let mut avg_pooling: AvgPooling1d = AvgPooling1d::build(2);
+        avg_pooling.set_stride(1).unwrap();
+        avg_pooling.set_padding(1);
+        avg_pooling.set_dilation(2).unwrap();
+        Layer::<f64>::compile(&mut avg_pooling, &[5, 2]).unwrap();
+        let x: Array2<f64> = Array2::from_shape_fn((2, 10), |(b, i)| (b * 10 + i) as f64 / 5.0);
+        let checks: Vec<GradientCheck> = check_layer(&mut avg_pooling, &x, 0.0001).unwrap();
+        assert_eq!(checks.len(), 1);
+        assert!(max_relative_error(&checks) < 0.0001);
+    }
+
+    #[test]
+    fn from_string_should_deserialize_layer() {
+        let mut max_pooling: MaxPooling1d = MaxPooling1d::build(3);
+        max_pooling.set_padding(1);
+        Layer::<f64>::compile(&mut max_pooling, &[7, 2]).unwrap();
+        let json: String = Layer::<f64>::to_json(&max_pooling).unwrap();
+        assert_eq!(
+            json,
+            "{\"kernel_size\":3,\"stride\":3,\"padding\":1,\"dilation\":1,\"input_shape\":[7,2]}"
+        );
+        let result: Box<dyn Layer<f64>> =
+            layer::from_string("MaxPooling1d".to_string(), &json).unwrap();
+        assert_eq!(result.get_shape(), (14, 6));
+        assert_eq!(result.get_name(), "MaxPooling1d");
+
+        let mut avg_pooling: AvgPooling1d = AvgPooling1d::build(2);
+        Layer::<f64>::compile(&mut avg_pooling, &[6, 3]).unwrap();
+        let json: String = Layer::<f64>::to_json(&avg_pooling).unwrap();
+        let result: Box<dyn Layer<f64>> =
+            layer::from_string("AvgPooling1d".to_string(), &json).unwrap();
+        assert_eq!(result.get_shape(), (18, 9));
+        assert_eq!(result.get_name(), "AvgPooling1d");
+    }
+}
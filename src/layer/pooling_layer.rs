@@ -0,0 +1,424 @@
+use crate::layer::convolution::Convolution;
+use crate::layer::layer::Layer;
+use crate::utils::error::MlError;
+use crate::utils::float::MlFloat;
+use ndarray::Array2;
+use serde::{Deserialize, Serialize};
+use std::fmt::Debug;
+use std::marker::PhantomData;
+
+/// Reduces the values of a pooling window to one value.
+pub trait Reduction: Debug + Clone + Send + Sync + 'static {
+    /// Prefix of the layer names, such as `Max` in `MaxPooling1d`.
+    const NAME: &'static str;
+
+    /// Returns the output of a window from its values and their input column, with the columns
+    /// its error is shared equally between. Padded positions are not part of the window.
+    fn reduce<F: MlFloat>(window: &[(usize, F)]) -> Result<(F, Vec<usize>), MlError>;
+}
+
+/// Keeps the maximum of the window, its error goes to that value only.
+#[derive(Debug, Clone)]
+pub struct Max;
+
+impl Reduction for Max {
+    const NAME: &'static str = "Max";
+
+    fn reduce<F: MlFloat>(window: &[(usize, F)]) -> Result<(F, Vec<usize>), MlError> {
+        let mut best: Option<(usize, F)> = None;
+        for &(index, value) in window {
+            if value.is_nan() {
+                return Err(MlError::Numeric("can not pool a NaN value".to_string()));
+            }
+            if best.map_or(true, |(_, max)| value > max) {
+                best = Some((index, value));
+            }
+        }
+        Ok(best.map_or((F::zero(), vec![]), |(index, max)| (max, vec![index])))
+    }
+}
+
+/// Averages the window, its error is shared equally between its values.
+#[derive(Debug, Clone)]
+pub struct Avg;
+
+impl Reduction for Avg {
+    const NAME: &'static str = "Avg";
+
+    fn reduce<F: MlFloat>(window: &[(usize, F)]) -> Result<(F, Vec<usize>), MlError> {
+        let count: F = F::cast(window.len().max(1) as f64);
+        let sum: F = window
+            .iter()
+            .fold(F::zero(), |sum, &(_, value)| sum + value);
+        Ok((
+            sum / count,
+            window.iter().map(|&(index, _)| index).collect(),
+        ))
+    }
+}
+
+/// Pooling of each channel over samples of `N` spatial axes followed by the channels flattened
+/// in rows. The windows span `kernel_size` values on every spatial axis and do not overlap
+/// unless a smaller stride is set, padded positions are left out of the windows.
+#[derive(Debug, Clone)]
+pub struct PoolingLayer<R: Reduction, const N: usize> {
+    reduction: PhantomData<R>,
+    /// Input columns sharing the error of every output value of the last forward pass, one
+    /// row of outputs after the other.
+    routes: Vec<Vec<usize>>,
+    convolution: Convolution,
+}
+
+/// Max pooling over images of shape `(h, w, c)`.
+pub type MaxPoolingLayer = PoolingLayer<Max, 2>;
+
+/// Average pooling over images of shape `(h, w, c)`.
+pub type AvgPoolingLayer = PoolingLayer<Avg, 2>;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PoolingLayerModel {
+    kernel_size: usize,
+    /// Layers saved before the stride was recorded used windows that do not overlap.
+    #[serde(default)]
+    stride: Option<usize>,
+    #[serde(default)]
+    padding: usize,
+    #[serde(default = "default_one")]
+    dilation: usize,
+    input_shape: Vec<usize>,
+}
+
+fn default_one() -> usize {
+    1
+}
+
+impl<R: Reduction, const N: usize> PoolingLayer<R, N> {
+    /// The stride defaults to `kernel_size`, the shape of the samples is inferred when the
+    /// network is compiled.
+    pub fn build(kernel_size: usize) -> PoolingLayer<R, N> {
+        let mut convolution: Convolution = Convolution::build(N, kernel_size, 0);
+        convolution.stride = kernel_size.max(1);
+        PoolingLayer {
+            reduction: PhantomData,
+            routes: vec![],
+            convolution,
+        }
+    }
+
+    pub fn from_json(json_str: &str) -> Result<PoolingLayer<R, N>, MlError> {
+        let model: PoolingLayerModel = serde_json::from_str(json_str)?;
+        if model.input_shape.len() != N + 1 {
+            return Err(MlError::Serialization(format!(
+                "expected an input shape of {} axes, actually: {:?}",
+                N + 1,
+                model.input_shape
+            )));
+        }
+        let mut layer: PoolingLayer<R, N> = PoolingLayer::build(model.kernel_size);
+        if let Some(stride) = model.stride {
+            layer.set_stride(stride)?;
+        }
+        layer.set_padding(model.padding);
+        layer.set_dilation(model.dilation)?;
+        layer.convolution.input_shape = model.input_shape;
+        Ok(layer)
+    }
+
+    /// Distance between two windows, `kernel_size` by default.
+    pub fn set_stride(&mut self, stride: usize) -> Result<(), MlError> {
+        self.convolution.set_stride(stride)
+    }
+
+    /// Number of positions added on both sides of every spatial axis, smaller than a window.
+    pub fn set_padding(&mut self, padding: usize) {
+        self.convolution.padding = padding;
+    }
+
+    /// Distance between two values of a window.
+    pub fn set_dilation(&mut self, dilation: usize) -> Result<(), MlError> {
+        self.convolution.set_dilation(dilation)
+    }
+
+    fn name() -> String {
+        match N {
+            1 => format!("{}Pooling1d", R::NAME),
+            3 => format!("{}Pooling3d", R::NAME),
+            _ => format!("{}PoolingLayer", R::NAME),
+        }
+    }
+
+    fn output_size(&self) -> usize {
+        self.convolution.positions() * self.convolution.channels()
+    }
+
+    /// Returns the pooled batch and the input columns sharing the error of each output value.
+    fn pool<F: MlFloat>(&self, x: &Array2<F>) -> Result<(Array2<F>, Vec<Vec<usize>>), MlError> {
+        let size: usize = self.convolution.input_size();
+        if x.shape()[1] != size {
+            return Err(MlError::ShapeMismatch(format!(
+                "{} expects {} input columns, actually: {}",
+                Self::name(),
+                size,
+                x.shape()[1]
+            )));
+        }
+        let (batch, c): (usize, usize) = (x.shape()[0], self.convolution.channels());
+        let mut output: Array2<F> = Array2::zeros((batch, self.output_size()));
+        let mut routes: Vec<Vec<usize>> = Vec::with_capacity(batch * self.output_size());
+        let windows: Vec<Option<usize>> = self.convolution.windows();
+        for b in 0..batch {
+            for (p, window) in windows
+                .chunks(self.convolution.kernel_volume().max(1))
+                .enumerate()
+            {
+                for ch in 0..c {
+                    let values: Vec<(usize, F)> = window
+                        .iter()
+                        .flatten()
+                        .map(|index| (index * c + ch, x[[b, index * c + ch]]))
+                        .collect();
+                    let (value, route): (F, Vec<usize>) = R::reduce(&values)?;
+                    output[[b, p * c + ch]] = value;
+                    routes.push(route);
+                }
+            }
+        }
+        Ok((output, routes))
+    }
+}
+
+impl<F: MlFloat, R: Reduction, const N: usize> Layer<F> for PoolingLayer<R, N> {
+    fn forward_propagation(&mut self, x: &Array2<F>) -> Result<Array2<F>, MlError> {
+        let (output, routes): (Array2<F>, Vec<Vec<usize>>) = self.pool(x)?;
+        self.routes = routes;
+        Ok(output)
+    }
+
+    fn predict(&self, x: &Array2<F>) -> Result<Array2<F>, MlError> {
+        Ok(self.pool(x)?.0)
+    }
+
+    /// Shares each output error equally between the input values its window was reduced from.
+    fn compute_gradients(&mut self, y: &Array2<F>) -> Result<Array2<F>, MlError> {
+        let outputs: usize = self.output_size();
+        if y.shape()[1] != outputs || y.shape()[0] * outputs != self.routes.len() {
+            return Err(MlError::ShapeMismatch(format!(
+                "{} expects {} output errors per sample for {} routes, actually: {:?}",
+                Self::name(),
+                outputs,
+                self.routes.len(),
+                y.shape()
+            )));
+        }
+        let mut input_error: Array2<F> =
+            Array2::zeros((y.shape()[0], self.convolution.input_size()));
+        for (i, route) in self.routes.iter().enumerate() {
+            let (b, o): (usize, usize) = (i / outputs, i % outputs);
+            let error: F = y[[b, o]] / F::cast(route.len().max(1) as f64);
+            for &index in route {
+                input_error[[b, index]] += error;
+            }
+        }
+        Ok(input_error)
+    }
+
+    fn compile(&mut self, input_shape: &[usize]) -> Result<Vec<usize>, MlError> {
+        if self.convolution.padding >= self.convolution.span() {
+            return Err(MlError::InvalidInput(format!(
+                "padding must be smaller than a window of {} values, actually: {}",
+                self.convolution.span(),
+                self.convolution.padding
+            )));
+        }
+        let mut output_shape: Vec<usize> = self.convolution.compile(input_shape)?;
+        output_shape.push(self.convolution.channels());
+        Ok(output_shape)
+    }
+
+    fn clone_layer(&self) -> Box<dyn Layer<F>> {
+        Box::new(self.clone())
+    }
+
+    fn get_shape(&self) -> (usize, usize) {
+        (self.convolution.input_size(), self.output_size())
+    }
+
+    fn get_name(&self) -> String {
+        Self::name()
+    }
+
+    fn to_json(&self) -> Result<String, MlError> {
+        let model: PoolingLayerModel = PoolingLayerModel {
+            kernel_size: self.convolution.kernel_size,
+            stride: Some(self.convolution.stride),
+            padding: self.convolution.padding,
+            dilation: self.convolution.dilation,
+            input_shape: self.convolution.input_shape.clone(),
+        };
+        Ok(serde_json::to_string(&model)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layer::layer;
+    use crate::utils::gradient_check::{check_layer, max_relative_error, GradientCheck};
+    use ndarray::arr2;
+
+    fn generate_test_max_pooling_layer() -> MaxPoolingLayer {
+        let mut layer: MaxPoolingLayer = MaxPoolingLayer::build(2);
+        Layer::<f64>::compile(&mut layer, &[3, 3, 2]).unwrap();
+        layer
+    }
+
+    fn generate_test_image() -> Array2<f64> {
+        Array2::from_shape_vec(
+            (1, 18),
+            vec![
+                30.0, 49.0, 36.0, 62.0, 45.5, 83.5, 22.5, 38.5, 34.5, 58.0, 44.5, 76.5, 28.5, 51.5,
+                35.0, 61.5, 44.5, 76.5,
+            ],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn build_should_initialize_layer() {
+        let layer: MaxPoolingLayer = MaxPoolingLayer::build(2);
+        assert_eq!(layer.routes.len(), 0);
+        assert_eq!(layer.convolution.kernel_size, 2);
+        assert_eq!(layer.convolution.stride, 2);
+    }
+
+    #[test]
+    fn forward_propagation_apply_kernel() {
+        let mut layer: MaxPoolingLayer = generate_test_max_pooling_layer();
+        let output: Array2<f64> = layer.forward_propagation(&generate_test_image()).unwrap();
+        assert_eq!(output, arr2(&[[36.0, 62.0]]));
+        assert_eq!(layer.routes, vec![vec![2], vec![3]]);
+    }
+
+    #[test]
+    fn forward_propagation_should_pool_channels_separately() {
+        let mut layer: MaxPoolingLayer = MaxPoolingLayer::build(2);
+        Layer::<f64>::compile(&mut layer, &[2, 2, 2]).unwrap();
+        let output: Array2<f64> = layer
+            .forward_propagation(&arr2(&[[1.0, 8.0, 4.0, 2.0, 3.0, 1.0, 2.0, 5.0]]))
+            .unwrap();
+        assert_eq!(output, arr2(&[[4.0, 8.0]]));
+    }
+
+    #[test]
+    fn forward_propagation_should_use_stride_padding_and_dilation() {
+        let mut layer: MaxPoolingLayer = MaxPoolingLayer::build(2);
+        layer.set_stride(1).unwrap();
+        layer.set_padding(1);
+        layer.set_dilation(2).unwrap();
+        assert_eq!(
+            Layer::<f64>::compile(&mut layer, &[2, 2, 1]).unwrap(),
+            vec![2, 2, 1]
+        );
+        // each window only sees the value diagonally opposite to its output position
+        let output: Array2<f64> = layer
+            .forward_propagation(&arr2(&[[-1.0, -2.0, -3.0, -4.0]]))
+            .unwrap();
+        assert_eq!(output, arr2(&[[-4.0, -3.0, -2.0, -1.0]]));
+    }
+
+    #[test]
+    fn backward_propagation_should_route_error_to_maximum() {
+        let mut layer: MaxPoolingLayer = generate_test_max_pooling_layer();
+        layer.forward_propagation(&generate_test_image()).unwrap();
+        let output: Array2<f64> = layer
+            .backward_propagation(&arr2(&[[30.0, 49.0]]), 0.0)
+            .unwrap();
+        let target: Array2<f64> = Array2::from_shape_vec(
+            (1, 18),
+            vec![
+                0.0, 0.0, 30.0, 49.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0,
+                0.0, 0.0,
+            ],
+        )
+        .unwrap();
+        assert_eq!(output, target);
+    }
+
+    #[test]
+    fn compute_gradients_should_pass_gradient_check() {
+        let mut layer: MaxPoolingLayer = MaxPoolingLayer::build(2);
+        Layer::<f64>::compile(&mut layer, &[4, 4, 2]).unwrap();
+        let x: Array2<f64> =
+            Array2::from_shape_fn((2, 32), |(b, i)| ((b * 32 + i) * 37 % 64) as f64 / 8.0);
+        let checks: Vec<GradientCheck> = check_layer(&mut layer, &x, 0.0001).unwrap();
+        assert!(max_relative_error(&checks) < 0.0001);
+    }
+
+    #[test]
+    fn avg_pooling_should_pass_gradient_check_with_stride_and_padding() {
+        let mut layer: AvgPoolingLayer = AvgPoolingLayer::build(3);
+        layer.set_stride(2).unwrap();
+        layer.set_padding(1);
+        assert_eq!(
+            Layer::<f64>::compile(&mut layer, &[4, 5, 2]).unwrap(),
+            vec![2, 3, 2]
+        );
+        let x: Array2<f64> = Array2::from_shape_fn((2, 40), |(b, i)| (b * 40 + i) as f64 / 20.0);
+        let checks: Vec<GradientCheck> = check_layer(&mut layer, &x, 0.0001).unwrap();
+        assert_eq!(checks.len(), 1);
+        assert!(max_relative_error(&checks) < 0.0001);
+    }
+
+    #[test]
+    fn compile_should_return_output_shape() {
+        let mut layer: MaxPoolingLayer = MaxPoolingLayer::build(2);
+        assert_eq!(
+            Layer::<f64>::compile(&mut layer, &[5, 4, 3]).unwrap(),
+            vec![2, 2, 3]
+        );
+        assert!(matches!(
+            Layer::<f64>::compile(&mut layer, &[12]),
+            Err(MlError::ShapeMismatch(_))
+        ));
+        layer.set_padding(2);
+        assert!(matches!(
+            Layer::<f64>::compile(&mut layer, &[5, 4, 3]),
+            Err(MlError::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn predict_should_fail_on_nan() {
+        let layer: MaxPoolingLayer = generate_test_max_pooling_layer();
+        let mut x: Array2<f64> = generate_test_image();
+        x[[0, 0]] = f64::NAN;
+        assert!(matches!(layer.predict(&x), Err(MlError::Numeric(_))));
+    }
+
+    #[test]
+    fn from_json_should_deserialize_layer() {
+        let mut layer: MaxPoolingLayer = generate_test_max_pooling_layer();
+        layer.set_stride(1).unwrap();
+        let result: MaxPoolingLayer =
+            MaxPoolingLayer::from_json(&Layer::<f64>::to_json(&layer).unwrap()).unwrap();
+        assert_eq!(result.convolution, layer.convolution);
+        let result: Box<dyn Layer<f64>> = layer::from_string(
+            "AvgPoolingLayer".to_string(),
+            &Layer::<f64>::to_json(&AvgPoolingLayer::build(2)).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(result.get_name(), "AvgPoolingLayer");
+    }
+
+    #[test]
+    fn from_json_should_default_stride_to_kernel_size() {
+        let layer: MaxPoolingLayer =
+            MaxPoolingLayer::from_json("{\"kernel_size\":2,\"input_shape\":[5,4,3]}").unwrap();
+        assert_eq!(layer.convolution.stride, 2);
+        assert_eq!(Layer::<f64>::get_shape(&layer), (60, 12));
+        assert!(matches!(
+            MaxPoolingLayer::from_json("{\"kernel_size\":2,\"input_shape\":[4,3]}"),
+            Err(MlError::Serialization(_))
+        ));
+    }
+}
@@ -0,0 +1,306 @@
+use crate::layer::layer::Layer;
+use crate::utils::error::{check_shapes, MlError};
+use crate::utils::float::MlFloat;
+use ndarray::{Array, Array2, Axis};
+use ndarray_rand::rand::rngs::StdRng;
+use ndarray_rand::rand::SeedableRng;
+use ndarray_rand::rand_distr::Uniform;
+use ndarray_rand::RandomExt;
+use serde::{Deserialize, Serialize};
+
+/// Sinusoidal encodings of "Attention Is All You Need", `sin(t / 10000^(2i / size))` in the even
+/// columns and `cos(t / 10000^(2i / size))` in the odd ones.
+pub fn sinusoidal_encodings<F: MlFloat>(timesteps: usize, size: usize) -> Array2<F> {
+    Array2::from_shape_fn((timesteps, size), |(t, j)| {
+        let angle: f64 = t as f64 / 10000_f64.powf((j - j % 2) as f64 / size as f64);
+        F::cast(if j % 2 == 0 { angle.sin() } else { angle.cos() })
+    })
+}
+
+/// Adds an encoding of the position to every timestep of samples of shape `(timesteps, size)`.
+/// Sinusoidal encodings are fixed and computed when the network is compiled, learned encodings
+/// are parameters of the layer.
+#[derive(Debug, Clone)]
+pub struct PositionalEncodingLayer<F: MlFloat = f64> {
+    /// One row per timestep.
+    encodings: Array2<F>,
+    encodings_gradient: Array2<F>,
+    learned: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PositionalEncodingLayerModel {
+    encodings: Option<Vec<f64>>,
+    shape: (usize, usize),
+    learned: bool,
+}
+
+impl<F: MlFloat> PositionalEncodingLayer<F> {
+    /// The shape of the encodings is inferred when the network is compiled.
+    pub fn sinusoidal() -> PositionalEncodingLayer<F> {
+        PositionalEncodingLayer::build_with_encodings(Array2::zeros((0, 0)), false)
+    }
+
+    pub fn learned(timesteps: usize, size: usize) -> PositionalEncodingLayer<F> {
+        PositionalEncodingLayer::build_with_encodings(
+            Array::random((timesteps, size), Uniform::new(F::zero(), F::one())) - F::cast(0.5),
+            true,
+        )
+    }
+
+    pub fn learned_with_seed(
+        timesteps: usize,
+        size: usize,
+        seed: u64,
+    ) -> PositionalEncodingLayer<F> {
+        let mut rng: StdRng = StdRng::seed_from_u64(seed);
+        PositionalEncodingLayer::build_with_encodings(
+            Array::random_using(
+                (timesteps, size),
+                Uniform::new(F::zero(), F::one()),
+                &mut rng,
+            ) - F::cast(0.5),
+            true,
+        )
+    }
+
+    fn build_with_encodings(encodings: Array2<F>, learned: bool) -> PositionalEncodingLayer<F> {
+        PositionalEncodingLayer {
+            encodings_gradient: Array2::zeros(encodings.raw_dim()),
+            encodings,
+            learned,
+        }
+    }
+
+    pub fn from_json(json_str: &str) -> Result<PositionalEncodingLayer<F>, MlError> {
+        let model: PositionalEncodingLayerModel = serde_json::from_str(json_str)?;
+        let encodings: Array2<F> = match model.encodings {
+            Some(encodings) => {
+                Array2::from_shape_vec(model.shape, encodings.into_iter().map(F::cast).collect())?
+            }
+            None => sinusoidal_encodings(model.shape.0, model.shape.1),
+        };
+        Ok(PositionalEncodingLayer::build_with_encodings(
+            encodings,
+            model.learned,
+        ))
+    }
+
+    pub fn get_encodings(&self) -> &Array2<F> {
+        &self.encodings
+    }
+
+    fn size(&self) -> usize {
+        self.encodings.len()
+    }
+}
+
+impl<F: MlFloat> Layer<F> for PositionalEncodingLayer<F> {
+    fn forward_propagation(&mut self, x: &Array2<F>) -> Result<Array2<F>, MlError> {
+        self.predict(x)
+    }
+
+    fn predict(&self, x: &Array2<F>) -> Result<Array2<F>, MlError> {
+        check_shapes(&x.shape()[1..], &[self.size()])?;
+        let encodings: Array2<F> = self
+            .encodings
+            .as_standard_layout()
+            .into_owned()
+            .into_shape((1, self.size()))?;
+        Ok(x + &encodings)
+    }
+
+    fn compute_gradients(&mut self, y: &Array2<F>) -> Result<Array2<F>, MlError> {
+        check_shapes(&y.shape()[1..], &[self.size()])?;
+        if self.learned {
+            self.encodings_gradient = y.sum_axis(Axis(0)).into_shape(self.encodings.raw_dim())?;
+        }
+        Ok(y.clone())
+    }
+
+    fn apply_gradients(&mut self, learning_rate: F) {
+        if self.learned {
+            self.encodings = &self.encodings - &(&self.encodings_gradient * learning_rate);
+        }
+    }
+
+    fn get_gradients(&self) -> Vec<Array2<F>> {
+        if self.learned {
+            vec![self.encodings_gradient.clone()]
+        } else {
+            vec![]
+        }
+    }
+
+    fn set_gradients(&mut self, gradients: Vec<Array2<F>>) -> Result<(), MlError> {
+        let expected: usize = self.get_gradients().len();
+        if gradients.len() != expected {
+            return Err(MlError::InvalidInput(format!(
+                "PositionalEncodingLayer expects {} gradients, actually: {}",
+                expected,
+                gradients.len()
+            )));
+        }
+        if let Some(gradient) = gradients.into_iter().next() {
+            check_shapes(gradient.shape(), self.encodings.shape())?;
+            self.encodings_gradient = gradient;
+        }
+        Ok(())
+    }
+
+    fn get_parameters(&self) -> Vec<Array2<F>> {
+        if self.learned {
+            vec![self.encodings.clone()]
+        } else {
+            vec![]
+        }
+    }
+
+    fn set_parameters(&mut self, parameters: Vec<Array2<F>>) -> Result<(), MlError> {
+        let expected: usize = self.get_parameters().len();
+        if parameters.len() != expected {
+            return Err(MlError::InvalidInput(format!(
+                "PositionalEncodingLayer expects {} parameters, actually: {}",
+                expected,
+                parameters.len()
+            )));
+        }
+        if let Some(parameter) = parameters.into_iter().next() {
+            check_shapes(parameter.shape(), self.encodings.shape())?;
+            self.encodings = parameter;
+        }
+        Ok(())
+    }
+
+    fn compile(&mut self, input_shape: &[usize]) -> Result<Vec<usize>, MlError> {
+        match *input_shape {
+            [timesteps, size] if !self.learned => {
+                self.encodings = sinusoidal_encodings(timesteps, size);
+                self.encodings_gradient = Array2::zeros((timesteps, size));
+                Ok(input_shape.to_vec())
+            }
+            [timesteps, size] if self.encodings.shape() == [timesteps, size] => {
+                Ok(input_shape.to_vec())
+            }
+            _ => Err(MlError::ShapeMismatch(format!(
+                "expected input shape {:?}, actually: {:?}",
+                self.encodings.shape(),
+                input_shape
+            ))),
+        }
+    }
+
+    /// Sinusoidal encodings are not trainable.
+    fn count_parameters(&self) -> (usize, usize) {
+        if self.learned {
+            (self.size(), 0)
+        } else {
+            (0, 0)
+        }
+    }
+
+    fn clone_layer(&self) -> Box<dyn Layer<F>> {
+        Box::new(self.clone())
+    }
+
+    fn get_shape(&self) -> (usize, usize) {
+        (self.size(), self.size())
+    }
+
+    fn get_name(&self) -> String {
+        "PositionalEncodingLayer".to_string()
+    }
+
+    fn to_json(&self) -> Result<String, MlError> {
+        let model: PositionalEncodingLayerModel = PositionalEncodingLayerModel {
+            encodings: if self.learned {
+                Some(self.encodings.iter().map(|&e| e.to_f64_lossy()).collect())
+            } else {
+                None
+            },
+            shape: (self.encodings.shape()[0], self.encodings.shape()[1]),
+            learned: self.learned,
+        };
+        Ok(serde_json::to_string(&model)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layer::layer;
+    use crate::utils::gradient_check::{check_layer, max_relative_error, GradientCheck};
+    use ndarray::arr2;
+
+    #[test]
+    fn sinusoidal_encodings_should_alternate_sine_and_cosine() {
+        let encodings: Array2<f64> = sinusoidal_encodings(3, 4);
+        assert_eq!(encodings.row(0).to_vec(), vec![0.0, 1.0, 0.0, 1.0]);
+        assert!((encodings[[2, 0]] - 2.0_f64.sin()).powf(2.0) < 0.00001);
+        assert!((encodings[[2, 1]] - 2.0_f64.cos()).powf(2.0) < 0.00001);
+        assert!((encodings[[2, 2]] - 0.02_f64.sin()).powf(2.0) < 0.00001);
+        assert!((encodings[[2, 3]] - 0.02_f64.cos()).powf(2.0) < 0.00001);
+    }
+
+    #[test]
+    fn forward_propagation_should_add_encodings_to_every_sample() {
+        let mut layer: PositionalEncodingLayer = PositionalEncodingLayer::sinusoidal();
+        assert_eq!(layer.compile(&[2, 2]).unwrap(), vec![2, 2]);
+        assert_eq!(layer.count_parameters(), (0, 0));
+        let result: Array2<f64> = layer
+            .forward_propagation(&arr2(&[[1.0, 1.0, 1.0, 1.0], [0.0, 0.0, 0.0, 0.0]]))
+            .unwrap();
+        assert_eq!(
+            result.row(0).to_vec(),
+            vec![1.0, 2.0, 1.0 + 1.0_f64.sin(), 1.0 + 1.0_f64.cos()]
+        );
+        assert_eq!(
+            result.row(1).to_vec(),
+            vec![0.0, 1.0, 1.0_f64.sin(), 1.0_f64.cos()]
+        );
+        assert!(matches!(
+            PositionalEncodingLayer::<f64>::learned(3, 2).compile(&[2, 2]),
+            Err(MlError::ShapeMismatch(_))
+        ));
+    }
+
+    #[test]
+    fn compute_gradients_should_pass_gradient_check() {
+        let x: Array2<f64> = arr2(&[[0.5, -1.0, 2.0, 1.0], [0.0, 0.3, -0.4, 2.0]]);
+        let mut layer: PositionalEncodingLayer =
+            PositionalEncodingLayer::learned_with_seed(2, 2, 1);
+        layer.compile(&[2, 2]).unwrap();
+        let checks: Vec<GradientCheck> = check_layer(&mut layer, &x, 0.0001).unwrap();
+        assert_eq!(checks.len(), 2);
+        assert!(max_relative_error(&checks) < 0.0001);
+
+        let mut layer: PositionalEncodingLayer = PositionalEncodingLayer::sinusoidal();
+        layer.compile(&[2, 2]).unwrap();
+        let checks: Vec<GradientCheck> = check_layer(&mut layer, &x, 0.0001).unwrap();
+        assert_eq!(checks.len(), 1);
+        assert!(max_relative_error(&checks) < 0.0001);
+    }
+
+    #[test]
+    fn from_string_should_deserialize_layer() {
+        let mut sinusoidal: PositionalEncodingLayer = PositionalEncodingLayer::sinusoidal();
+        sinusoidal.compile(&[3, 4]).unwrap();
+        let json: String = sinusoidal.to_json().unwrap();
+        assert_eq!(
+            json,
+            "{\"encodings\":null,\"shape\":[3,4],\"learned\":false}"
+        );
+        let result: Box<dyn Layer<f64>> =
+            layer::from_string("PositionalEncodingLayer".to_string(), &json).unwrap();
+        assert_eq!(result.get_shape(), (12, 12));
+        assert_eq!(result.count_parameters(), (0, 0));
+
+        let learned: PositionalEncodingLayer = PositionalEncodingLayer::learned_with_seed(3, 4, 1);
+        let result: Box<dyn Layer<f64>> = layer::from_string(
+            "PositionalEncodingLayer".to_string(),
+            &learned.to_json().unwrap(),
+        )
+        .unwrap();
+        assert_eq!(result.count_parameters(), (12, 0));
+    }
+}
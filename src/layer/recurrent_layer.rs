@@ -0,0 +1,654 @@
+use crate::layer::layer::Layer;
+use crate::utils::error::{check_shapes, MlError};
+use crate::utils::float::MlFloat;
+use ndarray::{concatenate, s, Array, Array2, ArrayView2, Axis};
+use ndarray_rand::rand::rngs::StdRng;
+use ndarray_rand::rand::SeedableRng;
+use ndarray_rand::rand_distr::Uniform;
+use ndarray_rand::RandomExt;
+use serde::{Deserialize, Serialize};
+use std::marker::PhantomData;
+
+/// Weights of a recurrent cell, made of `GATES` blocks of `units` columns.
+#[derive(Debug, Clone)]
+pub struct RecurrentWeights<F: MlFloat = f64> {
+    /// `(features, gates * units)`
+    pub(crate) input: Array2<F>,
+    /// `(units, gates * units)`
+    pub(crate) recurrent: Array2<F>,
+    /// `(1, gates * units)`
+    pub(crate) bias: Array2<F>,
+}
+
+impl<F: MlFloat> RecurrentWeights<F> {
+    fn zeros(&self) -> RecurrentWeights<F> {
+        RecurrentWeights {
+            input: Array2::zeros(self.input.raw_dim()),
+            recurrent: Array2::zeros(self.recurrent.raw_dim()),
+            bias: Array2::zeros(self.bias.raw_dim()),
+        }
+    }
+
+    /// `x . input + h . recurrent + bias`
+    pub(crate) fn linear(&self, x: &Array2<F>, h: &Array2<F>) -> Array2<F> {
+        x.dot(&self.input) + h.dot(&self.recurrent) + &self.bias
+    }
+
+    /// Adds the gradients of `linear` given the error `dz` of its output.
+    pub(crate) fn accumulate(&mut self, x: &Array2<F>, h: &Array2<F>, dz: &Array2<F>) {
+        self.input += &x.t().dot(dz);
+        self.recurrent += &h.t().dot(dz);
+        self.bias += &dz.sum_axis(Axis(0)).insert_axis(Axis(0));
+    }
+}
+
+/// Columns of the gate `index` of an array made of blocks of `units` columns.
+pub(crate) fn gate<F: MlFloat>(array: &Array2<F>, index: usize, units: usize) -> ArrayView2<'_, F> {
+    array.slice(s![.., index * units..(index + 1) * units])
+}
+
+/// New states of a step followed by the values its backward pass needs.
+pub type StepOutput<F> = (Vec<Array2<F>>, Vec<Array2<F>>);
+
+/// Step function of a recurrent layer.
+pub trait Cell: Clone + Send + Sync + 'static {
+    /// Number of weight blocks per unit.
+    const GATES: usize;
+    /// Number of states carried between steps, the first one is the output of the step.
+    const STATES: usize;
+    const NAME: &'static str;
+
+    fn initial_bias<F: MlFloat>(units: usize) -> Array2<F> {
+        Array2::zeros((1, Self::GATES * units))
+    }
+
+    /// Returns the new states from the input of the step and the previous states, with the
+    /// values the backward pass needs.
+    fn forward<F: MlFloat>(
+        weights: &RecurrentWeights<F>,
+        x: &Array2<F>,
+        states: &[Array2<F>],
+    ) -> Result<StepOutput<F>, MlError>;
+
+    /// Adds the weight gradients of the step and returns the errors of its input and previous
+    /// states from the errors of its new states.
+    fn backward<F: MlFloat>(
+        weights: &RecurrentWeights<F>,
+        gradients: &mut RecurrentWeights<F>,
+        x: &Array2<F>,
+        states: &[Array2<F>],
+        cache: &[Array2<F>],
+        errors: &[Array2<F>],
+    ) -> Result<(Array2<F>, Vec<Array2<F>>), MlError>;
+}
+
+/// Values of one step of the last forward pass.
+#[derive(Debug, Clone)]
+struct Step<F: MlFloat> {
+    x: Array2<F>,
+    states: Vec<Array2<F>>,
+    cache: Vec<Array2<F>>,
+}
+
+/// Output of a pass over a sequence, its steps and the final states.
+type Run<F> = (Array2<F>, Vec<Step<F>>, Vec<Array2<F>>);
+
+/// Recurrent layer over samples of shape `(timesteps, features)` flattened in rows, the step
+/// function is given by the cell.
+///
+/// A stateful layer starts each batch from the states the previous batch ended with; a batch
+/// with another number of samples, like the last batch of an epoch or the whole training set
+/// evaluated after it, starts from zeros, as after `reset_states`. With a
+/// truncation of `k` steps, errors are only propagated back within chunks of `k` timesteps.
+#[derive(Debug, Clone)]
+pub struct RecurrentLayer<F: MlFloat, C: Cell> {
+    cell: PhantomData<C>,
+    weights: RecurrentWeights<F>,
+    gradients: RecurrentWeights<F>,
+    units: usize,
+    timesteps: usize,
+    return_sequences: bool,
+    stateful: bool,
+    truncation: Option<usize>,
+    /// States at the end of the last forward pass of a stateful layer.
+    states: Vec<Array2<F>>,
+    steps: Vec<Step<F>>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RecurrentLayerModel {
+    input_weights: Vec<f64>,
+    recurrent_weights: Vec<f64>,
+    bias: Vec<f64>,
+    features: usize,
+    units: usize,
+    timesteps: usize,
+    return_sequences: bool,
+    stateful: bool,
+    truncation: Option<usize>,
+}
+
+impl<F: MlFloat, C: Cell> RecurrentLayer<F, C> {
+    /// The number of timesteps is inferred when the network is compiled.
+    pub fn build(features: usize, units: usize) -> RecurrentLayer<F, C> {
+        let columns: usize = C::GATES * units;
+        RecurrentLayer::build_with_weights(RecurrentWeights {
+            input: Array::random((features, columns), Uniform::new(F::zero(), F::one()))
+                - F::cast(0.5),
+            recurrent: Array::random((units, columns), Uniform::new(F::zero(), F::one()))
+                - F::cast(0.5),
+            bias: C::initial_bias(units),
+        })
+    }
+
+    pub fn build_with_seed(features: usize, units: usize, seed: u64) -> RecurrentLayer<F, C> {
+        let mut rng: StdRng = StdRng::seed_from_u64(seed);
+        let columns: usize = C::GATES * units;
+        RecurrentLayer::build_with_weights(RecurrentWeights {
+            input: Array::random_using(
+                (features, columns),
+                Uniform::new(F::zero(), F::one()),
+                &mut rng,
+            ) - F::cast(0.5),
+            recurrent: Array::random_using(
+                (units, columns),
+                Uniform::new(F::zero(), F::one()),
+                &mut rng,
+            ) - F::cast(0.5),
+            bias: C::initial_bias(units),
+        })
+    }
+
+    fn build_with_weights(weights: RecurrentWeights<F>) -> RecurrentLayer<F, C> {
+        RecurrentLayer {
+            cell: PhantomData,
+            gradients: weights.zeros(),
+            units: weights.recurrent.shape()[0],
+            weights,
+            timesteps: 0,
+            return_sequences: false,
+            stateful: false,
+            truncation: None,
+            states: vec![],
+            steps: vec![],
+        }
+    }
+
+    pub fn from_json(json_str: &str) -> Result<RecurrentLayer<F, C>, MlError> {
+        let model: RecurrentLayerModel = serde_json::from_str(json_str)?;
+        let columns: usize = C::GATES * model.units;
+        let mut layer: RecurrentLayer<F, C> =
+            RecurrentLayer::build_with_weights(RecurrentWeights {
+                input: Array2::from_shape_vec(
+                    (model.features, columns),
+                    model.input_weights.into_iter().map(F::cast).collect(),
+                )?,
+                recurrent: Array2::from_shape_vec(
+                    (model.units, columns),
+                    model.recurrent_weights.into_iter().map(F::cast).collect(),
+                )?,
+                bias: Array2::from_shape_vec(
+                    (1, columns),
+                    model.bias.into_iter().map(F::cast).collect(),
+                )?,
+            });
+        layer.timesteps = model.timesteps;
+        layer.return_sequences = model.return_sequences;
+        layer.stateful = model.stateful;
+        layer.truncation = model.truncation;
+        Ok(layer)
+    }
+
+    /// Outputs the states of every timestep instead of the last one.
+    pub fn set_return_sequences(&mut self, return_sequences: bool) {
+        self.return_sequences = return_sequences;
+    }
+
+    pub fn set_stateful(&mut self, stateful: bool) {
+        self.stateful = stateful;
+        self.states.clear();
+    }
+
+    /// Number of timesteps errors are propagated back through, `None` for the whole sequence.
+    pub fn set_truncation(&mut self, truncation: Option<usize>) -> Result<(), MlError> {
+        if truncation == Some(0) {
+            return Err(MlError::InvalidInput(
+                "truncation must be at least one timestep".to_string(),
+            ));
+        }
+        self.truncation = truncation;
+        Ok(())
+    }
+
+    pub fn reset_states(&mut self) {
+        self.states.clear();
+    }
+
+    fn features(&self) -> usize {
+        self.weights.input.shape()[0]
+    }
+
+    fn output_size(&self) -> usize {
+        if self.return_sequences {
+            self.timesteps * self.units
+        } else {
+            self.units
+        }
+    }
+
+    fn initial_states(&self, batch: usize) -> Vec<Array2<F>> {
+        if self.stateful && self.states.first().is_some_and(|s| s.shape()[0] == batch) {
+            return self.states.clone();
+        }
+        vec![Array2::zeros((batch, self.units)); C::STATES]
+    }
+
+    /// Runs the cell over the timesteps and returns the output, the steps and the last states.
+    fn run(&self, x: &Array2<F>) -> Result<Run<F>, MlError> {
+        let features: usize = self.features();
+        if self.timesteps == 0 || x.shape()[1] != self.timesteps * features {
+            return Err(MlError::ShapeMismatch(format!(
+                "{} expects {} input columns, actually: {}",
+                C::NAME,
+                self.timesteps * features,
+                x.shape()[1]
+            )));
+        }
+        let mut states: Vec<Array2<F>> = self.initial_states(x.shape()[0]);
+        let mut steps: Vec<Step<F>> = Vec::with_capacity(self.timesteps);
+        let mut outputs: Vec<Array2<F>> = Vec::with_capacity(self.timesteps);
+        for t in 0..self.timesteps {
+            let x_t: Array2<F> = x.slice(s![.., t * features..(t + 1) * features]).to_owned();
+            let (new_states, cache): (Vec<Array2<F>>, Vec<Array2<F>>) =
+                C::forward(&self.weights, &x_t, &states)?;
+            outputs.push(new_states[0].clone());
+            steps.push(Step {
+                x: x_t,
+                states,
+                cache,
+            });
+            states = new_states;
+        }
+        let output: Array2<F> = if self.return_sequences {
+            let views: Vec<ArrayView2<F>> = outputs.iter().map(|output| output.view()).collect();
+            concatenate(Axis(1), &views)?
+        } else {
+            states[0].clone()
+        };
+        Ok((output, steps, states))
+    }
+}
+
+impl<F: MlFloat, C: Cell> Layer<F> for RecurrentLayer<F, C> {
+    fn forward_propagation(&mut self, x: &Array2<F>) -> Result<Array2<F>, MlError> {
+        let (output, steps, states): (Array2<F>, Vec<Step<F>>, Vec<Array2<F>>) = self.run(x)?;
+        if self.stateful {
+            self.states = states;
+        }
+        self.steps = steps;
+        Ok(output)
+    }
+
+    /// A stateful layer starts from its states without updating them, or from zeros when `x`
+    /// does not have as many samples as the last batch.
+    fn predict(&self, x: &Array2<F>) -> Result<Array2<F>, MlError> {
+        Ok(self.run(x)?.0)
+    }
+
+    fn compute_gradients(&mut self, y: &Array2<F>) -> Result<Array2<F>, MlError> {
+        let batch: usize = self.steps.first().map_or(0, |step| step.x.shape()[0]);
+        check_shapes(y.shape(), &[batch, self.output_size()])?;
+        let (features, units): (usize, usize) = (self.features(), self.units);
+        let mut gradients: RecurrentWeights<F> = self.weights.zeros();
+        let mut input_error: Array2<F> = Array2::zeros((batch, self.timesteps * features));
+        let mut errors: Vec<Array2<F>> = vec![Array2::zeros((batch, units)); C::STATES];
+        for (t, step) in self.steps.iter().enumerate().rev() {
+            if self.return_sequences {
+                errors[0] += &y.slice(s![.., t * units..(t + 1) * units]);
+            } else if t + 1 == self.timesteps {
+                errors[0] += y;
+            }
+            let (x_error, states_error): (Array2<F>, Vec<Array2<F>>) = C::backward(
+                &self.weights,
+                &mut gradients,
+                &step.x,
+                &step.states,
+                &step.cache,
+                &errors,
+            )?;
+            input_error
+                .slice_mut(s![.., t * features..(t + 1) * features])
+                .assign(&x_error);
+            errors = match self.truncation {
+                Some(truncation) if t % truncation == 0 => {
+                    vec![Array2::zeros((batch, units)); C::STATES]
+                }
+                _ => states_error,
+            };
+        }
+        self.gradients = gradients;
+        Ok(input_error)
+    }
+
+    fn apply_gradients(&mut self, learning_rate: F) {
+        self.weights.input = &self.weights.input - &(&self.gradients.input * learning_rate);
+        self.weights.recurrent =
+            &self.weights.recurrent - &(&self.gradients.recurrent * learning_rate);
+        self.weights.bias = &self.weights.bias - &(&self.gradients.bias * learning_rate);
+    }
+
+    fn get_gradients(&self) -> Vec<Array2<F>> {
+        vec![
+            self.gradients.input.clone(),
+            self.gradients.recurrent.clone(),
+            self.gradients.bias.clone(),
+        ]
+    }
+
+    fn set_gradients(&mut self, gradients: Vec<Array2<F>>) -> Result<(), MlError> {
+        if gradients.len() != 3 {
+            return Err(MlError::InvalidInput(format!(
+                "{} expects 3 gradients, actually: {}",
+                C::NAME,
+                gradients.len()
+            )));
+        }
+        check_shapes(gradients[0].shape(), self.weights.input.shape())?;
+        check_shapes(gradients[1].shape(), self.weights.recurrent.shape())?;
+        check_shapes(gradients[2].shape(), self.weights.bias.shape())?;
+        self.gradients = RecurrentWeights {
+            input: gradients[0].clone(),
+            recurrent: gradients[1].clone(),
+            bias: gradients[2].clone(),
+        };
+        Ok(())
+    }
+
+    fn get_parameters(&self) -> Vec<Array2<F>> {
+        vec![
+            self.weights.input.clone(),
+            self.weights.recurrent.clone(),
+            self.weights.bias.clone(),
+        ]
+    }
+
+    fn set_parameters(&mut self, parameters: Vec<Array2<F>>) -> Result<(), MlError> {
+        if parameters.len() != 3 {
+            return Err(MlError::InvalidInput(format!(
+                "{} expects 3 parameters, actually: {}",
+                C::NAME,
+                parameters.len()
+            )));
+        }
+        check_shapes(parameters[0].shape(), self.weights.input.shape())?;
+        check_shapes(parameters[1].shape(), self.weights.recurrent.shape())?;
+        check_shapes(parameters[2].shape(), self.weights.bias.shape())?;
+        self.weights = RecurrentWeights {
+            input: parameters[0].clone(),
+            recurrent: parameters[1].clone(),
+            bias: parameters[2].clone(),
+        };
+        Ok(())
+    }
+
+    fn compile(&mut self, input_shape: &[usize]) -> Result<Vec<usize>, MlError> {
+        match input_shape {
+            &[timesteps, features] if timesteps > 0 && features == self.features() => {
+                self.timesteps = timesteps;
+                if self.return_sequences {
+                    Ok(vec![timesteps, self.units])
+                } else {
+                    Ok(vec![self.units])
+                }
+            }
+            _ => Err(MlError::ShapeMismatch(format!(
+                "expected input shape [timesteps, {}], actually: {:?}",
+                self.features(),
+                input_shape
+            ))),
+        }
+    }
+
+    fn is_stateful(&self) -> bool {
+        self.stateful
+    }
+
+    fn count_parameters(&self) -> (usize, usize) {
+        (
+            self.weights.input.len() + self.weights.recurrent.len() + self.weights.bias.len(),
+            0,
+        )
+    }
+
+    fn clone_layer(&self) -> Box<dyn Layer<F>> {
+        Box::new(self.clone())
+    }
+
+    fn get_shape(&self) -> (usize, usize) {
+        (self.timesteps * self.features(), self.output_size())
+    }
+
+    fn get_name(&self) -> String {
+        C::NAME.to_string()
+    }
+
+    fn to_json(&self) -> Result<String, MlError> {
+        let model: RecurrentLayerModel = RecurrentLayerModel {
+            input_weights: self
+                .weights
+                .input
+                .iter()
+                .map(|&w| w.to_f64_lossy())
+                .collect(),
+            recurrent_weights: self
+                .weights
+                .recurrent
+                .iter()
+                .map(|&w| w.to_f64_lossy())
+                .collect(),
+            bias: self
+                .weights
+                .bias
+                .iter()
+                .map(|&b| b.to_f64_lossy())
+                .collect(),
+            features: self.features(),
+            units: self.units,
+            timesteps: self.timesteps,
+            return_sequences: self.return_sequences,
+            stateful: self.stateful,
+            truncation: self.truncation,
+        };
+        Ok(serde_json::to_string(&model)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layer::rnn_layer::RnnLayer;
+    use crate::loss::mse::Mse;
+    use crate::network::sequential::Sequential;
+    use crate::report::report::Report;
+    use ndarray::{arr2, Array3};
+    use std::fs;
+
+    fn generate_test_rnn_layer(return_sequences: bool) -> RnnLayer {
+        let mut layer: RnnLayer = RnnLayer::build_with_seed(2, 3, 1);
+        layer.set_return_sequences(return_sequences);
+        layer.compile(&[4, 2]).unwrap();
+        layer
+    }
+
+    fn generate_test_sequences() -> Array2<f64> {
+        arr2(&[
+            [0.5, -1.0, 1.0, 0.25, -0.5, 0.75, 0.0, 1.5],
+            [1.0, 0.5, -0.25, -1.0, 0.5, 0.0, 1.25, -0.5],
+        ])
+    }
+
+    #[test]
+    fn compile_should_return_output_shape() {
+        let mut layer: RnnLayer = RnnLayer::build(2, 3);
+        assert_eq!(layer.compile(&[5, 2]).unwrap(), vec![3]);
+        assert_eq!(layer.get_shape(), (10, 3));
+        layer.set_return_sequences(true);
+        assert_eq!(layer.compile(&[5, 2]).unwrap(), vec![5, 3]);
+        assert_eq!(layer.get_shape(), (10, 15));
+        assert!(matches!(
+            layer.compile(&[5, 3]),
+            Err(MlError::ShapeMismatch(_))
+        ));
+        assert!(matches!(
+            layer.compile(&[10]),
+            Err(MlError::ShapeMismatch(_))
+        ));
+    }
+
+    #[test]
+    fn return_sequences_should_end_with_last_output() {
+        let x: Array2<f64> = generate_test_sequences();
+        let last: Array2<f64> = generate_test_rnn_layer(false).predict(&x).unwrap();
+        let sequences: Array2<f64> = generate_test_rnn_layer(true).predict(&x).unwrap();
+        assert_eq!(sequences.shape(), &[2, 12]);
+        assert_eq!(sequences.slice(s![.., 9..12]), last);
+    }
+
+    #[test]
+    fn stateful_layer_should_carry_states_between_batches() {
+        let x: Array2<f64> = generate_test_sequences();
+        let mut layer: RnnLayer = generate_test_rnn_layer(false);
+        layer.set_stateful(true);
+        let first: Array2<f64> = layer.forward_propagation(&x).unwrap();
+        let second: Array2<f64> = layer.forward_propagation(&x).unwrap();
+        assert_ne!(first, second);
+        assert_eq!(
+            layer.predict(&x).unwrap(),
+            layer.forward_propagation(&x).unwrap()
+        );
+        let row: Array2<f64> = x.slice(s![0..1, ..]).to_owned();
+        assert_eq!(
+            layer.predict(&row).unwrap(),
+            generate_test_rnn_layer(false).predict(&row).unwrap()
+        );
+        layer.reset_states();
+        assert_eq!(layer.forward_propagation(&x).unwrap(), first);
+        layer.forward_propagation(&row).unwrap();
+        assert_eq!(layer.forward_propagation(&x).unwrap(), first);
+    }
+
+    #[test]
+    fn stateful_layer_should_fit_batches_smaller_than_dataset() {
+        let x: Array3<f64> =
+            Array3::from_shape_fn((5, 4, 2), |(b, t, i)| ((b + t + i) % 7) as f64 / 6.0 - 0.5);
+        let y: Array2<f64> = Array2::from_shape_fn((5, 3), |(b, i)| ((b + i) % 2) as f64);
+        let mut layer: RnnLayer = RnnLayer::build_with_seed(2, 3, 1);
+        layer.set_stateful(true);
+        let mut network: Sequential = Sequential::build(Box::new(Mse));
+        network.add_layer(Box::new(layer));
+        network.compile(&[4, 2]).unwrap();
+        network
+            .fit(
+                &x,
+                &y,
+                Some(&x),
+                Some(&y),
+                3,
+                0.1,
+                2,
+                &mut [],
+                &mut Report::build("./test_report_stateful_rnn"),
+                100,
+            )
+            .unwrap();
+        fs::remove_dir_all("./test_report_stateful_rnn").ok();
+        assert_eq!(network.predict(&x).unwrap().shape(), &[5, 3]);
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn stateful_layer_should_train_on_a_single_thread_when_parallel() {
+        let x: Array3<f64> =
+            Array3::from_shape_fn((4, 4, 2), |(b, t, i)| ((b + t + i) % 7) as f64 / 6.0 - 0.5);
+        let y: Array2<f64> = Array2::from_shape_fn((4, 3), |(b, i)| ((b + i) % 2) as f64);
+        let mut networks: Vec<Sequential> = (1..3)
+            .map(|threads| {
+                let mut layer: RnnLayer = RnnLayer::build_with_seed(2, 3, 1);
+                layer.set_stateful(true);
+                let mut network: Sequential = Sequential::build(Box::new(Mse));
+                network.add_layer(Box::new(layer));
+                network.compile(&[4, 2]).unwrap();
+                network.set_threads(threads).unwrap();
+                network
+            })
+            .collect();
+
+        for (index, network) in networks.iter_mut().enumerate() {
+            let path: String = format!("./test_report_parallel_rnn_{}", index);
+            network
+                .fit(
+                    &x,
+                    &y,
+                    None,
+                    None,
+                    2,
+                    0.1,
+                    2,
+                    &mut [],
+                    &mut Report::build(&path),
+                    100,
+                )
+                .unwrap();
+            fs::remove_dir_all(&path).ok();
+        }
+
+        assert_eq!(
+            networks[0].predict(&x).unwrap(),
+            networks[1].predict(&x).unwrap()
+        );
+    }
+
+    #[test]
+    fn truncation_should_stop_errors_between_chunks() {
+        let x: Array2<f64> = generate_test_sequences();
+        let mut layer: RnnLayer = generate_test_rnn_layer(false);
+        layer.set_truncation(Some(2)).unwrap();
+        layer.forward_propagation(&x).unwrap();
+        let input_error: Array2<f64> = layer.compute_gradients(&Array2::ones((2, 3))).unwrap();
+        assert!(input_error.slice(s![.., 0..4]).iter().all(|&e| e == 0.0));
+        assert!(input_error.slice(s![.., 4..8]).iter().any(|&e| e != 0.0));
+        assert!(matches!(
+            layer.set_truncation(Some(0)),
+            Err(MlError::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn compute_gradients_should_check_output_shape() {
+        let x: Array2<f64> = generate_test_sequences();
+        let mut layer: RnnLayer = generate_test_rnn_layer(true);
+        layer.forward_propagation(&x).unwrap();
+        assert!(matches!(
+            layer.compute_gradients(&Array2::ones((2, 3))),
+            Err(MlError::ShapeMismatch(_))
+        ));
+        assert!(matches!(
+            layer.predict(&Array2::ones((2, 6))),
+            Err(MlError::ShapeMismatch(_))
+        ));
+    }
+
+    #[test]
+    fn from_json_should_keep_options() {
+        let mut layer: RnnLayer = generate_test_rnn_layer(true);
+        layer.set_stateful(true);
+        layer.set_truncation(Some(3)).unwrap();
+        let result: RnnLayer = RnnLayer::from_json(&layer.to_json().unwrap()).unwrap();
+        assert_eq!(result.units, 3);
+        assert_eq!(result.timesteps, 4);
+        assert!(result.return_sequences);
+        assert!(result.stateful);
+        assert_eq!(result.truncation, Some(3));
+        assert_eq!(result.count_parameters(), layer.count_parameters());
+    }
+}
@@ -0,0 +1,147 @@
+use crate::layer::layer::Layer;
+use crate::utils::error::{check_shapes, MlError};
+use crate::utils::float::MlFloat;
+use ndarray::{Array2, Axis};
+use serde::{Deserialize, Serialize};
+
+/// Repeats vectors of `size` values `repetitions` times into samples of shape
+/// `(repetitions, size)`, such as the encoding of a sequence fed at every timestep of a decoder.
+#[derive(Debug, Clone)]
+pub struct RepeatVector {
+    repetitions: usize,
+    size: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RepeatVectorModel {
+    repetitions: usize,
+    size: usize,
+}
+
+impl RepeatVector {
+    /// The size of the vectors is inferred when the network is compiled.
+    pub fn build(repetitions: usize) -> RepeatVector {
+        RepeatVector {
+            repetitions,
+            size: 0,
+        }
+    }
+
+    pub fn from_json(json_str: &str) -> Result<RepeatVector, MlError> {
+        let model: RepeatVectorModel = serde_json::from_str(json_str)?;
+        let mut layer: RepeatVector = RepeatVector::build(model.repetitions);
+        layer.size = model.size;
+        Ok(layer)
+    }
+}
+
+impl<F: MlFloat> Layer<F> for RepeatVector {
+    fn forward_propagation(&mut self, x: &Array2<F>) -> Result<Array2<F>, MlError> {
+        self.predict(x)
+    }
+
+    fn predict(&self, x: &Array2<F>) -> Result<Array2<F>, MlError> {
+        check_shapes(&x.shape()[1..], &[self.size])?;
+        Ok(Array2::from_shape_fn(
+            (x.shape()[0], self.repetitions * self.size),
+            |(b, i)| x[[b, i % self.size]],
+        ))
+    }
+
+    /// Sums the errors of every repetition.
+    fn compute_gradients(&mut self, y: &Array2<F>) -> Result<Array2<F>, MlError> {
+        check_shapes(&y.shape()[1..], &[self.repetitions * self.size])?;
+        Ok(y.as_standard_layout()
+            .into_owned()
+            .into_shape((y.shape()[0], self.repetitions, self.size))?
+            .sum_axis(Axis(1)))
+    }
+
+    fn compile(&mut self, input_shape: &[usize]) -> Result<Vec<usize>, MlError> {
+        match *input_shape {
+            [size] if self.repetitions > 0 => {
+                self.size = size;
+                Ok(vec![self.repetitions, size])
+            }
+            _ => Err(MlError::ShapeMismatch(format!(
+                "expected input shape [n] and at least 1 repetition, actually: {:?} and {}",
+                input_shape, self.repetitions
+            ))),
+        }
+    }
+
+    fn clone_layer(&self) -> Box<dyn Layer<F>> {
+        Box::new(self.clone())
+    }
+
+    fn get_shape(&self) -> (usize, usize) {
+        (self.size, self.repetitions * self.size)
+    }
+
+    fn get_name(&self) -> String {
+        "RepeatVector".to_string()
+    }
+
+    fn to_json(&self) -> Result<String, MlError> {
+        let model: RepeatVectorModel = RepeatVectorModel {
+            repetitions: self.repetitions,
+            size: self.size,
+        };
+        Ok(serde_json::to_string(&model)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layer::layer;
+    use crate::utils::gradient_check::{check_layer, max_relative_error, GradientCheck};
+    use ndarray::arr2;
+
+    #[test]
+    fn forward_propagation_should_repeat_vectors() {
+        let mut repeat_vector: RepeatVector = RepeatVector::build(3);
+        assert_eq!(
+            Layer::<f64>::compile(&mut repeat_vector, &[2]).unwrap(),
+            vec![3, 2]
+        );
+        let output: Array2<f64> = repeat_vector
+            .forward_propagation(&arr2(&[[1.0, 2.0], [3.0, 4.0]]))
+            .unwrap();
+        assert_eq!(
+            output,
+            arr2(&[
+                [1.0, 2.0, 1.0, 2.0, 1.0, 2.0],
+                [3.0, 4.0, 3.0, 4.0, 3.0, 4.0]
+            ])
+        );
+        assert!(matches!(
+            Layer::<f64>::compile(&mut repeat_vector, &[2, 2]),
+            Err(MlError::ShapeMismatch(_))
+        ));
+    }
+
+    #[test]
+    fn compute_gradients_should_sum_repetitions() {
+        let mut repeat_vector: RepeatVector = RepeatVector::build(2);
+        Layer::<f64>::compile(&mut repeat_vector, &[3]).unwrap();
+        let input_error: Array2<f64> = repeat_vector
+            .compute_gradients(&arr2(&[[1.0, 2.0, 3.0, 4.0, 5.0, 6.0]]))
+            .unwrap();
+        assert_eq!(input_error, arr2(&[[5.0, 7.0, 9.0]]));
+        let x: Array2<f64> = arr2(&[[0.5, -1.0, 2.0], [0.0, 0.3, -0.4]]);
+        let checks: Vec<GradientCheck> = check_layer(&mut repeat_vector, &x, 0.0001).unwrap();
+        assert!(max_relative_error(&checks) < 0.0001);
+    }
+
+    #[test]
+    fn from_string_should_deserialize_layer() {
+        let mut repeat_vector: RepeatVector = RepeatVector::build(4);
+        Layer::<f64>::compile(&mut repeat_vector, &[3]).unwrap();
+        let json: String = Layer::<f64>::to_json(&repeat_vector).unwrap();
+        assert_eq!(json, "{\"repetitions\":4,\"size\":3}");
+        let result: Box<dyn Layer<f64>> =
+            layer::from_string("RepeatVector".to_string(), &json).unwrap();
+        assert_eq!(result.get_shape(), (3, 12));
+    }
+}
@@ -0,0 +1,189 @@
+use crate::layer::layer::Layer;
+use crate::utils::error::{check_shapes, MlError};
+use crate::utils::float::MlFloat;
+use ndarray::Array2;
+use serde::{Deserialize, Serialize};
+
+/// Gives the samples a new shape with the same number of values. Samples are stored flattened in
+/// the rows of a batch, only the shape seen by the next layers changes. One axis of the target
+/// shape may be `-1`, its length is inferred from the input shape when the network is compiled.
+#[derive(Debug, Clone)]
+pub struct ReshapeLayer {
+    target_shape: Vec<isize>,
+    input_shape: Vec<usize>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ReshapeLayerModel {
+    target_shape: Vec<isize>,
+    input_shape: Vec<usize>,
+}
+
+impl ReshapeLayer {
+    pub fn build(target_shape: &[isize]) -> Result<ReshapeLayer, MlError> {
+        if target_shape.iter().filter(|&&axis| axis == -1).count() > 1
+            || target_shape.iter().any(|&axis| axis == 0 || axis < -1)
+        {
+            return Err(MlError::InvalidInput(format!(
+                "target shape must hold positive lengths and at most one -1, actually: {:?}",
+                target_shape
+            )));
+        }
+        Ok(ReshapeLayer {
+            target_shape: target_shape.to_vec(),
+            input_shape: vec![],
+        })
+    }
+
+    pub fn from_json(json_str: &str) -> Result<ReshapeLayer, MlError> {
+        let model: ReshapeLayerModel = serde_json::from_str(json_str)?;
+        let mut layer: ReshapeLayer = ReshapeLayer::build(&model.target_shape)?;
+        if !model.input_shape.is_empty() {
+            Layer::<f64>::compile(&mut layer, &model.input_shape)?;
+        }
+        Ok(layer)
+    }
+
+    fn size(&self) -> usize {
+        self.input_shape.iter().product()
+    }
+}
+
+impl<F: MlFloat> Layer<F> for ReshapeLayer {
+    fn forward_propagation(&mut self, x: &Array2<F>) -> Result<Array2<F>, MlError> {
+        self.predict(x)
+    }
+
+    fn predict(&self, x: &Array2<F>) -> Result<Array2<F>, MlError> {
+        check_shapes(&x.shape()[1..], &[self.size()])?;
+        Ok(x.clone())
+    }
+
+    fn compute_gradients(&mut self, y: &Array2<F>) -> Result<Array2<F>, MlError> {
+        check_shapes(&y.shape()[1..], &[self.size()])?;
+        Ok(y.clone())
+    }
+
+    fn compile(&mut self, input_shape: &[usize]) -> Result<Vec<usize>, MlError> {
+        let size: usize = input_shape.iter().product();
+        let known: usize = self
+            .target_shape
+            .iter()
+            .filter(|&&axis| axis > 0)
+            .map(|&axis| axis as usize)
+            .product();
+        let inferred: Option<usize> = if self.target_shape.contains(&-1) {
+            Some(size / known).filter(|_| size % known == 0)
+        } else {
+            Some(1).filter(|_| size == known)
+        };
+        match inferred {
+            Some(inferred) => {
+                self.input_shape = input_shape.to_vec();
+                Ok(self
+                    .target_shape
+                    .iter()
+                    .map(|&axis| if axis == -1 { inferred } else { axis as usize })
+                    .collect())
+            }
+            None => Err(MlError::ShapeMismatch(format!(
+                "can not reshape input shape {:?} into {:?}",
+                input_shape, self.target_shape
+            ))),
+        }
+    }
+
+    fn clone_layer(&self) -> Box<dyn Layer<F>> {
+        Box::new(self.clone())
+    }
+
+    fn get_shape(&self) -> (usize, usize) {
+        (self.size(), self.size())
+    }
+
+    fn get_name(&self) -> String {
+        "ReshapeLayer".to_string()
+    }
+
+    fn to_json(&self) -> Result<String, MlError> {
+        let model: ReshapeLayerModel = ReshapeLayerModel {
+            target_shape: self.target_shape.clone(),
+            input_shape: self.input_shape.clone(),
+        };
+        Ok(serde_json::to_string(&model)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layer::layer;
+    use crate::utils::gradient_check::{check_layer, max_relative_error, GradientCheck};
+    use ndarray::arr2;
+
+    #[test]
+    fn build_should_reject_invalid_target_shape() {
+        assert!(matches!(
+            ReshapeLayer::build(&[-1, 2, -1]),
+            Err(MlError::InvalidInput(_))
+        ));
+        assert!(matches!(
+            ReshapeLayer::build(&[0, 2]),
+            Err(MlError::InvalidInput(_))
+        ));
+        assert!(matches!(
+            ReshapeLayer::build(&[-2, 2]),
+            Err(MlError::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn compile_should_infer_missing_axis() {
+        let mut reshape_layer: ReshapeLayer = ReshapeLayer::build(&[-1, 3]).unwrap();
+        assert_eq!(
+            Layer::<f64>::compile(&mut reshape_layer, &[2, 3, 2]).unwrap(),
+            vec![4, 3]
+        );
+        assert!(matches!(
+            Layer::<f64>::compile(&mut reshape_layer, &[5, 2]),
+            Err(MlError::ShapeMismatch(_))
+        ));
+
+        let mut reshape_layer: ReshapeLayer = ReshapeLayer::build(&[2, 6]).unwrap();
+        assert_eq!(
+            Layer::<f64>::compile(&mut reshape_layer, &[12]).unwrap(),
+            vec![2, 6]
+        );
+        assert!(matches!(
+            Layer::<f64>::compile(&mut reshape_layer, &[3, 3]),
+            Err(MlError::ShapeMismatch(_))
+        ));
+    }
+
+    #[test]
+    fn propagation_should_keep_rows() {
+        let mut reshape_layer: ReshapeLayer = ReshapeLayer::build(&[2, -1]).unwrap();
+        Layer::<f64>::compile(&mut reshape_layer, &[4]).unwrap();
+        let x: Array2<f64> = arr2(&[[1.0, 2.0, 3.0, 4.0], [5.0, 6.0, 7.0, 8.0]]);
+        assert_eq!(reshape_layer.forward_propagation(&x).unwrap(), x);
+        assert_eq!(reshape_layer.backward_propagation(&x, 0.1).unwrap(), x);
+        let checks: Vec<GradientCheck> = check_layer(&mut reshape_layer, &x, 0.0001).unwrap();
+        assert!(max_relative_error(&checks) < 0.0001);
+        assert!(matches!(
+            reshape_layer.predict(&arr2(&[[1.0, 2.0]])),
+            Err(MlError::ShapeMismatch(_))
+        ));
+    }
+
+    #[test]
+    fn from_string_should_deserialize_layer() {
+        let mut reshape_layer: ReshapeLayer = ReshapeLayer::build(&[3, -1]).unwrap();
+        Layer::<f64>::compile(&mut reshape_layer, &[2, 3]).unwrap();
+        let json: String = Layer::<f64>::to_json(&reshape_layer).unwrap();
+        assert_eq!(json, "{\"target_shape\":[3,-1],\"input_shape\":[2,3]}");
+        let mut result: Box<dyn Layer<f64>> =
+            layer::from_string("ReshapeLayer".to_string(), &json).unwrap();
+        assert_eq!(result.get_shape(), (6, 6));
+        assert_eq!(result.compile(&[6]).unwrap(), vec![3, 2]);
+    }
+}
@@ -0,0 +1,356 @@
+use crate::layer::layer;
+use crate::layer::layer::Layer;
+use crate::utils::error::{check_shapes, MlError};
+use crate::utils::float::MlFloat;
+use ndarray::Array2;
+use serde::{Deserialize, Serialize};
+
+/// Adds the input of the block to the output of its inner layers. The optional projection is
+/// applied to the input first, when the inner layers change the shape of the samples.
+pub struct ResidualBlock<F: MlFloat = f64> {
+    layers: Vec<Box<dyn Layer<F>>>,
+    projection: Option<Box<dyn Layer<F>>>,
+    input_shape: Vec<usize>,
+    output_shape: Vec<usize>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ResidualBlockModel {
+    layers: Vec<(String, String)>,
+    projection: Option<(String, String)>,
+    input_shape: Vec<usize>,
+    output_shape: Vec<usize>,
+}
+
+impl<F: MlFloat> ResidualBlock<F> {
+    pub fn build(
+        layers: Vec<Box<dyn Layer<F>>>,
+        projection: Option<Box<dyn Layer<F>>>,
+    ) -> ResidualBlock<F> {
+        ResidualBlock {
+            layers,
+            projection,
+            input_shape: vec![],
+            output_shape: vec![],
+        }
+    }
+
+    pub fn from_json(json_str: &str) -> Result<ResidualBlock<F>, MlError> {
+        let model: ResidualBlockModel = serde_json::from_str(json_str)?;
+        let mut layers: Vec<Box<dyn Layer<F>>> = vec![];
+        for (name, layer_json) in model.layers {
+            layers.push(layer::from_string(name, layer_json.as_str())?);
+        }
+        let projection: Option<Box<dyn Layer<F>>> = model
+            .projection
+            .map(|(name, layer_json)| layer::from_string(name, layer_json.as_str()))
+            .transpose()?;
+        Ok(ResidualBlock {
+            layers,
+            projection,
+            input_shape: model.input_shape,
+            output_shape: model.output_shape,
+        })
+    }
+
+    fn layers_mut(&mut self) -> impl Iterator<Item = &mut Box<dyn Layer<F>>> {
+        self.layers.iter_mut().chain(self.projection.iter_mut())
+    }
+
+    fn layers(&self) -> impl Iterator<Item = &Box<dyn Layer<F>>> {
+        self.layers.iter().chain(self.projection.iter())
+    }
+}
+
+fn layer_to_model<F: MlFloat>(layer: &dyn Layer<F>) -> Result<(String, String), MlError> {
+    Ok((layer.get_name(), layer.to_json()?))
+}
+
+impl<F: MlFloat> Layer<F> for ResidualBlock<F> {
+    fn forward_propagation(&mut self, x: &Array2<F>) -> Result<Array2<F>, MlError> {
+        let output: Array2<F> = self
+            .layers
+            .iter_mut()
+            .try_fold(x.clone(), |output, layer| {
+                layer.forward_propagation(&output)
+            })?;
+        let shortcut: Array2<F> = match &mut self.projection {
+            Some(projection) => projection.forward_propagation(x)?,
+            None => x.clone(),
+        };
+        check_shapes(output.shape(), shortcut.shape())?;
+        Ok(output + shortcut)
+    }
+
+    fn predict(&self, x: &Array2<F>) -> Result<Array2<F>, MlError> {
+        let output: Array2<F> = self
+            .layers
+            .iter()
+            .try_fold(x.clone(), |output, layer| layer.predict(&output))?;
+        let shortcut: Array2<F> = match &self.projection {
+            Some(projection) => projection.predict(x)?,
+            None => x.clone(),
+        };
+        check_shapes(output.shape(), shortcut.shape())?;
+        Ok(output + shortcut)
+    }
+
+    /// The output error flows through the inner layers and through the shortcut, the input
+    /// error is the sum of both.
+    fn compute_gradients(&mut self, y: &Array2<F>) -> Result<Array2<F>, MlError> {
+        let mut error: Array2<F> = y.clone();
+        for layer in self.layers.iter_mut().rev() {
+            error = layer.compute_gradients(&error)?;
+        }
+        let shortcut_error: Array2<F> = match &mut self.projection {
+            Some(projection) => projection.compute_gradients(y)?,
+            None => y.clone(),
+        };
+        check_shapes(error.shape(), shortcut_error.shape())?;
+        Ok(error + shortcut_error)
+    }
+
+    fn apply_gradients(&mut self, learning_rate: F) {
+        self.layers_mut()
+            .for_each(|layer| layer.apply_gradients(learning_rate));
+    }
+
+    /// Gradients of the inner layers followed by the ones of the projection.
+    fn get_gradients(&self) -> Vec<Array2<F>> {
+        self.layers()
+            .flat_map(|layer| layer.get_gradients())
+            .collect()
+    }
+
+    fn set_gradients(&mut self, gradients: Vec<Array2<F>>) -> Result<(), MlError> {
+        let expected: usize = self.get_gradients().len();
+        if gradients.len() != expected {
+            return Err(MlError::InvalidInput(format!(
+                "ResidualBlock expects {} gradients, actually: {}",
+                expected,
+                gradients.len()
+            )));
+        }
+        let mut gradients = gradients.into_iter();
+        for layer in self.layers_mut() {
+            let count: usize = layer.get_gradients().len();
+            layer.set_gradients(gradients.by_ref().take(count).collect())?;
+        }
+        Ok(())
+    }
+
+    /// Parameters of the inner layers followed by the ones of the projection.
+    fn get_parameters(&self) -> Vec<Array2<F>> {
+        self.layers()
+            .flat_map(|layer| layer.get_parameters())
+            .collect()
+    }
+
+    fn set_parameters(&mut self, parameters: Vec<Array2<F>>) -> Result<(), MlError> {
+        let expected: usize = self.get_parameters().len();
+        if parameters.len() != expected {
+            return Err(MlError::InvalidInput(format!(
+                "ResidualBlock expects {} parameters, actually: {}",
+                expected,
+                parameters.len()
+            )));
+        }
+        let mut parameters = parameters.into_iter();
+        for layer in self.layers_mut() {
+            let count: usize = layer.get_parameters().len();
+            layer.set_parameters(parameters.by_ref().take(count).collect())?;
+        }
+        Ok(())
+    }
+
+    fn compile(&mut self, input_shape: &[usize]) -> Result<Vec<usize>, MlError> {
+        let mut output_shape: Vec<usize> = input_shape.to_vec();
+        for layer in self.layers.iter_mut() {
+            output_shape = layer.compile(&output_shape)?;
+        }
+        let shortcut_shape: Vec<usize> = match &mut self.projection {
+            Some(projection) => projection.compile(input_shape)?,
+            None => input_shape.to_vec(),
+        };
+        if output_shape != shortcut_shape {
+            return Err(MlError::ShapeMismatch(format!(
+                "block output shape {:?} does not match shortcut shape {:?}, a projection is needed",
+                output_shape, shortcut_shape
+            )));
+        }
+        self.input_shape = input_shape.to_vec();
+        self.output_shape = output_shape.clone();
+        Ok(output_shape)
+    }
+
+    fn is_stateful(&self) -> bool {
+        self.layers().any(|layer| layer.is_stateful())
+    }
+
+    fn count_parameters(&self) -> (usize, usize) {
+        self.layers()
+            .map(|layer| layer.count_parameters())
+            .fold((0, 0), |(trainable, frozen), (t, f)| {
+                (trainable + t, frozen + f)
+            })
+    }
+
+    fn clone_layer(&self) -> Box<dyn Layer<F>> {
+        Box::new(ResidualBlock {
+            layers: self
+                .layers
+                .iter()
+                .map(|layer| layer.clone_layer())
+                .collect(),
+            projection: self
+                .projection
+                .as_ref()
+                .map(|projection| projection.clone_layer()),
+            input_shape: self.input_shape.clone(),
+            output_shape: self.output_shape.clone(),
+        })
+    }
+
+    fn get_shape(&self) -> (usize, usize) {
+        (
+            self.input_shape.iter().product(),
+            self.output_shape.iter().product(),
+        )
+    }
+
+    fn get_name(&self) -> String {
+        "ResidualBlock".to_string()
+    }
+
+    fn to_json(&self) -> Result<String, MlError> {
+        let model: ResidualBlockModel = ResidualBlockModel {
+            layers: self
+                .layers
+                .iter()
+                .map(|layer| layer_to_model(layer.as_ref()))
+                .collect::<Result<Vec<(String, String)>, MlError>>()?,
+            projection: self
+                .projection
+                .as_ref()
+                .map(|projection| layer_to_model(projection.as_ref()))
+                .transpose()?,
+            input_shape: self.input_shape.clone(),
+            output_shape: self.output_shape.clone(),
+        };
+        Ok(serde_json::to_string(&model)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::activation::tanh::Tanh;
+    use crate::layer::activation_layer::ActivationLayer;
+    use crate::layer::fc_layer::FCLayer;
+    use crate::utils::gradient_check::{check_layer, max_relative_error, GradientCheck};
+    use ndarray::arr2;
+
+    fn generate_test_residual_block(projection: bool) -> ResidualBlock {
+        let output_size: usize = if projection { 3 } else { 2 };
+        let mut block: ResidualBlock = ResidualBlock::build(
+            vec![
+                Box::new(FCLayer::build_with_seed(2, 4, 1)),
+                Box::new(ActivationLayer::build(Box::new(Tanh))),
+                Box::new(FCLayer::build_with_seed(4, output_size, 2)),
+            ],
+            if projection {
+                Some(Box::new(FCLayer::build_with_seed(2, 3, 3)))
+            } else {
+                None
+            },
+        );
+        block.compile(&[2]).unwrap();
+        block
+    }
+
+    #[test]
+    fn forward_propagation_should_add_input_to_output() {
+        let mut block: ResidualBlock = generate_test_residual_block(false);
+        let x: Array2<f64> = arr2(&[[0.5, -1.0], [1.0, 2.0]]);
+        let inner: Array2<f64> = block
+            .layers
+            .iter()
+            .fold(x.clone(), |output, layer| layer.predict(&output).unwrap());
+        assert_eq!(block.forward_propagation(&x).unwrap(), &inner + &x);
+        assert_eq!(block.predict(&x).unwrap(), inner + x);
+    }
+
+    #[test]
+    fn compute_gradients_should_pass_gradient_check() {
+        for projection in [false, true] {
+            let mut block: ResidualBlock = generate_test_residual_block(projection);
+            let x: Array2<f64> = arr2(&[[0.5, -1.0], [1.0, 2.0]]);
+            let checks: Vec<GradientCheck> = check_layer(&mut block, &x, 0.0001).unwrap();
+            assert_eq!(checks.len(), if projection { 7 } else { 5 });
+            assert!(max_relative_error(&checks) < 0.0001);
+        }
+    }
+
+    #[test]
+    fn gradients_should_cover_inner_layers_and_projection() {
+        let mut block: ResidualBlock = generate_test_residual_block(true);
+        assert_eq!(block.get_gradients().len(), 6);
+        assert_eq!(block.count_parameters(), (8 + 4 + 12 + 3 + 6 + 3, 0));
+        let gradients: Vec<Array2<f64>> = block
+            .get_gradients()
+            .iter()
+            .map(|gradient| gradient.mapv(|_| 1.0))
+            .collect();
+        block.set_gradients(gradients).unwrap();
+        assert!(block
+            .get_gradients()
+            .iter()
+            .all(|gradient| gradient.iter().all(|&g| g == 1.0)));
+        assert!(matches!(
+            block.set_gradients(vec![]),
+            Err(MlError::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn compile_should_fail_without_projection_when_shapes_differ() {
+        let mut block: ResidualBlock =
+            ResidualBlock::build(vec![Box::new(FCLayer::build(2, 3))], None);
+        assert!(matches!(
+            block.compile(&[2]),
+            Err(MlError::ShapeMismatch(_))
+        ));
+        let mut block: ResidualBlock = ResidualBlock::build(
+            vec![Box::new(FCLayer::build(2, 3))],
+            Some(Box::new(FCLayer::build(2, 3))),
+        );
+        assert_eq!(block.compile(&[2]).unwrap(), vec![3]);
+        assert_eq!(block.get_shape(), (2, 3));
+    }
+
+    #[test]
+    fn from_json_should_deserialize_inner_layers() {
+        let mut nested: ResidualBlock =
+            ResidualBlock::build(vec![Box::new(generate_test_residual_block(true))], None);
+        assert!(matches!(
+            nested.compile(&[2]),
+            Err(MlError::ShapeMismatch(_))
+        ));
+        let mut nested: ResidualBlock = ResidualBlock::build(
+            vec![Box::new(generate_test_residual_block(true))],
+            Some(Box::new(FCLayer::build_with_seed(2, 3, 4))),
+        );
+        nested.compile(&[2]).unwrap();
+
+        let result: Box<dyn Layer<f64>> =
+            layer::from_string("ResidualBlock".to_string(), &nested.to_json().unwrap()).unwrap();
+        assert_eq!(result.get_name(), "ResidualBlock");
+        assert_eq!(result.get_shape(), (2, 3));
+        assert_eq!(result.count_parameters(), nested.count_parameters());
+        let x: Array2<f64> = arr2(&[[0.5, -1.0], [1.0, 2.0]]);
+        let expected: Array2<f64> = nested.predict(&x).unwrap();
+        for (e, r) in expected.iter().zip(result.predict(&x).unwrap().iter()) {
+            assert!((e - r).powf(2.0) < 0.00001);
+        }
+    }
+}
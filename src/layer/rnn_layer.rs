@@ -0,0 +1,92 @@
+use crate::layer::recurrent_layer::{Cell, RecurrentLayer, RecurrentWeights, StepOutput};
+use crate::utils::error::MlError;
+use crate::utils::float::MlFloat;
+use ndarray::Array2;
+
+/// `h = tanh(x . input + h . recurrent + bias)`
+#[derive(Debug, Clone)]
+pub struct RnnCell;
+
+impl Cell for RnnCell {
+    const GATES: usize = 1;
+    const STATES: usize = 1;
+    const NAME: &'static str = "RnnLayer";
+
+    fn forward<F: MlFloat>(
+        weights: &RecurrentWeights<F>,
+        x: &Array2<F>,
+        states: &[Array2<F>],
+    ) -> Result<StepOutput<F>, MlError> {
+        let h: Array2<F> = weights.linear(x, &states[0]).mapv(F::tanh);
+        Ok((vec![h.clone()], vec![h]))
+    }
+
+    fn backward<F: MlFloat>(
+        weights: &RecurrentWeights<F>,
+        gradients: &mut RecurrentWeights<F>,
+        x: &Array2<F>,
+        states: &[Array2<F>],
+        cache: &[Array2<F>],
+        errors: &[Array2<F>],
+    ) -> Result<(Array2<F>, Vec<Array2<F>>), MlError> {
+        let dz: Array2<F> = &errors[0] * &cache[0].mapv(|h| F::one() - h * h);
+        gradients.accumulate(x, &states[0], &dz);
+        Ok((
+            dz.dot(&weights.input.t()),
+            vec![dz.dot(&weights.recurrent.t())],
+        ))
+    }
+}
+
+/// Fully connected recurrent layer with a tanh activation.
+pub type RnnLayer<F = f64> = RecurrentLayer<F, RnnCell>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layer::layer;
+    use crate::layer::layer::Layer;
+    use crate::utils::gradient_check::{check_layer, max_relative_error, GradientCheck};
+    use ndarray::arr2;
+
+    #[test]
+    fn forward_propagation_should_apply_cell_over_timesteps() {
+        let mut rnn_layer: RnnLayer = RnnLayer::from_json(
+            "{\"input_weights\":[0.5],\"recurrent_weights\":[2.0],\"bias\":[0.1],\"features\":1,\
+             \"units\":1,\"timesteps\":2,\"return_sequences\":true,\"stateful\":false,\
+             \"truncation\":null}",
+        )
+        .unwrap();
+        let output: Array2<f64> = rnn_layer
+            .forward_propagation(&arr2(&[[1.0, -1.0]]))
+            .unwrap();
+        let h_1: f64 = (0.5_f64 + 0.1).tanh();
+        let h_2: f64 = (-0.5 + 2.0 * h_1 + 0.1).tanh();
+        assert!((output[[0, 0]] - h_1).powf(2.0) < 0.00001);
+        assert!((output[[0, 1]] - h_2).powf(2.0) < 0.00001);
+    }
+
+    #[test]
+    fn compute_gradients_should_pass_gradient_check() {
+        let x: Array2<f64> =
+            Array2::from_shape_fn((2, 8), |(b, i)| ((b * 8 + i) % 5) as f64 / 4.0 - 0.5);
+        for return_sequences in [false, true] {
+            let mut rnn_layer: RnnLayer = RnnLayer::build_with_seed(2, 3, 1);
+            rnn_layer.set_return_sequences(return_sequences);
+            rnn_layer.compile(&[4, 2]).unwrap();
+            let checks: Vec<GradientCheck> = check_layer(&mut rnn_layer, &x, 0.0001).unwrap();
+            assert_eq!(checks.len(), 4);
+            assert!(max_relative_error(&checks) < 0.0001);
+        }
+    }
+
+    #[test]
+    fn from_string_should_deserialize_layer() {
+        let mut rnn_layer: RnnLayer = RnnLayer::build_with_seed(2, 3, 1);
+        rnn_layer.compile(&[4, 2]).unwrap();
+        let result: Box<dyn Layer<f64>> =
+            layer::from_string("RnnLayer".to_string(), &rnn_layer.to_json().unwrap()).unwrap();
+        assert_eq!(result.get_name(), "RnnLayer");
+        assert_eq!(result.get_shape(), (8, 3));
+    }
+}
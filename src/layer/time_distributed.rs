@@ -0,0 +1,262 @@
+use crate::layer::layer;
+use crate::layer::layer::Layer;
+use crate::utils::error::{check_shapes, MlError};
+use crate::utils::float::MlFloat;
+use ndarray::Array2;
+use serde::{Deserialize, Serialize};
+
+/// Applies the same layer independently at every timestep of samples of shape
+/// `(timesteps, ...)`. The timesteps are stacked as rows of a larger batch, so the gradients of
+/// the wrapped layer are summed over them.
+pub struct TimeDistributed<F: MlFloat = f64> {
+    layer: Box<dyn Layer<F>>,
+    input_shape: Vec<usize>,
+    output_shape: Vec<usize>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TimeDistributedModel {
+    layer: (String, String),
+    input_shape: Vec<usize>,
+    output_shape: Vec<usize>,
+}
+
+/// Turns a batch of sequences into a batch of timesteps, or back.
+fn reshape<F: MlFloat>(x: &Array2<F>, rows: usize, columns: usize) -> Result<Array2<F>, MlError> {
+    Ok(x.as_standard_layout()
+        .into_owned()
+        .into_shape((rows, columns))?)
+}
+
+impl<F: MlFloat> TimeDistributed<F> {
+    pub fn build(layer: Box<dyn Layer<F>>) -> TimeDistributed<F> {
+        TimeDistributed {
+            layer,
+            input_shape: vec![],
+            output_shape: vec![],
+        }
+    }
+
+    pub fn from_json(json_str: &str) -> Result<TimeDistributed<F>, MlError> {
+        let model: TimeDistributedModel = serde_json::from_str(json_str)?;
+        let (name, layer_json): (String, String) = model.layer;
+        Ok(TimeDistributed {
+            layer: layer::from_string(name, layer_json.as_str())?,
+            input_shape: model.input_shape,
+            output_shape: model.output_shape,
+        })
+    }
+
+    fn timesteps(&self) -> usize {
+        self.input_shape.first().copied().unwrap_or(0)
+    }
+
+    fn step_sizes(&self) -> (usize, usize) {
+        (
+            self.input_shape.iter().skip(1).product(),
+            self.output_shape.iter().skip(1).product(),
+        )
+    }
+
+    fn check_input(&self, x: &Array2<F>) -> Result<(), MlError> {
+        if self.input_shape.is_empty() {
+            return Err(MlError::ShapeMismatch(
+                "TimeDistributed must be compiled before use".to_string(),
+            ));
+        }
+        check_shapes(&x.shape()[1..], &[self.input_shape.iter().product()])
+    }
+}
+
+impl<F: MlFloat> Layer<F> for TimeDistributed<F> {
+    fn forward_propagation(&mut self, x: &Array2<F>) -> Result<Array2<F>, MlError> {
+        self.check_input(x)?;
+        let (input_size, output_size): (usize, usize) = self.step_sizes();
+        let (batch, timesteps): (usize, usize) = (x.shape()[0], self.timesteps());
+        let steps: Array2<F> = reshape(x, batch * timesteps, input_size)?;
+        let output: Array2<F> = self.layer.forward_propagation(&steps)?;
+        reshape(&output, batch, timesteps * output_size)
+    }
+
+    fn predict(&self, x: &Array2<F>) -> Result<Array2<F>, MlError> {
+        self.check_input(x)?;
+        let (input_size, output_size): (usize, usize) = self.step_sizes();
+        let (batch, timesteps): (usize, usize) = (x.shape()[0], self.timesteps());
+        let steps: Array2<F> = reshape(x, batch * timesteps, input_size)?;
+        let output: Array2<F> = self.layer.predict(&steps)?;
+        reshape(&output, batch, timesteps * output_size)
+    }
+
+    fn compute_gradients(&mut self, y: &Array2<F>) -> Result<Array2<F>, MlError> {
+        check_shapes(&y.shape()[1..], &[self.output_shape.iter().product()])?;
+        let (input_size, output_size): (usize, usize) = self.step_sizes();
+        let (batch, timesteps): (usize, usize) = (y.shape()[0], self.timesteps());
+        let errors: Array2<F> = reshape(y, batch * timesteps, output_size)?;
+        let input_error: Array2<F> = self.layer.compute_gradients(&errors)?;
+        reshape(&input_error, batch, timesteps * input_size)
+    }
+
+    fn apply_gradients(&mut self, learning_rate: F) {
+        self.layer.apply_gradients(learning_rate);
+    }
+
+    fn get_gradients(&self) -> Vec<Array2<F>> {
+        self.layer.get_gradients()
+    }
+
+    fn set_gradients(&mut self, gradients: Vec<Array2<F>>) -> Result<(), MlError> {
+        self.layer.set_gradients(gradients)
+    }
+
+    fn get_parameters(&self) -> Vec<Array2<F>> {
+        self.layer.get_parameters()
+    }
+
+    fn set_parameters(&mut self, parameters: Vec<Array2<F>>) -> Result<(), MlError> {
+        self.layer.set_parameters(parameters)
+    }
+
+    fn compile(&mut self, input_shape: &[usize]) -> Result<Vec<usize>, MlError> {
+        if input_shape.len() < 2 || input_shape[0] == 0 {
+            return Err(MlError::ShapeMismatch(format!(
+                "expected input shape [timesteps, ...], actually: {:?}",
+                input_shape
+            )));
+        }
+        let mut output_shape: Vec<usize> = vec![input_shape[0]];
+        output_shape.extend(self.layer.compile(&input_shape[1..])?);
+        self.input_shape = input_shape.to_vec();
+        self.output_shape = output_shape.clone();
+        Ok(output_shape)
+    }
+
+    fn is_stateful(&self) -> bool {
+        self.layer.is_stateful()
+    }
+
+    fn count_parameters(&self) -> (usize, usize) {
+        self.layer.count_parameters()
+    }
+
+    fn clone_layer(&self) -> Box<dyn Layer<F>> {
+        Box::new(TimeDistributed {
+            layer: self.layer.clone_layer(),
+            input_shape: self.input_shape.clone(),
+            output_shape: self.output_shape.clone(),
+        })
+    }
+
+    fn get_shape(&self) -> (usize, usize) {
+        (
+            self.input_shape.iter().product(),
+            self.output_shape.iter().product(),
+        )
+    }
+
+    fn get_name(&self) -> String {
+        "TimeDistributed".to_string()
+    }
+
+    fn to_json(&self) -> Result<String, MlError> {
+        let model: TimeDistributedModel = TimeDistributedModel {
+            layer: (self.layer.get_name(), self.layer.to_json()?),
+            input_shape: self.input_shape.clone(),
+            output_shape: self.output_shape.clone(),
+        };
+        Ok(serde_json::to_string(&model)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::activation::tanh::Tanh;
+    use crate::layer::activation_layer::ActivationLayer;
+    use crate::layer::fc_layer::FCLayer;
+    use crate::layer::lstm_layer::LstmLayer;
+    use crate::loss::mse::Mse;
+    use crate::network::sequential::Sequential;
+    use crate::utils::gradient_check::{check_layer, max_relative_error, GradientCheck};
+    use ndarray::{s, Array3};
+
+    #[test]
+    fn predict_should_apply_layer_at_every_timestep() {
+        let fc_layer: FCLayer = FCLayer::build_with_seed(2, 3, 1);
+        let mut time_distributed: TimeDistributed = TimeDistributed::build(Box::new(fc_layer));
+        assert_eq!(time_distributed.compile(&[4, 2]).unwrap(), vec![4, 3]);
+        let fc_layer: FCLayer = FCLayer::build_with_seed(2, 3, 1);
+        let x: Array2<f64> =
+            Array2::from_shape_fn((2, 8), |(b, i)| ((b * 8 + i) % 5) as f64 / 4.0 - 0.5);
+
+        let output: Array2<f64> = time_distributed.predict(&x).unwrap();
+        for t in 0..4 {
+            let step: Array2<f64> = x.slice(s![.., 2 * t..2 * t + 2]).to_owned();
+            assert_eq!(
+                output.slice(s![.., 3 * t..3 * t + 3]),
+                fc_layer.predict(&step).unwrap()
+            );
+        }
+        assert!(matches!(
+            time_distributed.predict(&Array2::zeros((2, 6))),
+            Err(MlError::ShapeMismatch(_))
+        ));
+    }
+
+    #[test]
+    fn compute_gradients_should_pass_gradient_check() {
+        let x: Array2<f64> =
+            Array2::from_shape_fn((2, 8), |(b, i)| ((b * 8 + i) % 5) as f64 / 4.0 - 0.5);
+        let layers: Vec<Box<dyn Layer>> = vec![
+            Box::new(FCLayer::build_with_seed(2, 3, 1)),
+            Box::new(ActivationLayer::build(Box::new(Tanh))),
+        ];
+        for layer in layers {
+            let mut time_distributed: TimeDistributed = TimeDistributed::build(layer);
+            time_distributed.compile(&[4, 2]).unwrap();
+            let checks: Vec<GradientCheck> =
+                check_layer(&mut time_distributed, &x, 0.0001).unwrap();
+            assert!(max_relative_error(&checks) < 0.0001);
+        }
+    }
+
+    #[test]
+    fn compile_should_follow_recurrent_layer_returning_sequences() {
+        let mut lstm_layer: LstmLayer = LstmLayer::build_with_seed(1, 4, 1);
+        lstm_layer.set_return_sequences(true);
+        let mut network: Sequential = Sequential::build(Box::new(Mse));
+        network.add_layer(Box::new(lstm_layer));
+        network.add_layer(Box::new(TimeDistributed::build(Box::new(
+            FCLayer::build_with_seed(4, 1, 2),
+        ))));
+        network.compile(&[5, 1]).unwrap();
+        let x: Array3<f64> = Array3::zeros((3, 5, 1));
+        assert_eq!(network.predict(&x).unwrap().shape(), &[3, 5]);
+        assert!(matches!(
+            TimeDistributed::<f64>::build(Box::new(FCLayer::build_with_seed(4, 1, 2)))
+                .compile(&[4]),
+            Err(MlError::ShapeMismatch(_))
+        ));
+    }
+
+    #[test]
+    fn from_string_should_deserialize_wrapped_layer() {
+        let mut time_distributed: TimeDistributed =
+            TimeDistributed::build(Box::new(FCLayer::build_with_seed(2, 3, 1)));
+        time_distributed.compile(&[4, 2]).unwrap();
+        let result: Box<dyn Layer<f64>> = layer::from_string(
+            "TimeDistributed".to_string(),
+            &time_distributed.to_json().unwrap(),
+        )
+        .unwrap();
+        assert_eq!(result.get_name(), "TimeDistributed");
+        assert_eq!(result.get_shape(), (8, 12));
+        assert_eq!(result.count_parameters(), (9, 0));
+        let x: Array2<f64> = Array2::ones((1, 8));
+        let expected: Array2<f64> = time_distributed.predict(&x).unwrap();
+        let actual: Array2<f64> = result.predict(&x).unwrap();
+        assert!(expected
+            .iter()
+            .zip(actual.iter())
+            .all(|(e, a)| (e - a).powf(2.0) < 0.00001));
+    }
+}
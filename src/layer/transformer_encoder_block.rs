@@ -0,0 +1,348 @@
+use crate::activation::relu::Relu;
+use crate::layer::activation_layer::ActivationLayer;
+use crate::layer::fc_layer::FCLayer;
+use crate::layer::layer;
+use crate::layer::layer::Layer;
+use crate::layer::layer_norm_layer::LayerNormLayer;
+use crate::layer::multi_head_attention::MultiHeadAttention;
+use crate::layer::residual_block::ResidualBlock;
+use crate::layer::time_distributed::TimeDistributed;
+use crate::utils::error::MlError;
+use crate::utils::float::MlFloat;
+use ndarray::Array2;
+use serde::{Deserialize, Serialize};
+
+/// Encoder block of a transformer over samples of shape `(timesteps, model_size)`: a residual
+/// multi-head self-attention followed by a layer norm, then a residual feed forward network
+/// applied at every timestep followed by a second layer norm.
+pub struct TransformerEncoderBlock<F: MlFloat = f64> {
+    layers: Vec<Box<dyn Layer<F>>>,
+    input_shape: Vec<usize>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TransformerEncoderBlockModel {
+    layers: Vec<(String, String)>,
+    input_shape: Vec<usize>,
+}
+
+impl<F: MlFloat> TransformerEncoderBlock<F> {
+    pub fn build(
+        model_size: usize,
+        heads: usize,
+        feed_forward_size: usize,
+    ) -> Result<TransformerEncoderBlock<F>, MlError> {
+        Ok(TransformerEncoderBlock::build_with_layers(
+            MultiHeadAttention::build(model_size, heads)?,
+            FCLayer::build(model_size, feed_forward_size),
+            FCLayer::build(feed_forward_size, model_size),
+        ))
+    }
+
+    pub fn build_with_seed(
+        model_size: usize,
+        heads: usize,
+        feed_forward_size: usize,
+        seed: u64,
+    ) -> Result<TransformerEncoderBlock<F>, MlError> {
+        Ok(TransformerEncoderBlock::build_with_layers(
+            MultiHeadAttention::build_with_seed(model_size, heads, seed)?,
+            FCLayer::build_with_seed(model_size, feed_forward_size, seed + 1),
+            FCLayer::build_with_seed(feed_forward_size, model_size, seed + 2),
+        ))
+    }
+
+    fn build_with_layers(
+        attention: MultiHeadAttention<F>,
+        expansion: FCLayer<F>,
+        contraction: FCLayer<F>,
+    ) -> TransformerEncoderBlock<F> {
+        let model_size: usize = contraction.get_shape().1;
+        TransformerEncoderBlock {
+            layers: vec![
+                Box::new(ResidualBlock::build(vec![Box::new(attention)], None)),
+                Box::new(LayerNormLayer::build(model_size)),
+                Box::new(ResidualBlock::build(
+                    vec![
+                        Box::new(TimeDistributed::build(Box::new(expansion))),
+                        Box::new(ActivationLayer::build(Box::new(Relu))),
+                        Box::new(TimeDistributed::build(Box::new(contraction))),
+                    ],
+                    None,
+                )),
+                Box::new(LayerNormLayer::build(model_size)),
+            ],
+            input_shape: vec![],
+        }
+    }
+
+    pub fn from_json(json_str: &str) -> Result<TransformerEncoderBlock<F>, MlError> {
+        let model: TransformerEncoderBlockModel = serde_json::from_str(json_str)?;
+        let mut layers: Vec<Box<dyn Layer<F>>> = vec![];
+        for (name, layer_json) in model.layers {
+            layers.push(layer::from_string(name, layer_json.as_str())?);
+        }
+        Ok(TransformerEncoderBlock {
+            layers,
+            input_shape: model.input_shape,
+        })
+    }
+}
+
+impl<F: MlFloat> Layer<F> for TransformerEncoderBlock<F> {
+    fn forward_propagation(&mut self, x: &Array2<F>) -> Result<Array2<F>, MlError> {
+        self.layers.iter_mut().try_fold(x.clone(), |output, layer| {
+            layer.forward_propagation(&output)
+        })
+    }
+
+    fn predict(&self, x: &Array2<F>) -> Result<Array2<F>, MlError> {
+        self.layers
+            .iter()
+            .try_fold(x.clone(), |output, layer| layer.predict(&output))
+    }
+
+    fn compute_gradients(&mut self, y: &Array2<F>) -> Result<Array2<F>, MlError> {
+        self.layers
+            .iter_mut()
+            .rev()
+            .try_fold(y.clone(), |error, layer| layer.compute_gradients(&error))
+    }
+
+    fn apply_gradients(&mut self, learning_rate: F) {
+        self.layers
+            .iter_mut()
+            .for_each(|layer| layer.apply_gradients(learning_rate));
+    }
+
+    fn get_gradients(&self) -> Vec<Array2<F>> {
+        self.layers
+            .iter()
+            .flat_map(|layer| layer.get_gradients())
+            .collect()
+    }
+
+    fn set_gradients(&mut self, gradients: Vec<Array2<F>>) -> Result<(), MlError> {
+        let expected: usize = self.get_gradients().len();
+        if gradients.len() != expected {
+            return Err(MlError::InvalidInput(format!(
+                "TransformerEncoderBlock expects {} gradients, actually: {}",
+                expected,
+                gradients.len()
+            )));
+        }
+        let mut gradients = gradients.into_iter();
+        for layer in self.layers.iter_mut() {
+            let count: usize = layer.get_gradients().len();
+            layer.set_gradients(gradients.by_ref().take(count).collect())?;
+        }
+        Ok(())
+    }
+
+    fn get_parameters(&self) -> Vec<Array2<F>> {
+        self.layers
+            .iter()
+            .flat_map(|layer| layer.get_parameters())
+            .collect()
+    }
+
+    fn set_parameters(&mut self, parameters: Vec<Array2<F>>) -> Result<(), MlError> {
+        let expected: usize = self.get_parameters().len();
+        if parameters.len() != expected {
+            return Err(MlError::InvalidInput(format!(
+                "TransformerEncoderBlock expects {} parameters, actually: {}",
+                expected,
+                parameters.len()
+            )));
+        }
+        let mut parameters = parameters.into_iter();
+        for layer in self.layers.iter_mut() {
+            let count: usize = layer.get_parameters().len();
+            layer.set_parameters(parameters.by_ref().take(count).collect())?;
+        }
+        Ok(())
+    }
+
+    fn compile(&mut self, input_shape: &[usize]) -> Result<Vec<usize>, MlError> {
+        let mut output_shape: Vec<usize> = input_shape.to_vec();
+        for layer in self.layers.iter_mut() {
+            output_shape = layer.compile(&output_shape)?;
+        }
+        self.input_shape = input_shape.to_vec();
+        Ok(output_shape)
+    }
+
+    fn is_stateful(&self) -> bool {
+        self.layers.iter().any(|layer| layer.is_stateful())
+    }
+
+    fn count_parameters(&self) -> (usize, usize) {
+        self.layers
+            .iter()
+            .map(|layer| layer.count_parameters())
+            .fold((0, 0), |(trainable, frozen), (t, f)| {
+                (trainable + t, frozen + f)
+            })
+    }
+
+    fn clone_layer(&self) -> Box<dyn Layer<F>> {
+        Box::new(TransformerEncoderBlock {
+            layers: self
+                .layers
+                .iter()
+                .map(|layer| layer.clone_layer())
+                .collect(),
+            input_shape: self.input_shape.clone(),
+        })
+    }
+
+    fn get_shape(&self) -> (usize, usize) {
+        let size: usize = self.input_shape.iter().product();
+        (size, size)
+    }
+
+    fn get_name(&self) -> String {
+        "TransformerEncoderBlock".to_string()
+    }
+
+    fn to_json(&self) -> Result<String, MlError> {
+        let model: TransformerEncoderBlockModel = TransformerEncoderBlockModel {
+            layers: self
+                .layers
+                .iter()
+                .map(|layer| Ok((layer.get_name(), layer.to_json()?)))
+                .collect::<Result<Vec<(String, String)>, MlError>>()?,
+            input_shape: self.input_shape.clone(),
+        };
+        Ok(serde_json::to_string(&model)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::accuracy::accuracy::argmax_rows;
+    use crate::activation::softmax::Softmax;
+    use crate::layer::embedding_layer::EmbeddingLayer;
+    use crate::layer::flatten_layer::FlattenLayer;
+    use crate::layer::positional_encoding_layer::PositionalEncodingLayer;
+    use crate::loss::categorical_cross_entropy::CategoricalCrossEntropy;
+    use crate::network::mlp::Mlp;
+    use crate::report::report::Report;
+    use crate::utils::gradient_check::{check_layer, max_relative_error, GradientCheck};
+    use ndarray::Array1;
+    use std::fs;
+
+    #[test]
+    fn compile_should_keep_input_shape() {
+        let mut block: TransformerEncoderBlock =
+            TransformerEncoderBlock::build_with_seed(4, 2, 8, 1).unwrap();
+        assert_eq!(block.compile(&[3, 4]).unwrap(), vec![3, 4]);
+        // attention, layer norms and feed forward network
+        assert_eq!(
+            block.count_parameters(),
+            (4 * 4 * 4 + 4 * 4 + 2 * 8 + (4 * 8 + 8) + (8 * 4 + 4), 0)
+        );
+        assert!(matches!(
+            block.compile(&[3, 5]),
+            Err(MlError::ShapeMismatch(_))
+        ));
+    }
+
+    #[test]
+    fn compute_gradients_should_pass_gradient_check() {
+        let mut block: TransformerEncoderBlock =
+            TransformerEncoderBlock::build_with_seed(4, 2, 8, 1).unwrap();
+        block.compile(&[3, 4]).unwrap();
+        let x: Array2<f64> =
+            Array2::from_shape_fn((2, 12), |(b, i)| ((b * 12 + i) % 7) as f64 / 3.0 - 1.0);
+        let checks: Vec<GradientCheck> = check_layer(&mut block, &x, 0.0001).unwrap();
+        assert_eq!(checks.len(), 17);
+        assert!(max_relative_error(&checks) < 0.0001);
+    }
+
+    #[test]
+    fn transformer_should_learn_to_reverse_sequences() {
+        let (tokens, timesteps): (usize, usize) = (4, 4);
+        let x: Array2<f64> =
+            Array2::from_shape_fn((tokens.pow(timesteps as u32), timesteps), |(i, t)| {
+                ((i / tokens.pow(t as u32)) % tokens) as f64
+            });
+        // one-hot encoding of the reversed sequence
+        let y: Array2<f64> = Array2::from_shape_fn((x.shape()[0], timesteps * tokens), |(i, j)| {
+            let reversed: f64 = x[[i, timesteps - 1 - j / tokens]];
+            if reversed == (j % tokens) as f64 {
+                1.0
+            } else {
+                0.0
+            }
+        });
+        let mut mlp: Mlp = Mlp::build(Box::new(CategoricalCrossEntropy));
+        mlp.add_layer(Box::new(EmbeddingLayer::build_with_seed(tokens, 8, 1)));
+        mlp.add_layer(Box::new(PositionalEncodingLayer::sinusoidal()));
+        mlp.add_layer(Box::new(
+            TransformerEncoderBlock::build_with_seed(8, 2, 16, 2).unwrap(),
+        ));
+        mlp.add_layer(Box::new(TimeDistributed::build(Box::new(
+            FCLayer::build_with_seed(8, tokens, 5),
+        ))));
+        mlp.add_layer(Box::new(TimeDistributed::build(Box::new(
+            ActivationLayer::build(Box::new(Softmax)),
+        ))));
+        mlp.add_layer(Box::new(FlattenLayer::new()));
+        assert_eq!(mlp.compile(&[timesteps]).unwrap(), vec![timesteps * tokens]);
+
+        mlp.fit(
+            &x,
+            &y,
+            None,
+            None,
+            30,
+            0.08,
+            8,
+            &mut [],
+            &mut Report::build("./test_report_transformer_1"),
+            1000,
+        )
+        .unwrap();
+        fs::remove_dir_all("./test_report_transformer_1").ok();
+
+        // one row per timestep to compare the predicted tokens
+        let steps: usize = x.len();
+        let predicted: Array1<usize> = argmax_rows(
+            &mlp.predict(&x)
+                .unwrap()
+                .into_shape((steps, tokens))
+                .unwrap(),
+        )
+        .unwrap();
+        let expected: Array1<usize> = argmax_rows(&y.into_shape((steps, tokens)).unwrap()).unwrap();
+        let correct: usize = predicted
+            .iter()
+            .zip(expected.iter())
+            .filter(|(p, e)| p == e)
+            .count();
+        assert!(correct as f64 / steps as f64 > 0.9);
+    }
+
+    #[test]
+    fn from_string_should_deserialize_block() {
+        let mut block: TransformerEncoderBlock =
+            TransformerEncoderBlock::build_with_seed(4, 2, 8, 1).unwrap();
+        block.compile(&[3, 4]).unwrap();
+        let result: Box<dyn Layer<f64>> = layer::from_string(
+            "TransformerEncoderBlock".to_string(),
+            &block.to_json().unwrap(),
+        )
+        .unwrap();
+        assert_eq!(result.get_shape(), (12, 12));
+        let x: Array2<f64> =
+            Array2::from_shape_fn((2, 12), |(b, i)| ((b * 12 + i) % 7) as f64 / 3.0 - 1.0);
+        let expected: Array2<f64> = block.predict(&x).unwrap();
+        let actual: Array2<f64> = result.predict(&x).unwrap();
+        assert!(expected
+            .iter()
+            .zip(actual.iter())
+            .all(|(e, a)| (e - a).powf(2.0) < 0.00001));
+    }
+}
@@ -0,0 +1,258 @@
+use crate::layer::layer::Layer;
+use crate::utils::error::MlError;
+use crate::utils::float::MlFloat;
+use ndarray::Array2;
+use serde::{Deserialize, Serialize};
+
+/// How the values between the input pixels are computed, a copy of the nearest pixel or a
+/// bilinear interpolation of the four closest ones.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Interpolation {
+    Nearest,
+    Bilinear,
+}
+
+/// Scales the height and the width of feature maps of shape `(h, w, c)` flattened in rows by
+/// integer factors. Bilinear interpolation aligns the centers of the pixels and repeats the
+/// border pixels.
+#[derive(Debug, Clone)]
+pub struct UpSamplingLayer {
+    size: (usize, usize),
+    interpolation: Interpolation,
+    input_shape: (usize, usize, usize),
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UpSamplingLayerModel {
+    size: (usize, usize),
+    interpolation: Interpolation,
+    input_shape: (usize, usize, usize),
+}
+
+/// Input pixels along an axis each output pixel is interpolated from, with their weight.
+type Weights = Vec<Vec<(usize, f64)>>;
+
+impl UpSamplingLayer {
+    /// The shape of the feature maps is inferred when the network is compiled.
+    pub fn build(size: (usize, usize), interpolation: Interpolation) -> UpSamplingLayer {
+        UpSamplingLayer {
+            size,
+            interpolation,
+            input_shape: (0, 0, 0),
+        }
+    }
+
+    pub fn from_json(json_str: &str) -> Result<UpSamplingLayer, MlError> {
+        let model: UpSamplingLayerModel = serde_json::from_str(json_str)?;
+        let mut layer: UpSamplingLayer = UpSamplingLayer::build(model.size, model.interpolation);
+        layer.input_shape = model.input_shape;
+        Ok(layer)
+    }
+
+    fn output_shape(&self) -> (usize, usize, usize) {
+        let (h, w, c): (usize, usize, usize) = self.input_shape;
+        (h * self.size.0, w * self.size.1, c)
+    }
+
+    fn axis_weights(&self, length: usize, factor: usize) -> Weights {
+        (0..length * factor)
+            .map(|o| match self.interpolation {
+                Interpolation::Nearest => vec![(o / factor, 1.0)],
+                Interpolation::Bilinear => {
+                    let source: f64 = ((o as f64 + 0.5) / factor as f64 - 0.5)
+                        .clamp(0.0, length.saturating_sub(1) as f64);
+                    let low: usize = source.floor() as usize;
+                    let high: usize = (low + 1).min(length - 1);
+                    let t: f64 = source - low as f64;
+                    vec![(low, 1.0 - t), (high, t)]
+                }
+            })
+            .collect()
+    }
+
+    /// Output pixels as weighted sums of input pixels, `(output pixel, input pixel, weight)`.
+    fn weights(&self) -> Vec<(usize, usize, f64)> {
+        let (h, w, _): (usize, usize, usize) = self.input_shape;
+        let (_, ow, _): (usize, usize, usize) = self.output_shape();
+        let (rows, columns): (Weights, Weights) = (
+            self.axis_weights(h, self.size.0),
+            self.axis_weights(w, self.size.1),
+        );
+        let mut weights: Vec<(usize, usize, f64)> = vec![];
+        for (i, row) in rows.iter().enumerate() {
+            for (j, column) in columns.iter().enumerate() {
+                for &(r, row_weight) in row {
+                    for &(col, column_weight) in column {
+                        if row_weight * column_weight != 0.0 {
+                            weights.push((i * ow + j, r * w + col, row_weight * column_weight));
+                        }
+                    }
+                }
+            }
+        }
+        weights
+    }
+
+    /// Interpolates the output pixels of `x`, or sends the errors `x` of the output pixels back
+    /// to the input pixels when `backward`.
+    fn resample<F: MlFloat>(
+        &self,
+        x: &Array2<F>,
+        to_size: usize,
+        backward: bool,
+    ) -> Result<Array2<F>, MlError> {
+        let c: usize = self.input_shape.2;
+        let mut output: Array2<F> = Array2::zeros((x.shape()[0], to_size * c));
+        for (o, i, weight) in self.weights() {
+            let (to, from): (usize, usize) = if backward { (i, o) } else { (o, i) };
+            let weight: F = F::cast(weight);
+            for ch in 0..c {
+                let mut column = output.column_mut(to * c + ch);
+                column.scaled_add(weight, &x.column(from * c + ch));
+            }
+        }
+        Ok(output)
+    }
+}
+
+impl<F: MlFloat> Layer<F> for UpSamplingLayer {
+    fn forward_propagation(&mut self, x: &Array2<F>) -> Result<Array2<F>, MlError> {
+        Layer::<F>::predict(self, x)
+    }
+
+    fn predict(&self, x: &Array2<F>) -> Result<Array2<F>, MlError> {
+        let (input_size, _): (usize, usize) = Layer::<F>::get_shape(self);
+        if x.shape()[1] != input_size {
+            return Err(MlError::ShapeMismatch(format!(
+                "UpSamplingLayer expects {} input columns, actually: {}",
+                input_size,
+                x.shape()[1]
+            )));
+        }
+        let (oh, ow, _): (usize, usize, usize) = self.output_shape();
+        self.resample(x, oh * ow, false)
+    }
+
+    /// Sends each output error back to the input pixels it was interpolated from.
+    fn compute_gradients(&mut self, y: &Array2<F>) -> Result<Array2<F>, MlError> {
+        let (_, output_size): (usize, usize) = Layer::<F>::get_shape(self);
+        if y.shape()[1] != output_size {
+            return Err(MlError::ShapeMismatch(format!(
+                "UpSamplingLayer expects {} output errors, actually: {}",
+                output_size,
+                y.shape()[1]
+            )));
+        }
+        let (h, w, _): (usize, usize, usize) = self.input_shape;
+        self.resample(y, h * w, true)
+    }
+
+    fn compile(&mut self, input_shape: &[usize]) -> Result<Vec<usize>, MlError> {
+        match *input_shape {
+            [h, w, c] if h > 0 && w > 0 && self.size.0 > 0 && self.size.1 > 0 => {
+                self.input_shape = (h, w, c);
+                let (oh, ow, c): (usize, usize, usize) = self.output_shape();
+                Ok(vec![oh, ow, c])
+            }
+            _ => Err(MlError::ShapeMismatch(format!(
+                "expected input shape [h, w, c] and a size of at least (1, 1), actually: {:?} and {:?}",
+                input_shape, self.size
+            ))),
+        }
+    }
+
+    fn clone_layer(&self) -> Box<dyn Layer<F>> {
+        Box::new(self.clone())
+    }
+
+    fn get_shape(&self) -> (usize, usize) {
+        let (h, w, c): (usize, usize, usize) = self.input_shape;
+        let (oh, ow, c_out): (usize, usize, usize) = self.output_shape();
+        (h * w * c, oh * ow * c_out)
+    }
+
+    fn get_name(&self) -> String {
+        "UpSamplingLayer".to_string()
+    }
+
+    fn to_json(&self) -> Result<String, MlError> {
+        let model: UpSamplingLayerModel = UpSamplingLayerModel {
+            size: self.size,
+            interpolation: self.interpolation,
+            input_shape: self.input_shape,
+        };
+        Ok(serde_json::to_string(&model)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layer::layer;
+    use crate::utils::gradient_check::{check_layer, max_relative_error, GradientCheck};
+    use ndarray::arr2;
+
+    #[test]
+    fn forward_propagation_should_repeat_nearest_pixels() {
+        let mut up_sampling: UpSamplingLayer =
+            UpSamplingLayer::build((2, 1), Interpolation::Nearest);
+        assert_eq!(
+            Layer::<f64>::compile(&mut up_sampling, &[2, 2, 1]).unwrap(),
+            vec![4, 2, 1]
+        );
+        let output: Array2<f64> = up_sampling
+            .forward_propagation(&arr2(&[[1.0, 2.0, 3.0, 4.0]]))
+            .unwrap();
+        assert_eq!(output, arr2(&[[1.0, 2.0, 1.0, 2.0, 3.0, 4.0, 3.0, 4.0]]));
+        let input_error: Array2<f64> = up_sampling
+            .compute_gradients(&arr2(&[[1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0]]))
+            .unwrap();
+        assert_eq!(input_error, arr2(&[[4.0, 6.0, 12.0, 14.0]]));
+    }
+
+    #[test]
+    fn forward_propagation_should_interpolate_bilinearly() {
+        let mut up_sampling: UpSamplingLayer =
+            UpSamplingLayer::build((1, 2), Interpolation::Bilinear);
+        Layer::<f64>::compile(&mut up_sampling, &[1, 2, 2]).unwrap();
+        let output: Array2<f64> = up_sampling
+            .forward_propagation(&arr2(&[[2.0, 0.0, 6.0, 4.0]]))
+            .unwrap();
+        assert_eq!(output, arr2(&[[2.0, 0.0, 3.0, 1.0, 5.0, 3.0, 6.0, 4.0]]));
+    }
+
+    #[test]
+    fn compute_gradients_should_pass_gradient_check() {
+        let x: Array2<f64> =
+            Array2::from_shape_fn((2, 12), |(b, i)| ((b * 12 + i) % 5) as f64 - 2.0);
+        for interpolation in [Interpolation::Nearest, Interpolation::Bilinear] {
+            let mut up_sampling: UpSamplingLayer = UpSamplingLayer::build((2, 3), interpolation);
+            Layer::<f64>::compile(&mut up_sampling, &[2, 3, 2]).unwrap();
+            let checks: Vec<GradientCheck> = check_layer(&mut up_sampling, &x, 0.0001).unwrap();
+            assert_eq!(checks.len(), 1);
+            assert!(max_relative_error(&checks) < 0.0001);
+        }
+    }
+
+    #[test]
+    fn from_string_should_deserialize_layer() {
+        let mut up_sampling: UpSamplingLayer =
+            UpSamplingLayer::build((2, 2), Interpolation::Bilinear);
+        Layer::<f64>::compile(&mut up_sampling, &[3, 4, 2]).unwrap();
+        let json: String = Layer::<f64>::to_json(&up_sampling).unwrap();
+        assert_eq!(
+            json,
+            "{\"size\":[2,2],\"interpolation\":\"Bilinear\",\"input_shape\":[3,4,2]}"
+        );
+        let result: Box<dyn Layer<f64>> =
+            layer::from_string("UpSamplingLayer".to_string(), &json).unwrap();
+        assert_eq!(result.get_shape(), (24, 96));
+        assert!(matches!(
+            Layer::<f64>::compile(
+                &mut UpSamplingLayer::build((0, 2), Interpolation::Nearest),
+                &[3, 4, 2]
+            ),
+            Err(MlError::ShapeMismatch(_))
+        ));
+    }
+}
@@ -0,0 +1,167 @@
+use crate::layer::cropping2d::{crop_images, Margins};
+use crate::layer::layer::Layer;
+use crate::utils::error::{check_shapes, MlError};
+use crate::utils::float::MlFloat;
+use ndarray::{s, Array2, Array4, ArrayView4};
+use serde::{Deserialize, Serialize};
+
+/// Surrounds `(h, w, c)` images flattened in the rows of `x` with `((top, bottom), (left, right))`
+/// rows and columns of zeros.
+pub(crate) fn pad_images<F: MlFloat>(
+    x: &Array2<F>,
+    input_shape: (usize, usize, usize),
+    margins: Margins,
+) -> Result<Array2<F>, MlError> {
+    let (h, w, c): (usize, usize, usize) = input_shape;
+    let ((top, bottom), (left, right)): Margins = margins;
+    check_shapes(&x.shape()[1..], &[h * w * c])?;
+    let batch: usize = x.shape()[0];
+    let (oh, ow): (usize, usize) = (h + top + bottom, w + left + right);
+    let x = x.as_standard_layout();
+    let images: ArrayView4<F> = x.view().into_shape((batch, h, w, c))?;
+    let mut padded: Array4<F> = Array4::zeros((batch, oh, ow, c));
+    padded
+        .slice_mut(s![.., top..(top + h), left..(left + w), ..])
+        .assign(&images);
+    Ok(padded.into_shape((batch, oh * ow * c))?)
+}
+
+/// Adds rows and columns of zeros at the border of feature maps of shape `(h, w, c)`.
+#[derive(Debug, Clone)]
+pub struct ZeroPadding2D {
+    padding: Margins,
+    input_shape: (usize, usize, usize),
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ZeroPadding2DModel {
+    padding: Margins,
+    input_shape: (usize, usize, usize),
+}
+
+impl ZeroPadding2D {
+    /// Adds `((top, bottom), (left, right))` rows and columns, the shape of the feature maps is
+    /// inferred when the network is compiled.
+    pub fn build(padding: Margins) -> ZeroPadding2D {
+        ZeroPadding2D {
+            padding,
+            input_shape: (0, 0, 0),
+        }
+    }
+
+    pub fn from_json(json_str: &str) -> Result<ZeroPadding2D, MlError> {
+        let model: ZeroPadding2DModel = serde_json::from_str(json_str)?;
+        let mut layer: ZeroPadding2D = ZeroPadding2D::build(model.padding);
+        layer.input_shape = model.input_shape;
+        Ok(layer)
+    }
+
+    fn output_shape(&self) -> (usize, usize, usize) {
+        let (h, w, c): (usize, usize, usize) = self.input_shape;
+        let ((top, bottom), (left, right)): Margins = self.padding;
+        (h + top + bottom, w + left + right, c)
+    }
+}
+
+impl<F: MlFloat> Layer<F> for ZeroPadding2D {
+    fn forward_propagation(&mut self, x: &Array2<F>) -> Result<Array2<F>, MlError> {
+        self.predict(x)
+    }
+
+    fn predict(&self, x: &Array2<F>) -> Result<Array2<F>, MlError> {
+        pad_images(x, self.input_shape, self.padding)
+    }
+
+    /// Crops the errors of the padding, it does not depend on the input.
+    fn compute_gradients(&mut self, y: &Array2<F>) -> Result<Array2<F>, MlError> {
+        crop_images(y, self.output_shape(), self.padding)
+    }
+
+    fn compile(&mut self, input_shape: &[usize]) -> Result<Vec<usize>, MlError> {
+        match *input_shape {
+            [h, w, c] => {
+                self.input_shape = (h, w, c);
+                let (oh, ow, c): (usize, usize, usize) = self.output_shape();
+                Ok(vec![oh, ow, c])
+            }
+            _ => Err(MlError::ShapeMismatch(format!(
+                "expected input shape [h, w, c], actually: {:?}",
+                input_shape
+            ))),
+        }
+    }
+
+    fn clone_layer(&self) -> Box<dyn Layer<F>> {
+        Box::new(self.clone())
+    }
+
+    fn get_shape(&self) -> (usize, usize) {
+        let (h, w, c): (usize, usize, usize) = self.input_shape;
+        let (oh, ow, c_out): (usize, usize, usize) = self.output_shape();
+        (h * w * c, oh * ow * c_out)
+    }
+
+    fn get_name(&self) -> String {
+        "ZeroPadding2D".to_string()
+    }
+
+    fn to_json(&self) -> Result<String, MlError> {
+        let model: ZeroPadding2DModel = ZeroPadding2DModel {
+            padding: self.padding,
+            input_shape: self.input_shape,
+        };
+        Ok(serde_json::to_string(&model)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layer::layer;
+    use crate::utils::gradient_check::{check_layer, max_relative_error, GradientCheck};
+    use ndarray::arr2;
+
+    #[test]
+    fn forward_propagation_should_surround_images_with_zeros() {
+        let mut zero_padding: ZeroPadding2D = ZeroPadding2D::build(((0, 1), (1, 0)));
+        assert_eq!(
+            Layer::<f64>::compile(&mut zero_padding, &[1, 2, 2]).unwrap(),
+            vec![2, 3, 2]
+        );
+        let output: Array2<f64> = zero_padding
+            .forward_propagation(&arr2(&[[1.0, 2.0, 3.0, 4.0]]))
+            .unwrap();
+        assert_eq!(
+            output,
+            arr2(&[[0.0, 0.0, 1.0, 2.0, 3.0, 4.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0]])
+        );
+        assert_eq!(
+            zero_padding.compute_gradients(&output).unwrap(),
+            arr2(&[[1.0, 2.0, 3.0, 4.0]])
+        );
+        assert!(matches!(
+            Layer::<f64>::compile(&mut zero_padding, &[1, 2]),
+            Err(MlError::ShapeMismatch(_))
+        ));
+    }
+
+    #[test]
+    fn compute_gradients_should_pass_gradient_check() {
+        let mut zero_padding: ZeroPadding2D = ZeroPadding2D::build(((1, 2), (0, 1)));
+        Layer::<f64>::compile(&mut zero_padding, &[2, 2, 2]).unwrap();
+        let x: Array2<f64> = Array2::from_shape_fn((2, 8), |(b, i)| (b * 8 + i) as f64 / 10.0);
+        let checks: Vec<GradientCheck> = check_layer(&mut zero_padding, &x, 0.0001).unwrap();
+        assert!(max_relative_error(&checks) < 0.0001);
+    }
+
+    #[test]
+    fn from_string_should_deserialize_layer() {
+        let mut zero_padding: ZeroPadding2D = ZeroPadding2D::build(((1, 1), (1, 1)));
+        Layer::<f64>::compile(&mut zero_padding, &[2, 3, 1]).unwrap();
+        let json: String = Layer::<f64>::to_json(&zero_padding).unwrap();
+        assert_eq!(json, "{\"padding\":[[1,1],[1,1]],\"input_shape\":[2,3,1]}");
+        let result: Box<dyn Layer<f64>> =
+            layer::from_string("ZeroPadding2D".to_string(), &json).unwrap();
+        assert_eq!(result.get_shape(), (6, 20));
+    }
+}
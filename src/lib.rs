@@ -1,7 +1,9 @@
 pub mod accuracy;
 pub mod activation;
+pub mod autograd;
 pub mod layer;
 pub mod loss;
+pub mod merge;
 pub mod network;
 pub mod report;
 pub mod utils;
@@ -1,16 +1,25 @@
 use crate::loss::loss::Loss;
+use crate::utils::error::{check_shapes, MlError};
+use crate::utils::float::MlFloat;
 use ndarray::Array2;
 use ndarray_stats::EntropyExt;
 
+/// Cross entropy averaged over the rows of the batch, like `Mse`, so the size of a gradient step
+/// does not depend on the batch size.
 pub struct CategoricalCrossEntropy;
 
-impl Loss for CategoricalCrossEntropy {
-    fn function(&self, y_true: &Array2<f64>, y_pred: &Array2<f64>) -> f64 {
-        y_true.cross_entropy(y_pred).unwrap()
+impl<F: MlFloat> Loss<F> for CategoricalCrossEntropy {
+    fn function(&self, y_true: &Array2<F>, y_pred: &Array2<F>) -> Result<F, MlError> {
+        check_shapes(y_true.shape(), y_pred.shape())?;
+        let cross_entropy: F = y_true
+            .cross_entropy(y_pred)
+            .map_err(|error| MlError::Numeric(error.to_string()))?;
+        Ok(cross_entropy / F::cast(y_true.shape()[0] as f64))
     }
 
-    fn derivative(&self, y_true: &Array2<f64>, y_pred: &Array2<f64>) -> Array2<f64> {
-        (y_true / (y_pred + 1e-10)) * -1.0
+    fn derivative(&self, y_true: &Array2<F>, y_pred: &Array2<F>) -> Result<Array2<F>, MlError> {
+        check_shapes(y_true.shape(), y_pred.shape())?;
+        Ok(-(y_true / &(y_pred + F::cast(1e-10))) / F::cast(y_true.shape()[0] as f64))
     }
 
     fn get_name(&self) -> String {
@@ -19,8 +28,10 @@ impl Loss for CategoricalCrossEntropy {
 }
 
 #[cfg(test)]
+#[allow(clippy::unused_unit)]
 mod tests {
     use super::*;
+    use crate::utils::gradient_check::{check_loss, GradientCheck};
     use ndarray::arr2;
 
     #[test]
@@ -28,7 +39,9 @@ mod tests {
         let categorical_cross_entropy: CategoricalCrossEntropy = CategoricalCrossEntropy;
         let y_true: Array2<f64> = arr2(&[[0.0, 1.0, 0.0, 0.0]]);
         let y_pred: Array2<f64> = arr2(&[[0.05, 0.85, 0.10, 0.0]]);
-        let output: f64 = categorical_cross_entropy.function(&y_true, &y_pred);
+        let output: f64 = categorical_cross_entropy
+            .function(&y_true, &y_pred)
+            .unwrap();
         assert!((output - 0.16251892949777494).sqrt() < 0.0001)
     }
 
@@ -37,7 +50,9 @@ mod tests {
         let categorical_cross_entropy: CategoricalCrossEntropy = CategoricalCrossEntropy;
         let y_true: Array2<f64> = arr2(&[[0.0, 1.0, 0.0, 0.0]]);
         let y_pred: Array2<f64> = arr2(&[[0.05, 0.85, 0.10, 0.0]]);
-        let output: Array2<f64> = categorical_cross_entropy.derivative(&y_true, &y_pred);
+        let output: Array2<f64> = categorical_cross_entropy
+            .derivative(&y_true, &y_pred)
+            .unwrap();
         assert_eq!(output.shape(), &[1, 4]);
         let output_vec: Vec<f64> = output.into_raw_vec();
         assert!(output_vec[0].powf(2.0) < 0.0000001);
@@ -46,10 +61,28 @@ mod tests {
         assert!((output_vec[1] - -1.1764705880968858).powf(2.0) < 0.0000001);
     }
 
+    #[test]
+    fn loss_categorical_cross_entropy_derivative_should_pass_gradient_check() {
+        let y_true: Array2<f64> = arr2(&[[0.0, 1.0, 0.0, 0.0], [0.5, 0.0, 0.0, 0.5]]);
+        let y_pred: Array2<f64> = arr2(&[[0.05, 0.85, 0.05, 0.05], [0.3, 0.2, 0.1, 0.4]]);
+        let check: GradientCheck =
+            check_loss(&CategoricalCrossEntropy, &y_true, &y_pred, 0.000001).unwrap();
+        assert!(check.relative_error < 0.0001);
+    }
+
+    #[test]
+    fn loss_categorical_cross_entropy_should_average_rows() {
+        let y_true: Array2<f64> = arr2(&[[0.0, 1.0], [1.0, 0.0]]);
+        let y_pred: Array2<f64> = arr2(&[[0.2, 0.8], [0.6, 0.4]]);
+        let output: f64 = CategoricalCrossEntropy.function(&y_true, &y_pred).unwrap();
+        let expected: f64 = -(0.8f64.ln() + 0.6f64.ln()) / 2.0;
+        assert!((output - expected).powf(2.0) < 0.00001);
+    }
+
     #[test]
     fn get_name_should_return_struct_name() -> () {
         assert_eq!(
-            CategoricalCrossEntropy.get_name(),
+            Loss::<f64>::get_name(&CategoricalCrossEntropy),
             "categorical_cross_entropy"
         );
     }
@@ -1,39 +1,38 @@
 use crate::loss::categorical_cross_entropy::CategoricalCrossEntropy;
 use crate::loss::mse::Mse;
+use crate::utils::error::MlError;
+use crate::utils::float::MlFloat;
 use ndarray::Array2;
-use std::io::{Error, ErrorKind};
 
-pub trait Loss {
-    fn function(&self, y_true: &Array2<f64>, y_pred: &Array2<f64>) -> f64;
+pub trait Loss<F: MlFloat = f64>: Send + Sync {
+    fn function(&self, y_true: &Array2<F>, y_pred: &Array2<F>) -> Result<F, MlError>;
 
-    fn derivative(&self, y_true: &Array2<f64>, y_pred: &Array2<f64>) -> Array2<f64>;
+    fn derivative(&self, y_true: &Array2<F>, y_pred: &Array2<F>) -> Result<Array2<F>, MlError>;
 
     fn get_name(&self) -> String;
 }
 
-pub fn from_string(name: String) -> Result<Box<dyn Loss>, Error> {
+pub fn from_string<F: MlFloat>(name: String) -> Result<Box<dyn Loss<F>>, MlError> {
     match name.to_uppercase().as_str() {
         "MSE" => Ok(Box::new(Mse)),
         "CATEGORICAL_CROSS_ENTROPY" => Ok(Box::new(CategoricalCrossEntropy)),
-        _ => Err(Error::new(
-            ErrorKind::InvalidInput,
-            format!("unknown loss '{}'", name),
-        )),
+        _ => Err(MlError::unknown_component("loss", &name)),
     }
 }
 
 #[cfg(test)]
+#[allow(clippy::unused_unit)]
 mod tests {
     use super::*;
 
     #[test]
     fn from_string_should_return_loss() -> () {
         assert_eq!(
-            from_string("MSE".to_string()).unwrap().get_name(),
+            from_string::<f64>("MSE".to_string()).unwrap().get_name(),
             "MSE".to_string()
         );
         assert_eq!(
-            from_string("categorical_cross_entropy".to_string())
+            from_string::<f32>("categorical_cross_entropy".to_string())
                 .unwrap()
                 .get_name(),
             "categorical_cross_entropy".to_string()
@@ -41,8 +40,8 @@ mod tests {
     }
 
     #[test]
-    #[should_panic(expected = "unknown loss 'Unknown'")]
     fn from_string_should_raise_error_when_name_is_unknown() -> () {
-        from_string("Unknown".to_string()).unwrap();
+        let error: MlError = from_string::<f64>("Unknown".to_string()).err().unwrap();
+        assert_eq!(error.to_string(), "unknown loss 'Unknown'");
     }
 }
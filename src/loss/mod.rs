@@ -1,3 +1,4 @@
 pub mod categorical_cross_entropy;
+#[allow(clippy::module_inception)]
 pub mod loss;
 pub mod mse;
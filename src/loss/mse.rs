@@ -1,16 +1,22 @@
 use crate::loss::loss::Loss;
+use crate::utils::error::{check_shapes, MlError};
+use crate::utils::float::MlFloat;
 use ndarray::Array2;
 
 pub struct Mse;
 
-impl Loss for Mse {
-    fn function(&self, y_true: &Array2<f64>, y_pred: &Array2<f64>) -> f64 {
-        let dif: Array2<f64> = y_true - y_pred;
-        dif.mapv(|x| x.powf(2.0)).mean().unwrap()
+impl<F: MlFloat> Loss<F> for Mse {
+    fn function(&self, y_true: &Array2<F>, y_pred: &Array2<F>) -> Result<F, MlError> {
+        check_shapes(y_true.shape(), y_pred.shape())?;
+        let dif: Array2<F> = y_true - y_pred;
+        dif.mapv(|x| x.powi(2)).mean().ok_or_else(|| {
+            MlError::InvalidInput("can not compute the loss of empty arrays".to_string())
+        })
     }
 
-    fn derivative(&self, y_true: &Array2<f64>, y_pred: &Array2<f64>) -> Array2<f64> {
-        (2.0 * (y_pred - y_true)) / y_true.len() as f64
+    fn derivative(&self, y_true: &Array2<F>, y_pred: &Array2<F>) -> Result<Array2<F>, MlError> {
+        check_shapes(y_true.shape(), y_pred.shape())?;
+        Ok((y_pred - y_true) * F::cast(2.0) / F::cast(y_true.len() as f64))
     }
 
     fn get_name(&self) -> String {
@@ -19,8 +25,10 @@ impl Loss for Mse {
 }
 
 #[cfg(test)]
+#[allow(clippy::unused_unit)]
 mod tests {
     use super::*;
+    use crate::utils::gradient_check::{check_loss, GradientCheck};
     use ndarray::arr2;
 
     #[test]
@@ -28,7 +36,7 @@ mod tests {
         let mse: Mse = Mse;
         let y_true: Array2<f64> = arr2(&[[1.0, 2.0, 1.0, 0.0]]);
         let y_pred: Array2<f64> = arr2(&[[2.0, 1.0, 1.0, 0.0]]);
-        let output: f64 = mse.function(&y_true, &y_pred);
+        let output: f64 = mse.function(&y_true, &y_pred).unwrap();
         assert!((output - 0.5).sqrt() < 0.0001)
     }
 
@@ -37,7 +45,7 @@ mod tests {
         let mse: Mse = Mse;
         let y_true: Array2<f64> = arr2(&[[1.0, 2.0, 1.0, 0.0]]);
         let y_pred: Array2<f64> = arr2(&[[2.0, 1.0, 1.0, 0.0]]);
-        let output: Array2<f64> = mse.derivative(&y_true, &y_pred);
+        let output: Array2<f64> = mse.derivative(&y_true, &y_pred).unwrap();
         let target: Array2<f64> = arr2(&[[0.5, -0.5, 0.0, 0.0]]);
         assert_eq!(output.shape(), target.shape());
         let output_vec: Vec<f64> = output.into_raw_vec();
@@ -47,8 +55,30 @@ mod tests {
         }
     }
 
+    #[test]
+    fn loss_mse_should_fail_when_shapes_are_not_equal() {
+        let y_true: Array2<f64> = arr2(&[[1.0, 2.0]]);
+        let y_pred: Array2<f64> = arr2(&[[2.0, 1.0, 1.0]]);
+        assert!(matches!(
+            Mse.function(&y_true, &y_pred),
+            Err(MlError::ShapeMismatch(_))
+        ));
+        assert!(matches!(
+            Mse.derivative(&y_true, &y_pred),
+            Err(MlError::ShapeMismatch(_))
+        ));
+    }
+
+    #[test]
+    fn loss_mse_derivative_should_pass_gradient_check() {
+        let y_true: Array2<f64> = arr2(&[[0.0, 1.0, 0.5], [1.0, 0.0, 0.25]]);
+        let y_pred: Array2<f64> = arr2(&[[0.2, 0.7, 0.5], [0.4, 0.3, 1.0]]);
+        let check: GradientCheck = check_loss(&Mse, &y_true, &y_pred, 0.0001).unwrap();
+        assert!(check.relative_error < 0.0001);
+    }
+
     #[test]
     fn get_name_should_return_struct_name() -> () {
-        assert_eq!(Mse.get_name(), "MSE");
+        assert_eq!(Loss::<f64>::get_name(&Mse), "MSE");
     }
 }
@@ -0,0 +1,104 @@
+use crate::merge::merge::{check_inputs, check_same_shapes, Merge};
+use crate::utils::error::MlError;
+use crate::utils::float::MlFloat;
+use ndarray::Array2;
+use serde::{Deserialize, Serialize};
+
+/// Element-wise sum of inputs of the same shape.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Add {
+    #[serde(skip)]
+    inputs: usize,
+}
+
+impl Add {
+    pub fn new() -> Add {
+        Add { inputs: 0 }
+    }
+
+    pub fn from_json(json_str: &str) -> Result<Add, MlError> {
+        Ok(serde_json::from_str(json_str)?)
+    }
+}
+
+impl<F: MlFloat> Merge<F> for Add {
+    fn forward_propagation(&mut self, inputs: &[Array2<F>]) -> Result<Array2<F>, MlError> {
+        let output: Array2<F> = self.predict(inputs)?;
+        self.inputs = inputs.len();
+        Ok(output)
+    }
+
+    fn predict(&self, inputs: &[Array2<F>]) -> Result<Array2<F>, MlError> {
+        check_inputs(inputs)?;
+        Ok(inputs[1..]
+            .iter()
+            .fold(inputs[0].clone(), |sum, input| sum + input))
+    }
+
+    fn backward_propagation(&mut self, y: &Array2<F>) -> Result<Vec<Array2<F>>, MlError> {
+        Ok(vec![y.clone(); self.inputs])
+    }
+
+    fn compile(&mut self, input_shapes: &[Vec<usize>]) -> Result<Vec<usize>, MlError> {
+        check_same_shapes(input_shapes)
+    }
+
+    fn clone_merge(&self) -> Box<dyn Merge<F>> {
+        Box::new(self.clone())
+    }
+
+    fn get_name(&self) -> String {
+        "Add".to_string()
+    }
+
+    fn to_json(&self) -> Result<String, MlError> {
+        Ok(serde_json::to_string(self)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::gradient_check::{check_merge, max_relative_error, GradientCheck};
+    use ndarray::arr2;
+
+    #[test]
+    fn forward_propagation_should_sum_inputs() {
+        let mut add: Add = Add::new();
+        let output: Array2<f64> = add
+            .forward_propagation(&[
+                arr2(&[[1.0, 2.0]]),
+                arr2(&[[0.5, -1.0]]),
+                arr2(&[[1.0, 1.0]]),
+            ])
+            .unwrap();
+        assert_eq!(output, arr2(&[[2.5, 2.0]]));
+        assert_eq!(
+            add.backward_propagation(&arr2(&[[0.1, 0.2]])).unwrap(),
+            vec![arr2(&[[0.1, 0.2]]); 3]
+        );
+    }
+
+    #[test]
+    fn predict_should_fail_when_shapes_are_not_equal() {
+        let add: Add = Add::new();
+        assert!(matches!(
+            add.predict(&[arr2(&[[1.0, 2.0]]), arr2(&[[1.0]])]),
+            Err(MlError::ShapeMismatch(_))
+        ));
+    }
+
+    #[test]
+    fn backward_propagation_should_pass_gradient_check() {
+        let mut merge: Add = Add::new();
+        let inputs: Vec<Array2<f64>> = vec![
+            arr2(&[[1.0, 2.0], [-0.5, 0.25]]),
+            arr2(&[[0.5, -1.0], [2.0, 1.5]]),
+            arr2(&[[1.0, 1.0], [0.0, -2.0]]),
+        ];
+        Merge::<f64>::compile(&mut merge, &vec![vec![2]; 3]).unwrap();
+        let checks: Vec<GradientCheck> = check_merge(&mut merge, &inputs, 0.0001).unwrap();
+        assert_eq!(checks.len(), 3);
+        assert!(max_relative_error(&checks) < 0.0001);
+    }
+}
@@ -0,0 +1,214 @@
+use crate::merge::merge::Merge;
+use crate::utils::error::{check_shapes, MlError};
+use crate::utils::float::MlFloat;
+use ndarray::{concatenate, s, Array2, ArrayView2, Axis};
+use serde::{Deserialize, Serialize};
+
+/// Joins the inputs along the last axis of their samples, the other axes must be equal.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Concatenate {
+    input_shapes: Vec<Vec<usize>>,
+}
+
+impl Concatenate {
+    /// The shapes of the inputs are inferred when the network is compiled.
+    pub fn new() -> Concatenate {
+        Concatenate {
+            input_shapes: vec![],
+        }
+    }
+
+    pub fn from_json(json_str: &str) -> Result<Concatenate, MlError> {
+        Ok(serde_json::from_str(json_str)?)
+    }
+
+    /// Number of vectors along the last axis in a sample and their size for every input.
+    fn blocks(&self) -> (usize, Vec<usize>) {
+        let outer: usize = self.input_shapes.first().map_or(0, |shape| {
+            shape[..shape.len().saturating_sub(1)].iter().product()
+        });
+        let sizes: Vec<usize> = self
+            .input_shapes
+            .iter()
+            .map(|shape| shape.last().copied().unwrap_or(1))
+            .collect();
+        (outer, sizes)
+    }
+}
+
+impl<F: MlFloat> Merge<F> for Concatenate {
+    fn forward_propagation(&mut self, inputs: &[Array2<F>]) -> Result<Array2<F>, MlError> {
+        self.predict(inputs)
+    }
+
+    fn predict(&self, inputs: &[Array2<F>]) -> Result<Array2<F>, MlError> {
+        if self.input_shapes.is_empty() {
+            return Err(MlError::InvalidInput(
+                "Concatenate must be compiled before use".to_string(),
+            ));
+        }
+        if inputs.len() != self.input_shapes.len() {
+            return Err(MlError::InvalidInput(format!(
+                "Concatenate expects {} inputs, actually: {}",
+                self.input_shapes.len(),
+                inputs.len()
+            )));
+        }
+        let rows: usize = inputs[0].shape()[0];
+        let (outer, sizes): (usize, Vec<usize>) = self.blocks();
+        let blocks: Vec<Array2<F>> = inputs
+            .iter()
+            .zip(&sizes)
+            .map(|(input, &size)| {
+                check_shapes(input.shape(), &[rows, outer * size])?;
+                Ok(input
+                    .as_standard_layout()
+                    .into_owned()
+                    .into_shape((rows * outer, size))?)
+            })
+            .collect::<Result<Vec<Array2<F>>, MlError>>()?;
+        let views: Vec<ArrayView2<F>> = blocks.iter().map(|block| block.view()).collect();
+        let output: Array2<F> = concatenate(Axis(1), &views)?;
+        Ok(output
+            .as_standard_layout()
+            .into_owned()
+            .into_shape((rows, outer * sizes.iter().sum::<usize>()))?)
+    }
+
+    fn backward_propagation(&mut self, y: &Array2<F>) -> Result<Vec<Array2<F>>, MlError> {
+        let rows: usize = y.shape()[0];
+        let (outer, sizes): (usize, Vec<usize>) = self.blocks();
+        let total: usize = sizes.iter().sum();
+        check_shapes(y.shape(), &[rows, outer * total])?;
+        let y: Array2<F> = y
+            .as_standard_layout()
+            .into_owned()
+            .into_shape((rows * outer, total))?;
+        let mut start: usize = 0;
+        sizes
+            .iter()
+            .map(|&size| {
+                let error: Array2<F> = y
+                    .slice(s![.., start..(start + size)])
+                    .as_standard_layout()
+                    .into_owned();
+                start += size;
+                Ok(error.into_shape((rows, outer * size))?)
+            })
+            .collect()
+    }
+
+    fn compile(&mut self, input_shapes: &[Vec<usize>]) -> Result<Vec<usize>, MlError> {
+        if input_shapes.len() < 2 {
+            return Err(MlError::InvalidInput(format!(
+                "merge expects at least 2 inputs, actually: {}",
+                input_shapes.len()
+            )));
+        }
+        let first: &[usize] = &input_shapes[0];
+        if first.is_empty() {
+            return Err(MlError::ShapeMismatch(
+                "can not concatenate scalar samples".to_string(),
+            ));
+        }
+        for shape in &input_shapes[1..] {
+            check_shapes(
+                &shape[..shape.len().saturating_sub(1)],
+                &first[..first.len() - 1],
+            )?;
+        }
+        self.input_shapes = input_shapes.to_vec();
+        let mut output_shape: Vec<usize> = first.to_vec();
+        output_shape[first.len() - 1] = input_shapes
+            .iter()
+            .map(|shape| shape.last().copied().unwrap_or(0))
+            .sum();
+        Ok(output_shape)
+    }
+
+    fn clone_merge(&self) -> Box<dyn Merge<F>> {
+        Box::new(self.clone())
+    }
+
+    fn get_name(&self) -> String {
+        "Concatenate".to_string()
+    }
+
+    fn to_json(&self) -> Result<String, MlError> {
+        Ok(serde_json::to_string(self)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::gradient_check::{check_merge, max_relative_error, GradientCheck};
+    use ndarray::arr2;
+
+    #[test]
+    fn compile_should_sum_last_axis() {
+        let mut merge: Concatenate = Concatenate::new();
+        assert_eq!(
+            Merge::<f64>::compile(&mut merge, &[vec![2, 3], vec![2, 1]]).unwrap(),
+            vec![2, 4]
+        );
+        assert!(matches!(
+            Merge::<f64>::compile(&mut merge, &[vec![2, 3], vec![3, 1]]),
+            Err(MlError::ShapeMismatch(_))
+        ));
+    }
+
+    #[test]
+    fn forward_propagation_should_join_last_axis() {
+        let mut merge: Concatenate = Concatenate::new();
+        Merge::<f64>::compile(&mut merge, &[vec![2, 2], vec![2, 1]]).unwrap();
+        let output: Array2<f64> = merge
+            .forward_propagation(&[
+                arr2(&[[1.0, 2.0, 3.0, 4.0], [5.0, 6.0, 7.0, 8.0]]),
+                arr2(&[[-1.0, -2.0], [-3.0, -4.0]]),
+            ])
+            .unwrap();
+        assert_eq!(
+            output,
+            arr2(&[
+                [1.0, 2.0, -1.0, 3.0, 4.0, -2.0],
+                [5.0, 6.0, -3.0, 7.0, 8.0, -4.0]
+            ])
+        );
+    }
+
+    #[test]
+    fn predict_should_fail_when_not_compiled() {
+        let merge: Concatenate = Concatenate::new();
+        assert!(matches!(
+            Merge::<f64>::predict(&merge, &[]),
+            Err(MlError::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn backward_propagation_should_split_error() {
+        let mut merge: Concatenate = Concatenate::new();
+        Merge::<f64>::compile(&mut merge, &[vec![2, 2], vec![2, 1]]).unwrap();
+        let errors: Vec<Array2<f64>> = merge
+            .backward_propagation(&arr2(&[[1.0, 2.0, -1.0, 3.0, 4.0, -2.0]]))
+            .unwrap();
+        assert_eq!(
+            errors,
+            vec![arr2(&[[1.0, 2.0, 3.0, 4.0]]), arr2(&[[-1.0, -2.0]])]
+        );
+    }
+
+    #[test]
+    fn backward_propagation_should_pass_gradient_check() {
+        let mut merge: Concatenate = Concatenate::new();
+        let inputs: Vec<Array2<f64>> = vec![
+            arr2(&[[1.0, 2.0, 3.0, 4.0], [-0.5, 0.25, 1.0, 0.0]]),
+            arr2(&[[0.5, -1.0], [2.0, 1.5]]),
+        ];
+        Merge::<f64>::compile(&mut merge, &[vec![2, 2], vec![2, 1]]).unwrap();
+        let checks: Vec<GradientCheck> = check_merge(&mut merge, &inputs, 0.0001).unwrap();
+        assert_eq!(checks.len(), 2);
+        assert!(max_relative_error(&checks) < 0.0001);
+    }
+}
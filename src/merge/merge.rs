@@ -0,0 +1,109 @@
+use crate::merge::add::Add;
+use crate::merge::concatenate::Concatenate;
+use crate::merge::multiply::Multiply;
+use crate::utils::error::{check_shapes, MlError};
+use crate::utils::float::MlFloat;
+use ndarray::Array2;
+
+/// Layer combining the outputs of several nodes of a graph into one batch.
+pub trait Merge<F: MlFloat = f64>: Send + Sync {
+    fn forward_propagation(&mut self, inputs: &[Array2<F>]) -> Result<Array2<F>, MlError>;
+
+    fn predict(&self, inputs: &[Array2<F>]) -> Result<Array2<F>, MlError>;
+
+    /// Returns the error of every input for the output error `y`.
+    fn backward_propagation(&mut self, y: &Array2<F>) -> Result<Vec<Array2<F>>, MlError>;
+
+    /// Checks the merge accepts samples of `input_shapes` and returns the shape of its output
+    /// samples.
+    fn compile(&mut self, input_shapes: &[Vec<usize>]) -> Result<Vec<usize>, MlError>;
+
+    fn clone_merge(&self) -> Box<dyn Merge<F>>;
+
+    fn get_name(&self) -> String;
+
+    fn to_json(&self) -> Result<String, MlError>;
+}
+
+pub fn from_string<F: MlFloat>(name: String, json_str: &str) -> Result<Box<dyn Merge<F>>, MlError> {
+    match name.to_uppercase().as_str() {
+        "ADD" => Ok(Box::new(Add::from_json(json_str)?)),
+        "CONCATENATE" => Ok(Box::new(Concatenate::from_json(json_str)?)),
+        "MULTIPLY" => Ok(Box::new(Multiply::from_json(json_str)?)),
+        _ => Err(MlError::unknown_component("merge", &name)),
+    }
+}
+
+/// Checks the merge has at least two inputs with the same shape and returns it.
+pub fn check_same_shapes(input_shapes: &[Vec<usize>]) -> Result<Vec<usize>, MlError> {
+    if input_shapes.len() < 2 {
+        return Err(MlError::InvalidInput(format!(
+            "merge expects at least 2 inputs, actually: {}",
+            input_shapes.len()
+        )));
+    }
+    for shape in &input_shapes[1..] {
+        check_shapes(shape, &input_shapes[0])?;
+    }
+    Ok(input_shapes[0].clone())
+}
+
+/// Checks all the batches have the shape of the first one.
+pub fn check_inputs<F: MlFloat>(inputs: &[Array2<F>]) -> Result<(), MlError> {
+    if inputs.is_empty() {
+        return Err(MlError::InvalidInput("merge expects inputs".to_string()));
+    }
+    for input in &inputs[1..] {
+        check_shapes(input.shape(), inputs[0].shape())?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_string_should_return_merge() {
+        assert_eq!(
+            from_string::<f64>("Add".to_string(), "{}")
+                .unwrap()
+                .get_name(),
+            "Add".to_string()
+        );
+        assert_eq!(
+            from_string::<f32>("Concatenate".to_string(), "{\"input_shapes\":[[2],[3]]}")
+                .unwrap()
+                .get_name(),
+            "Concatenate".to_string()
+        );
+        assert_eq!(
+            from_string::<f64>("Multiply".to_string(), "{}")
+                .unwrap()
+                .get_name(),
+            "Multiply".to_string()
+        );
+    }
+
+    #[test]
+    fn from_string_should_raise_error_when_name_is_unknown() {
+        let error: MlError = from_string::<f64>("Unknown".to_string(), "").err().unwrap();
+        assert_eq!(error.to_string(), "unknown merge 'Unknown'");
+    }
+
+    #[test]
+    fn check_same_shapes_should_fail_on_different_shapes() {
+        assert_eq!(
+            check_same_shapes(&[vec![2, 3], vec![2, 3]]).unwrap(),
+            vec![2, 3]
+        );
+        assert!(matches!(
+            check_same_shapes(&[vec![2, 3], vec![3, 2]]),
+            Err(MlError::ShapeMismatch(_))
+        ));
+        assert!(matches!(
+            check_same_shapes(&[vec![2, 3]]),
+            Err(MlError::InvalidInput(_))
+        ));
+    }
+}
@@ -0,0 +1,5 @@
+pub mod add;
+pub mod concatenate;
+#[allow(clippy::module_inception)]
+pub mod merge;
+pub mod multiply;
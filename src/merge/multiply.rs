@@ -0,0 +1,119 @@
+use crate::merge::merge::{check_inputs, check_same_shapes, Merge};
+use crate::utils::error::{check_shapes, MlError};
+use crate::utils::float::MlFloat;
+use ndarray::Array2;
+
+/// Element-wise product of inputs of the same shape.
+#[derive(Debug, Clone, Default)]
+pub struct Multiply<F: MlFloat = f64> {
+    inputs: Vec<Array2<F>>,
+}
+
+impl<F: MlFloat> Multiply<F> {
+    pub fn new() -> Multiply<F> {
+        Multiply { inputs: vec![] }
+    }
+
+    /// The merge has no parameter, `json_str` is only checked to be valid.
+    pub fn from_json(json_str: &str) -> Result<Multiply<F>, MlError> {
+        serde_json::from_str::<serde_json::Value>(json_str)?;
+        Ok(Multiply::new())
+    }
+}
+
+impl<F: MlFloat> Merge<F> for Multiply<F> {
+    fn forward_propagation(&mut self, inputs: &[Array2<F>]) -> Result<Array2<F>, MlError> {
+        let output: Array2<F> = self.predict(inputs)?;
+        self.inputs = inputs.to_vec();
+        Ok(output)
+    }
+
+    fn predict(&self, inputs: &[Array2<F>]) -> Result<Array2<F>, MlError> {
+        check_inputs(inputs)?;
+        Ok(inputs[1..]
+            .iter()
+            .fold(inputs[0].clone(), |product, input| product * input))
+    }
+
+    /// The error of an input is the output error times the product of the other inputs.
+    fn backward_propagation(&mut self, y: &Array2<F>) -> Result<Vec<Array2<F>>, MlError> {
+        (0..self.inputs.len())
+            .map(|i| {
+                check_shapes(y.shape(), self.inputs[i].shape())?;
+                Ok(self
+                    .inputs
+                    .iter()
+                    .enumerate()
+                    .filter(|(j, _)| *j != i)
+                    .fold(y.clone(), |error, (_, input)| error * input))
+            })
+            .collect()
+    }
+
+    fn compile(&mut self, input_shapes: &[Vec<usize>]) -> Result<Vec<usize>, MlError> {
+        check_same_shapes(input_shapes)
+    }
+
+    fn clone_merge(&self) -> Box<dyn Merge<F>> {
+        Box::new(self.clone())
+    }
+
+    fn get_name(&self) -> String {
+        "Multiply".to_string()
+    }
+
+    fn to_json(&self) -> Result<String, MlError> {
+        Ok("{}".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::gradient_check::{check_merge, max_relative_error, GradientCheck};
+    use ndarray::arr2;
+
+    #[test]
+    fn forward_propagation_should_multiply_inputs() {
+        let mut multiply: Multiply = Multiply::new();
+        let output: Array2<f64> = multiply
+            .forward_propagation(&[arr2(&[[1.0, 2.0]]), arr2(&[[0.5, -1.0]])])
+            .unwrap();
+        assert_eq!(output, arr2(&[[0.5, -2.0]]));
+    }
+
+    #[test]
+    fn backward_propagation_should_multiply_error_by_other_inputs() {
+        let mut multiply: Multiply = Multiply::new();
+        multiply
+            .forward_propagation(&[
+                arr2(&[[1.0, 2.0]]),
+                arr2(&[[0.5, -1.0]]),
+                arr2(&[[2.0, 3.0]]),
+            ])
+            .unwrap();
+        let errors: Vec<Array2<f64>> = multiply.backward_propagation(&arr2(&[[1.0, 0.5]])).unwrap();
+        assert_eq!(
+            errors,
+            vec![
+                arr2(&[[1.0, -1.5]]),
+                arr2(&[[2.0, 3.0]]),
+                arr2(&[[0.5, -1.0]])
+            ]
+        );
+    }
+
+    #[test]
+    fn backward_propagation_should_pass_gradient_check() {
+        let mut merge: Multiply<f64> = Multiply::new();
+        let inputs: Vec<Array2<f64>> = vec![
+            arr2(&[[1.0, 2.0], [-0.5, 0.25]]),
+            arr2(&[[0.5, -1.0], [2.0, 1.5]]),
+            arr2(&[[1.0, 1.5], [0.75, -2.0]]),
+        ];
+        Merge::<f64>::compile(&mut merge, &vec![vec![2]; 3]).unwrap();
+        let checks: Vec<GradientCheck> = check_merge(&mut merge, &inputs, 0.0001).unwrap();
+        assert_eq!(checks.len(), 3);
+        assert!(max_relative_error(&checks) < 0.0001);
+    }
+}
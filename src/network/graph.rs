@@ -0,0 +1,924 @@
+use crate::accuracy::accuracy::Metric;
+use crate::layer::layer;
+use crate::layer::layer::Layer;
+use crate::loss::loss;
+use crate::loss::loss::Loss;
+use crate::merge::merge;
+use crate::merge::merge::Merge;
+use crate::network::mlp::{
+    batch_shape, check_batch_size, check_report_step, log_epoch, report_classification, rows_loss,
+};
+use crate::network::network::Network;
+use crate::report::report::Report;
+use crate::utils::error::{check_shapes, MlError};
+use crate::utils::float::MlFloat;
+use ndarray::{Array2, Axis};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::fs::read_to_string;
+use std::time::Instant;
+
+/// Index of a node in a `Graph`.
+pub type NodeId = usize;
+
+/// Batches of every input and targets of every output.
+type Samples<'a, F> = (&'a [Array2<F>], &'a [Array2<F>]);
+
+enum Operation<F: MlFloat> {
+    /// Samples of the given shape fed by the caller.
+    Input(Vec<usize>),
+    Layer(Box<dyn Layer<F>>),
+    Merge(Box<dyn Merge<F>>),
+}
+
+struct Node<F: MlFloat> {
+    name: String,
+    operation: Operation<F>,
+    parents: Vec<NodeId>,
+}
+
+/// Network whose layers are the nodes of a directed acyclic graph. Merge nodes combine several
+/// nodes, so skip connections and models with many inputs or outputs can be built. Every
+/// output is trained with the same loss and the losses of the outputs are summed. Every layer
+/// is trained, layers can not be frozen like in an `Mlp`.
+pub struct Graph<F: MlFloat = f64> {
+    nodes: Vec<Node<F>>,
+    inputs: Vec<NodeId>,
+    outputs: Vec<NodeId>,
+    loss: Box<dyn Loss<F>>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub enum NodeModel {
+    Input {
+        name: String,
+        shape: Vec<usize>,
+    },
+    Layer {
+        name: String,
+        layer: (String, String),
+        parent: String,
+    },
+    Merge {
+        name: String,
+        merge: (String, String),
+        parents: Vec<String>,
+    },
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GraphModel {
+    nodes: Vec<NodeModel>,
+    inputs: Vec<String>,
+    outputs: Vec<String>,
+    loss: String,
+    dtype: String,
+}
+
+impl<F: MlFloat> Graph<F> {
+    pub fn build(loss: Box<dyn Loss<F>>) -> Graph<F> {
+        Graph {
+            nodes: vec![],
+            inputs: vec![],
+            outputs: vec![],
+            loss,
+        }
+    }
+
+    fn add_node(
+        &mut self,
+        name: &str,
+        operation: Operation<F>,
+        parents: Vec<NodeId>,
+    ) -> Result<NodeId, MlError> {
+        if self.find_node(name).is_some() {
+            return Err(MlError::InvalidInput(format!(
+                "node '{}' already exists",
+                name
+            )));
+        }
+        self.nodes.push(Node {
+            name: name.to_string(),
+            operation,
+            parents,
+        });
+        Ok(self.nodes.len() - 1)
+    }
+
+    fn check_node(&self, node: NodeId) -> Result<(), MlError> {
+        if node >= self.nodes.len() {
+            return Err(MlError::InvalidInput(format!("unknown node {}", node)));
+        }
+        Ok(())
+    }
+
+    pub fn find_node(&self, name: &str) -> Option<NodeId> {
+        self.nodes.iter().position(|node| node.name == name)
+    }
+
+    /// Adds an input fed with samples of `shape`, inputs are fed in the order they are added.
+    pub fn add_input(&mut self, name: &str, shape: &[usize]) -> Result<NodeId, MlError> {
+        let node: NodeId = self.add_node(name, Operation::Input(shape.to_vec()), vec![])?;
+        self.inputs.push(node);
+        Ok(node)
+    }
+
+    pub fn add_layer(
+        &mut self,
+        name: &str,
+        layer: Box<dyn Layer<F>>,
+        parent: NodeId,
+    ) -> Result<NodeId, MlError> {
+        self.check_node(parent)?;
+        self.add_node(name, Operation::Layer(layer), vec![parent])
+    }
+
+    pub fn add_merge(
+        &mut self,
+        name: &str,
+        merge: Box<dyn Merge<F>>,
+        parents: &[NodeId],
+    ) -> Result<NodeId, MlError> {
+        for &parent in parents {
+            self.check_node(parent)?;
+        }
+        self.add_node(name, Operation::Merge(merge), parents.to_vec())
+    }
+
+    /// Nodes whose values are returned by `predict`, in this order.
+    pub fn set_outputs(&mut self, outputs: &[NodeId]) -> Result<(), MlError> {
+        for &output in outputs {
+            self.check_node(output)?;
+        }
+        self.outputs = outputs.to_vec();
+        Ok(())
+    }
+
+    /// Orders the nodes so each one comes after its parents, fails when the graph has a cycle.
+    fn topological_order(&self) -> Result<Vec<NodeId>, MlError> {
+        let mut missing_parents: Vec<usize> =
+            self.nodes.iter().map(|node| node.parents.len()).collect();
+        let mut children: Vec<Vec<NodeId>> = vec![vec![]; self.nodes.len()];
+        for (id, node) in self.nodes.iter().enumerate() {
+            for &parent in &node.parents {
+                children[parent].push(id);
+            }
+        }
+        let mut ready: VecDeque<NodeId> = (0..self.nodes.len())
+            .filter(|&id| missing_parents[id] == 0)
+            .collect();
+        let mut order: Vec<NodeId> = vec![];
+        while let Some(id) = ready.pop_front() {
+            order.push(id);
+            for &child in &children[id] {
+                missing_parents[child] -= 1;
+                if missing_parents[child] == 0 {
+                    ready.push_back(child);
+                }
+            }
+        }
+        if order.len() != self.nodes.len() {
+            return Err(MlError::InvalidInput("graph has a cycle".to_string()));
+        }
+        Ok(order)
+    }
+
+    /// Infers the shape of every node from the shapes of the inputs and returns the shapes of
+    /// the outputs.
+    pub fn compile(&mut self) -> Result<Vec<Vec<usize>>, MlError> {
+        if self.outputs.is_empty() {
+            return Err(MlError::InvalidInput("graph has no output".to_string()));
+        }
+        let mut shapes: Vec<Vec<usize>> = vec![vec![]; self.nodes.len()];
+        for id in self.topological_order()? {
+            let parent_shapes: Vec<Vec<usize>> = self.nodes[id]
+                .parents
+                .iter()
+                .map(|&parent| shapes[parent].clone())
+                .collect();
+            let node: &mut Node<F> = &mut self.nodes[id];
+            shapes[id] = match &mut node.operation {
+                Operation::Input(shape) => Ok(shape.clone()),
+                Operation::Layer(layer) => layer.compile(&parent_shapes[0]),
+                Operation::Merge(merge) => merge.compile(&parent_shapes),
+            }
+            .map_err(|error| MlError::layer(id, &node.name, error))?;
+        }
+        Ok(self.outputs.iter().map(|&id| shapes[id].clone()).collect())
+    }
+
+    fn check_inputs(&self, x: &[Array2<F>]) -> Result<(), MlError> {
+        if x.len() != self.inputs.len() {
+            return Err(MlError::InvalidInput(format!(
+                "graph expects {} inputs, actually: {}",
+                self.inputs.len(),
+                x.len()
+            )));
+        }
+        Ok(())
+    }
+
+    /// Runs the nodes in topological order and keeps what the backward pass needs.
+    fn forward_propagation(&mut self, x: &[Array2<F>]) -> Result<Vec<Array2<F>>, MlError> {
+        self.check_inputs(x)?;
+        let mut values: Vec<Option<Array2<F>>> = vec![None; self.nodes.len()];
+        for (input, &id) in x.iter().zip(&self.inputs) {
+            values[id] = Some(input.clone());
+        }
+        for id in self.topological_order()? {
+            let parent_values: Vec<Array2<F>> = self.nodes[id]
+                .parents
+                .iter()
+                .map(|&parent| values[parent].clone().unwrap_or_default())
+                .collect();
+            let node: &mut Node<F> = &mut self.nodes[id];
+            let value: Option<Array2<F>> = match &mut node.operation {
+                Operation::Input(_) => None,
+                Operation::Layer(layer) => Some(layer.forward_propagation(&parent_values[0])),
+                Operation::Merge(merge) => Some(merge.forward_propagation(&parent_values)),
+            }
+            .transpose()
+            .map_err(|error| MlError::layer(id, &node.name, error))?;
+            if value.is_some() {
+                values[id] = value;
+            }
+        }
+        Ok(self
+            .outputs
+            .iter()
+            .map(|&id| values[id].clone().unwrap_or_default())
+            .collect())
+    }
+
+    /// Returns the value of every output for the batches of every input.
+    pub fn predict(&self, x: &[Array2<F>]) -> Result<Vec<Array2<F>>, MlError> {
+        self.check_inputs(x)?;
+        let mut values: Vec<Option<Array2<F>>> = vec![None; self.nodes.len()];
+        for (input, &id) in x.iter().zip(&self.inputs) {
+            values[id] = Some(input.clone());
+        }
+        for id in self.topological_order()? {
+            let node: &Node<F> = &self.nodes[id];
+            let parent_values: Vec<Array2<F>> = node
+                .parents
+                .iter()
+                .map(|&parent| values[parent].clone().unwrap_or_default())
+                .collect();
+            let value: Option<Array2<F>> = match &node.operation {
+                Operation::Input(_) => None,
+                Operation::Layer(layer) => Some(layer.predict(&parent_values[0])),
+                Operation::Merge(merge) => Some(merge.predict(&parent_values)),
+            }
+            .transpose()
+            .map_err(|error| MlError::layer(id, &node.name, error))?;
+            if value.is_some() {
+                values[id] = value;
+            }
+        }
+        Ok(self
+            .outputs
+            .iter()
+            .map(|&id| values[id].clone().unwrap_or_default())
+            .collect())
+    }
+
+    /// Mean loss of the samples, summed over the outputs.
+    pub fn compute_loss(&self, x: &[Array2<F>], y: &[Array2<F>]) -> Result<f64, MlError> {
+        let y_pred: Vec<Array2<F>> = self.predict(x)?;
+        let rows: usize = y.first().map_or(0, |y| y.shape()[0]);
+        Ok(self.batch_loss(y, &y_pred)? / rows as f64)
+    }
+
+    /// Summed loss of the rows of the batch over every output.
+    fn batch_loss(&self, y_true: &[Array2<F>], y_pred: &[Array2<F>]) -> Result<f64, MlError> {
+        self.check_targets(y_true)?;
+        y_true
+            .iter()
+            .zip(y_pred)
+            .map(|(y_true, y_pred)| rows_loss(self.loss.as_ref(), y_true, y_pred))
+            .sum()
+    }
+
+    fn check_targets(&self, y: &[Array2<F>]) -> Result<(), MlError> {
+        if y.len() != self.outputs.len() {
+            return Err(MlError::InvalidInput(format!(
+                "graph has {} outputs, actually: {} targets",
+                self.outputs.len(),
+                y.len()
+            )));
+        }
+        Ok(())
+    }
+
+    /// Evaluates every metric on every output. Metric names are prefixed with the name of the
+    /// output when the graph has several outputs, like `gated_r2`.
+    pub fn evaluate(
+        &self,
+        x: &[Array2<F>],
+        y: &[Array2<F>],
+        metrics: &mut [Box<dyn Metric>],
+    ) -> Result<Vec<(String, f64)>, MlError> {
+        if metrics.is_empty() {
+            return Ok(vec![]);
+        }
+        self.check_targets(y)?;
+        let y_pred: Vec<Array2<F>> = self.predict(x)?;
+        let mut results: Vec<(String, f64)> = vec![];
+        for ((&id, y_true), y_pred) in self.outputs.iter().zip(y).zip(&y_pred) {
+            let y_true: Array2<f64> = y_true.mapv(F::to_f64_lossy);
+            let y_pred: Array2<f64> = y_pred.mapv(F::to_f64_lossy);
+            for metric in metrics.iter_mut() {
+                metric.reset();
+                metric.update(&y_pred, &y_true)?;
+                let name: String = if self.outputs.len() > 1 {
+                    format!("{}_{}", self.nodes[id].name, metric.get_name())
+                } else {
+                    metric.get_name()
+                };
+                results.push((name, metric.result()));
+            }
+        }
+        Ok(results)
+    }
+
+    /// Runs one optimization step on the batch and returns the summed loss of its rows. The
+    /// errors reaching a node from all its children are summed before being propagated in
+    /// reverse topological order. Every layer is updated with `learning_rate`, the graph has no
+    /// trainable flags or learning rate multipliers like the `LayerTraining` of an `Mlp`.
+    fn train_batch(
+        &mut self,
+        x: &[Array2<F>],
+        y: &[Array2<F>],
+        learning_rate: F,
+    ) -> Result<f64, MlError> {
+        let output: Vec<Array2<F>> = self.forward_propagation(x)?;
+        let loss: f64 = self.batch_loss(y, &output)?;
+        let mut errors: Vec<Option<Array2<F>>> = vec![None; self.nodes.len()];
+        for ((&id, y), output) in self.outputs.iter().zip(y).zip(&output) {
+            accumulate(&mut errors[id], self.loss.derivative(y, output)?)?;
+        }
+        for id in self.topological_order()?.into_iter().rev() {
+            let error: Array2<F> = match errors[id].take() {
+                Some(error) => error,
+                None => continue,
+            };
+            let node: &mut Node<F> = &mut self.nodes[id];
+            let parent_errors: Vec<Array2<F>> = match &mut node.operation {
+                Operation::Input(_) => Ok(vec![]),
+                Operation::Layer(layer) => layer
+                    .backward_propagation(&error, learning_rate)
+                    .map(|error| vec![error]),
+                Operation::Merge(merge) => merge.backward_propagation(&error),
+            }
+            .map_err(|error| MlError::layer(id, &node.name, error))?;
+            for (&parent, parent_error) in node.parents.iter().zip(parent_errors) {
+                accumulate(&mut errors[parent], parent_error)?;
+            }
+        }
+        Ok(loss)
+    }
+
+    /// Checks the batches of every input and the targets of every output hold the same rows and
+    /// the compiled shapes.
+    fn check_samples(
+        &self,
+        x: &[Array2<F>],
+        y: &[Array2<F>],
+        output_shapes: &[Vec<usize>],
+    ) -> Result<(), MlError> {
+        self.check_inputs(x)?;
+        self.check_targets(y)?;
+        let rows: usize = x.first().map_or(0, |x| x.shape()[0]);
+        for (x, &id) in x.iter().zip(&self.inputs) {
+            if let Operation::Input(shape) = &self.nodes[id].operation {
+                check_shapes(x.shape(), &[rows, shape.iter().product()])?;
+            }
+        }
+        for (y, shape) in y.iter().zip(output_shapes) {
+            check_shapes(y.shape(), &[rows, shape.iter().product()])?;
+        }
+        Ok(())
+    }
+
+    /// Adds the classification report of the first output.
+    fn add_classification_report(
+        &self,
+        x_test: &[Array2<F>],
+        y_test: &[Array2<F>],
+        report: &mut Report,
+    ) -> Result<(), MlError> {
+        let y_test: &Array2<F> = match y_test.first() {
+            Some(y_test) if y_test.iter().all(|&y| y == F::zero() || y == F::one()) => y_test,
+            _ => return Ok(()),
+        };
+        let y_pred: Vec<Array2<F>> = self.predict(x_test)?;
+        report_classification(&y_pred[0], y_test, report)
+    }
+
+    /// Compiles the graph and trains it by mini-batches on the batches of every input and
+    /// the targets of every output, like `Mlp::fit`. The report describes the first input and
+    /// the first output.
+    #[allow(clippy::too_many_arguments)]
+    pub fn fit(
+        &mut self,
+        x_train: &[Array2<F>],
+        y_train: &[Array2<F>],
+        x_test: Option<&[Array2<F>]>,
+        y_test: Option<&[Array2<F>]>,
+        epochs: usize,
+        learning_rate: F,
+        batch_size: usize,
+        metrics: &mut [Box<dyn Metric>],
+        report: &mut Report,
+        report_step: usize,
+    ) -> Result<(), MlError> {
+        check_batch_size(batch_size)?;
+        check_report_step(report_step)?;
+        self.check_inputs(x_train)?;
+        let output_shapes: Vec<Vec<usize>> = self.compile()?;
+        let rows: usize = x_train.first().map_or(0, |x| x.shape()[0]);
+        if rows == 0 {
+            return Err(MlError::InvalidInput(
+                "training set must not be empty".to_string(),
+            ));
+        }
+        self.check_samples(x_train, y_train, &output_shapes)?;
+        let test_set: Option<Samples<F>> = x_test.zip(y_test);
+        if let Some((x_test, y_test)) = test_set {
+            self.check_samples(x_test, y_test, &output_shapes)?;
+        }
+        let start: Instant = Instant::now();
+        let x_train_shape: Vec<usize> = batch_shape(rows, &self.input_shape(0));
+        let x_test_shape: Option<Vec<usize>> = test_set.map(|(x_test, _)| {
+            batch_shape(
+                x_test.first().map_or(0, |x| x.shape()[0]),
+                &self.input_shape(0),
+            )
+        });
+        let y_test_shape: Option<&[usize]> =
+            test_set.and_then(|(_, y_test)| y_test.first().map(|y| y.shape()));
+
+        for i in 0..epochs {
+            let mut error: f64 = 0.0;
+            for start in (0..rows).step_by(batch_size) {
+                let end: usize = (start + batch_size).min(rows);
+                let x_batch: Vec<Array2<F>> = x_train
+                    .iter()
+                    .map(|x| x.slice_axis(Axis(0), (start..end).into()).to_owned())
+                    .collect();
+                let y_batch: Vec<Array2<F>> = y_train
+                    .iter()
+                    .map(|y| y.slice_axis(Axis(0), (start..end).into()).to_owned())
+                    .collect();
+                error += self.train_batch(&x_batch, &y_batch, learning_rate)?;
+            }
+
+            let train_loss: f64 = error / rows as f64;
+            if !train_loss.is_finite() {
+                return Err(MlError::Numeric(format!(
+                    "train loss diverged to {} at epoch {}",
+                    train_loss, i
+                )));
+            }
+            let train_metrics: Vec<(String, f64)> = self.evaluate(x_train, y_train, metrics)?;
+            let test_metrics: Option<Vec<(String, f64)>> = test_set
+                .map(|(x_test, y_test)| self.evaluate(x_test, y_test, metrics))
+                .transpose()?;
+            let test_loss: Option<f64> = test_set
+                .map(|(x_test, y_test)| self.compute_loss(x_test, y_test))
+                .transpose()?;
+
+            log_epoch(i, epochs, train_loss, test_loss, &train_metrics);
+            report.add_data(i, train_metrics, train_loss, test_metrics, test_loss);
+
+            if i > 0 && (i % report_step == 0 || i == epochs - 1) {
+                if let Some((x_test, y_test)) = test_set {
+                    self.add_classification_report(x_test, y_test, report)?;
+                }
+                report.generate(
+                    &self.get_name(),
+                    start,
+                    epochs,
+                    &x_train_shape,
+                    y_train[0].shape(),
+                    x_test_shape.as_deref(),
+                    y_test_shape,
+                    self.loss.get_name().as_str(),
+                    None,
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Shape of the samples of the input at `index`.
+    fn input_shape(&self, index: usize) -> Vec<usize> {
+        match &self.nodes[self.inputs[index]].operation {
+            Operation::Input(shape) => shape.clone(),
+            _ => vec![],
+        }
+    }
+}
+
+fn accumulate<F: MlFloat>(sum: &mut Option<Array2<F>>, error: Array2<F>) -> Result<(), MlError> {
+    match sum {
+        Some(sum) => {
+            check_shapes(sum.shape(), error.shape())?;
+            *sum += &error;
+        }
+        None => *sum = Some(error),
+    }
+    Ok(())
+}
+
+impl<F: MlFloat> Network<Graph<F>> for Graph<F> {
+    fn get_name(&self) -> String {
+        "Graph".to_string()
+    }
+
+    /// Nodes may be listed in any order, parents are referenced by name.
+    fn from_json(json_str: &str) -> Result<Graph<F>, MlError> {
+        let model: GraphModel = serde_json::from_str(json_str)?;
+        let mut names: HashMap<String, NodeId> = HashMap::new();
+        for (id, node) in model.nodes.iter().enumerate() {
+            let name: &String = match node {
+                NodeModel::Input { name, .. }
+                | NodeModel::Layer { name, .. }
+                | NodeModel::Merge { name, .. } => name,
+            };
+            if names.insert(name.clone(), id).is_some() {
+                return Err(MlError::InvalidInput(format!(
+                    "node '{}' already exists",
+                    name
+                )));
+            }
+        }
+        let find = |name: &String| -> Result<NodeId, MlError> {
+            names
+                .get(name)
+                .copied()
+                .ok_or_else(|| MlError::unknown_component("node", name))
+        };
+        let mut graph: Graph<F> = Graph::build(loss::from_string(model.loss)?);
+        for node in &model.nodes {
+            let node: Node<F> = match node {
+                NodeModel::Input { name, shape } => Node {
+                    name: name.clone(),
+                    operation: Operation::Input(shape.clone()),
+                    parents: vec![],
+                },
+                NodeModel::Layer {
+                    name,
+                    layer: (layer_name, layer_json),
+                    parent,
+                } => Node {
+                    name: name.clone(),
+                    operation: Operation::Layer(layer::from_string(
+                        layer_name.clone(),
+                        layer_json,
+                    )?),
+                    parents: vec![find(parent)?],
+                },
+                NodeModel::Merge {
+                    name,
+                    merge: (merge_name, merge_json),
+                    parents,
+                } => Node {
+                    name: name.clone(),
+                    operation: Operation::Merge(merge::from_string(
+                        merge_name.clone(),
+                        merge_json,
+                    )?),
+                    parents: parents.iter().map(find).collect::<Result<_, MlError>>()?,
+                },
+            };
+            graph.nodes.push(node);
+        }
+        graph.inputs = model
+            .inputs
+            .iter()
+            .map(find)
+            .collect::<Result<_, MlError>>()?;
+        graph.outputs = model
+            .outputs
+            .iter()
+            .map(find)
+            .collect::<Result<_, MlError>>()?;
+        graph.topological_order()?;
+        Ok(graph)
+    }
+
+    fn to_json(&self) -> Result<String, MlError> {
+        let name = |id: &NodeId| self.nodes[*id].name.clone();
+        let mut nodes: Vec<NodeModel> = vec![];
+        for node in &self.nodes {
+            nodes.push(match &node.operation {
+                Operation::Input(shape) => NodeModel::Input {
+                    name: node.name.clone(),
+                    shape: shape.clone(),
+                },
+                Operation::Layer(layer) => NodeModel::Layer {
+                    name: node.name.clone(),
+                    layer: (layer.get_name(), layer.to_json()?),
+                    parent: name(&node.parents[0]),
+                },
+                Operation::Merge(merge) => NodeModel::Merge {
+                    name: node.name.clone(),
+                    merge: (merge.get_name(), merge.to_json()?),
+                    parents: node.parents.iter().map(name).collect(),
+                },
+            });
+        }
+        let model: GraphModel = GraphModel {
+            nodes,
+            inputs: self.inputs.iter().map(name).collect(),
+            outputs: self.outputs.iter().map(name).collect(),
+            loss: self.loss.get_name(),
+            dtype: F::DTYPE.to_string(),
+        };
+        Ok(serde_json::to_string(&model)?)
+    }
+
+    fn load(path: &str) -> Result<Graph<F>, MlError> {
+        let content: String = read_to_string(path)?;
+        Graph::from_json(content.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::accuracy::regression::RegressionMetric;
+    use crate::accuracy::regression_score::RegressionScore;
+    use crate::activation::tanh::Tanh;
+    use crate::layer::activation_layer::ActivationLayer;
+    use crate::layer::fc_layer::FCLayer;
+    use crate::loss::mse::Mse;
+    use crate::merge::add::Add;
+    use crate::merge::concatenate::Concatenate;
+    use crate::merge::multiply::Multiply;
+    use ndarray::{arr2, s};
+    use std::fs;
+    use std::fs::remove_file;
+    use std::path::Path;
+
+    /// Two inputs, a residual connection on the first one and a product with the second one.
+    fn generate_test_graph() -> Graph {
+        let mut graph: Graph = Graph::build(Box::new(Mse));
+        let a: NodeId = graph.add_input("a", &[2]).unwrap();
+        let b: NodeId = graph.add_input("b", &[1]).unwrap();
+        let hidden: NodeId = graph
+            .add_layer("hidden", Box::new(FCLayer::build_with_seed(2, 2, 3)), a)
+            .unwrap();
+        let tanh: NodeId = graph
+            .add_layer(
+                "tanh",
+                Box::new(ActivationLayer::build(Box::new(Tanh))),
+                hidden,
+            )
+            .unwrap();
+        let skip: NodeId = graph
+            .add_merge("skip", Box::new(Add::new()), &[tanh, a])
+            .unwrap();
+        let joined: NodeId = graph
+            .add_merge("joined", Box::new(Concatenate::new()), &[skip, b])
+            .unwrap();
+        let output: NodeId = graph
+            .add_layer(
+                "output",
+                Box::new(FCLayer::build_with_seed(3, 1, 4)),
+                joined,
+            )
+            .unwrap();
+        let gated: NodeId = graph
+            .add_merge("gated", Box::new(Multiply::new()), &[output, b])
+            .unwrap();
+        graph.set_outputs(&[output, gated]).unwrap();
+        graph
+    }
+
+    fn generate_test_data() -> (Vec<Array2<f64>>, Vec<Array2<f64>>) {
+        let a: Array2<f64> = arr2(&[[0.0, 0.0], [0.0, 1.0], [1.0, 0.0], [1.0, 1.0]]);
+        let b: Array2<f64> = arr2(&[[1.0], [0.5], [0.5], [1.0]]);
+        let y: Array2<f64> = arr2(&[[0.0], [0.5], [0.5], [1.0]]);
+        let gated: Array2<f64> = &y * &b;
+        (vec![a, b], vec![y, gated])
+    }
+
+    #[test]
+    fn compile_should_infer_output_shapes() {
+        let mut graph: Graph = generate_test_graph();
+        assert_eq!(graph.compile().unwrap(), vec![vec![1], vec![1]]);
+    }
+
+    #[test]
+    fn compile_should_report_node_that_does_not_fit() {
+        let mut graph: Graph = Graph::build(Box::new(Mse));
+        let a: NodeId = graph.add_input("a", &[2]).unwrap();
+        let b: NodeId = graph.add_input("b", &[3]).unwrap();
+        let sum: NodeId = graph
+            .add_merge("sum", Box::new(Add::new()), &[a, b])
+            .unwrap();
+        graph.set_outputs(&[sum]).unwrap();
+        let error: MlError = graph.compile().unwrap_err();
+        assert!(matches!(error, MlError::Layer { index: 2, .. }));
+        assert!(error.to_string().starts_with("layer 2 (sum)"));
+    }
+
+    #[test]
+    fn add_node_should_check_names_and_parents() {
+        let mut graph: Graph = Graph::build(Box::new(Mse));
+        graph.add_input("a", &[2]).unwrap();
+        assert!(matches!(
+            graph.add_input("a", &[2]),
+            Err(MlError::InvalidInput(_))
+        ));
+        assert!(matches!(
+            graph.add_layer("fc", Box::new(FCLayer::build(2, 1)), 3),
+            Err(MlError::InvalidInput(_))
+        ));
+        assert_eq!(graph.find_node("a"), Some(0));
+    }
+
+    #[test]
+    fn topological_order_should_fail_on_cycle() {
+        let mut graph: Graph = generate_test_graph();
+        graph.nodes[2].parents = vec![6];
+        assert!(matches!(
+            graph.topological_order(),
+            Err(MlError::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn fit_should_train_every_output() {
+        let (x, y): (Vec<Array2<f64>>, Vec<Array2<f64>>) = generate_test_data();
+        let mut graph: Graph = generate_test_graph();
+        graph.compile().unwrap();
+        let initial_loss: f64 = graph.compute_loss(&x, &y).unwrap();
+
+        graph
+            .fit(
+                &x,
+                &y,
+                Some(&x),
+                Some(&y),
+                300,
+                0.1,
+                1,
+                &mut [Box::new(RegressionScore::build(RegressionMetric::R2))],
+                &mut Report::build("./test_report_graph_1"),
+                100,
+            )
+            .unwrap();
+
+        let loss: f64 = graph.compute_loss(&x, &y).unwrap();
+        assert!(loss < initial_loss / 10.0);
+        let result: Vec<Array2<f64>> = graph.predict(&x).unwrap();
+        assert_eq!(result.len(), 2);
+        assert_eq!(result[0].shape(), &[4, 1]);
+        let metrics: Vec<(String, f64)> = graph
+            .evaluate(
+                &x,
+                &y,
+                &mut [Box::new(RegressionScore::build(RegressionMetric::R2))],
+            )
+            .unwrap();
+        assert_eq!(metrics[0].0, "output_r2");
+        assert_eq!(metrics[1].0, "gated_r2");
+        assert!(Path::new("./test_report_graph_1/299/report.html").exists());
+        fs::remove_dir_all("./test_report_graph_1").unwrap();
+    }
+
+    #[test]
+    fn compute_loss_should_average_rows_like_mlp() {
+        let (x, y): (Vec<Array2<f64>>, Vec<Array2<f64>>) = generate_test_data();
+        let mut graph: Graph = generate_test_graph();
+        graph.compile().unwrap();
+        let y_pred: Vec<Array2<f64>> = graph.predict(&x).unwrap();
+        let expected: f64 = y
+            .iter()
+            .zip(&y_pred)
+            .map(|(y, y_pred)| {
+                (0..4)
+                    .map(|r| (y[[r, 0]] - y_pred[[r, 0]]).powf(2.0))
+                    .sum::<f64>()
+                    / 4.0
+            })
+            .sum();
+        assert!((graph.compute_loss(&x, &y).unwrap() - expected).powf(2.0) < 0.00001);
+    }
+
+    #[test]
+    fn train_batch_should_decrease_loss() {
+        let (x, y): (Vec<Array2<f64>>, Vec<Array2<f64>>) = generate_test_data();
+        let mut graph: Graph = generate_test_graph();
+        graph.compile().unwrap();
+        let learning_rate: f64 = 0.001;
+        let before: f64 = graph.compute_loss(&x, &y).unwrap();
+        graph.train_batch(&x, &y, learning_rate).unwrap();
+        let after: f64 = graph.compute_loss(&x, &y).unwrap();
+        assert!(after < before);
+    }
+
+    #[test]
+    fn fit_should_fail_when_inputs_do_not_match() {
+        let (x, y): (Vec<Array2<f64>>, Vec<Array2<f64>>) = generate_test_data();
+        let mut graph: Graph = generate_test_graph();
+        let mut fit = |x: &[Array2<f64>], y: &[Array2<f64>], x_test: &[Array2<f64>]| {
+            graph.fit(
+                x,
+                y,
+                Some(x_test),
+                Some(y),
+                1,
+                0.1,
+                1,
+                &mut [],
+                &mut Report::build("./test_report_graph_2"),
+                10,
+            )
+        };
+        assert!(matches!(
+            fit(&x[..1], &y, &x),
+            Err(MlError::InvalidInput(_))
+        ));
+        assert!(matches!(
+            fit(&[x[1].clone(), x[1].clone()], &y, &x),
+            Err(MlError::ShapeMismatch(_))
+        ));
+        assert!(matches!(
+            fit(&x, &y[..1], &x),
+            Err(MlError::InvalidInput(_))
+        ));
+        assert!(matches!(
+            fit(&x, &y, &[x[0].slice(s![..2, ..]).to_owned(), x[1].clone()]),
+            Err(MlError::ShapeMismatch(_))
+        ));
+        assert!(!Path::new("./test_report_graph_2").exists());
+    }
+
+    #[test]
+    fn fit_should_fail_when_report_step_is_zero() {
+        let (x, y): (Vec<Array2<f64>>, Vec<Array2<f64>>) = generate_test_data();
+        let mut graph: Graph = generate_test_graph();
+        assert!(matches!(
+            graph.fit(
+                &x,
+                &y,
+                None,
+                None,
+                2,
+                0.1,
+                1,
+                &mut [],
+                &mut Report::build("./test_report_graph_3"),
+                0,
+            ),
+            Err(MlError::InvalidInput(_))
+        ));
+        assert!(!Path::new("./test_report_graph_3").exists());
+    }
+
+    #[test]
+    fn load_should_read_graph() {
+        let (x, _): (Vec<Array2<f64>>, Vec<Array2<f64>>) = generate_test_data();
+        let mut graph: Graph = generate_test_graph();
+        graph.compile().unwrap();
+        graph.save("", "UnitTestGraph1".to_string()).unwrap();
+
+        let mut loaded: Graph = Graph::load("UnitTestGraph1.json").unwrap();
+        remove_file("UnitTestGraph1.json").unwrap();
+
+        assert_eq!(loaded.get_name(), "Graph");
+        assert_eq!(loaded.compile().unwrap(), vec![vec![1], vec![1]]);
+        let expected: Vec<Array2<f64>> = graph.predict(&x).unwrap();
+        let result: Vec<Array2<f64>> = loaded.predict(&x).unwrap();
+        for (e, r) in expected.iter().zip(&result) {
+            for (e, r) in e.iter().zip(r.iter()) {
+                assert!((e - r).powf(2.0) < 0.00001);
+            }
+        }
+    }
+
+    #[test]
+    fn from_json_should_accept_nodes_in_any_order() {
+        let json: &str = "{\"nodes\":[\
+            {\"Layer\":{\"name\":\"fc\",\"layer\":[\"FCLayer\",\"{\\\"weights\\\":[1.0,2.0],\\\"bias\\\":[0.5],\\\"shape\\\":[2,1]}\"],\"parent\":\"x\"}},\
+            {\"Input\":{\"name\":\"x\",\"shape\":[2]}}],\
+            \"inputs\":[\"x\"],\"outputs\":[\"fc\"],\"loss\":\"MSE\",\"dtype\":\"f64\"}";
+        let graph: Graph = Graph::from_json(json).unwrap();
+        assert_eq!(
+            graph.predict(&[arr2(&[[1.0, 1.0]])]).unwrap(),
+            vec![arr2(&[[3.5]])]
+        );
+        assert!(matches!(
+            Graph::<f64>::from_json(&json.replace("\"parent\":\"x\"", "\"parent\":\"y\"")),
+            Err(MlError::UnknownComponent { .. })
+        ));
+        assert!(matches!(
+            Graph::<f64>::from_json(&json.replace("\"name\":\"fc\"", "\"name\":\"x\"")),
+            Err(MlError::InvalidInput(_))
+        ));
+    }
+}
@@ -1,144 +1,766 @@
-use crate::accuracy::accuracy::accuracy;
+use crate::accuracy::accuracy::Metric;
+use crate::accuracy::confusion_matrix::ConfusionMatrix;
+use crate::accuracy::ranking::{one_vs_rest_curves, precision_recall_curve, roc_curve};
 use crate::layer::layer;
 use crate::layer::layer::Layer;
 use crate::loss::loss;
 use crate::loss::loss::Loss;
 use crate::network::network::Network;
+use crate::network::summary::{LayerSummary, Summary};
 use crate::report::report::Report;
-use ndarray::{Array2, Axis};
+use crate::utils::error::{check_shapes, MlError};
+use crate::utils::float::MlFloat;
+use ndarray::{concatenate, Array2, ArrayView2, Axis};
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
-use std::error::Error;
+use std::collections::HashMap;
 use std::fs::read_to_string;
+use std::mem::size_of;
+use std::ops::Range;
 use std::time::Instant;
 
-pub struct Mlp {
-    layers: Vec<Box<dyn Layer>>,
-    loss: Box<dyn Loss>,
+/// Cloned layers and the batch rows they train on.
+#[cfg(feature = "parallel")]
+type Worker<F> = (Vec<Box<dyn Layer<F>>>, Array2<F>);
+
+pub struct Mlp<F: MlFloat = f64> {
+    layers: Vec<Box<dyn Layer<F>>>,
+    /// Training settings of the layer at the same index.
+    training: Vec<LayerTraining>,
+    loss: Box<dyn Loss<F>>,
+    input_shape: Option<Vec<usize>>,
+    #[cfg(feature = "parallel")]
+    threads: usize,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct MlpModel {
     layers: Vec<(String, String)>,
     loss: String,
+    #[serde(default = "default_dtype")]
+    dtype: String,
+    #[serde(default)]
+    input_shape: Option<Vec<usize>>,
+    /// Empty for models saved before layers could be frozen, every layer is then trainable.
+    #[serde(default)]
+    training: Vec<LayerTraining>,
+}
+
+/// How the optimizer updates a layer. A frozen layer keeps its parameters but still sends the
+/// error back to the previous layers, so the layers before it can be fine-tuned. The settings
+/// apply to a whole layer of the network: the layers inside a `ResidualBlock` or a `Graph`
+/// can not be frozen or scaled one by one.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct LayerTraining {
+    pub trainable: bool,
+    /// Factor applied to the learning rate of the network for this layer.
+    pub learning_rate_multiplier: f64,
+}
+
+impl Default for LayerTraining {
+    fn default() -> LayerTraining {
+        LayerTraining {
+            trainable: true,
+            learning_rate_multiplier: 1.0,
+        }
+    }
+}
+
+impl LayerTraining {
+    /// Learning rate of the layer, `None` when it is frozen.
+    fn learning_rate<F: MlFloat>(&self, learning_rate: F) -> Option<F> {
+        self.trainable
+            .then(|| learning_rate * F::cast(self.learning_rate_multiplier))
+    }
+}
+
+/// Models saved before the dtype was recorded were always f64.
+fn default_dtype() -> String {
+    f64::DTYPE.to_string()
 }
 
-impl Mlp {
-    pub fn build(loss: Box<dyn Loss>) -> Mlp {
+impl<F: MlFloat> Mlp<F> {
+    pub fn build(loss: Box<dyn Loss<F>>) -> Mlp<F> {
         Mlp {
             layers: vec![],
+            training: vec![],
             loss,
+            input_shape: None,
+            #[cfg(feature = "parallel")]
+            threads: 1,
+        }
+    }
+
+    /// Number of shards each mini-batch is split into during training. Networks with stateful
+    /// layers are trained on a single thread, the states of a batch depend on the previous one.
+    #[cfg(feature = "parallel")]
+    pub fn set_threads(&mut self, threads: usize) -> Result<(), MlError> {
+        if threads == 0 {
+            return Err(MlError::InvalidInput(
+                "threads must be greater than 0".to_string(),
+            ));
         }
+        self.threads = threads;
+        Ok(())
     }
 
-    pub fn add_layer(&mut self, layer: Box<dyn Layer>) -> () {
+    pub fn add_layer(&mut self, layer: Box<dyn Layer<F>>) {
         self.layers.push(layer);
+        self.training.push(LayerTraining::default());
     }
 
-    pub fn predict(&mut self, x: &Array2<f64>) -> Array2<f64> {
-        let mut result: Array2<f64> = Array2::zeros((0, self.layers.last().unwrap().get_shape().1));
-        for r in 0..(x.shape()[0]) {
-            let mut output: Array2<f64> = x.select(Axis(0), &[r]);
+    fn check_layer_index(&self, index: usize) -> Result<(), MlError> {
+        if index >= self.layers.len() {
+            return Err(MlError::InvalidInput(format!(
+                "layer index must be smaller than {}, actually: {}",
+                self.layers.len(),
+                index
+            )));
+        }
+        Ok(())
+    }
+
+    /// Freezes the layer at `index` when `trainable` is false, its parameters are no longer
+    /// updated during training. A container such as `ResidualBlock` is frozen as a whole.
+    pub fn set_trainable(&mut self, index: usize, trainable: bool) -> Result<(), MlError> {
+        self.check_layer_index(index)?;
+        self.training[index].trainable = trainable;
+        Ok(())
+    }
+
+    /// Scales the learning rate of the layer at `index`, 1 by default. A multiplier of 0 stops
+    /// the updates like `set_trainable(index, false)`.
+    pub fn set_learning_rate_multiplier(
+        &mut self,
+        index: usize,
+        multiplier: f64,
+    ) -> Result<(), MlError> {
+        self.check_layer_index(index)?;
+        if !multiplier.is_finite() || multiplier < 0.0 {
+            return Err(MlError::InvalidInput(format!(
+                "learning rate multiplier must be a non-negative number, actually: {}",
+                multiplier
+            )));
+        }
+        self.training[index].learning_rate_multiplier = multiplier;
+        Ok(())
+    }
+
+    pub fn get_layer_training(&self, index: usize) -> Option<LayerTraining> {
+        self.training.get(index).copied()
+    }
+
+    /// Removes the last layer, the network stays compiled.
+    pub fn pop_layer(&mut self) -> Option<Box<dyn Layer<F>>> {
+        self.training.pop();
+        self.layers.pop()
+    }
+
+    /// Keeps the first `n` layers, the network stays compiled.
+    pub fn truncate(&mut self, n: usize) {
+        self.layers.truncate(n);
+        self.training.truncate(n);
+    }
+
+    /// Inserts a trainable layer before the layer at `index`. A compiled network is compiled
+    /// again and the layer is removed when the network no longer fits together.
+    pub fn insert_layer(&mut self, index: usize, layer: Box<dyn Layer<F>>) -> Result<(), MlError> {
+        if index > self.layers.len() {
+            return Err(MlError::InvalidInput(format!(
+                "layer index must not be greater than {}, actually: {}",
+                self.layers.len(),
+                index
+            )));
+        }
+        self.layers.insert(index, layer);
+        self.training.insert(index, LayerTraining::default());
+        if let Some(input_shape) = self.input_shape.clone() {
+            if let Err(error) = self.compile(&input_shape) {
+                self.layers.remove(index);
+                self.training.remove(index);
+                self.compile(&input_shape)?;
+                return Err(error);
+            }
+        }
+        Ok(())
+    }
+
+    /// Copies the layers in `range` with their training settings into a new network with the
+    /// same loss, like the first layers of a trained classifier used as a feature extractor. The
+    /// copy is compiled for the output shape of the previous layers when the network is compiled.
+    pub fn sub_network(&self, range: Range<usize>) -> Result<Mlp<F>, MlError> {
+        if range.start > range.end || range.end > self.layers.len() {
+            return Err(MlError::InvalidInput(format!(
+                "layer range must be within 0..{}, actually: {:?}",
+                self.layers.len(),
+                range
+            )));
+        }
+        let mut mlp: Mlp<F> = Mlp::build(loss::from_string(self.loss.get_name())?);
+        for index in range.clone() {
+            mlp.add_layer(self.layers[index].clone_layer());
+            mlp.training[index - range.start] = self.training[index];
+        }
+        if let Some(input_shape) = self.get_input_shape() {
+            let mut shape: Vec<usize> = input_shape.to_vec();
+            for layer in &self.layers[..range.start] {
+                shape = layer.clone_layer().compile(&shape)?;
+            }
+            mlp.compile(&shape)?;
+        }
+        Ok(mlp)
+    }
+
+    /// Names identifying the layers across networks, the layer type followed by its rank among
+    /// the layers of the same type, like `FCLayer_1` for the second `FCLayer`.
+    pub fn get_layer_names(&self) -> Vec<String> {
+        let mut counts: HashMap<String, usize> = HashMap::new();
+        self.layers
+            .iter()
+            .map(|layer| {
+                let count: &mut usize = counts.entry(layer.get_name()).or_insert(0);
+                *count += 1;
+                format!("{}_{}", layer.get_name(), *count - 1)
+            })
+            .collect()
+    }
+
+    /// Replaces the parameters of the layer at `index` by the ones of the layer at
+    /// `source_index` of `source`. Both layers must have the same type, shape and number of
+    /// parameters. Only the parameter arrays are copied, the layer keeps its own configuration
+    /// (like the function of an `ActivationLayer`) and training settings.
+    pub fn copy_layer_weights(
+        &mut self,
+        index: usize,
+        source: &Mlp<F>,
+        source_index: usize,
+    ) -> Result<(), MlError> {
+        self.check_layer_index(index)?;
+        source.check_layer_index(source_index)?;
+        let (layer, source_layer): (&dyn Layer<F>, &dyn Layer<F>) = (
+            self.layers[index].as_ref(),
+            source.layers[source_index].as_ref(),
+        );
+        if layer.get_name() != source_layer.get_name()
+            || layer.get_shape() != source_layer.get_shape()
+            || layer.count_parameters() != source_layer.count_parameters()
+        {
+            return Err(MlError::ShapeMismatch(format!(
+                "expected a {} of shape {:?}, actually: {} of shape {:?}",
+                layer.get_name(),
+                layer.get_shape(),
+                source_layer.get_name(),
+                source_layer.get_shape()
+            )));
+        }
+        if layer.count_parameters() == (0, 0) {
+            return Ok(());
+        }
+        let parameters: Vec<Array2<F>> = source_layer.get_parameters();
+        self.layers[index].set_parameters(parameters)
+    }
+
+    /// Copies the parameters of every layer of `source` with the same name and matching shapes,
+    /// see `get_layer_names`. Layers without parameters are skipped. Returns the names of the
+    /// copied layers.
+    pub fn copy_weights_by_name(&mut self, source: &Mlp<F>) -> Vec<String> {
+        let source_names: Vec<String> = source.get_layer_names();
+        self.get_layer_names()
+            .into_iter()
+            .enumerate()
+            .filter_map(|(index, name)| {
+                if self.layers[index].count_parameters() == (0, 0) {
+                    return None;
+                }
+                let source_index: usize = source_names.iter().position(|n| *n == name)?;
+                self.copy_layer_weights(index, source, source_index)
+                    .ok()
+                    .map(|_| name)
+            })
+            .collect()
+    }
+
+    /// Infers the shape of every layer from the shape of the input samples (without the batch
+    /// axis) and returns the shape of the output samples. Fails on the first layer that does not
+    /// accept the output of the previous one.
+    pub fn compile(&mut self, input_shape: &[usize]) -> Result<Vec<usize>, MlError> {
+        let mut shape: Vec<usize> = input_shape.to_vec();
+        for (index, layer) in self.layers.iter_mut().enumerate() {
+            shape = layer
+                .compile(&shape)
+                .map_err(|error| MlError::layer(index, &layer.get_name(), error))?;
+        }
+        self.input_shape = Some(input_shape.to_vec());
+        Ok(shape)
+    }
+
+    /// Shape of the input samples the network was last compiled with.
+    pub fn get_input_shape(&self) -> Option<&[usize]> {
+        self.input_shape.as_deref()
+    }
+
+    /// Prints the layers of the compiled network with their output shapes, parameter counts and
+    /// memory footprint, and returns them.
+    pub fn summary(&self) -> Result<Summary, MlError> {
+        let summary: Summary = self.build_summary()?;
+        print!("{}", summary);
+        Ok(summary)
+    }
+
+    fn build_summary(&self) -> Result<Summary, MlError> {
+        let input_shape: &[usize] = self.get_input_shape().ok_or_else(|| {
+            MlError::InvalidInput("network must be compiled before its summary".to_string())
+        })?;
+        let mut shape: Vec<usize> = input_shape.to_vec();
+        let mut layers: Vec<LayerSummary> = vec![];
+        for (index, layer) in self.layers.iter().enumerate() {
+            shape = layer
+                .clone_layer()
+                .compile(&shape)
+                .map_err(|error| MlError::layer(index, &layer.get_name(), error))?;
+            let (mut trainable_params, mut non_trainable_params): (usize, usize) =
+                layer.count_parameters();
+            if !self.training[index].trainable {
+                non_trainable_params += trainable_params;
+                trainable_params = 0;
+            }
+            layers.push(LayerSummary {
+                name: layer.get_name(),
+                output_shape: shape.clone(),
+                trainable_params,
+                non_trainable_params,
+                memory: (trainable_params + non_trainable_params) * size_of::<F>(),
+            });
+        }
+        Ok(Summary::build(input_shape.to_vec(), layers))
+    }
+
+    /// Float type of the weights, `"f32"` or `"f64"`.
+    pub fn get_dtype(&self) -> &'static str {
+        F::DTYPE
+    }
+
+    /// Converts the network to another float precision.
+    pub fn cast<G: MlFloat>(&self) -> Result<Mlp<G>, MlError> {
+        Mlp::<G>::from_json(self.to_json()?.as_str())
+    }
+
+    pub fn predict(&self, x: &Array2<F>) -> Result<Array2<F>, MlError> {
+        self.layers
+            .iter()
+            .enumerate()
+            .try_fold(x.clone(), |output, (index, layer)| {
+                layer
+                    .predict(&output)
+                    .map_err(|error| MlError::layer(index, &layer.get_name(), error))
+            })
+    }
+
+    /// Runs the inference by chunks of `batch_size` rows to bound the memory used by activations.
+    pub fn predict_batch(&self, x: &Array2<F>, batch_size: usize) -> Result<Array2<F>, MlError> {
+        check_batch_size(batch_size)?;
+        if x.shape()[0] == 0 {
+            return self.predict(x);
+        }
+        let outputs: Vec<Array2<F>> = x
+            .axis_chunks_iter(Axis(0), batch_size)
+            .map(|chunk| self.predict(&chunk.to_owned()))
+            .collect::<Result<Vec<Array2<F>>, MlError>>()?;
+        let output_views: Vec<ArrayView2<F>> = outputs.iter().map(|o| o.view()).collect();
+        Ok(concatenate(Axis(0), &output_views)?)
+    }
+
+    pub fn evaluate(
+        &self,
+        x: &Array2<F>,
+        y: &Array2<F>,
+        metrics: &mut [Box<dyn Metric>],
+    ) -> Result<Vec<(String, f64)>, MlError> {
+        if metrics.is_empty() {
+            return Ok(vec![]);
+        }
+        let y_pred: Array2<f64> = self.predict(x)?.mapv(F::to_f64_lossy);
+        let y: Array2<f64> = y.mapv(F::to_f64_lossy);
+        metrics
+            .iter_mut()
+            .map(|metric| {
+                metric.reset();
+                metric.update(&y_pred, &y)?;
+                Ok((metric.get_name(), metric.result()))
+            })
+            .collect()
+    }
+
+    fn batch_loss(&self, y_true: &Array2<F>, y_pred: &Array2<F>) -> Result<f64, MlError> {
+        rows_loss(self.loss.as_ref(), y_true, y_pred)
+    }
+
+    pub fn compute_loss(&self, x: &Array2<F>, y: &Array2<F>) -> Result<f64, MlError> {
+        check_rows(x, y)?;
+        Ok(self.batch_loss(y, &self.predict(x)?)? / y.shape()[0] as f64)
+    }
+
+    /// Runs one optimization step on the batch and returns the summed loss of its rows.
+    fn train_batch(
+        &mut self,
+        x: &Array2<F>,
+        y: &Array2<F>,
+        learning_rate: F,
+    ) -> Result<f64, MlError> {
+        #[cfg(feature = "parallel")]
+        if self.threads > 1
+            && x.shape()[0] > 1
+            && !self.layers.iter().any(|layer| layer.is_stateful())
+        {
+            return self.train_batch_parallel(x, y, learning_rate);
+        }
+        let output: Array2<F> =
             self.layers
                 .iter_mut()
-                .for_each(|l| output = l.forward_propagation(&output));
-            result.push_row(output.row(0)).unwrap();
+                .enumerate()
+                .try_fold(x.clone(), |output, (index, layer)| {
+                    layer
+                        .forward_propagation(&output)
+                        .map_err(|error| MlError::layer(index, &layer.get_name(), error))
+                })?;
+        let mut error_buffer: Array2<F> = self.loss.derivative(y, &output)?;
+        for (layer, training) in self.layers.iter_mut().zip(&self.training).rev() {
+            error_buffer = layer.compute_gradients(&error_buffer)?;
+            if let Some(learning_rate) = training.learning_rate(learning_rate) {
+                layer.apply_gradients(learning_rate);
+            }
         }
-        result
+        self.batch_loss(y, &output)
     }
 
+    /// Shards the batch rows over cloned layers, the loss derivative is taken on the gathered
+    /// outputs so the summed shard gradients equal the single threaded batch gradient.
+    #[cfg(feature = "parallel")]
+    fn train_batch_parallel(
+        &mut self,
+        x: &Array2<F>,
+        y: &Array2<F>,
+        learning_rate: F,
+    ) -> Result<f64, MlError> {
+        let shard_size: usize = x.shape()[0].div_ceil(self.threads);
+        let mut workers: Vec<Worker<F>> = x
+            .axis_chunks_iter(Axis(0), shard_size)
+            .map(|shard| {
+                (
+                    self.layers
+                        .iter()
+                        .map(|layer| layer.clone_layer())
+                        .collect(),
+                    shard.to_owned(),
+                )
+            })
+            .collect();
+
+        let outputs: Vec<Array2<F>> = workers
+            .par_iter_mut()
+            .map(|(layers, shard)| {
+                layers.iter_mut().try_fold(shard.clone(), |output, layer| {
+                    layer.forward_propagation(&output)
+                })
+            })
+            .collect::<Result<Vec<Array2<F>>, MlError>>()?;
+        let output_views: Vec<ArrayView2<F>> = outputs.iter().map(|o| o.view()).collect();
+        let output: Array2<F> = concatenate(Axis(0), &output_views)?;
+        let error_buffer: Array2<F> = self.loss.derivative(y, &output)?;
+        let shard_errors: Vec<Array2<F>> = error_buffer
+            .axis_chunks_iter(Axis(0), shard_size)
+            .map(|shard_error| shard_error.to_owned())
+            .collect();
+
+        let gradients: Vec<Vec<Vec<Array2<F>>>> = workers
+            .par_iter_mut()
+            .zip(shard_errors)
+            .map(|((layers, _), mut error)| {
+                for layer in layers.iter_mut().rev() {
+                    error = layer.compute_gradients(&error)?;
+                }
+                Ok(layers.iter().map(|layer| layer.get_gradients()).collect())
+            })
+            .collect::<Result<Vec<Vec<Vec<Array2<F>>>>, MlError>>()?;
+
+        for (l, (layer, training)) in self.layers.iter_mut().zip(&self.training).enumerate() {
+            let Some(learning_rate) = training.learning_rate(learning_rate) else {
+                continue;
+            };
+            let mut layer_gradients: Vec<Array2<F>> = gradients[0][l].clone();
+            for worker_gradients in &gradients[1..] {
+                for (sum, gradient) in layer_gradients.iter_mut().zip(&worker_gradients[l]) {
+                    *sum += gradient;
+                }
+            }
+            layer.set_gradients(layer_gradients)?;
+            layer.apply_gradients(learning_rate);
+        }
+        self.batch_loss(y, &output)
+    }
+
+    fn add_classification_report(
+        &self,
+        x_test: &Array2<F>,
+        y_test: &Array2<F>,
+        report: &mut Report,
+    ) -> Result<(), MlError> {
+        if !y_test.iter().all(|&y| y == F::zero() || y == F::one()) {
+            return Ok(());
+        }
+        report_classification(&self.predict(x_test)?, y_test, report)
+    }
+
+    #[allow(clippy::too_many_arguments)]
     pub fn fit(
         &mut self,
-        x_train: &Array2<f64>,
-        y_train: &Array2<f64>,
-        x_test: Option<&Array2<f64>>,
-        y_test: Option<&Array2<f64>>,
+        x_train: &Array2<F>,
+        y_train: &Array2<F>,
+        x_test: Option<&Array2<F>>,
+        y_test: Option<&Array2<F>>,
+        epochs: usize,
+        learning_rate: F,
+        batch_size: usize,
+        metrics: &mut [Box<dyn Metric>],
+        report: &mut Report,
+        report_step: usize,
+    ) -> Result<(), MlError> {
+        let network_name: String = self.get_name();
+        self.fit_samples(
+            &network_name,
+            &x_train.shape()[1..],
+            x_train,
+            y_train,
+            x_test,
+            y_test,
+            epochs,
+            learning_rate,
+            batch_size,
+            metrics,
+            report,
+            report_step,
+        )
+    }
+
+    /// Trains on samples of `sample_shape` flattened in the rows of `x_train` and `x_test`.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn fit_samples(
+        &mut self,
+        network_name: &str,
+        sample_shape: &[usize],
+        x_train: &Array2<F>,
+        y_train: &Array2<F>,
+        x_test: Option<&Array2<F>>,
+        y_test: Option<&Array2<F>>,
         epochs: usize,
-        learning_rate: f64,
-        accuracy_function: &str,
+        learning_rate: F,
+        batch_size: usize,
+        metrics: &mut [Box<dyn Metric>],
         report: &mut Report,
         report_step: usize,
-    ) -> () {
+    ) -> Result<(), MlError> {
+        check_batch_size(batch_size)?;
+        check_report_step(report_step)?;
+        check_rows(x_train, y_train)?;
+        if x_train.shape()[0] == 0 {
+            return Err(MlError::InvalidInput(
+                "training set must not be empty".to_string(),
+            ));
+        }
+        check_shapes(&x_train.shape()[1..], &[sample_shape.iter().product()])?;
+        let output_shape: Vec<usize> = self.compile(sample_shape)?;
+        let summary: Summary = self.build_summary()?;
+        if y_train.shape()[1..] != output_shape {
+            return Err(MlError::ShapeMismatch(format!(
+                "network output shape {:?} does not match y shape {:?}",
+                output_shape,
+                &y_train.shape()[1..]
+            )));
+        }
         let start: Instant = Instant::now();
-        let (x_test_shape, y_test_shape): (Option<&[usize]>, Option<&[usize]>) =
-            if x_test.is_some() && y_test.is_some() {
-                (Some(x_test.unwrap().shape()), Some(y_test.unwrap().shape()))
-            } else {
-                (None, None)
-            };
+        let test_set: Option<(&Array2<F>, &Array2<F>)> = x_test.zip(y_test);
+        let x_train_shape: Vec<usize> = batch_shape(x_train.shape()[0], sample_shape);
+        let x_test_shape: Option<Vec<usize>> =
+            test_set.map(|(x_test, _)| batch_shape(x_test.shape()[0], sample_shape));
+        let y_test_shape: Option<&[usize]> = test_set.map(|(_, y_test)| y_test.shape());
+        if let Some((x_test, y_test)) = test_set {
+            check_rows(x_test, y_test)?;
+        }
 
         for i in 0..epochs {
             let mut error: f64 = 0.0;
 
-            for r in 0..(x_train.shape()[0]) {
-                let mut output: Array2<f64> = x_train.select(Axis(0), &[r]);
-                self.layers
-                    .iter_mut()
-                    .for_each(|l| output = l.forward_propagation(&output));
-
-                error += self.loss.function(&y_train.select(Axis(0), &[r]), &output);
-                let mut error_buffer: Array2<f64> = self
-                    .loss
-                    .derivative(&y_train.select(Axis(0), &[r]), &output);
-
-                self.layers.iter_mut().rev().for_each(|layer| {
-                    error_buffer = layer.backward_propagation(&error_buffer, learning_rate)
-                })
+            for (x_batch, y_batch) in x_train
+                .axis_chunks_iter(Axis(0), batch_size)
+                .zip(y_train.axis_chunks_iter(Axis(0), batch_size))
+            {
+                error +=
+                    self.train_batch(&x_batch.to_owned(), &y_batch.to_owned(), learning_rate)?;
             }
 
-            let train_accuracy: f64 =
-                accuracy(accuracy_function, &self.predict(&x_train), &y_train);
             let train_loss: f64 = error / x_train.shape()[0] as f64;
-            let test_accuracy: Option<f64> = if x_test.is_some() && y_test.is_some() {
-                Some(accuracy(
-                    accuracy_function,
-                    &self.predict(&x_test.unwrap()),
-                    &y_test.unwrap(),
-                ))
-            } else {
-                None
-            };
-            report.add_data(i, train_accuracy, train_loss, test_accuracy);
+            if !train_loss.is_finite() {
+                return Err(MlError::Numeric(format!(
+                    "train loss diverged to {} at epoch {}",
+                    train_loss, i
+                )));
+            }
+            let train_metrics: Vec<(String, f64)> = self.evaluate(x_train, y_train, metrics)?;
+            let test_metrics: Option<Vec<(String, f64)>> = test_set
+                .map(|(x_test, y_test)| self.evaluate(x_test, y_test, metrics))
+                .transpose()?;
+            let test_loss: Option<f64> = test_set
+                .map(|(x_test, y_test)| self.compute_loss(x_test, y_test))
+                .transpose()?;
+
+            log_epoch(i, epochs, train_loss, test_loss, &train_metrics);
+            report.add_data(i, train_metrics, train_loss, test_metrics, test_loss);
 
-            println!(
-                "epochs {}/{} train loss {} train accuracy {}",
-                i, epochs, train_loss, train_accuracy
-            );
             if i > 0 && (i % report_step == 0 || i == epochs - 1) {
+                if let Some((x_test, y_test)) = test_set {
+                    self.add_classification_report(x_test, y_test, report)?;
+                }
                 report.generate(
-                    self.get_name().as_str(),
+                    network_name,
                     start,
                     epochs,
-                    x_train.shape(),
+                    &x_train_shape,
                     y_train.shape(),
-                    x_test_shape,
+                    x_test_shape.as_deref(),
                     y_test_shape,
-                    accuracy_function,
                     self.loss.get_name().as_str(),
-                );
+                    Some(&summary),
+                )?;
             }
         }
+        Ok(())
+    }
+}
+
+/// Summed loss of the rows of the batch, so the loss of an epoch does not depend on how the
+/// training set is split in batches.
+pub(crate) fn rows_loss<F: MlFloat>(
+    loss: &dyn Loss<F>,
+    y_true: &Array2<F>,
+    y_pred: &Array2<F>,
+) -> Result<f64, MlError> {
+    check_shapes(y_true.shape(), y_pred.shape())?;
+    (0..y_true.shape()[0])
+        .map(|r| {
+            loss.function(&y_true.select(Axis(0), &[r]), &y_pred.select(Axis(0), &[r]))
+                .map(F::to_f64_lossy)
+        })
+        .sum()
+}
+
+/// Adds the confusion matrix and the ROC and precision-recall curves of a test set whose targets
+/// are all 0 or 1.
+pub(crate) fn report_classification<F: MlFloat>(
+    y_pred: &Array2<F>,
+    y_test: &Array2<F>,
+    report: &mut Report,
+) -> Result<(), MlError> {
+    let y_pred: Array2<f64> = y_pred.mapv(F::to_f64_lossy);
+    let y_test: Array2<f64> = y_test.mapv(F::to_f64_lossy);
+    if y_test.shape()[1] > 1 {
+        let mut confusion_matrix: ConfusionMatrix = ConfusionMatrix::new();
+        confusion_matrix.update(&y_pred, &y_test)?;
+        report.set_confusion_matrix(confusion_matrix.get_matrix().clone());
+    }
+    report.set_roc_curves(one_vs_rest_curves(&y_pred, &y_test, roc_curve));
+    report.set_pr_curves(one_vs_rest_curves(&y_pred, &y_test, precision_recall_curve));
+    Ok(())
+}
+
+pub(crate) fn log_epoch(
+    epoch: usize,
+    epochs: usize,
+    train_loss: f64,
+    test_loss: Option<f64>,
+    train_metrics: &[(String, f64)],
+) {
+    let mut log: String = format!("epochs {}/{} train loss {}", epoch, epochs, train_loss);
+    if let Some(test_loss) = test_loss {
+        log.push_str(&format!(" test loss {}", test_loss));
+    }
+    train_metrics
+        .iter()
+        .for_each(|(name, value)| log.push_str(&format!(" train {} {}", name, value)));
+    println!("{}", log);
+}
+
+pub(crate) fn check_batch_size(batch_size: usize) -> Result<(), MlError> {
+    if batch_size == 0 {
+        return Err(MlError::InvalidInput(
+            "batch size must be greater than 0".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+pub(crate) fn check_report_step(report_step: usize) -> Result<(), MlError> {
+    if report_step == 0 {
+        return Err(MlError::InvalidInput(
+            "report step must be greater than 0".to_string(),
+        ));
     }
+    Ok(())
 }
 
-impl Network<Mlp> for Mlp {
+pub(crate) fn batch_shape(rows: usize, sample_shape: &[usize]) -> Vec<usize> {
+    [&[rows], sample_shape].concat()
+}
+
+pub(crate) fn check_rows<F: MlFloat>(x: &Array2<F>, y: &Array2<F>) -> Result<(), MlError> {
+    if x.shape()[0] != y.shape()[0] {
+        return Err(MlError::ShapeMismatch(format!(
+            "x and y must have the same row length: {} != {}",
+            x.shape()[0],
+            y.shape()[0]
+        )));
+    }
+    Ok(())
+}
+
+impl<F: MlFloat> Network<Mlp<F>> for Mlp<F> {
     fn get_name(&self) -> String {
         "Mlp".to_string()
     }
 
-    fn from_json(json_str: &str) -> Result<Mlp, Box<dyn Error>> {
+    /// Weights are cast to `F` whatever the dtype recorded in the model.
+    fn from_json(json_str: &str) -> Result<Mlp<F>, MlError> {
         let model: MlpModel = serde_json::from_str(json_str)?;
-        let mut layers: Vec<Box<dyn Layer>> = vec![];
+        let mut layers: Vec<Box<dyn Layer<F>>> = vec![];
         for layer in model.layers {
             layers.push(layer::from_string(layer.0, layer.1.as_str())?)
         }
-        let mlp: Mlp = Mlp {
+        let training: Vec<LayerTraining> = if model.training.is_empty() {
+            vec![LayerTraining::default(); layers.len()]
+        } else if model.training.len() == layers.len() {
+            model.training
+        } else {
+            return Err(MlError::Serialization(format!(
+                "expected training settings for {} layers, actually: {}",
+                layers.len(),
+                model.training.len()
+            )));
+        };
+        let mut mlp: Mlp<F> = Mlp {
             layers,
+            training,
             loss: loss::from_string(model.loss)?,
+            input_shape: None,
+            #[cfg(feature = "parallel")]
+            threads: 1,
         };
+        if let Some(input_shape) = model.input_shape {
+            mlp.compile(&input_shape)?;
+        }
         Ok(mlp)
     }
 
-    fn to_json(&self) -> Result<String, Box<dyn Error>> {
+    fn to_json(&self) -> Result<String, MlError> {
         let mut layers: Vec<(String, String)> = vec![];
         for layer in &self.layers {
             layers.push((layer.get_name(), layer.to_json()?));
@@ -146,28 +768,41 @@ impl Network<Mlp> for Mlp {
         let model: MlpModel = MlpModel {
             layers,
             loss: self.loss.get_name(),
+            dtype: F::DTYPE.to_string(),
+            input_shape: self.input_shape.clone(),
+            training: self.training.clone(),
         };
         Ok(serde_json::to_string(&model)?)
     }
 
-    fn load(path: &str) -> Result<Mlp, Box<dyn Error>> {
+    fn load(path: &str) -> Result<Mlp<F>, MlError> {
         let content: String = read_to_string(path)?;
-        Ok(Mlp::from_json(content.as_str())?)
+        Mlp::from_json(content.as_str())
     }
 }
 
 #[cfg(test)]
+#[allow(clippy::unused_unit)]
 mod tests {
     use super::*;
+    use crate::accuracy::categorical_accuracy::CategoricalAccuracy;
+    use crate::accuracy::regression::RegressionMetric;
+    use crate::accuracy::regression_score::RegressionScore;
+    use crate::activation::relu::Relu;
+    #[cfg(feature = "parallel")]
+    use crate::activation::sigmoid::Sigmoid;
+    use crate::activation::softmax::Softmax;
     use crate::activation::tanh::Tanh;
     use crate::layer::activation_layer::ActivationLayer;
     use crate::layer::fc_layer::FCLayer;
+    use crate::loss::categorical_cross_entropy::CategoricalCrossEntropy;
     use crate::loss::mse::Mse;
-    use ndarray::{arr2, Array1};
+    use ndarray::{arr2, concatenate, Array1};
     use ndarray_stats::QuantileExt;
     use std::fs;
     use std::fs::remove_file;
     use std::path::Path;
+    use std::thread;
 
     #[test]
     fn mlp_should_build_train_and_predict() -> () {
@@ -177,10 +812,10 @@ mod tests {
         let y_test: Array2<f64> = arr2(&[[1.0, 0.0], [0.0, 1.0], [0.0, 1.0], [1.0, 0.0]]);
 
         let mut mlp: Mlp = Mlp::build(Box::new(Mse));
-        mlp.add_layer(Box::new(FCLayer::build(2, 3)));
-        mlp.add_layer(Box::new(ActivationLayer::build(Box::new(Tanh), 3, 3)));
-        mlp.add_layer(Box::new(FCLayer::build(3, 2)));
-        mlp.add_layer(Box::new(ActivationLayer::build(Box::new(Tanh), 3, 2)));
+        mlp.add_layer(Box::new(FCLayer::build_with_seed(2, 3, 1)));
+        mlp.add_layer(Box::new(ActivationLayer::build(Box::new(Tanh))));
+        mlp.add_layer(Box::new(FCLayer::build_with_seed(3, 2, 101)));
+        mlp.add_layer(Box::new(ActivationLayer::build(Box::new(Tanh))));
 
         mlp.fit(
             &x_train,
@@ -189,28 +824,281 @@ mod tests {
             Some(&y_test),
             1000,
             0.1,
-            "categorical_accuracy",
+            1,
+            &mut [Box::new(CategoricalAccuracy::new())],
             &mut Report::build("./test_report_mlp_1"),
             500,
-        );
+        )
+        .unwrap();
 
-        let result: Array2<f64> = mlp.predict(&x_test);
+        let result: Array2<f64> = mlp.predict(&x_test).unwrap();
         let result_argmax: Array1<usize> = result.map_axis(Axis(1), |row| row.argmax().unwrap());
         assert_eq!(mlp.layers.len(), 4);
         assert_eq!(result_argmax, Array1::from_vec(vec![0, 1, 1, 0]));
         assert!(Path::new("./test_report_mlp_1/500").exists());
         assert!(Path::new("./test_report_mlp_1/999").exists());
+        assert!(read_to_string("./test_report_mlp_1/999/report.html")
+            .unwrap()
+            .contains("Test Confusion Matrix"));
 
         fs::remove_dir_all("./test_report_mlp_1").unwrap();
     }
 
+    #[test]
+    fn mlp_should_fit_regression_without_metric() {
+        let x_train: Array2<f64> = arr2(&[[0.0, 0.0], [0.0, 1.0], [1.0, 0.0], [1.0, 1.0]]);
+        let y_train: Array2<f64> = arr2(&[[0.0], [0.5], [0.5], [1.0]]);
+
+        let mut mlp: Mlp = Mlp::build(Box::new(Mse));
+        mlp.add_layer(Box::new(FCLayer::build(2, 1)));
+
+        mlp.fit(
+            &x_train,
+            &y_train,
+            Some(&x_train),
+            Some(&y_train),
+            300,
+            0.1,
+            1,
+            &mut [],
+            &mut Report::build("./test_report_mlp_3"),
+            100,
+        )
+        .unwrap();
+
+        let mut metrics: Vec<Box<dyn Metric>> =
+            vec![Box::new(RegressionScore::build(RegressionMetric::R2))];
+        let result: Vec<(String, f64)> = mlp.evaluate(&x_train, &y_train, &mut metrics).unwrap();
+        assert_eq!(result[0].0, "r2");
+        assert!(result[0].1 > 0.99);
+        assert!(Path::new("./test_report_mlp_3/299/report.html").exists());
+        fs::remove_dir_all("./test_report_mlp_3").unwrap();
+    }
+
+    #[test]
+    fn fit_should_train_with_mini_batches() {
+        let x_train: Array2<f64> = arr2(&[[0.0, 0.0], [0.0, 1.0], [1.0, 0.0], [1.0, 1.0]]);
+        let y_train: Array2<f64> = arr2(&[[0.0], [0.5], [0.5], [1.0]]);
+
+        let mut mlp: Mlp = Mlp::build(Box::new(Mse));
+        mlp.add_layer(Box::new(FCLayer::build_with_seed(2, 1, 3)));
+        let initial_loss: f64 = mlp.compute_loss(&x_train, &y_train).unwrap();
+
+        mlp.fit(
+            &x_train,
+            &y_train,
+            None,
+            None,
+            500,
+            0.1,
+            3,
+            &mut [],
+            &mut Report::build("./test_report_mlp_4"),
+            1000,
+        )
+        .unwrap();
+
+        assert!(mlp.compute_loss(&x_train, &y_train).unwrap() < initial_loss / 100.0);
+        fs::remove_dir_all("./test_report_mlp_4").unwrap();
+    }
+
+    #[test]
+    fn fit_should_fail_when_batch_size_is_zero() {
+        let x: Array2<f64> = arr2(&[[0.0, 0.0]]);
+        let mut mlp: Mlp = Mlp::build(Box::new(Mse));
+        mlp.add_layer(Box::new(FCLayer::build(2, 2)));
+        let result: Result<(), MlError> = mlp.fit(
+            &x,
+            &x,
+            None,
+            None,
+            1,
+            0.1,
+            0,
+            &mut [],
+            &mut Report::build("./test_report_mlp_5"),
+            1,
+        );
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "invalid input: batch size must be greater than 0"
+        );
+    }
+
+    #[test]
+    fn fit_should_fail_when_report_step_is_zero() {
+        let x: Array2<f64> = arr2(&[[0.0, 0.0]]);
+        let mut mlp: Mlp = Mlp::build(Box::new(Mse));
+        mlp.add_layer(Box::new(FCLayer::build(2, 2)));
+        let result: Result<(), MlError> = mlp.fit(
+            &x,
+            &x,
+            None,
+            None,
+            2,
+            0.1,
+            1,
+            &mut [],
+            &mut Report::build("./test_report_mlp_9"),
+            0,
+        );
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "invalid input: report step must be greater than 0"
+        );
+        assert!(!Path::new("./test_report_mlp_9").exists());
+    }
+
+    #[test]
+    fn fit_should_fail_on_bad_request_instead_of_panicking() {
+        let mut mlp: Mlp = Mlp::build(Box::new(Mse));
+        mlp.add_layer(Box::new(FCLayer::build_with_seed(2, 1, 3)));
+        let mut fit = |x: &Array2<f64>, y: &Array2<f64>| {
+            mlp.fit(
+                x,
+                y,
+                None,
+                None,
+                2,
+                0.1,
+                1,
+                &mut [],
+                &mut Report::build("./test_report_mlp_8"),
+                10,
+            )
+        };
+
+        assert!(matches!(
+            fit(&arr2(&[[0.0, 1.0, 2.0]]), &arr2(&[[1.0]])),
+            Err(MlError::Layer { index: 0, .. })
+        ));
+        assert!(matches!(
+            fit(&arr2(&[[0.0, 1.0]]), &arr2(&[[1.0, 0.0]])),
+            Err(MlError::ShapeMismatch(_))
+        ));
+        assert!(matches!(
+            fit(&arr2(&[[0.0, 1.0], [1.0, 0.0]]), &arr2(&[[1.0]])),
+            Err(MlError::ShapeMismatch(_))
+        ));
+        assert!(matches!(
+            fit(&arr2(&[[1e200, 1e200]]), &arr2(&[[0.0]])),
+            Err(MlError::Numeric(_))
+        ));
+        assert!(matches!(
+            mlp.predict(&arr2(&[[0.0]])),
+            Err(MlError::Layer { index: 0, .. })
+        ));
+        assert!(!Path::new("./test_report_mlp_8").exists());
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn fit_should_match_single_threaded_training_when_parallel() {
+        let x_train: Array2<f64> = arr2(&[
+            [0.0, 0.0],
+            [0.0, 1.0],
+            [1.0, 0.0],
+            [1.0, 1.0],
+            [0.5, 0.0],
+            [0.0, 0.5],
+            [0.5, 1.0],
+            [1.0, 0.5],
+        ]);
+        let y_train: Array2<f64> = arr2(&[
+            [1.0, 0.0],
+            [0.0, 1.0],
+            [0.0, 1.0],
+            [1.0, 0.0],
+            [0.0, 1.0],
+            [0.0, 1.0],
+            [0.0, 1.0],
+            [0.0, 1.0],
+        ]);
+        let mut mlp: Mlp = Mlp::build(Box::new(Mse));
+        mlp.add_layer(Box::new(FCLayer::build_with_seed(2, 3, 1)));
+        mlp.add_layer(Box::new(ActivationLayer::build(Box::new(Tanh))));
+        mlp.add_layer(Box::new(FCLayer::build_with_seed(3, 2, 101)));
+        mlp.add_layer(Box::new(ActivationLayer::build(Box::new(Sigmoid))));
+        let network_str: String = mlp.to_json().unwrap();
+
+        let mut single_mlp: Mlp = Mlp::from_json(network_str.as_str()).unwrap();
+        let mut parallel_mlp: Mlp = Mlp::from_json(network_str.as_str()).unwrap();
+        parallel_mlp.set_threads(3).unwrap();
+        for mlp in [&mut single_mlp, &mut parallel_mlp] {
+            mlp.fit(
+                &x_train,
+                &y_train,
+                None,
+                None,
+                50,
+                0.5,
+                4,
+                &mut [],
+                &mut Report::build("./test_report_mlp_6"),
+                100,
+            )
+            .unwrap();
+        }
+
+        let single_result: Array2<f64> = single_mlp.predict(&x_train).unwrap();
+        let parallel_result: Array2<f64> = parallel_mlp.predict(&x_train).unwrap();
+        single_result
+            .iter()
+            .zip(parallel_result.iter())
+            .for_each(|(s, p)| assert!((s - p).abs() < 1e-10));
+        fs::remove_dir_all("./test_report_mlp_6").unwrap();
+    }
+
+    #[test]
+    fn predict_should_run_batch_and_be_shared_between_threads() {
+        let mut mlp: Mlp = Mlp::build(Box::new(Mse));
+        mlp.add_layer(Box::new(FCLayer::build(2, 3)));
+        mlp.add_layer(Box::new(ActivationLayer::build(Box::new(Tanh))));
+        mlp.add_layer(Box::new(FCLayer::build(3, 2)));
+        mlp.add_layer(Box::new(ActivationLayer::build(Box::new(Softmax))));
+        let x: Array2<f64> = arr2(&[[0.0, 0.0], [0.0, 1.0], [1.0, 0.0], [1.0, 1.0], [0.5, 0.5]]);
+
+        let result: Array2<f64> = mlp.predict(&x).unwrap();
+        assert_eq!(result.shape(), &[5, 2]);
+        result
+            .rows()
+            .into_iter()
+            .for_each(|row| assert!((row.sum() - 1.0).powf(2.0) < 0.00001));
+        assert_eq!(mlp.predict_batch(&x, 2).unwrap(), result);
+        assert_eq!(
+            mlp.predict(&Array2::zeros((0, 2))).unwrap().shape(),
+            &[0, 2]
+        );
+
+        let mlp_ref: &Mlp = &mlp;
+        thread::scope(|scope| {
+            let handles: Vec<thread::ScopedJoinHandle<Array2<f64>>> = (0..4)
+                .map(|_| scope.spawn(|| mlp_ref.predict(&x).unwrap()))
+                .collect();
+            for handle in handles {
+                assert_eq!(handle.join().unwrap(), result);
+            }
+        });
+    }
+
+    #[test]
+    fn predict_batch_should_fail_when_batch_size_is_zero() {
+        let mut mlp: Mlp = Mlp::build(Box::new(Mse));
+        mlp.add_layer(Box::new(FCLayer::build(2, 3)));
+        assert!(matches!(
+            mlp.predict_batch(&Array2::zeros((2, 2)), 0),
+            Err(MlError::InvalidInput(_))
+        ));
+    }
+
     #[test]
     fn to_json_should_serialize_mlp() -> () {
         let mut mlp: Mlp = Mlp::build(Box::new(Mse));
         mlp.add_layer(Box::new(FCLayer::build(2, 3)));
-        mlp.add_layer(Box::new(ActivationLayer::build(Box::new(Tanh), 3, 3)));
+        mlp.add_layer(Box::new(ActivationLayer::build(Box::new(Tanh))));
         mlp.add_layer(Box::new(FCLayer::build(3, 1)));
-        mlp.add_layer(Box::new(ActivationLayer::build(Box::new(Tanh), 3, 1)));
+        mlp.add_layer(Box::new(ActivationLayer::build(Box::new(Tanh))));
+        mlp.compile(&[2]).unwrap();
 
         let result: String = mlp.to_json().unwrap();
 
@@ -220,7 +1108,62 @@ mod tests {
         assert_eq!(result.matches("MSE").count(), 1);
         assert_eq!(result.matches("[2,3]").count(), 1);
         assert_eq!(result.matches("[3,3]").count(), 1);
-        assert_eq!(result.matches("[3,1]").count(), 2);
+        assert_eq!(result.matches("[3,1]").count(), 1);
+        assert_eq!(result.matches("[1,1]").count(), 1);
+    }
+
+    #[test]
+    fn compile_should_infer_activation_shapes() {
+        let mut mlp: Mlp = Mlp::build(Box::new(Mse));
+        mlp.add_layer(Box::new(FCLayer::build(2, 4)));
+        mlp.add_layer(Box::new(ActivationLayer::build(Box::new(Tanh))));
+        mlp.add_layer(Box::new(FCLayer::build(4, 3)));
+        mlp.add_layer(Box::new(ActivationLayer::build(Box::new(Tanh))));
+
+        let output_shape: Vec<usize> = mlp.compile(&[2]).unwrap();
+
+        assert_eq!(output_shape, vec![3]);
+        assert_eq!(mlp.get_input_shape(), Some(&[2][..]));
+        assert_eq!(mlp.layers[1].get_shape(), (4, 4));
+        assert_eq!(mlp.layers[3].get_shape(), (3, 3));
+    }
+
+    #[test]
+    fn summary_should_list_layers_and_parameters() {
+        let mut mlp: Mlp<f32> = Mlp::build(Box::new(Mse));
+        mlp.add_layer(Box::new(FCLayer::build(2, 4)));
+        mlp.add_layer(Box::new(ActivationLayer::build(Box::new(Tanh))));
+        mlp.add_layer(Box::new(FCLayer::build(4, 3)));
+        assert!(matches!(mlp.summary(), Err(MlError::InvalidInput(_))));
+        mlp.compile(&[2]).unwrap();
+
+        let summary: Summary = mlp.summary().unwrap();
+
+        assert_eq!(summary.get_input_shape(), &[2]);
+        assert_eq!(summary.get_layers().len(), 3);
+        assert_eq!(summary.get_layers()[1].output_shape, vec![4]);
+        assert_eq!(summary.get_layers()[2].output_shape, vec![3]);
+        assert_eq!(summary.get_layers()[0].trainable_params, 12);
+        assert_eq!(summary.get_layers()[1].trainable_params, 0);
+        assert_eq!(summary.get_trainable_params(), 27);
+        assert_eq!(summary.get_non_trainable_params(), 0);
+        assert_eq!(summary.get_memory(), 27 * 4);
+    }
+
+    #[test]
+    fn compile_should_report_index_of_mismatching_layer() {
+        let mut mlp: Mlp = Mlp::build(Box::new(Mse));
+        mlp.add_layer(Box::new(FCLayer::build(2, 4)));
+        mlp.add_layer(Box::new(FCLayer::build(3, 2)));
+
+        let error: MlError = mlp.compile(&[2]).unwrap_err();
+
+        assert!(matches!(error, MlError::Layer { index: 1, .. }));
+        assert_eq!(
+            error.to_string(),
+            "layer 1 (FCLayer): shape mismatch: expected input shape [3], actually: [4]"
+        );
+        assert_eq!(mlp.get_input_shape(), None);
     }
 
     #[test]
@@ -230,10 +1173,10 @@ mod tests {
         let x_test: Array2<f64> = arr2(&[[-0.05, -0.05], [0.0, 0.95], [0.95, 0.0], [1.05, 1.05]]);
 
         let mut mlp: Mlp = Mlp::build(Box::new(Mse));
-        mlp.add_layer(Box::new(FCLayer::build(2, 3)));
-        mlp.add_layer(Box::new(ActivationLayer::build(Box::new(Tanh), 3, 3)));
-        mlp.add_layer(Box::new(FCLayer::build(3, 2)));
-        mlp.add_layer(Box::new(ActivationLayer::build(Box::new(Tanh), 3, 2)));
+        mlp.add_layer(Box::new(FCLayer::build_with_seed(2, 3, 1)));
+        mlp.add_layer(Box::new(ActivationLayer::build(Box::new(Tanh))));
+        mlp.add_layer(Box::new(FCLayer::build_with_seed(3, 2, 101)));
+        mlp.add_layer(Box::new(ActivationLayer::build(Box::new(Tanh))));
 
         mlp.fit(
             &x_train,
@@ -242,33 +1185,108 @@ mod tests {
             None,
             1000,
             0.1,
-            "categorical_accuracy",
+            1,
+            &mut [Box::new(CategoricalAccuracy::new())],
             &mut Report::build("./test_report_mlp_2"),
             500,
-        );
+        )
+        .unwrap();
 
         let network_str: String = mlp.to_json().unwrap();
-        let mut mlp: Mlp = Mlp::from_json(network_str.as_str()).unwrap();
+        let mlp: Mlp = Mlp::from_json(network_str.as_str()).unwrap();
 
-        let result: Array2<f64> = mlp.predict(&x_test);
+        let result: Array2<f64> = mlp.predict(&x_test).unwrap();
         let result_argmax: Array1<usize> = result.map_axis(Axis(1), |row| row.argmax().unwrap());
         assert_eq!(mlp.layers.len(), 4);
         assert_eq!(result_argmax, Array1::from_vec(vec![0, 1, 1, 0]));
         fs::remove_dir_all("./test_report_mlp_2").unwrap();
     }
 
+    #[test]
+    fn mlp_should_train_in_f32() {
+        let x_train: Array2<f32> = arr2(&[[0.0, 0.0], [0.0, 1.0], [1.0, 0.0], [1.0, 1.0]]);
+        let y_train: Array2<f32> = arr2(&[[0.0], [0.5], [0.5], [1.0]]);
+
+        let mut mlp: Mlp<f32> = Mlp::build(Box::new(Mse));
+        mlp.add_layer(Box::new(FCLayer::build_with_seed(2, 1, 3)));
+        let initial_loss: f64 = mlp.compute_loss(&x_train, &y_train).unwrap();
+
+        mlp.fit(
+            &x_train,
+            &y_train,
+            None,
+            None,
+            300,
+            0.1,
+            1,
+            &mut [Box::new(RegressionScore::build(RegressionMetric::R2))],
+            &mut Report::build("./test_report_mlp_7"),
+            1000,
+        )
+        .unwrap();
+
+        assert_eq!(mlp.get_dtype(), "f32");
+        assert!(mlp.compute_loss(&x_train, &y_train).unwrap() < initial_loss / 100.0);
+        fs::remove_dir_all("./test_report_mlp_7").unwrap();
+    }
+
+    #[test]
+    fn to_json_should_record_dtype() {
+        let mut mlp: Mlp<f32> = Mlp::build(Box::new(Mse));
+        mlp.add_layer(Box::new(FCLayer::build(2, 3)));
+
+        let result: String = mlp.to_json().unwrap();
+
+        assert!(result.contains("\"dtype\":\"f32\""));
+        assert!(Mlp::<f64>::build(Box::new(Mse))
+            .to_json()
+            .unwrap()
+            .contains("\"dtype\":\"f64\""));
+    }
+
+    #[test]
+    fn load_should_cast_between_precisions() {
+        let mut mlp: Mlp = Mlp::build(Box::new(Mse));
+        mlp.add_layer(Box::new(FCLayer::build_with_seed(2, 3, 1)));
+        mlp.add_layer(Box::new(ActivationLayer::build(Box::new(Tanh))));
+        mlp.save("", "UnitTestMLP3".to_string()).unwrap();
+        let x: Array2<f64> = arr2(&[[0.0, 0.5], [1.0, -1.0]]);
+
+        let mlp_f32: Mlp<f32> = Mlp::load("UnitTestMLP3.json").unwrap();
+        remove_file("UnitTestMLP3.json").unwrap();
+        let result_f32: Array2<f32> = mlp_f32.predict(&x.mapv(|v| v as f32)).unwrap();
+        let mlp_f64: Mlp<f64> = mlp_f32.cast().unwrap();
+
+        assert_eq!(mlp_f32.get_dtype(), "f32");
+        assert_eq!(mlp_f64.get_dtype(), "f64");
+        mlp.predict(&x)
+            .unwrap()
+            .iter()
+            .zip(result_f32.iter())
+            .for_each(|(r64, &r32)| assert!((r64 - r32 as f64).powf(2.0) < 0.00001));
+        let legacy_json: String = mlp.to_json().unwrap().replace(",\"dtype\":\"f64\"", "");
+        assert_eq!(
+            Mlp::<f32>::from_json(legacy_json.as_str())
+                .unwrap()
+                .layers
+                .len(),
+            2
+        );
+    }
+
     #[test]
     fn get_name_should_return_struct_name() -> () {
-        assert_eq!(Mlp::build(Box::new(Mse)).get_name(), "Mlp");
+        assert_eq!(Mlp::<f64>::build(Box::new(Mse)).get_name(), "Mlp");
     }
 
     #[test]
     fn save_should_write_mlp_to_file() -> () {
         let mut mlp: Mlp = Mlp::build(Box::new(Mse));
         mlp.add_layer(Box::new(FCLayer::build(2, 3)));
-        mlp.add_layer(Box::new(ActivationLayer::build(Box::new(Tanh), 3, 3)));
+        mlp.add_layer(Box::new(ActivationLayer::build(Box::new(Tanh))));
         mlp.add_layer(Box::new(FCLayer::build(3, 1)));
-        mlp.add_layer(Box::new(ActivationLayer::build(Box::new(Tanh), 3, 1)));
+        mlp.add_layer(Box::new(ActivationLayer::build(Box::new(Tanh))));
+        mlp.compile(&[2]).unwrap();
         mlp.save("", "UnitTestMLP1".to_string()).unwrap();
 
         let result: String = read_to_string("UnitTestMLP1.json").unwrap();
@@ -280,16 +1298,18 @@ mod tests {
         assert_eq!(result.matches("MSE").count(), 1);
         assert_eq!(result.matches("[2,3]").count(), 1);
         assert_eq!(result.matches("[3,3]").count(), 1);
-        assert_eq!(result.matches("[3,1]").count(), 2);
+        assert_eq!(result.matches("[3,1]").count(), 1);
+        assert_eq!(result.matches("[1,1]").count(), 1);
     }
 
     #[test]
     fn load_should_read_mlp() -> () {
         let mut mlp: Mlp = Mlp::build(Box::new(Mse));
         mlp.add_layer(Box::new(FCLayer::build(2, 3)));
-        mlp.add_layer(Box::new(ActivationLayer::build(Box::new(Tanh), 3, 3)));
+        mlp.add_layer(Box::new(ActivationLayer::build(Box::new(Tanh))));
         mlp.add_layer(Box::new(FCLayer::build(3, 1)));
-        mlp.add_layer(Box::new(ActivationLayer::build(Box::new(Tanh), 3, 1)));
+        mlp.add_layer(Box::new(ActivationLayer::build(Box::new(Tanh))));
+        mlp.compile(&[2]).unwrap();
         mlp.save("", "UnitTestMLP2".to_string()).unwrap();
 
         let mlp: Mlp = Mlp::load("UnitTestMLP2.json").unwrap();
@@ -302,6 +1322,317 @@ mod tests {
         assert_eq!(result.matches("MSE").count(), 1);
         assert_eq!(result.matches("[2,3]").count(), 1);
         assert_eq!(result.matches("[3,3]").count(), 1);
-        assert_eq!(result.matches("[3,1]").count(), 2);
+        assert_eq!(result.matches("[3,1]").count(), 1);
+        assert_eq!(result.matches("[1,1]").count(), 1);
+    }
+
+    #[test]
+    fn train_batch_step_should_not_depend_on_batch_size() {
+        let x: Array2<f64> = arr2(&[[0.0, 1.0], [1.0, 0.5], [-1.0, 0.5]]);
+        let y: Array2<f64> = arr2(&[[1.0, 0.0], [0.0, 1.0], [1.0, 0.0]]);
+        let x_twice: Array2<f64> = concatenate![Axis(0), x, x];
+        let y_twice: Array2<f64> = concatenate![Axis(0), y, y];
+        let losses: Vec<fn() -> Box<dyn Loss>> =
+            vec![|| Box::new(Mse), || Box::new(CategoricalCrossEntropy)];
+        for build_loss in losses {
+            let build_mlp = || {
+                let mut mlp: Mlp = Mlp::build(build_loss());
+                mlp.add_layer(Box::new(FCLayer::build_with_seed(2, 2, 5)));
+                mlp.add_layer(Box::new(ActivationLayer::build(Box::new(Softmax))));
+                mlp
+            };
+            let mut mlp: Mlp = build_mlp();
+            let mut mlp_twice: Mlp = build_mlp();
+
+            mlp.train_batch(&x, &y, 0.5).unwrap();
+            mlp_twice.train_batch(&x_twice, &y_twice, 0.5).unwrap();
+
+            mlp.predict(&x)
+                .unwrap()
+                .iter()
+                .zip(mlp_twice.predict(&x).unwrap().iter())
+                .for_each(|(output, output_twice)| {
+                    assert!((output - output_twice).powf(2.0) < 0.0000001)
+                });
+            assert_ne!(mlp.predict(&x).unwrap(), build_mlp().predict(&x).unwrap());
+        }
+    }
+
+    fn build_fine_tuned_mlp() -> Mlp {
+        let mut mlp: Mlp = Mlp::build(Box::new(Mse));
+        mlp.add_layer(Box::new(FCLayer::build_with_seed(2, 3, 1)));
+        mlp.add_layer(Box::new(ActivationLayer::build(Box::new(Tanh))));
+        mlp.add_layer(Box::new(FCLayer::build_with_seed(3, 1, 101)));
+        mlp.compile(&[2]).unwrap();
+        mlp
+    }
+
+    #[test]
+    fn train_batch_should_not_update_frozen_layers() {
+        let x: Array2<f64> = arr2(&[[0.0, 1.0], [1.0, 0.5], [-1.0, 0.5]]);
+        let y: Array2<f64> = arr2(&[[0.5], [1.0], [-0.5]]);
+        let mut mlp: Mlp = build_fine_tuned_mlp();
+        mlp.set_trainable(2, false).unwrap();
+        let first_layer: String = mlp.layers[0].to_json().unwrap();
+        let last_layer: String = mlp.layers[2].to_json().unwrap();
+        let initial_loss: f64 = mlp.compute_loss(&x, &y).unwrap();
+
+        for _ in 0..50 {
+            mlp.train_batch(&x, &y, 0.1).unwrap();
+        }
+
+        assert_ne!(mlp.layers[0].to_json().unwrap(), first_layer);
+        assert_eq!(mlp.layers[2].to_json().unwrap(), last_layer);
+        assert!(mlp.compute_loss(&x, &y).unwrap() < initial_loss);
+    }
+
+    #[test]
+    fn train_batch_should_scale_learning_rate_of_layer() {
+        let x: Array2<f64> = arr2(&[[0.0, 1.0], [1.0, 0.5]]);
+        let y: Array2<f64> = arr2(&[[0.5], [1.0]]);
+        let mut scaled_mlp: Mlp = build_fine_tuned_mlp();
+        scaled_mlp.set_learning_rate_multiplier(0, 2.0).unwrap();
+        scaled_mlp.set_learning_rate_multiplier(2, 0.0).unwrap();
+        let mut mlp: Mlp = build_fine_tuned_mlp();
+
+        scaled_mlp.train_batch(&x, &y, 0.05).unwrap();
+        mlp.train_batch(&x, &y, 0.1).unwrap();
+
+        assert_eq!(
+            scaled_mlp.layers[0].to_json().unwrap(),
+            mlp.layers[0].to_json().unwrap()
+        );
+        assert_eq!(
+            scaled_mlp.layers[2].to_json().unwrap(),
+            build_fine_tuned_mlp().layers[2].to_json().unwrap()
+        );
+    }
+
+    #[test]
+    fn set_trainable_should_reject_unknown_layer() {
+        let mut mlp: Mlp = build_fine_tuned_mlp();
+        assert!(matches!(
+            mlp.set_trainable(3, false),
+            Err(MlError::InvalidInput(_))
+        ));
+        assert!(matches!(
+            mlp.set_learning_rate_multiplier(3, 0.5),
+            Err(MlError::InvalidInput(_))
+        ));
+        assert!(matches!(
+            mlp.set_learning_rate_multiplier(0, -0.5),
+            Err(MlError::InvalidInput(msg))
+                if msg == "learning rate multiplier must be a non-negative number, actually: -0.5"
+        ));
+        assert!(matches!(
+            mlp.set_learning_rate_multiplier(0, f64::NAN),
+            Err(MlError::InvalidInput(_))
+        ));
+        mlp.set_learning_rate_multiplier(0, 0.0).unwrap();
+        assert_eq!(
+            mlp.get_layer_training(0)
+                .map(|t| t.learning_rate_multiplier),
+            Some(0.0)
+        );
+        mlp.set_learning_rate_multiplier(0, 1.0).unwrap();
+        assert_eq!(mlp.get_layer_training(3), None);
+        assert_eq!(mlp.get_layer_training(0), Some(LayerTraining::default()));
+    }
+
+    #[test]
+    fn summary_should_count_frozen_parameters_as_non_trainable() {
+        let mut mlp: Mlp = build_fine_tuned_mlp();
+        mlp.set_trainable(0, false).unwrap();
+
+        let summary: Summary = mlp.build_summary().unwrap();
+
+        assert_eq!(summary.get_layers()[0].trainable_params, 0);
+        assert_eq!(summary.get_layers()[0].non_trainable_params, 9);
+        assert_eq!(summary.get_trainable_params(), 4);
+        assert_eq!(summary.get_non_trainable_params(), 9);
+    }
+
+    #[test]
+    fn from_json_should_restore_layer_training() {
+        let mut mlp: Mlp = build_fine_tuned_mlp();
+        mlp.set_trainable(0, false).unwrap();
+        mlp.set_learning_rate_multiplier(2, 0.1).unwrap();
+
+        let network_str: String = mlp.to_json().unwrap();
+        let loaded: Mlp = Mlp::from_json(network_str.as_str()).unwrap();
+
+        assert_eq!(
+            loaded.get_layer_training(0),
+            Some(LayerTraining {
+                trainable: false,
+                learning_rate_multiplier: 1.0
+            })
+        );
+        assert_eq!(
+            loaded.get_layer_training(2),
+            Some(LayerTraining {
+                trainable: true,
+                learning_rate_multiplier: 0.1
+            })
+        );
+        let legacy_json: String = network_str.replace(
+            ",\"training\":[{\"trainable\":false,\"learning_rate_multiplier\":1.0},\
+            {\"trainable\":true,\"learning_rate_multiplier\":1.0},\
+            {\"trainable\":true,\"learning_rate_multiplier\":0.1}]",
+            "",
+        );
+        assert_ne!(legacy_json, network_str);
+        assert_eq!(
+            Mlp::<f64>::from_json(legacy_json.as_str())
+                .unwrap()
+                .get_layer_training(0),
+            Some(LayerTraining::default())
+        );
+        let truncated_json: String = network_str.replace(
+            "{\"trainable\":false,\"learning_rate_multiplier\":1.0},",
+            "",
+        );
+        assert!(matches!(
+            Mlp::<f64>::from_json(truncated_json.as_str()),
+            Err(MlError::Serialization(_))
+        ));
+    }
+
+    #[test]
+    fn pop_truncate_and_insert_layer_should_edit_layers() {
+        let mut mlp: Mlp = build_fine_tuned_mlp();
+        mlp.set_trainable(0, false).unwrap();
+
+        assert_eq!(mlp.pop_layer().unwrap().get_shape(), (3, 1));
+        mlp.truncate(1);
+        assert_eq!(mlp.layers.len(), 1);
+        mlp.insert_layer(1, Box::new(FCLayer::build(3, 2))).unwrap();
+        mlp.insert_layer(1, Box::new(ActivationLayer::build(Box::new(Tanh))))
+            .unwrap();
+
+        assert_eq!(
+            mlp.get_layer_names(),
+            vec!["FCLayer_0", "ActivationLayer_0", "FCLayer_1"]
+        );
+        assert_eq!(mlp.layers[1].get_shape(), (3, 3));
+        assert!(!mlp.get_layer_training(0).unwrap().trainable);
+        assert!(mlp.get_layer_training(2).unwrap().trainable);
+        assert_eq!(mlp.predict(&arr2(&[[0.0, 1.0]])).unwrap().shape(), &[1, 2]);
+        assert!(matches!(
+            mlp.insert_layer(1, Box::new(FCLayer::build(3, 4))),
+            Err(MlError::Layer { index: 3, .. })
+        ));
+        assert!(matches!(
+            mlp.insert_layer(4, Box::new(FCLayer::build(2, 2))),
+            Err(MlError::InvalidInput(_))
+        ));
+        assert_eq!(mlp.layers.len(), 3);
+        assert_eq!(mlp.get_input_shape(), Some(&[2][..]));
+        mlp.truncate(5);
+        assert_eq!(mlp.layers.len(), 3);
+    }
+
+    #[test]
+    fn sub_network_should_extract_features() {
+        let x: Array2<f64> = arr2(&[[0.0, 1.0], [1.0, 0.5]]);
+        let mut mlp: Mlp = build_fine_tuned_mlp();
+        mlp.set_learning_rate_multiplier(2, 0.5).unwrap();
+
+        let feature_extractor: Mlp = mlp.sub_network(0..2).unwrap();
+        let head: Mlp = mlp.sub_network(2..3).unwrap();
+
+        assert_eq!(feature_extractor.get_input_shape(), Some(&[2][..]));
+        assert_eq!(head.get_input_shape(), Some(&[3][..]));
+        assert_eq!(
+            head.get_layer_training(0).unwrap().learning_rate_multiplier,
+            0.5
+        );
+        let features: Array2<f64> = feature_extractor.predict(&x).unwrap();
+        assert_eq!(features.shape(), &[2, 3]);
+        assert_eq!(head.predict(&features).unwrap(), mlp.predict(&x).unwrap());
+        assert_eq!(mlp.sub_network(1..1).unwrap().layers.len(), 0);
+        assert!(matches!(
+            mlp.sub_network(1..4),
+            Err(MlError::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn copy_weights_should_copy_matching_layers() {
+        let x: Array2<f64> = arr2(&[[0.0, 1.0], [1.0, 0.5]]);
+        let source: Mlp = build_fine_tuned_mlp();
+        let mut mlp: Mlp = Mlp::build(Box::new(Mse));
+        mlp.add_layer(Box::new(FCLayer::build_with_seed(2, 3, 7)));
+        mlp.add_layer(Box::new(ActivationLayer::build(Box::new(Tanh))));
+        mlp.add_layer(Box::new(FCLayer::build_with_seed(3, 2, 8)));
+        mlp.compile(&[2]).unwrap();
+
+        let copied: Vec<String> = mlp.copy_weights_by_name(&source);
+
+        assert_eq!(copied, vec!["FCLayer_0"]);
+        assert_eq!(
+            mlp.sub_network(0..2).unwrap().predict(&x).unwrap(),
+            source.sub_network(0..2).unwrap().predict(&x).unwrap()
+        );
+        assert!(matches!(
+            mlp.copy_layer_weights(2, &source, 2),
+            Err(MlError::ShapeMismatch(_))
+        ));
+        assert!(matches!(
+            mlp.copy_layer_weights(1, &source, 0),
+            Err(MlError::ShapeMismatch(_))
+        ));
+        assert!(matches!(
+            mlp.copy_layer_weights(0, &source, 3),
+            Err(MlError::InvalidInput(_))
+        ));
+        let mut target: Mlp = build_fine_tuned_mlp();
+        target.set_trainable(2, false).unwrap();
+        target.copy_layer_weights(2, &mlp, 0).unwrap_err();
+        target.copy_layer_weights(0, &mlp, 0).unwrap();
+        assert!(!target.get_layer_training(2).unwrap().trainable);
+    }
+
+    #[test]
+    fn copy_weights_should_keep_target_activation() {
+        let source: Mlp = build_fine_tuned_mlp();
+        let mut mlp: Mlp = Mlp::build(Box::new(Mse));
+        mlp.add_layer(Box::new(FCLayer::build_with_seed(2, 3, 7)));
+        mlp.add_layer(Box::new(ActivationLayer::build(Box::new(Relu))));
+        mlp.add_layer(Box::new(FCLayer::build_with_seed(3, 1, 8)));
+        mlp.compile(&[2]).unwrap();
+
+        assert_eq!(
+            mlp.copy_weights_by_name(&source),
+            vec!["FCLayer_0", "FCLayer_1"]
+        );
+        mlp.copy_layer_weights(1, &source, 1).unwrap();
+
+        assert!(mlp.layers[1].to_json().unwrap().contains("Relu"));
+        assert_eq!(
+            mlp.layers[0].get_parameters(),
+            source.layers[0].get_parameters()
+        );
+        assert_eq!(
+            mlp.layers[2].get_parameters(),
+            source.layers[2].get_parameters()
+        );
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn fit_should_not_update_frozen_layers_when_parallel() {
+        let x: Array2<f64> = arr2(&[[0.0, 1.0], [1.0, 0.5], [-1.0, 0.5], [0.5, 0.5]]);
+        let y: Array2<f64> = arr2(&[[0.5], [1.0], [-0.5], [0.0]]);
+        let mut mlp: Mlp = build_fine_tuned_mlp();
+        mlp.set_threads(2).unwrap();
+        mlp.set_trainable(0, false).unwrap();
+        let first_layer: String = mlp.layers[0].to_json().unwrap();
+        let last_layer: String = mlp.layers[2].to_json().unwrap();
+
+        mlp.train_batch(&x, &y, 0.1).unwrap();
+
+        assert_eq!(mlp.layers[0].to_json().unwrap(), first_layer);
+        assert_ne!(mlp.layers[2].to_json().unwrap(), last_layer);
     }
 }
@@ -1,2 +1,6 @@
+pub mod graph;
 pub mod mlp;
+#[allow(clippy::module_inception)]
 pub mod network;
+pub mod sequential;
+pub mod summary;
@@ -0,0 +1,425 @@
+use crate::accuracy::accuracy::Metric;
+use crate::layer::layer::Layer;
+use crate::loss::loss::Loss;
+use crate::network::mlp::{LayerTraining, Mlp};
+use crate::network::network::Network;
+use crate::network::summary::Summary;
+use crate::report::report::Report;
+use crate::utils::error::{check_shapes, MlError};
+use crate::utils::float::MlFloat;
+use ndarray::{Array, Array2, Dimension};
+use std::fs::read_to_string;
+use std::ops::Range;
+
+/// Stack of layers trained on batches of any dimension, like images of shape
+/// `(batch, h, w, c)`. Samples are flattened in rows and the layers get their shape back when
+/// the network is compiled. The JSON model is the one of `Mlp`.
+pub struct Sequential<F: MlFloat = f64> {
+    network: Mlp<F>,
+}
+
+impl<F: MlFloat> Sequential<F> {
+    pub fn build(loss: Box<dyn Loss<F>>) -> Sequential<F> {
+        Sequential {
+            network: Mlp::build(loss),
+        }
+    }
+
+    /// Number of shards each mini-batch is split into during training.
+    #[cfg(feature = "parallel")]
+    pub fn set_threads(&mut self, threads: usize) -> Result<(), MlError> {
+        self.network.set_threads(threads)
+    }
+
+    pub fn add_layer(&mut self, layer: Box<dyn Layer<F>>) {
+        self.network.add_layer(layer);
+    }
+
+    /// Freezes the layer at `index` when `trainable` is false, a container layer is frozen as a
+    /// whole.
+    pub fn set_trainable(&mut self, index: usize, trainable: bool) -> Result<(), MlError> {
+        self.network.set_trainable(index, trainable)
+    }
+
+    /// Scales the learning rate of the layer at `index`, 1 by default, 0 stops its updates.
+    pub fn set_learning_rate_multiplier(
+        &mut self,
+        index: usize,
+        multiplier: f64,
+    ) -> Result<(), MlError> {
+        self.network.set_learning_rate_multiplier(index, multiplier)
+    }
+
+    pub fn get_layer_training(&self, index: usize) -> Option<LayerTraining> {
+        self.network.get_layer_training(index)
+    }
+
+    pub fn pop_layer(&mut self) -> Option<Box<dyn Layer<F>>> {
+        self.network.pop_layer()
+    }
+
+    /// Keeps the first `n` layers.
+    pub fn truncate(&mut self, n: usize) {
+        self.network.truncate(n);
+    }
+
+    /// Inserts a layer before the layer at `index`, see `Mlp::insert_layer`.
+    pub fn insert_layer(&mut self, index: usize, layer: Box<dyn Layer<F>>) -> Result<(), MlError> {
+        self.network.insert_layer(index, layer)
+    }
+
+    /// Copies the layers in `range` into a new network, see `Mlp::sub_network`.
+    pub fn sub_network(&self, range: Range<usize>) -> Result<Sequential<F>, MlError> {
+        Ok(Sequential {
+            network: self.network.sub_network(range)?,
+        })
+    }
+
+    pub fn get_layer_names(&self) -> Vec<String> {
+        self.network.get_layer_names()
+    }
+
+    /// Replaces the parameters of the layer at `index` by the ones of the layer at
+    /// `source_index` of `source`.
+    pub fn copy_layer_weights(
+        &mut self,
+        index: usize,
+        source: &Sequential<F>,
+        source_index: usize,
+    ) -> Result<(), MlError> {
+        self.network
+            .copy_layer_weights(index, &source.network, source_index)
+    }
+
+    /// Copies the parameters of every layer of `source` with the same name and matching shapes.
+    pub fn copy_weights_by_name(&mut self, source: &Sequential<F>) -> Vec<String> {
+        self.network.copy_weights_by_name(&source.network)
+    }
+
+    /// Infers the shape of every layer from the shape of the input samples (without the batch
+    /// axis) and returns the shape of the output samples.
+    pub fn compile(&mut self, input_shape: &[usize]) -> Result<Vec<usize>, MlError> {
+        self.network.compile(input_shape)
+    }
+
+    pub fn get_input_shape(&self) -> Option<&[usize]> {
+        self.network.get_input_shape()
+    }
+
+    pub fn summary(&self) -> Result<Summary, MlError> {
+        self.network.summary()
+    }
+
+    /// Checks the samples have the compiled input shape and flattens them in rows.
+    fn to_rows<D: Dimension>(&self, x: &Array<F, D>) -> Result<Array2<F>, MlError> {
+        let input_shape: &[usize] = self.get_input_shape().ok_or_else(|| {
+            MlError::InvalidInput("network must be compiled before inference".to_string())
+        })?;
+        check_shapes(x.shape().get(1..).unwrap_or(&[]), input_shape)?;
+        flatten_samples(x)
+    }
+
+    pub fn predict<D: Dimension>(&self, x: &Array<F, D>) -> Result<Array2<F>, MlError> {
+        self.network.predict(&self.to_rows(x)?)
+    }
+
+    /// Runs the inference by chunks of `batch_size` samples.
+    pub fn predict_batch<D: Dimension>(
+        &self,
+        x: &Array<F, D>,
+        batch_size: usize,
+    ) -> Result<Array2<F>, MlError> {
+        self.network.predict_batch(&self.to_rows(x)?, batch_size)
+    }
+
+    pub fn evaluate<D: Dimension>(
+        &self,
+        x: &Array<F, D>,
+        y: &Array2<F>,
+        metrics: &mut [Box<dyn Metric>],
+    ) -> Result<Vec<(String, f64)>, MlError> {
+        self.network.evaluate(&self.to_rows(x)?, y, metrics)
+    }
+
+    pub fn compute_loss<D: Dimension>(
+        &self,
+        x: &Array<F, D>,
+        y: &Array2<F>,
+    ) -> Result<f64, MlError> {
+        self.network.compute_loss(&self.to_rows(x)?, y)
+    }
+
+    /// Compiles the network for the shape of the `x_train` samples and trains it.
+    #[allow(clippy::too_many_arguments)]
+    pub fn fit<D: Dimension>(
+        &mut self,
+        x_train: &Array<F, D>,
+        y_train: &Array2<F>,
+        x_test: Option<&Array<F, D>>,
+        y_test: Option<&Array2<F>>,
+        epochs: usize,
+        learning_rate: F,
+        batch_size: usize,
+        metrics: &mut [Box<dyn Metric>],
+        report: &mut Report,
+        report_step: usize,
+    ) -> Result<(), MlError> {
+        let sample_shape: &[usize] = x_train.shape().get(1..).unwrap_or(&[]);
+        let x_test: Option<Array2<F>> = x_test
+            .map(|x_test| {
+                check_shapes(x_test.shape().get(1..).unwrap_or(&[]), sample_shape)?;
+                flatten_samples(x_test)
+            })
+            .transpose()?;
+        let network_name: String = self.get_name();
+        self.network.fit_samples(
+            &network_name,
+            sample_shape,
+            &flatten_samples(x_train)?,
+            y_train,
+            x_test.as_ref(),
+            y_test,
+            epochs,
+            learning_rate,
+            batch_size,
+            metrics,
+            report,
+            report_step,
+        )
+    }
+}
+
+/// Reshapes a batch to one row per sample.
+fn flatten_samples<F: MlFloat, D: Dimension>(x: &Array<F, D>) -> Result<Array2<F>, MlError> {
+    if x.ndim() < 2 {
+        return Err(MlError::ShapeMismatch(format!(
+            "expected a batch of samples with at least 2 axes, actually: {:?}",
+            x.shape()
+        )));
+    }
+    let rows: usize = x.shape()[0];
+    let columns: usize = x.shape()[1..].iter().product();
+    Ok(x.as_standard_layout()
+        .into_owned()
+        .into_shape((rows, columns))?)
+}
+
+impl<F: MlFloat> Network<Sequential<F>> for Sequential<F> {
+    fn get_name(&self) -> String {
+        "Sequential".to_string()
+    }
+
+    fn from_json(json_str: &str) -> Result<Sequential<F>, MlError> {
+        Ok(Sequential {
+            network: Mlp::from_json(json_str)?,
+        })
+    }
+
+    fn to_json(&self) -> Result<String, MlError> {
+        self.network.to_json()
+    }
+
+    fn load(path: &str) -> Result<Sequential<F>, MlError> {
+        let content: String = read_to_string(path)?;
+        Sequential::from_json(content.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::accuracy::categorical_accuracy::CategoricalAccuracy;
+    use crate::activation::relu::Relu;
+    use crate::activation::sigmoid::Sigmoid;
+    use crate::layer::activation_layer::ActivationLayer;
+    use crate::layer::conv_layer::ConvLayer;
+    use crate::layer::fc_layer::FCLayer;
+    use crate::layer::flatten_layer::FlattenLayer;
+    use crate::layer::pooling_layer::MaxPoolingLayer;
+    use crate::loss::mse::Mse;
+    use ndarray::{Array4, Axis};
+    use ndarray_rand::rand::rngs::StdRng;
+    use ndarray_rand::rand::{Rng, SeedableRng};
+    use std::fs;
+    use std::fs::remove_file;
+    use std::path::Path;
+
+    /// 6x6 gray images holding a vertical (class 0) or an horizontal (class 1) bar over noise.
+    fn generate_bar_images(samples: usize, seed: u64) -> (Array4<f64>, Array2<f64>) {
+        let mut rng: StdRng = StdRng::seed_from_u64(seed);
+        let mut x: Array4<f64> = Array4::zeros((samples, 6, 6, 1));
+        let mut y: Array2<f64> = Array2::zeros((samples, 2));
+        for s in 0..samples {
+            let class: usize = s % 2;
+            let position: usize = rng.gen_range(0..6);
+            for i in 0..6 {
+                for j in 0..6 {
+                    let on_bar: bool =
+                        (class == 0 && j == position) || (class == 1 && i == position);
+                    x[[s, i, j, 0]] = if on_bar { 1.0 } else { 0.0 } + rng.gen_range(0.0..0.2);
+                }
+            }
+            y[[s, class]] = 1.0;
+        }
+        (x, y)
+    }
+
+    fn generate_test_network() -> Sequential {
+        let mut network: Sequential = Sequential::build(Box::new(Mse));
+        network.add_layer(Box::new(ConvLayer::build_with_seed(3, 4, 1, 5)));
+        network.add_layer(Box::new(ActivationLayer::build(Box::new(Relu))));
+        network.add_layer(Box::new(MaxPoolingLayer::build(2)));
+        network.add_layer(Box::new(FlattenLayer::new()));
+        network.add_layer(Box::new(FCLayer::build_with_seed(16, 2, 9)));
+        network.add_layer(Box::new(ActivationLayer::build(Box::new(Sigmoid))));
+        network
+    }
+
+    #[test]
+    fn sequential_should_learn_to_classify_images() {
+        let (x_train, y_train): (Array4<f64>, Array2<f64>) = generate_bar_images(40, 1);
+        let (x_test, y_test): (Array4<f64>, Array2<f64>) = generate_bar_images(20, 2);
+        let mut network: Sequential = generate_test_network();
+
+        network
+            .fit(
+                &x_train,
+                &y_train,
+                Some(&x_test),
+                Some(&y_test),
+                40,
+                0.2,
+                4,
+                &mut [Box::new(CategoricalAccuracy::new())],
+                &mut Report::build("./test_report_sequential_1"),
+                20,
+            )
+            .unwrap();
+
+        let accuracy: Vec<(String, f64)> = network
+            .evaluate(
+                &x_test,
+                &y_test,
+                &mut [Box::new(CategoricalAccuracy::new())],
+            )
+            .unwrap();
+        assert!(accuracy[0].1 > 0.9);
+        assert_eq!(network.get_input_shape(), Some(&[6, 6, 1][..]));
+        assert_eq!(network.predict(&x_test).unwrap().shape(), &[20, 2]);
+        assert!(read_to_string("./test_report_sequential_1/39/report.html")
+            .unwrap()
+            .contains("<tr><td>X Train</td><td>[40, 6, 6, 1]</td></tr>"));
+        fs::remove_dir_all("./test_report_sequential_1").unwrap();
+    }
+
+    #[test]
+    fn predict_should_check_sample_shape() {
+        let mut network: Sequential = generate_test_network();
+        assert!(matches!(
+            network.predict(&Array4::<f64>::zeros((2, 6, 6, 1))),
+            Err(MlError::InvalidInput(_))
+        ));
+        network.compile(&[6, 6, 1]).unwrap();
+        assert!(matches!(
+            network.predict(&Array4::<f64>::zeros((2, 5, 6, 1))),
+            Err(MlError::ShapeMismatch(_))
+        ));
+        assert_eq!(
+            network
+                .predict_batch(&Array4::<f64>::zeros((5, 6, 6, 1)), 2)
+                .unwrap()
+                .shape(),
+            &[5, 2]
+        );
+    }
+
+    #[test]
+    fn fit_should_report_layer_that_does_not_fit_images() {
+        let (x_train, y_train): (Array4<f64>, Array2<f64>) = generate_bar_images(4, 1);
+        let mut network: Sequential = Sequential::build(Box::new(Mse));
+        network.add_layer(Box::new(ConvLayer::build(3, 4, 1)));
+        network.add_layer(Box::new(FCLayer::build(16, 2)));
+        let result: Result<(), MlError> = network.fit(
+            &x_train,
+            &y_train,
+            None,
+            None,
+            1,
+            0.1,
+            1,
+            &mut [],
+            &mut Report::build("./test_report_sequential_2"),
+            1,
+        );
+        assert!(matches!(result, Err(MlError::Layer { index: 1, .. })));
+        assert!(!Path::new("./test_report_sequential_2").exists());
+    }
+
+    #[test]
+    fn load_should_read_sequential() {
+        let (x, _): (Array4<f64>, Array2<f64>) = generate_bar_images(3, 3);
+        let mut network: Sequential = generate_test_network();
+        network.compile(&[6, 6, 1]).unwrap();
+        network.save("", "UnitTestSequential1".to_string()).unwrap();
+
+        let loaded: Sequential = Sequential::load("UnitTestSequential1.json").unwrap();
+        remove_file("UnitTestSequential1.json").unwrap();
+
+        assert_eq!(loaded.get_name(), "Sequential");
+        assert_eq!(loaded.get_input_shape(), Some(&[6, 6, 1][..]));
+        assert_eq!(loaded.to_json().unwrap().matches("ConvLayer").count(), 1);
+        assert_eq!(
+            loaded.to_json().unwrap().matches("MaxPoolingLayer").count(),
+            1
+        );
+        assert_eq!(loaded.to_json().unwrap().matches("FlattenLayer").count(), 1);
+        let expected: Array2<f64> = network.predict(&x).unwrap();
+        let result: Array2<f64> = loaded.predict(&x).unwrap();
+        for (e, r) in expected.iter().zip(result.iter()) {
+            assert!((e - r).powf(2.0) < 0.00001);
+        }
+        assert_eq!(result.len_of(Axis(0)), 3);
+    }
+
+    #[test]
+    fn sequential_should_replace_head_of_loaded_network() {
+        let (x, _): (Array4<f64>, Array2<f64>) = generate_bar_images(4, 3);
+        let mut trained: Sequential = generate_test_network();
+        trained.compile(&[6, 6, 1]).unwrap();
+        let mut network: Sequential =
+            Sequential::from_json(trained.to_json().unwrap().as_str()).unwrap();
+
+        network.pop_layer();
+        network.pop_layer();
+        network.add_layer(Box::new(FCLayer::build_with_seed(16, 3, 4)));
+        network.add_layer(Box::new(ActivationLayer::build(Box::new(Sigmoid))));
+        (0..4).for_each(|index| network.set_trainable(index, false).unwrap());
+        let feature_extractor: Sequential = network.sub_network(0..4).unwrap();
+
+        assert_eq!(network.compile(&[6, 6, 1]).unwrap(), vec![3]);
+        assert_eq!(network.summary().unwrap().get_trainable_params(), 51);
+        assert_eq!(feature_extractor.predict(&x).unwrap().shape(), &[4, 16]);
+        feature_extractor
+            .predict(&x)
+            .unwrap()
+            .iter()
+            .zip(
+                trained
+                    .sub_network(0..4)
+                    .unwrap()
+                    .predict(&x)
+                    .unwrap()
+                    .iter(),
+            )
+            .for_each(|(feature, expected)| assert!((feature - expected).powf(2.0) < 0.00001));
+        let mut copy: Sequential = generate_test_network();
+        copy.compile(&[6, 6, 1]).unwrap();
+        assert_eq!(copy.copy_weights_by_name(&network), vec!["ConvLayer_0"]);
+        copy.copy_layer_weights(4, &trained, 4).unwrap();
+        copy.predict(&x)
+            .unwrap()
+            .iter()
+            .zip(trained.predict(&x).unwrap().iter())
+            .for_each(|(output, expected)| assert!((output - expected).powf(2.0) < 0.00001));
+    }
+}
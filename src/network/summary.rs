@@ -0,0 +1,158 @@
+use std::fmt;
+
+/// One row of a network summary.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LayerSummary {
+    pub name: String,
+    pub output_shape: Vec<usize>,
+    pub trainable_params: usize,
+    pub non_trainable_params: usize,
+    /// Size of the parameters in bytes.
+    pub memory: usize,
+}
+
+/// Layers of a compiled network with their output shapes and parameter counts.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Summary {
+    input_shape: Vec<usize>,
+    layers: Vec<LayerSummary>,
+}
+
+impl Summary {
+    pub fn build(input_shape: Vec<usize>, layers: Vec<LayerSummary>) -> Summary {
+        Summary {
+            input_shape,
+            layers,
+        }
+    }
+
+    pub fn get_input_shape(&self) -> &[usize] {
+        &self.input_shape
+    }
+
+    pub fn get_layers(&self) -> &[LayerSummary] {
+        &self.layers
+    }
+
+    pub fn get_trainable_params(&self) -> usize {
+        self.layers.iter().map(|layer| layer.trainable_params).sum()
+    }
+
+    pub fn get_non_trainable_params(&self) -> usize {
+        self.layers
+            .iter()
+            .map(|layer| layer.non_trainable_params)
+            .sum()
+    }
+
+    pub fn get_memory(&self) -> usize {
+        self.layers.iter().map(|layer| layer.memory).sum()
+    }
+
+    fn rows(&self) -> Vec<[String; 5]> {
+        let mut rows: Vec<[String; 5]> = vec![[
+            "Layer".to_string(),
+            "Output Shape".to_string(),
+            "Trainable".to_string(),
+            "Non-trainable".to_string(),
+            "Memory (B)".to_string(),
+        ]];
+        for (index, layer) in self.layers.iter().enumerate() {
+            rows.push([
+                format!("{} ({})", index, layer.name),
+                format!("{:?}", layer.output_shape),
+                layer.trainable_params.to_string(),
+                layer.non_trainable_params.to_string(),
+                layer.memory.to_string(),
+            ]);
+        }
+        rows.push([
+            "Total".to_string(),
+            String::new(),
+            self.get_trainable_params().to_string(),
+            self.get_non_trainable_params().to_string(),
+            self.get_memory().to_string(),
+        ]);
+        rows
+    }
+}
+
+impl fmt::Display for Summary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let rows: Vec<[String; 5]> = self.rows();
+        let widths: Vec<usize> = (0..5)
+            .map(|c| rows.iter().map(|row| row[c].len()).max().unwrap_or(0))
+            .collect();
+        let separator: String = "-".repeat(widths.iter().sum::<usize>() + 2 * (widths.len() - 1));
+        writeln!(f, "Input Shape: {:?}", self.input_shape)?;
+        for (r, row) in rows.iter().enumerate() {
+            if r == 1 || r == rows.len() - 1 {
+                writeln!(f, "{}", separator)?;
+            }
+            let line: Vec<String> = row
+                .iter()
+                .zip(&widths)
+                .enumerate()
+                .map(|(c, (cell, width))| {
+                    if c < 2 {
+                        format!("{:<width$}", cell, width = width)
+                    } else {
+                        format!("{:>width$}", cell, width = width)
+                    }
+                })
+                .collect();
+            writeln!(f, "{}", line.join("  ").trim_end())?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn generate_test_summary() -> Summary {
+        Summary::build(
+            vec![2],
+            vec![
+                LayerSummary {
+                    name: "FCLayer".to_string(),
+                    output_shape: vec![3],
+                    trainable_params: 9,
+                    non_trainable_params: 0,
+                    memory: 72,
+                },
+                LayerSummary {
+                    name: "ActivationLayer".to_string(),
+                    output_shape: vec![3],
+                    trainable_params: 0,
+                    non_trainable_params: 0,
+                    memory: 0,
+                },
+            ],
+        )
+    }
+
+    #[test]
+    fn summary_should_sum_layers() {
+        let summary: Summary = generate_test_summary();
+        assert_eq!(summary.get_trainable_params(), 9);
+        assert_eq!(summary.get_non_trainable_params(), 0);
+        assert_eq!(summary.get_memory(), 72);
+    }
+
+    #[test]
+    fn to_string_should_format_table() {
+        assert_eq!(
+            generate_test_summary().to_string(),
+            "Input Shape: [2]
+Layer                Output Shape  Trainable  Non-trainable  Memory (B)
+-----------------------------------------------------------------------
+0 (FCLayer)          [3]                   9              0          72
+1 (ActivationLayer)  [3]                   0              0           0
+-----------------------------------------------------------------------
+Total                                      9              0          72
+"
+        );
+    }
+}
@@ -1,3 +1,6 @@
+use crate::network::summary::Summary;
+use ndarray::{Array2, Axis};
+
 pub fn generate_images_html(images: Vec<&str>) -> String {
     let mut html: String = String::new();
     for image in images {
@@ -16,6 +19,7 @@ pub fn generate_images_html(images: Vec<&str>) -> String {
     html
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn generate_resume_html(
     network_name: &str,
     duration: u64,
@@ -25,11 +29,12 @@ pub fn generate_resume_html(
     y_train_shape: &[usize],
     x_test_shape: Option<&[usize]>,
     y_test_shape: Option<&[usize]>,
-    accuracy_function: &str,
     loss_function: &str,
-    train_accuracy: f64,
+    train_metrics: &[(String, f64)],
     train_loss: f64,
-    test_accuracy: Option<f64>,
+    test_metrics: Option<&[(String, f64)]>,
+    test_loss: Option<f64>,
+    summary: Option<&Summary>,
 ) -> String {
     let mut html: String = "<div class=\"tableBlock\">\n\t<table>".to_string();
     html.push_str(&format!(
@@ -52,41 +57,107 @@ pub fn generate_resume_html(
         "\n\t\t<tr><td>Y Train</td><td>{:?}</td></tr>",
         y_train_shape
     ));
-    if x_test_shape.is_some() {
+    if let Some(x_test_shape) = x_test_shape {
         html.push_str(&format!(
             "\n\t\t<tr><td>X Test</td><td>{:?}</td></tr>",
-            x_test_shape.unwrap()
+            x_test_shape
         ));
     }
-    if y_test_shape.is_some() {
+    if let Some(y_test_shape) = y_test_shape {
         html.push_str(&format!(
             "\n\t\t<tr><td>Y Test</td><td>{:?}</td></tr>",
-            y_test_shape.unwrap()
+            y_test_shape
         ));
     }
     html.push_str(&format!(
-        "\n\t\t<tr><td>Accuracy Function</td><td>{}</td></tr>",
-        accuracy_function
+        "\n\t\t<tr><td>Metrics</td><td>{}</td></tr>",
+        train_metrics
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .collect::<Vec<&str>>()
+            .join(", ")
     ));
     html.push_str(&format!(
         "\n\t\t<tr><td>Loss Function</td><td>{}</td></tr>",
         loss_function
     ));
-    html.push_str(&format!(
-        "\n\t\t<tr><td>Train Accuracy</td><td>{:?}</td></tr>",
-        train_accuracy
-    ));
+    for (name, value) in train_metrics {
+        html.push_str(&format!(
+            "\n\t\t<tr><td>Train {}</td><td>{:?}</td></tr>",
+            name, value
+        ));
+    }
     html.push_str(&format!(
         "\n\t\t<tr><td>Train Loss</td><td>{:?}</td></tr>",
         train_loss
     ));
-    if test_accuracy.is_some() {
+    if let Some(test_metrics) = test_metrics {
+        for (name, value) in test_metrics {
+            html.push_str(&format!(
+                "\n\t\t<tr><td>Test {}</td><td>{:?}</td></tr>",
+                name, value
+            ));
+        }
+    }
+    if let Some(test_loss) = test_loss {
+        html.push_str(&format!(
+            "\n\t\t<tr><td>Test Loss</td><td>{:?}</td></tr>",
+            test_loss
+        ));
+    }
+    html.push_str("\n\t</table>\n</div>\n");
+    if let Some(summary) = summary {
+        html.push_str(&generate_summary_html(summary));
+    }
+    html
+}
+
+pub fn generate_summary_html(summary: &Summary) -> String {
+    let mut html: String = "<div class=\"tableBlock\">\n\t<h2>Summary</h2>\n\t<table>".to_string();
+    html.push_str(&format!(
+        "\n\t\t<tr><th>Input Shape</th><td colspan=\"4\">{:?}</td></tr>",
+        summary.get_input_shape()
+    ));
+    html.push_str(
+        "\n\t\t<tr><th>Layer</th><th>Output Shape</th><th>Trainable</th><th>Non-trainable</th><th>Memory (B)</th></tr>",
+    );
+    for (index, layer) in summary.get_layers().iter().enumerate() {
         html.push_str(&format!(
-            "\n\t\t<tr><td>Test Accuracy</td><td>{:?}</td></tr>",
-            test_accuracy.unwrap()
+            "\n\t\t<tr><td>{} ({})</td><td>{:?}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+            index,
+            layer.name,
+            layer.output_shape,
+            layer.trainable_params,
+            layer.non_trainable_params,
+            layer.memory
         ));
     }
-    html.push_str(&format!("\n\t</table>\n</div>\n"));
+    html.push_str(&format!(
+        "\n\t\t<tr><th>Total</th><td></td><td>{}</td><td>{}</td><td>{}</td></tr>",
+        summary.get_trainable_params(),
+        summary.get_non_trainable_params(),
+        summary.get_memory()
+    ));
+    html.push_str("\n\t</table>\n</div>\n");
+    html
+}
+
+pub fn generate_confusion_matrix_html(confusion_matrix: &Array2<f64>) -> String {
+    let mut html: String =
+        "<div class=\"tableBlock\">\n\t<h2>Test Confusion Matrix</h2>\n\t<table>".to_string();
+    html.push_str("\n\t\t<tr><th>True \\ Predicted</th>");
+    for c in 0..confusion_matrix.shape()[1] {
+        html.push_str(&format!("<th>{}</th>", c));
+    }
+    html.push_str("</tr>");
+    for (r, row) in confusion_matrix.axis_iter(Axis(0)).enumerate() {
+        html.push_str(&format!("\n\t\t<tr><th>{}</th>", r));
+        for value in row {
+            html.push_str(&format!("<td>{}</td>", value));
+        }
+        html.push_str("</tr>");
+    }
+    html.push_str("\n\t</table>\n</div>\n");
     html
 }
 
@@ -113,8 +184,11 @@ table, th, td {{
 }
 
 #[cfg(test)]
+#[allow(clippy::unused_unit)]
 mod tests {
     use super::*;
+    use crate::network::summary::LayerSummary;
+    use ndarray::arr2;
 
     #[test]
     fn generate_images_html_should_format_list_of_image() -> () {
@@ -146,11 +220,12 @@ mod tests {
             &[5, 1],
             Some(&[6, 1]),
             Some(&[4, 1]),
-            "Categorical Accuracy",
             "MSE",
-            0.66,
+            &[("categorical_accuracy".to_string(), 0.66)],
             0.33,
-            Some(0.8),
+            Some(&[("categorical_accuracy".to_string(), 0.8)]),
+            Some(0.4),
+            None,
         );
         assert_eq!(
             resume_html,
@@ -163,11 +238,12 @@ mod tests {
 		<tr><td>Y Train</td><td>[5, 1]</td></tr>
 		<tr><td>X Test</td><td>[6, 1]</td></tr>
 		<tr><td>Y Test</td><td>[4, 1]</td></tr>
-		<tr><td>Accuracy Function</td><td>Categorical Accuracy</td></tr>
+		<tr><td>Metrics</td><td>categorical_accuracy</td></tr>
 		<tr><td>Loss Function</td><td>MSE</td></tr>
-		<tr><td>Train Accuracy</td><td>0.66</td></tr>
+		<tr><td>Train categorical_accuracy</td><td>0.66</td></tr>
 		<tr><td>Train Loss</td><td>0.33</td></tr>
-		<tr><td>Test Accuracy</td><td>0.8</td></tr>
+		<tr><td>Test categorical_accuracy</td><td>0.8</td></tr>
+		<tr><td>Test Loss</td><td>0.4</td></tr>
 	</table>
 </div>
 "
@@ -185,11 +261,12 @@ mod tests {
             &[5, 1],
             None,
             None,
-            "Categorical Accuracy",
             "MSE",
-            0.66,
+            &[("categorical_accuracy".to_string(), 0.66)],
             0.33,
             None,
+            None,
+            None,
         );
         assert_eq!(
             resume_html,
@@ -200,9 +277,9 @@ mod tests {
 		<tr><td>Epochs</td><td>5/100</td></tr>
 		<tr><td>X Train</td><td>[10, 1]</td></tr>
 		<tr><td>Y Train</td><td>[5, 1]</td></tr>
-		<tr><td>Accuracy Function</td><td>Categorical Accuracy</td></tr>
+		<tr><td>Metrics</td><td>categorical_accuracy</td></tr>
 		<tr><td>Loss Function</td><td>MSE</td></tr>
-		<tr><td>Train Accuracy</td><td>0.66</td></tr>
+		<tr><td>Train categorical_accuracy</td><td>0.66</td></tr>
 		<tr><td>Train Loss</td><td>0.33</td></tr>
 	</table>
 </div>
@@ -210,6 +287,51 @@ mod tests {
         )
     }
 
+    #[test]
+    fn generate_summary_html_should_format_layers() {
+        let summary: Summary = Summary::build(
+            vec![2],
+            vec![LayerSummary {
+                name: "FCLayer".to_string(),
+                output_shape: vec![3],
+                trainable_params: 9,
+                non_trainable_params: 0,
+                memory: 72,
+            }],
+        );
+        assert_eq!(
+            generate_summary_html(&summary),
+            "<div class=\"tableBlock\">
+	<h2>Summary</h2>
+	<table>
+		<tr><th>Input Shape</th><td colspan=\"4\">[2]</td></tr>
+		<tr><th>Layer</th><th>Output Shape</th><th>Trainable</th><th>Non-trainable</th><th>Memory (B)</th></tr>
+		<tr><td>0 (FCLayer)</td><td>[3]</td><td>9</td><td>0</td><td>72</td></tr>
+		<tr><th>Total</th><td></td><td>9</td><td>0</td><td>72</td></tr>
+	</table>
+</div>
+"
+        )
+    }
+
+    #[test]
+    fn generate_confusion_matrix_html_should_format_matrix() {
+        let confusion_matrix_html: String =
+            generate_confusion_matrix_html(&arr2(&[[3.0, 1.0], [0.0, 4.0]]));
+        assert_eq!(
+            confusion_matrix_html,
+            "<div class=\"tableBlock\">
+	<h2>Test Confusion Matrix</h2>
+	<table>
+		<tr><th>True \\ Predicted</th><th>0</th><th>1</th></tr>
+		<tr><th>0</th><td>3</td><td>1</td></tr>
+		<tr><th>1</th><td>0</td><td>4</td></tr>
+	</table>
+</div>
+"
+        )
+    }
+
     #[test]
     fn generate_full_html_should_create_full_report() -> () {
         let resume_html: String = generate_resume_html(
@@ -221,11 +343,12 @@ mod tests {
             &[5, 1],
             Some(&[6, 1]),
             Some(&[4, 1]),
-            "Categorical Accuracy",
             "MSE",
-            0.66,
+            &[("categorical_accuracy".to_string(), 0.66)],
             0.33,
-            Some(0.8),
+            Some(&[("categorical_accuracy".to_string(), 0.8)]),
+            Some(0.4),
+            None,
         );
         let images_html: String = generate_images_html(vec!["image1.png", "image2.png"]);
         let report_html: String = generate_full_html(resume_html, images_html);
@@ -252,11 +375,12 @@ table, th, td {
 		<tr><td>Y Train</td><td>[5, 1]</td></tr>
 		<tr><td>X Test</td><td>[6, 1]</td></tr>
 		<tr><td>Y Test</td><td>[4, 1]</td></tr>
-		<tr><td>Accuracy Function</td><td>Categorical Accuracy</td></tr>
+		<tr><td>Metrics</td><td>categorical_accuracy</td></tr>
 		<tr><td>Loss Function</td><td>MSE</td></tr>
-		<tr><td>Train Accuracy</td><td>0.66</td></tr>
+		<tr><td>Train categorical_accuracy</td><td>0.66</td></tr>
 		<tr><td>Train Loss</td><td>0.33</td></tr>
-		<tr><td>Test Accuracy</td><td>0.8</td></tr>
+		<tr><td>Test categorical_accuracy</td><td>0.8</td></tr>
+		<tr><td>Test Loss</td><td>0.4</td></tr>
 	</table>
 </div>
 
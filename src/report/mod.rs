@@ -1,3 +1,4 @@
 mod html;
 mod plot;
+#[allow(clippy::module_inception)]
 pub mod report;
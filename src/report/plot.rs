@@ -1,54 +1,137 @@
+use crate::utils::error::MlError;
 use plotters::prelude::*;
-use std::path;
+use std::fmt::Display;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Drawing errors come from the bitmap backend writing the image.
+fn plot_error<E: Display>(error: E) -> MlError {
+    MlError::Io(io::Error::other(error.to_string()))
+}
 
 pub fn generate_2d_plot(
     output_directory: &str,
-    x: &Vec<f64>,
-    y: &Vec<f64>,
+    x: &[f64],
+    series: &[(&str, &[f64])],
     x_label: &str,
     y_label: &str,
     title: &str,
-) -> () {
-    if x.len() != y.len() {
-        panic!(
-            "vectors must have the same length: {} != {}",
-            x.len(),
-            y.len()
-        )
+) -> Result<(), MlError> {
+    for (_, y) in series {
+        if x.len() != y.len() {
+            return Err(MlError::ShapeMismatch(format!(
+                "vectors must have the same length: {} != {}",
+                x.len(),
+                y.len()
+            )));
+        }
     }
+    let empty_error = || MlError::InvalidInput(format!("plot '{}' has no value", title));
 
-    let x_min: f64 = x.iter().map(|&x| x as i32).min().unwrap() as f64;
-    let x_max: f64 = x.iter().map(|&x| x as i32).max().unwrap() as f64;
-    let y_min: f64 = y.iter().map(|&y| y as i32).min().unwrap() as f64;
-    let y_max: f64 = y.iter().map(|&y| y as i32).max().unwrap() as f64;
+    let x_min: f64 = x.iter().map(|&x| x as i32).min().ok_or_else(empty_error)? as f64;
+    let x_max: f64 = x.iter().map(|&x| x as i32).max().ok_or_else(empty_error)? as f64;
+    let y_min: f64 = series
+        .iter()
+        .flat_map(|(_, y)| y.iter().map(|&y| y as i32))
+        .min()
+        .ok_or_else(empty_error)? as f64;
+    let y_max: f64 = series
+        .iter()
+        .flat_map(|(_, y)| y.iter().map(|&y| y as i32))
+        .max()
+        .ok_or_else(empty_error)? as f64;
 
-    let path: String = path::Path::new(output_directory)
-        .join(title.to_string() + ".png")
-        .to_str()
-        .unwrap()
-        .to_string();
+    let path: PathBuf = Path::new(output_directory).join(title.to_string() + ".png");
 
     let root_area = BitMapBackend::new(&path, (600, 400)).into_drawing_area();
-    root_area.fill(&WHITE).unwrap();
+    root_area.fill(&WHITE).map_err(plot_error)?;
 
     let mut ctx = ChartBuilder::on(&root_area)
         .set_label_area_size(LabelAreaPosition::Left, 40)
         .set_label_area_size(LabelAreaPosition::Bottom, 40)
         .caption(title, ("sans-serif", 40))
         .build_cartesian_2d((x_min - 1.0)..(x_max + 1.0), (y_min - 1.0)..(y_max + 1.0))
-        .unwrap();
+        .map_err(plot_error)?;
 
     ctx.configure_mesh()
         .y_desc(y_label)
         .x_desc(x_label)
         .draw()
-        .unwrap();
+        .map_err(plot_error)?;
 
-    ctx.draw_series(LineSeries::new((0..x.len()).map(|i| (x[i], y[i])), &BLUE))
-        .unwrap();
+    for (i, (name, y)) in series.iter().enumerate() {
+        let color = Palette99::pick(i).to_rgba();
+        ctx.draw_series(LineSeries::new((0..x.len()).map(|j| (x[j], y[j])), color))
+            .map_err(plot_error)?
+            .label(*name)
+            .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], color));
+    }
+
+    ctx.configure_series_labels()
+        .background_style(WHITE.mix(0.8))
+        .border_style(BLACK)
+        .draw()
+        .map_err(plot_error)?;
+    root_area.present().map_err(plot_error)
+}
+
+pub fn generate_curves_plot(
+    output_directory: &str,
+    curves: &[(String, Vec<f64>, Vec<f64>)],
+    x_label: &str,
+    y_label: &str,
+    title: &str,
+) -> Result<(), MlError> {
+    for (name, x, y) in curves {
+        if x.len() != y.len() {
+            return Err(MlError::ShapeMismatch(format!(
+                "vectors of curve '{}' must have the same length: {} != {}",
+                name,
+                x.len(),
+                y.len()
+            )));
+        }
+    }
+
+    let path: PathBuf = Path::new(output_directory).join(title.to_string() + ".png");
+
+    let root_area = BitMapBackend::new(&path, (600, 400)).into_drawing_area();
+    root_area.fill(&WHITE).map_err(plot_error)?;
+
+    let mut ctx = ChartBuilder::on(&root_area)
+        .set_label_area_size(LabelAreaPosition::Left, 40)
+        .set_label_area_size(LabelAreaPosition::Bottom, 40)
+        .caption(title, ("sans-serif", 40))
+        .build_cartesian_2d(0.0..1.0, 0.0..1.0)
+        .map_err(plot_error)?;
+
+    ctx.configure_mesh()
+        .y_desc(y_label)
+        .x_desc(x_label)
+        .draw()
+        .map_err(plot_error)?;
+
+    for (i, (name, x, y)) in curves.iter().enumerate() {
+        let color = Palette99::pick(i).to_rgba();
+        ctx.draw_series(LineSeries::new(
+            (0..x.len()).map(|j| (x[j], y[j])),
+            color.stroke_width(2),
+        ))
+        .map_err(plot_error)?
+        .label(name.as_str())
+        .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], color));
+    }
+
+    ctx.configure_series_labels()
+        .background_style(WHITE.mix(0.8))
+        .border_style(BLACK)
+        .draw()
+        .map_err(plot_error)?;
+    root_area.present().map_err(plot_error)
 }
 
 #[cfg(test)]
+#[allow(clippy::unused_unit)]
 mod tests {
     use super::*;
     use std::fs;
@@ -56,17 +139,76 @@ mod tests {
     #[test]
     fn generate_2d_plot_should_create_png() -> () {
         let x: Vec<f64> = vec![0.0, 1.0, 2.0];
-        let y: Vec<f64> = vec![2.0, 4.0, 6.0];
-        generate_2d_plot(".", &x, &y, "x_label", "y_label", "test");
-        assert!(path::Path::new("./test.png").exists());
+        let y_train: Vec<f64> = vec![2.0, 4.0, 6.0];
+        let y_test: Vec<f64> = vec![3.0, 5.0, 8.0];
+        generate_2d_plot(
+            ".",
+            &x,
+            &[("train", &y_train), ("test", &y_test)],
+            "x_label",
+            "y_label",
+            "test",
+        )
+        .unwrap();
+        assert!(Path::new("./test.png").exists());
         fs::remove_file("./test.png").unwrap();
     }
 
     #[test]
-    #[should_panic(expected = "vectors must have the same length: 3 != 4")]
-    fn generate_2d_plot_should_panic_when_vectors_have_not_same_length() -> () {
+    fn generate_curves_plot_should_create_png() {
+        let curves: Vec<(String, Vec<f64>, Vec<f64>)> = vec![
+            (
+                "class 0".to_string(),
+                vec![0.0, 0.5, 1.0],
+                vec![0.0, 0.8, 1.0],
+            ),
+            ("class 1".to_string(), vec![0.0, 1.0], vec![0.0, 1.0]),
+        ];
+        generate_curves_plot(".", &curves, "x_label", "y_label", "test curves").unwrap();
+        assert!(Path::new("./test curves.png").exists());
+        fs::remove_file("./test curves.png").unwrap();
+    }
+
+    #[test]
+    fn generate_curves_plot_should_fail_when_vectors_have_not_same_length() {
+        let curves: Vec<(String, Vec<f64>, Vec<f64>)> =
+            vec![("class 0".to_string(), vec![0.0, 1.0], vec![0.0, 0.5, 1.0])];
+        let error: MlError =
+            generate_curves_plot(".", &curves, "x_label", "y_label", "test curves panic")
+                .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "shape mismatch: vectors of curve 'class 0' must have the same length: 2 != 3"
+        );
+    }
+
+    #[test]
+    fn generate_2d_plot_should_fail_when_vectors_have_not_same_length() {
         let x: Vec<f64> = vec![0.0, 1.0, 2.0];
         let y: Vec<f64> = vec![2.0, 4.0, 6.0, 8.0];
-        generate_2d_plot(".", &x, &y, "x_label", "y_label", "test");
+        let error: MlError =
+            generate_2d_plot(".", &x, &[("train", &y)], "x_label", "y_label", "test").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "shape mismatch: vectors must have the same length: 3 != 4"
+        );
+    }
+
+    #[test]
+    fn generate_2d_plot_should_fail_when_directory_does_not_exist() {
+        let x: Vec<f64> = vec![0.0, 1.0];
+        let result: Result<(), MlError> = generate_2d_plot(
+            "./missing_plot_directory",
+            &x,
+            &[("train", &x)],
+            "x_label",
+            "y_label",
+            "test",
+        );
+        assert!(matches!(result, Err(MlError::Io(_))));
+        assert!(matches!(
+            generate_2d_plot(".", &[], &[], "x_label", "y_label", "empty"),
+            Err(MlError::InvalidInput(_))
+        ));
     }
 }
@@ -1,16 +1,26 @@
-use crate::report::html::{generate_full_html, generate_images_html, generate_resume_html};
-use crate::report::plot::generate_2d_plot;
+use crate::accuracy::ranking::Curve;
+use crate::network::summary::Summary;
+use crate::report::html::{
+    generate_confusion_matrix_html, generate_full_html, generate_images_html, generate_resume_html,
+};
+use crate::report::plot::{generate_2d_plot, generate_curves_plot};
+use crate::utils::error::MlError;
+use ndarray::Array2;
 use std::fs;
 use std::fs::File;
 use std::io::Write;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::time::Instant;
 
 pub struct Report {
     steps: Vec<usize>,
-    train_accuracies: Vec<f64>,
+    train_metrics: Vec<(String, Vec<f64>)>,
     train_losses: Vec<f64>,
-    test_accuracies: Vec<f64>,
+    test_metrics: Vec<(String, Vec<f64>)>,
+    test_losses: Vec<f64>,
+    test_confusion_matrix: Option<Array2<f64>>,
+    roc_curves: Vec<Curve>,
+    pr_curves: Vec<Curve>,
     output_directory: String,
 }
 
@@ -18,9 +28,13 @@ impl Report {
     pub fn build(output_directory: &str) -> Report {
         Report {
             steps: vec![],
-            train_accuracies: vec![],
+            train_metrics: vec![],
             train_losses: vec![],
-            test_accuracies: vec![],
+            test_metrics: vec![],
+            test_losses: vec![],
+            test_confusion_matrix: None,
+            roc_curves: vec![],
+            pr_curves: vec![],
             output_directory: output_directory.to_string(),
         }
     }
@@ -28,18 +42,51 @@ impl Report {
     pub fn add_data(
         &mut self,
         step: usize,
-        train_accuracy: f64,
+        train_metrics: Vec<(String, f64)>,
         train_loss: f64,
-        test_accuracy: Option<f64>,
-    ) -> () {
+        test_metrics: Option<Vec<(String, f64)>>,
+        test_loss: Option<f64>,
+    ) {
         self.steps.push(step);
-        self.train_accuracies.push(train_accuracy);
+        Report::push_metrics(&mut self.train_metrics, train_metrics);
         self.train_losses.push(train_loss);
-        if test_accuracy.is_some() {
-            self.test_accuracies.push(test_accuracy.unwrap());
+        if let Some(test_metrics) = test_metrics {
+            Report::push_metrics(&mut self.test_metrics, test_metrics);
         }
+        if let Some(test_loss) = test_loss {
+            self.test_losses.push(test_loss);
+        }
+    }
+
+    pub fn set_confusion_matrix(&mut self, confusion_matrix: Array2<f64>) {
+        self.test_confusion_matrix = Some(confusion_matrix);
+    }
+
+    pub fn set_roc_curves(&mut self, roc_curves: Vec<Curve>) {
+        self.roc_curves = roc_curves;
+    }
+
+    pub fn set_pr_curves(&mut self, pr_curves: Vec<Curve>) {
+        self.pr_curves = pr_curves;
     }
 
+    fn push_metrics(history: &mut Vec<(String, Vec<f64>)>, metrics: Vec<(String, f64)>) {
+        for (name, value) in metrics {
+            match history.iter_mut().find(|(metric, _)| *metric == name) {
+                Some((_, values)) => values.push(value),
+                None => history.push((name, vec![value])),
+            }
+        }
+    }
+
+    fn last_metrics(history: &[(String, Vec<f64>)]) -> Vec<(String, f64)> {
+        history
+            .iter()
+            .filter_map(|(name, values)| values.last().map(|value| (name.clone(), *value)))
+            .collect()
+    }
+
+    #[allow(clippy::too_many_arguments)]
     pub fn generate(
         &self,
         network_name: &str,
@@ -49,25 +96,32 @@ impl Report {
         y_train_shape: &[usize],
         x_test_shape: Option<&[usize]>,
         y_test_shape: Option<&[usize]>,
-        accuracy_function: &str,
         loss_function: &str,
-    ) -> () {
-        let image_directory: String = Path::new(self.output_directory.as_str())
-            .join((self.train_accuracies.len() - 1).to_string())
-            .join("static")
-            .to_str()
-            .unwrap()
-            .to_string();
-        fs::create_dir_all(image_directory.as_str()).unwrap();
-        self.generate_plots(image_directory.as_str(), accuracy_function, loss_function);
-        let mut images: Vec<&str> = vec!["static/train accuracy.png", "static/train loss.png"];
-        if !self.test_accuracies.is_empty() {
-            images.push("static/test accuracy.png");
+        summary: Option<&Summary>,
+    ) -> Result<(), MlError> {
+        let train_loss: f64 = *self.train_losses.last().ok_or_else(|| {
+            MlError::InvalidInput("report can not be generated without data".to_string())
+        })?;
+        let step_directory: PathBuf =
+            Path::new(self.output_directory.as_str()).join((self.steps.len() - 1).to_string());
+        let image_directory: PathBuf = step_directory.join("static");
+        fs::create_dir_all(&image_directory)?;
+        self.generate_plots(&image_directory.to_string_lossy(), loss_function)?;
+        let mut images: Vec<String> = vec!["static/loss.png".to_string()];
+        for (name, _) in &self.train_metrics {
+            images.push(format!("static/{}.png", name));
         }
-        let image_html: String = generate_images_html(images);
+        if !self.roc_curves.is_empty() {
+            images.push("static/roc curve.png".to_string());
+        }
+        if !self.pr_curves.is_empty() {
+            images.push("static/pr curve.png".to_string());
+        }
+        let image_html: String =
+            generate_images_html(images.iter().map(|image| image.as_str()).collect());
 
-        let test_accuracy: Option<f64> = if self.test_accuracies.len() > 0 {
-            Some(*self.test_accuracies.last().unwrap())
+        let test_metrics: Option<Vec<(String, f64)>> = if !self.test_losses.is_empty() {
+            Some(Report::last_metrics(&self.test_metrics))
         } else {
             None
         };
@@ -80,62 +134,89 @@ impl Report {
             y_train_shape,
             x_test_shape,
             y_test_shape,
-            accuracy_function,
             loss_function,
-            *self.train_accuracies.last().unwrap(),
-            *self.train_losses.last().unwrap(),
-            test_accuracy,
+            &Report::last_metrics(&self.train_metrics),
+            train_loss,
+            test_metrics.as_deref(),
+            self.test_losses.last().copied(),
+            summary,
         );
 
-        let report_html: String = generate_full_html(resume_html, image_html);
-        let mut report_file: File = File::create(
-            Path::new(self.output_directory.as_str())
-                .join((self.train_accuracies.len() - 1).to_string())
-                .join("report.html"),
-        )
-        .unwrap();
-        report_file.write_all(report_html.as_bytes()).unwrap();
+        let confusion_matrix_html: String = match &self.test_confusion_matrix {
+            Some(confusion_matrix) => generate_confusion_matrix_html(confusion_matrix),
+            None => String::new(),
+        };
+
+        let report_html: String =
+            generate_full_html(resume_html + confusion_matrix_html.as_str(), image_html);
+        let mut report_file: File = File::create(step_directory.join("report.html"))?;
+        report_file.write_all(report_html.as_bytes())?;
+        Ok(())
     }
 
-    fn generate_plots(
-        &self,
-        output_directory: &str,
-        accuracy_function: &str,
-        loss_function: &str,
-    ) -> () {
+    fn generate_plots(&self, output_directory: &str, loss_function: &str) -> Result<(), MlError> {
         let step_float: Vec<f64> = self.steps.iter().map(|&step| step as f64).collect();
+        let mut loss_series: Vec<(&str, &[f64])> = vec![("train", &self.train_losses)];
+        if !self.test_losses.is_empty() {
+            loss_series.push(("test", &self.test_losses));
+        }
         generate_2d_plot(
             output_directory,
             &step_float,
-            &self.train_accuracies,
-            "epochs",
-            accuracy_function,
-            "train accuracy",
-        );
-        generate_2d_plot(
-            output_directory,
-            &step_float,
-            &self.train_losses,
+            &loss_series,
             "epochs",
             loss_function,
-            "train loss",
-        );
-        if !self.test_accuracies.is_empty() {
+            "loss",
+        )?;
+        for (name, train_values) in &self.train_metrics {
+            let mut metric_series: Vec<(&str, &[f64])> = vec![("train", train_values)];
+            if let Some((_, test_values)) = self
+                .test_metrics
+                .iter()
+                .find(|(test_name, _)| test_name == name)
+            {
+                metric_series.push(("test", test_values));
+            }
             generate_2d_plot(
                 output_directory,
                 &step_float,
-                &self.test_accuracies,
+                &metric_series,
                 "epochs",
-                accuracy_function,
-                "test accuracy",
-            );
+                name,
+                name,
+            )?;
+        }
+        if !self.roc_curves.is_empty() {
+            generate_curves_plot(
+                output_directory,
+                &self.roc_curves,
+                "false positive rate",
+                "true positive rate",
+                "roc curve",
+            )?;
+        }
+        if !self.pr_curves.is_empty() {
+            generate_curves_plot(
+                output_directory,
+                &self.pr_curves,
+                "recall",
+                "precision",
+                "pr curve",
+            )?;
         }
+        Ok(())
     }
 }
 
 #[cfg(test)]
+#[allow(clippy::unused_unit)]
 mod tests {
     use super::*;
+    use ndarray::arr2;
+
+    fn metrics(value: f64) -> Vec<(String, f64)> {
+        vec![("categorical_accuracy".to_string(), value)]
+    }
 
     #[test]
     fn add_data_should_push_in_vectors() -> () {
@@ -143,30 +224,57 @@ mod tests {
         let mut report_without_test: Report = Report::build("test_report");
 
         for i in 0..5 {
-            report_with_test.add_data(i, i as f64 * 1.5, i as f64 * 2.0, Some(i as f64 * 5.0));
-            report_without_test.add_data(i, i as f64 * 1.5, i as f64 * 2.0, None);
+            report_with_test.add_data(
+                i,
+                metrics(i as f64 * 1.5),
+                i as f64 * 2.0,
+                Some(metrics(i as f64 * 5.0)),
+                Some(i as f64 * 3.0),
+            );
+            report_without_test.add_data(i, metrics(i as f64 * 1.5), i as f64 * 2.0, None, None);
         }
 
         assert_eq!(report_with_test.steps, vec![0, 1, 2, 3, 4]);
         assert_eq!(
-            report_with_test.train_accuracies,
-            vec![0.0, 1.5, 3.0, 4.5, 6.0]
+            report_with_test.train_metrics,
+            vec![(
+                "categorical_accuracy".to_string(),
+                vec![0.0, 1.5, 3.0, 4.5, 6.0]
+            )]
         );
         assert_eq!(report_with_test.train_losses, vec![0.0, 2.0, 4.0, 6.0, 8.0]);
         assert_eq!(
-            report_with_test.test_accuracies,
-            vec![0.0, 5.0, 10.0, 15.0, 20.0]
+            report_with_test.test_metrics,
+            vec![(
+                "categorical_accuracy".to_string(),
+                vec![0.0, 5.0, 10.0, 15.0, 20.0]
+            )]
         );
+        assert_eq!(report_with_test.test_losses, vec![0.0, 3.0, 6.0, 9.0, 12.0]);
         assert_eq!(report_without_test.steps, vec![0, 1, 2, 3, 4]);
         assert_eq!(
-            report_without_test.train_accuracies,
-            vec![0.0, 1.5, 3.0, 4.5, 6.0]
+            report_without_test.train_metrics,
+            vec![(
+                "categorical_accuracy".to_string(),
+                vec![0.0, 1.5, 3.0, 4.5, 6.0]
+            )]
         );
         assert_eq!(
             report_without_test.train_losses,
             vec![0.0, 2.0, 4.0, 6.0, 8.0]
         );
-        assert!(report_without_test.test_accuracies.is_empty());
+        assert!(report_without_test.test_metrics.is_empty());
+        assert!(report_without_test.test_losses.is_empty());
+    }
+
+    #[test]
+    fn add_data_should_accept_no_metric() {
+        let mut report: Report = Report::build("test_report");
+        report.add_data(0, vec![], 0.5, Some(vec![]), Some(0.75));
+        assert_eq!(report.steps, vec![0]);
+        assert!(report.train_metrics.is_empty());
+        assert!(report.test_metrics.is_empty());
+        assert_eq!(report.test_losses, vec![0.75]);
     }
 
     #[test]
@@ -175,41 +283,135 @@ mod tests {
         let now: Instant = Instant::now();
 
         for i in 0..5 {
-            report.add_data(i, i as f64 * 1.5, i as f64 * 2.0, Some(i as f64 * 5.0));
+            report.add_data(
+                i,
+                metrics(i as f64 * 1.5),
+                i as f64 * 2.0,
+                Some(metrics(i as f64 * 5.0)),
+                Some(i as f64 * 3.0),
+            );
         }
 
-        report.generate(
-            "MLP",
-            now,
-            1000,
-            &[5, 5],
-            &[8, 8],
-            Some(&[6, 8]),
-            Some(&[8, 5]),
-            "Categorical Accuracy",
-            "MSE",
-        );
+        report
+            .generate(
+                "MLP",
+                now,
+                1000,
+                &[5, 5],
+                &[8, 8],
+                Some(&[6, 8]),
+                Some(&[8, 5]),
+                "MSE",
+                None,
+            )
+            .unwrap();
 
         assert!(Path::new("test_report").exists());
         fs::remove_dir_all("test_report").unwrap();
     }
 
+    #[test]
+    fn generate_should_render_confusion_matrix() {
+        let mut report: Report = Report::build("test_report_confusion_matrix");
+        report.add_data(0, metrics(0.5), 1.0, Some(metrics(0.25)), Some(1.5));
+        report.set_confusion_matrix(arr2(&[[3.0, 1.0], [0.0, 4.0]]));
+
+        report
+            .generate(
+                "MLP",
+                Instant::now(),
+                1,
+                &[8, 2],
+                &[8, 2],
+                Some(&[8, 2]),
+                Some(&[8, 2]),
+                "MSE",
+                None,
+            )
+            .unwrap();
+
+        let report_html: String =
+            fs::read_to_string("test_report_confusion_matrix/0/report.html").unwrap();
+        fs::remove_dir_all("test_report_confusion_matrix").unwrap();
+        assert!(report_html.contains("Test Confusion Matrix"));
+        assert!(report_html.contains("<tr><th>0</th><td>3</td><td>1</td></tr>"));
+    }
+
+    #[test]
+    fn generate_should_fail_without_data() {
+        let report: Report = Report::build("test_report_empty");
+        let result: Result<(), MlError> = report.generate(
+            "MLP",
+            Instant::now(),
+            1,
+            &[4, 2],
+            &[4, 1],
+            None,
+            None,
+            "MSE",
+            None,
+        );
+        assert!(matches!(result, Err(MlError::InvalidInput(_))));
+        assert!(!Path::new("test_report_empty").exists());
+    }
+
     #[test]
     fn generate_plots_should_create_png() -> () {
         let mut report: Report = Report::build("test_report");
 
         for i in 0..5 {
-            report.add_data(i, i as f64 * 1.5, i as f64 * 2.0, Some(i as f64 * 5.0));
+            report.add_data(
+                i,
+                metrics(i as f64 * 1.5),
+                i as f64 * 2.0,
+                Some(metrics(i as f64 * 5.0)),
+                Some(i as f64 * 3.0),
+            );
         }
 
-        report.generate_plots(".", "Categorical Accuracy", "MSE");
+        report.generate_plots(".", "MSE").unwrap();
+
+        assert!(Path::new("./loss.png").exists());
+        assert!(Path::new("./categorical_accuracy.png").exists());
+
+        fs::remove_file("./loss.png").unwrap();
+        fs::remove_file("./categorical_accuracy.png").unwrap();
+    }
+
+    #[test]
+    fn generate_should_plot_roc_and_pr_curves() {
+        let mut report: Report = Report::build("test_report_curves");
+        report.add_data(0, metrics(0.5), 1.0, None, None);
+        report.set_roc_curves(vec![(
+            "positive".to_string(),
+            vec![0.0, 0.5, 1.0],
+            vec![0.0, 1.0, 1.0],
+        )]);
+        report.set_pr_curves(vec![(
+            "positive".to_string(),
+            vec![0.0, 1.0, 1.0],
+            vec![1.0, 1.0, 0.5],
+        )]);
 
-        assert!(Path::new("./train loss.png").exists());
-        assert!(Path::new("./train accuracy.png").exists());
-        assert!(Path::new("./test accuracy.png").exists());
+        report
+            .generate(
+                "MLP",
+                Instant::now(),
+                1,
+                &[4, 2],
+                &[4, 1],
+                None,
+                None,
+                "MSE",
+                None,
+            )
+            .unwrap();
 
-        fs::remove_file("./train loss.png").unwrap();
-        fs::remove_file("./train accuracy.png").unwrap();
-        fs::remove_file("./test accuracy.png").unwrap();
+        assert!(Path::new("test_report_curves/0/static/roc curve.png").exists());
+        assert!(Path::new("test_report_curves/0/static/pr curve.png").exists());
+        let report_html: String = fs::read_to_string("test_report_curves/0/report.html").unwrap();
+        fs::remove_dir_all("test_report_curves").unwrap();
+        assert!(report_html.contains("static/roc curve.png"));
+        assert!(report_html.contains("static/pr curve.png"));
     }
 }
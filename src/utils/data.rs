@@ -1,26 +1,35 @@
+use crate::utils::error::MlError;
 use ndarray::{Array2, Axis};
-use ndarray_stats::QuantileExt;
 use rand::seq::SliceRandom;
 use rand::thread_rng;
 
-pub fn one_hot_encoding(x: &Array2<f64>) -> Array2<f64> {
+pub fn one_hot_encoding(x: &Array2<f64>) -> Result<Array2<f64>, MlError> {
     if x.shape()[1] != 1 {
-        panic!(
+        return Err(MlError::ShapeMismatch(format!(
             "array must have only one column, actually: {}",
             x.shape()[1]
-        );
+        )));
+    }
+    if let Some(value) = x.iter().find(|value| **value < 0.0 || value.fract() != 0.0) {
+        return Err(MlError::InvalidInput(format!(
+            "classes must be positive integers, actually: {}",
+            value
+        )));
     }
-    let max: usize = *x.max().unwrap() as usize;
+    let class_number: usize = x.iter().fold(0.0, |max: f64, &value| max.max(value + 1.0)) as usize;
     let mut one_hot_vec_buffer: Vec<f64> = vec![];
     x.iter().for_each(|&value| {
-        let mut one_hot_row_vec: Vec<f64> = vec![0.0; max + 1];
+        let mut one_hot_row_vec: Vec<f64> = vec![0.0; class_number];
         one_hot_row_vec[value as usize] = 1.0;
         one_hot_vec_buffer.append(&mut one_hot_row_vec);
     });
-    Array2::from_shape_vec((x.len(), max + 1), one_hot_vec_buffer).unwrap()
+    Ok(Array2::from_shape_vec(
+        (x.len(), class_number),
+        one_hot_vec_buffer,
+    )?)
 }
 
-pub fn shuffle_arrays(arrays: Vec<&Array2<f64>>) -> Vec<Array2<f64>> {
+pub fn shuffle_arrays(arrays: Vec<&Array2<f64>>) -> Result<Vec<Array2<f64>>, MlError> {
     let array_lens: Vec<usize> = arrays.iter().map(|array| array.shape()[0]).collect();
     if array_lens
         .iter()
@@ -28,25 +37,28 @@ pub fn shuffle_arrays(arrays: Vec<&Array2<f64>>) -> Vec<Array2<f64>> {
         .count()
         > 0
     {
-        panic!("arrays must have the same column length")
+        return Err(MlError::ShapeMismatch(
+            "arrays must have the same column length".to_string(),
+        ));
     }
-    let mut random_indexes: Vec<usize> = (0..arrays[0].shape()[0]).collect();
+    let mut random_indexes: Vec<usize> = (0..array_lens.first().copied().unwrap_or(0)).collect();
     random_indexes.shuffle(&mut thread_rng());
-    arrays
+    Ok(arrays
         .iter()
         .map(|array| array.select(Axis(0), random_indexes.as_slice()))
-        .collect()
+        .collect())
 }
 
 #[cfg(test)]
+#[allow(clippy::unused_unit)]
 mod tests {
     use super::*;
-    use ndarray::arr2;
+    use ndarray::{arr2, Array};
 
     #[test]
     fn one_hot_encoding_should_encode_array() -> () {
         let x: Array2<f64> = arr2(&[[0.0], [1.0], [2.0], [3.0]]);
-        let x_one_hot: Array2<f64> = one_hot_encoding(&x);
+        let x_one_hot: Array2<f64> = one_hot_encoding(&x).unwrap();
         assert_eq!(
             x_one_hot,
             arr2(&[
@@ -59,26 +71,46 @@ mod tests {
     }
 
     #[test]
-    #[should_panic(expected = "array must have only one column, actually: 2")]
-    fn one_hot_encoding_should_panic_when_array_has_not_one_column() -> () {
+    fn one_hot_encoding_should_fail_when_array_has_not_one_column() {
         let x: Array2<f64> = arr2(&[[0.0, 3.0]]);
-        one_hot_encoding(&x);
+        assert_eq!(
+            one_hot_encoding(&x).unwrap_err().to_string(),
+            "shape mismatch: array must have only one column, actually: 2"
+        );
+    }
+
+    #[test]
+    fn one_hot_encoding_should_fail_when_class_is_not_an_index() {
+        assert!(matches!(
+            one_hot_encoding(&arr2(&[[0.0], [-1.0]])),
+            Err(MlError::InvalidInput(_))
+        ));
+        assert!(matches!(
+            one_hot_encoding(&arr2(&[[0.5]])),
+            Err(MlError::InvalidInput(_))
+        ));
     }
 
     #[test]
     fn shuffle_arrays_should_same_shuffle_vec_of_arrays() -> () {
-        let x: Array2<f64> = arr2(&[[0.0], [1.0], [2.0], [3.0]]);
+        let x: Array2<f64> = Array::range(0.0, 20.0, 1.0).insert_axis(Axis(1));
         let y: Array2<f64> = x.clone();
-        let shuffle_arrays: Vec<Array2<f64>> = shuffle_arrays(vec![&x, &y]);
+        let shuffle_arrays: Vec<Array2<f64>> = shuffle_arrays(vec![&x, &y]).unwrap();
         assert_eq!(shuffle_arrays[0], shuffle_arrays[1]);
-        assert!(shuffle_arrays[0] != arr2(&[[0.0], [1.0], [2.0], [3.0]]));
+        assert!(shuffle_arrays[0] != x);
+        let mut values: Vec<f64> = shuffle_arrays[0].iter().copied().collect();
+        values.sort_by(f64::total_cmp);
+        assert_eq!(values, x.into_raw_vec());
     }
 
     #[test]
-    #[should_panic(expected = "arrays must have the same column length")]
-    fn shuffle_arrays_should_should_panic_when_arrays_have_not_same_size() -> () {
+    fn shuffle_arrays_should_fail_when_arrays_have_not_same_size() {
         let x: Array2<f64> = arr2(&[[0.0], [1.0], [2.0], [3.0]]);
         let y: Array2<f64> = arr2(&[[0.0], [1.0], [2.0]]);
-        shuffle_arrays(vec![&x, &y]);
+        assert!(matches!(
+            shuffle_arrays(vec![&x, &y]),
+            Err(MlError::ShapeMismatch(_))
+        ));
+        assert!(shuffle_arrays(vec![]).unwrap().is_empty());
     }
 }
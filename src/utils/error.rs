@@ -0,0 +1,119 @@
+use ndarray::ShapeError;
+use std::io;
+use thiserror::Error;
+
+/// Error returned by every fallible operation of the crate.
+#[derive(Debug, Error)]
+pub enum MlError {
+    /// Arrays whose dimensions do not fit together.
+    #[error("shape mismatch: {0}")]
+    ShapeMismatch(String),
+    /// Name of a layer, activation, loss or metric that is not known.
+    #[error("unknown {kind} '{name}'")]
+    UnknownComponent { kind: String, name: String },
+    #[error("serialization error: {0}")]
+    Serialization(String),
+    #[error("io error: {0}")]
+    Io(#[from] io::Error),
+    /// Values that can not be computed on, like a NaN prediction or a diverging loss.
+    #[error("numeric error: {0}")]
+    Numeric(String),
+    /// Argument outside of its domain, like a batch size of 0.
+    #[error("invalid input: {0}")]
+    InvalidInput(String),
+    /// Error raised by the layer at `index` of a network.
+    #[error("layer {index} ({name}): {source}")]
+    Layer {
+        index: usize,
+        name: String,
+        source: Box<MlError>,
+    },
+}
+
+impl MlError {
+    pub fn unknown_component(kind: &str, name: &str) -> MlError {
+        MlError::UnknownComponent {
+            kind: kind.to_string(),
+            name: name.to_string(),
+        }
+    }
+
+    pub fn layer(index: usize, name: &str, error: MlError) -> MlError {
+        MlError::Layer {
+            index,
+            name: name.to_string(),
+            source: Box::new(error),
+        }
+    }
+}
+
+impl From<serde_json::Error> for MlError {
+    fn from(error: serde_json::Error) -> MlError {
+        MlError::Serialization(error.to_string())
+    }
+}
+
+impl From<ShapeError> for MlError {
+    fn from(error: ShapeError) -> MlError {
+        MlError::ShapeMismatch(error.to_string())
+    }
+}
+
+pub fn check_shapes(left: &[usize], right: &[usize]) -> Result<(), MlError> {
+    if left != right {
+        return Err(MlError::ShapeMismatch(format!(
+            "shapes are not equals {:?} != {:?}",
+            left, right
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::Array2;
+
+    #[test]
+    fn unknown_component_should_format_kind_and_name() {
+        assert_eq!(
+            MlError::unknown_component("loss", "Unknown").to_string(),
+            "unknown loss 'Unknown'"
+        );
+    }
+
+    #[test]
+    fn layer_should_prefix_error_with_layer_index() {
+        let error: MlError = MlError::layer(
+            2,
+            "FCLayer",
+            MlError::ShapeMismatch("expected [3], actually: [4]".to_string()),
+        );
+        assert_eq!(
+            error.to_string(),
+            "layer 2 (FCLayer): shape mismatch: expected [3], actually: [4]"
+        );
+    }
+
+    #[test]
+    fn from_should_convert_library_errors() {
+        let shape_error: MlError = Array2::<f64>::from_shape_vec((2, 2), vec![0.0])
+            .unwrap_err()
+            .into();
+        let json_error: MlError = serde_json::from_str::<Vec<f64>>("{").unwrap_err().into();
+        let io_error: MlError = io::Error::new(io::ErrorKind::NotFound, "missing").into();
+
+        assert!(matches!(shape_error, MlError::ShapeMismatch(_)));
+        assert!(matches!(json_error, MlError::Serialization(_)));
+        assert!(matches!(io_error, MlError::Io(_)));
+    }
+
+    #[test]
+    fn check_shapes_should_fail_when_shapes_are_not_equal() {
+        assert!(check_shapes(&[2, 3], &[2, 3]).is_ok());
+        assert_eq!(
+            check_shapes(&[2, 3], &[4, 3]).unwrap_err().to_string(),
+            "shape mismatch: shapes are not equals [2, 3] != [4, 3]"
+        );
+    }
+}
@@ -0,0 +1,74 @@
+use ndarray::{LinalgScalar, ScalarOperand};
+use ndarray_rand::rand_distr::uniform::SampleUniform;
+use num_traits::{Float, FromPrimitive};
+use std::fmt::{Debug, Display};
+use std::iter::Sum;
+use std::ops::{AddAssign, DivAssign, MulAssign, SubAssign};
+
+/// Floating point type a network can be trained with, implemented for `f32` and `f64`.
+pub trait MlFloat:
+    Float
+    + FromPrimitive
+    + LinalgScalar
+    + ScalarOperand
+    + SampleUniform
+    + AddAssign
+    + SubAssign
+    + MulAssign
+    + DivAssign
+    + Sum
+    + Debug
+    + Display
+    + Default
+    + Send
+    + Sync
+    + 'static
+{
+    const DTYPE: &'static str;
+
+    fn cast(value: f64) -> Self;
+
+    fn to_f64_lossy(self) -> f64;
+}
+
+impl MlFloat for f32 {
+    const DTYPE: &'static str = "f32";
+
+    fn cast(value: f64) -> f32 {
+        value as f32
+    }
+
+    fn to_f64_lossy(self) -> f64 {
+        self as f64
+    }
+}
+
+impl MlFloat for f64 {
+    const DTYPE: &'static str = "f64";
+
+    fn cast(value: f64) -> f64 {
+        value
+    }
+
+    fn to_f64_lossy(self) -> f64 {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dtype_should_name_precision() {
+        assert_eq!(f32::DTYPE, "f32");
+        assert_eq!(f64::DTYPE, "f64");
+    }
+
+    #[test]
+    fn cast_should_convert_between_precisions() {
+        assert_eq!(<f32 as MlFloat>::cast(0.5), 0.5_f32);
+        assert_eq!(<f64 as MlFloat>::cast(0.5), 0.5_f64);
+        assert_eq!(0.25_f32.to_f64_lossy(), 0.25_f64);
+    }
+}
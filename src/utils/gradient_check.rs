@@ -0,0 +1,300 @@
+use crate::activation::activation::Activation;
+use crate::layer::layer::Layer;
+use crate::loss::loss::Loss;
+use crate::merge::merge::Merge;
+use crate::utils::error::{check_shapes, MlError};
+use crate::utils::float::MlFloat;
+use ndarray::Array2;
+
+/// Analytic gradient of a component compared with its central finite differences.
+#[derive(Debug, Clone)]
+pub struct GradientCheck {
+    /// `input` for the error of the input, `gradient {i}` for the i-th parameter gradient.
+    pub name: String,
+    pub analytic: Array2<f64>,
+    pub numeric: Array2<f64>,
+    /// Largest `|analytic - numeric| / max(|analytic|, |numeric|, 1)` over the elements, small
+    /// gradients are compared in absolute terms.
+    pub relative_error: f64,
+}
+
+impl GradientCheck {
+    fn build<F: MlFloat>(
+        name: String,
+        analytic: &Array2<F>,
+        numeric: Array2<f64>,
+    ) -> GradientCheck {
+        let analytic: Array2<f64> = analytic.mapv(F::to_f64_lossy);
+        let relative_error: f64 = analytic
+            .iter()
+            .zip(numeric.iter())
+            .map(|(&a, &n)| (a - n).abs() / a.abs().max(n.abs()).max(1.0))
+            .fold(0.0, f64::max);
+        GradientCheck {
+            name,
+            analytic,
+            numeric,
+            relative_error,
+        }
+    }
+}
+
+/// Largest relative error of the checks, 0 when there are none.
+pub fn max_relative_error(checks: &[GradientCheck]) -> f64 {
+    checks
+        .iter()
+        .map(|check| check.relative_error)
+        .fold(0.0, f64::max)
+}
+
+/// Fixed error of the output the backward passes are checked with, so every output value gets a
+/// different weight.
+fn output_error<F: MlFloat>(shape: (usize, usize)) -> Array2<F> {
+    Array2::from_shape_fn(shape, |(i, j)| {
+        F::cast(((i * shape.1 + j) % 7) as f64 / 7.0 - 0.4)
+    })
+}
+
+fn numeric_gradient<F: MlFloat>(
+    x: &Array2<F>,
+    epsilon: f64,
+    mut function: impl FnMut(&Array2<F>) -> Result<f64, MlError>,
+) -> Result<Array2<f64>, MlError> {
+    let mut gradient: Array2<f64> = Array2::zeros(x.raw_dim());
+    for (index, value) in gradient.indexed_iter_mut() {
+        let mut x_plus: Array2<F> = x.clone();
+        x_plus[index] += F::cast(epsilon);
+        let mut x_minus: Array2<F> = x.clone();
+        x_minus[index] -= F::cast(epsilon);
+        *value = (function(&x_plus)? - function(&x_minus)?) / (2.0 * epsilon);
+    }
+    Ok(gradient)
+}
+
+fn weighted_output<F: MlFloat>(
+    layer: &dyn Layer<F>,
+    x: &Array2<F>,
+    y: &Array2<F>,
+) -> Result<f64, MlError> {
+    let output: Array2<F> = layer.predict(x)?;
+    check_shapes(output.shape(), y.shape())?;
+    Ok((output * y).sum().to_f64_lossy())
+}
+
+/// Checks the error of the input and every parameter gradient of a compiled layer on the batch
+/// `x`. Parameters are moved through `set_gradients` and `apply_gradients`, so any layer can be
+/// checked; its gradients are restored afterwards and its parameters up to rounding errors.
+pub fn check_layer<F: MlFloat>(
+    layer: &mut dyn Layer<F>,
+    x: &Array2<F>,
+    epsilon: f64,
+) -> Result<Vec<GradientCheck>, MlError> {
+    let output: Array2<F> = layer.forward_propagation(x)?;
+    let y: Array2<F> = output_error(output.dim());
+    let input_error: Array2<F> = layer.compute_gradients(&y)?;
+    let gradients: Vec<Array2<F>> = layer.get_gradients();
+
+    let mut checks: Vec<GradientCheck> = vec![GradientCheck::build(
+        "input".to_string(),
+        &input_error,
+        numeric_gradient(x, epsilon, |x| weighted_output(&*layer, x, &y))?,
+    )];
+    for (index, gradient) in gradients.iter().enumerate() {
+        let mut numeric: Array2<f64> = Array2::zeros(gradient.raw_dim());
+        for (position, value) in numeric.indexed_iter_mut() {
+            let mut step: Vec<Array2<F>> = gradients
+                .iter()
+                .map(|gradient| Array2::zeros(gradient.raw_dim()))
+                .collect();
+            step[index][position] = F::one();
+            layer.set_gradients(step)?;
+            layer.apply_gradients(F::cast(-epsilon));
+            let plus: f64 = weighted_output(&*layer, x, &y)?;
+            layer.apply_gradients(F::cast(2.0 * epsilon));
+            let minus: f64 = weighted_output(&*layer, x, &y)?;
+            layer.apply_gradients(F::cast(-epsilon));
+            *value = (plus - minus) / (2.0 * epsilon);
+        }
+        checks.push(GradientCheck::build(
+            format!("gradient {}", index),
+            gradient,
+            numeric,
+        ));
+    }
+    layer.set_gradients(gradients)?;
+    Ok(checks)
+}
+
+/// Checks the error of every input of a compiled merge on the batches `inputs`, the checks are
+/// named `input {i}`.
+pub fn check_merge<F: MlFloat>(
+    merge: &mut dyn Merge<F>,
+    inputs: &[Array2<F>],
+    epsilon: f64,
+) -> Result<Vec<GradientCheck>, MlError> {
+    let output: Array2<F> = merge.forward_propagation(inputs)?;
+    let y: Array2<F> = output_error(output.dim());
+    let input_errors: Vec<Array2<F>> = merge.backward_propagation(&y)?;
+    let merge: &dyn Merge<F> = merge;
+    inputs
+        .iter()
+        .zip(&input_errors)
+        .enumerate()
+        .map(|(index, (x, input_error))| {
+            let numeric: Array2<f64> = numeric_gradient(x, epsilon, |x| {
+                let mut perturbed: Vec<Array2<F>> = inputs.to_vec();
+                perturbed[index] = x.clone();
+                let output: Array2<F> = merge.predict(&perturbed)?;
+                check_shapes(output.shape(), y.shape())?;
+                Ok((output * &y).sum().to_f64_lossy())
+            })?;
+            Ok(GradientCheck::build(
+                format!("input {}", index),
+                input_error,
+                numeric,
+            ))
+        })
+        .collect()
+}
+
+/// Checks `Activation::backward` at `x`.
+pub fn check_activation<F: MlFloat>(
+    activation: &dyn Activation<F>,
+    x: &Array2<F>,
+    epsilon: f64,
+) -> Result<GradientCheck, MlError> {
+    let y: Array2<F> = output_error(x.dim());
+    let numeric: Array2<f64> = numeric_gradient(x, epsilon, |x| {
+        Ok((activation.function(x) * &y).sum().to_f64_lossy())
+    })?;
+    Ok(GradientCheck::build(
+        "input".to_string(),
+        &activation.backward(x, &y),
+        numeric,
+    ))
+}
+
+/// Checks `Loss::derivative` with respect to the predictions.
+pub fn check_loss<F: MlFloat>(
+    loss: &dyn Loss<F>,
+    y_true: &Array2<F>,
+    y_pred: &Array2<F>,
+    epsilon: f64,
+) -> Result<GradientCheck, MlError> {
+    let numeric: Array2<f64> = numeric_gradient(y_pred, epsilon, |y_pred| {
+        Ok(loss.function(y_true, y_pred)?.to_f64_lossy())
+    })?;
+    Ok(GradientCheck::build(
+        "input".to_string(),
+        &loss.derivative(y_true, y_pred)?,
+        numeric,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::activation::tanh::Tanh;
+    use crate::layer::fc_layer::FCLayer;
+    use crate::loss::mse::Mse;
+    use ndarray::arr2;
+
+    /// Tanh with the derivative of the identity.
+    struct WrongTanh;
+
+    impl Activation for WrongTanh {
+        fn function(&self, x: &Array2<f64>) -> Array2<f64> {
+            x.mapv(f64::tanh)
+        }
+
+        fn derivative(&self, x: &Array2<f64>) -> Array2<f64> {
+            Array2::ones(x.raw_dim())
+        }
+
+        fn get_name(&self) -> String {
+            "WrongTanh".to_string()
+        }
+
+        fn clone_activation(&self) -> Box<dyn Activation> {
+            Box::new(WrongTanh)
+        }
+    }
+
+    struct WrongMse;
+
+    impl Loss for WrongMse {
+        fn function(&self, y_true: &Array2<f64>, y_pred: &Array2<f64>) -> Result<f64, MlError> {
+            Mse.function(y_true, y_pred)
+        }
+
+        fn derivative(
+            &self,
+            y_true: &Array2<f64>,
+            y_pred: &Array2<f64>,
+        ) -> Result<Array2<f64>, MlError> {
+            Ok(y_pred - y_true)
+        }
+
+        fn get_name(&self) -> String {
+            "WrongMse".to_string()
+        }
+    }
+
+    #[test]
+    fn check_layer_should_compare_input_and_parameter_gradients() {
+        let mut layer: FCLayer = FCLayer::build_with_seed(3, 2, 1);
+        layer.compile(&[3]).unwrap();
+        let x: Array2<f64> = arr2(&[[0.5, -1.0, 2.0], [1.5, 0.25, -0.75]]);
+        let expected: Array2<f64> = layer.predict(&x).unwrap();
+        let checks: Vec<GradientCheck> = check_layer(&mut layer, &x, 0.0001).unwrap();
+
+        assert_eq!(
+            checks
+                .iter()
+                .map(|check| check.name.as_str())
+                .collect::<Vec<&str>>(),
+            vec!["input", "gradient 0", "gradient 1"]
+        );
+        assert_eq!(checks[1].numeric.shape(), &[3, 2]);
+        assert!(max_relative_error(&checks) < 0.0001);
+        for (e, r) in expected.iter().zip(layer.predict(&x).unwrap().iter()) {
+            assert!((e - r).powf(2.0) < 0.00001);
+        }
+        assert_eq!(layer.get_gradients()[0], checks[1].analytic);
+    }
+
+    #[test]
+    fn check_activation_should_detect_wrong_derivative() {
+        let x: Array2<f64> = arr2(&[[0.5, -1.0, 2.0], [1.5, 0.25, -0.75]]);
+        assert!(check_activation(&Tanh, &x, 0.0001).unwrap().relative_error < 0.0001);
+        assert!(
+            check_activation(&WrongTanh, &x, 0.0001)
+                .unwrap()
+                .relative_error
+                > 0.1
+        );
+    }
+
+    #[test]
+    fn check_loss_should_detect_wrong_derivative() {
+        let y_true: Array2<f64> = arr2(&[[0.0, 1.0], [1.0, 0.0]]);
+        let y_pred: Array2<f64> = arr2(&[[0.25, 0.5], [0.75, 0.5]]);
+        assert!(
+            check_loss(&Mse, &y_true, &y_pred, 0.0001)
+                .unwrap()
+                .relative_error
+                < 0.0001
+        );
+        assert!(
+            check_loss(&WrongMse, &y_true, &y_pred, 0.0001)
+                .unwrap()
+                .relative_error
+                > 0.1
+        );
+    }
+
+    #[test]
+    fn max_relative_error_should_return_zero_without_checks() {
+        assert_eq!(max_relative_error(&[]), 0.0);
+    }
+}
@@ -1 +1,4 @@
 pub mod data;
+pub mod error;
+pub mod float;
+pub mod gradient_check;